@@ -1,51 +1,18 @@
-use axum::{
-    body::Body,
-    extract::State,
-    http::{Request, StatusCode},
-    middleware::{self, Next},
-    response::Response,
-    routing::post,
-    Router,
-};
-use crypto_pay_api::prelude::*;
-use std::sync::Arc;
-
-async fn webhook_middleware(
-    State(handler): State<Arc<WebhookHandler>>,
-    req: Request<Body>,
-    next: Next,
-) -> Result<Response, StatusCode> {
-    let (parts, body) = req.into_parts();
-
-    let signature = parts
-        .headers
-        .get("crypto-pay-api-signature")
-        .and_then(|h| h.to_str().ok())
-        .ok_or(StatusCode::UNAUTHORIZED)?;
-
-    let body_bytes = axum::body::to_bytes(body, usize::MAX)
-        .await
-        .map_err(|_| StatusCode::BAD_REQUEST)?;
-    let body_str = String::from_utf8(body_bytes.to_vec()).map_err(|_| StatusCode::BAD_REQUEST)?;
-
-    if !handler.verify_signature(&body_str, signature) {
-        return Err(StatusCode::UNAUTHORIZED);
-    }
+//! Serves a webhook handler with axum. Run with `cargo run --example axum_webhook --features axum`.
 
-    handler
-        .handle_update(&body_str)
-        .await
-        .map_err(|_| StatusCode::BAD_REQUEST)?;
-
-    let req = Request::from_parts(parts, Body::from(body_str));
-    Ok(next.run(req).await)
-}
+use axum::{routing::get, Router};
+use crypto_pay_api::prelude::*;
 
 #[tokio::main]
 async fn main() {
     let client = CryptoBot::builder().api_token("your_token").build().unwrap();
 
-    let mut webhook_handler = client.webhook_handler().build();
+    let mut webhook_handler = client
+        .webhook_handler()
+        .require_signature(true)
+        .deduplicate(1000)
+        .build()
+        .unwrap();
 
     // Register handlers
     webhook_handler.on_update(|update| async move {
@@ -53,12 +20,10 @@ async fn main() {
         Ok(())
     });
 
-    let handler = Arc::new(webhook_handler);
-
+    // Signature, expiration and deduplication checks and the response status are handled by the router
     let app = Router::new()
-        .route("/webhook", post(|| async { "OK" }))
-        .with_state(handler.clone())
-        .layer(middleware::from_fn_with_state(handler, webhook_middleware));
+        .route("/health", get(|| async { "OK" }))
+        .merge(webhook_handler.into_router("/webhook"));
 
     let listener = tokio::net::TcpListener::bind("127.0.0.1:3000").await.unwrap();
     println!("listening on {}", listener.local_addr().unwrap());
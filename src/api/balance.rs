@@ -1,7 +1,7 @@
-use async_trait::async_trait;
-
+#[cfg(feature = "tokio")]
+use crate::client::RetryConfig;
 use crate::{
-    client::CryptoBot,
+    client::{CryptoBot, RequestOptions},
     error::CryptoBotError,
     models::{APIEndpoint, APIMethod, Balance, Method},
 };
@@ -10,17 +10,29 @@ use super::BalanceAPI;
 
 pub struct GetBalanceBuilder<'a> {
     client: &'a CryptoBot,
+    options: RequestOptions,
 }
 
 impl<'a> GetBalanceBuilder<'a> {
     pub fn new(client: &'a CryptoBot) -> Self {
-        Self { client }
+        Self {
+            client,
+            options: RequestOptions::default(),
+        }
+    }
+
+    /// Sends the request to `base_url` instead of the client's base URL, e.g. a mirror of the API.
+    /// Optional. The URL is validated when the request is executed.
+    pub fn base_url_override(mut self, base_url: impl Into<String>) -> Self {
+        self.options.base_url = Some(base_url.into());
+        self
     }
 
     /// Executes the request to get current balance
     pub async fn execute(self) -> Result<Vec<Balance>, CryptoBotError> {
         self.client
-            .make_request(
+            .make_request_with(
+                &self.options,
                 &APIMethod {
                     endpoint: APIEndpoint::GetBalance,
                     method: Method::GET,
@@ -29,9 +41,15 @@ impl<'a> GetBalanceBuilder<'a> {
             )
             .await
     }
+
+    /// Executes the request, retrying it with `retry` instead of the client's retry configuration
+    #[cfg(feature = "tokio")]
+    pub async fn execute_with_retry(mut self, retry: RetryConfig) -> Result<Vec<Balance>, CryptoBotError> {
+        self.options.retry = Some(retry);
+        self.execute().await
+    }
 }
 
-#[async_trait]
 impl BalanceAPI for CryptoBot {
     /// Gets current balance for all supported cryptocurrencies in your CryptoBot wallet
     ///
@@ -0,0 +1,203 @@
+use std::future::Future;
+
+use crate::{
+    client::CryptoBot,
+    error::{CryptoBotError, CryptoBotResult},
+    models::{APIEndpoint, Balance, Currency, ExchangeRate, GetMeResponse},
+};
+
+use super::{BalanceAPI, ExchangeRateAPI, MiscAPI};
+
+/// Runs independent read requests concurrently, see `CryptoBot::batch`
+pub struct BatchBuilder<'a> {
+    client: &'a CryptoBot,
+    me: bool,
+    balance: bool,
+    exchange_rates: bool,
+    currencies: bool,
+}
+
+impl<'a> BatchBuilder<'a> {
+    pub fn new(client: &'a CryptoBot) -> Self {
+        Self {
+            client,
+            me: false,
+            balance: false,
+            exchange_rates: false,
+            currencies: false,
+        }
+    }
+
+    /// Includes `getMe`.
+    pub fn me(mut self) -> Self {
+        self.me = true;
+        self
+    }
+
+    /// Includes `getBalance`.
+    pub fn balance(mut self) -> Self {
+        self.balance = true;
+        self
+    }
+
+    /// Includes `getExchangeRates`.
+    pub fn exchange_rates(mut self) -> Self {
+        self.exchange_rates = true;
+        self
+    }
+
+    /// Includes `getCurrencies`.
+    pub fn currencies(mut self) -> Self {
+        self.currencies = true;
+        self
+    }
+
+    /// Sends the included requests concurrently and waits for all of them
+    ///
+    /// A failed request does not cancel the others; its error is reported in [`BatchResults`].
+    pub async fn execute(self) -> BatchResults {
+        let client = self.client;
+        let (me, balance, exchange_rates, currencies) = futures_util::future::join4(
+            included(self.me, client.get_me().execute()),
+            included(self.balance, client.get_balance().execute()),
+            included(self.exchange_rates, client.get_exchange_rates().execute()),
+            included(self.currencies, client.get_currencies().execute()),
+        )
+        .await;
+
+        BatchResults {
+            me,
+            balance,
+            exchange_rates,
+            currencies,
+        }
+    }
+}
+
+async fn included<T>(included: bool, request: impl Future<Output = T>) -> Option<T> {
+    if included {
+        Some(request.await)
+    } else {
+        None
+    }
+}
+
+/// Outcome of the requests of a batch, `None` for requests that were not included.
+#[derive(Debug)]
+pub struct BatchResults {
+    /// Result of `getMe`.
+    pub me: Option<CryptoBotResult<GetMeResponse>>,
+
+    /// Result of `getBalance`.
+    pub balance: Option<CryptoBotResult<Vec<Balance>>>,
+
+    /// Result of `getExchangeRates`.
+    pub exchange_rates: Option<CryptoBotResult<Vec<ExchangeRate>>>,
+
+    /// Result of `getCurrencies`.
+    pub currencies: Option<CryptoBotResult<Vec<Currency>>>,
+}
+
+impl BatchResults {
+    /// Returns true, if every included request succeeded.
+    pub fn is_ok(&self) -> bool {
+        self.errors().is_empty()
+    }
+
+    /// Returns the endpoints of the failed requests with their errors.
+    pub fn errors(&self) -> Vec<(APIEndpoint, &CryptoBotError)> {
+        [
+            (APIEndpoint::GetMe, self.me.as_ref().and_then(|r| r.as_ref().err())),
+            (
+                APIEndpoint::GetBalance,
+                self.balance.as_ref().and_then(|r| r.as_ref().err()),
+            ),
+            (
+                APIEndpoint::GetExchangeRates,
+                self.exchange_rates.as_ref().and_then(|r| r.as_ref().err()),
+            ),
+            (
+                APIEndpoint::GetCurrencies,
+                self.currencies.as_ref().and_then(|r| r.as_ref().err()),
+            ),
+        ]
+        .into_iter()
+        .filter_map(|(endpoint, error)| error.map(|error| (endpoint, error)))
+        .collect()
+    }
+
+    /// Returns all failures on one line for logging, each prefixed with its endpoint, e.g. `getMe: ...`.
+    /// `None` if every included request succeeded.
+    pub fn error_report(&self) -> Option<String> {
+        let errors = self.errors();
+        if errors.is_empty() {
+            return None;
+        }
+
+        Some(
+            errors
+                .iter()
+                .map(|(endpoint, error)| format!("{}: {error}", endpoint.as_str()))
+                .collect::<Vec<_>>()
+                .join("; "),
+        )
+    }
+}
+
+impl CryptoBot {
+    /// Sends several independent read requests concurrently, e.g. to cut startup latency
+    ///
+    /// # Example
+    /// ```no_run
+    /// # use crypto_pay_api::prelude::*;
+    /// # async fn example(client: CryptoBot) -> Result<(), CryptoBotError> {
+    /// let results = client.batch().me().balance().exchange_rates().execute().await;
+    ///
+    /// if let Some(report) = results.error_report() {
+    ///     eprintln!("Startup requests failed: {report}");
+    /// }
+    /// let balances = results.balance.transpose()?.unwrap_or_default();
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn batch(&self) -> BatchBuilder<'_> {
+        BatchBuilder::new(self)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::utils::test_utils::TestContext;
+
+    #[test]
+    fn test_batch_reports_each_request() {
+        let mut ctx = TestContext::new();
+        let _balance = ctx.mock_balance_response();
+        let _rates = ctx.mock_exchange_rates_response();
+        let _me = ctx
+            .server
+            .mock("GET", "/getMe")
+            .with_status(401)
+            .with_body(r#"{"ok":false,"error":{"code":401,"name":"UNAUTHORIZED"}}"#)
+            .create();
+
+        let client = CryptoBot::builder()
+            .api_token("test_token")
+            .base_url(ctx.server.url())
+            .build()
+            .unwrap();
+
+        let results = ctx.run(client.batch().me().balance().exchange_rates().execute());
+
+        assert!(results.balance.as_ref().is_some_and(|r| r.is_ok()));
+        assert!(results.exchange_rates.as_ref().is_some_and(|r| r.is_ok()));
+        assert!(results.currencies.is_none());
+        assert!(!results.is_ok());
+
+        let errors = results.errors();
+        assert_eq!(errors.len(), 1);
+        assert_eq!(errors[0].0, APIEndpoint::GetMe);
+        assert!(results.error_report().unwrap().starts_with("getMe: "));
+    }
+}
@@ -1,31 +1,44 @@
-use async_trait::async_trait;
-use std::marker::PhantomData;
+use std::{collections::BTreeMap, marker::PhantomData};
 
+use futures_util::Stream;
 use rust_decimal::Decimal;
 use rust_decimal_macros::dec;
 
+#[cfg(feature = "tokio")]
+use crate::client::RetryConfig;
 use crate::utils::types::IntoDecimal;
 use crate::{
-    client::CryptoBot,
-    error::{CryptoBotError, CryptoBotResult, ValidationErrorKind},
+    client::{CryptoBot, RequestOptions},
+    error::{CryptoBotError, CryptoBotResult, ValidationCode, ValidationErrorKind},
     models::{
         APIEndpoint, APIMethod, Check, CheckStatus, CreateCheckParams, CryptoCurrencyCode, DeleteCheckParams,
-        GetChecksParams, GetChecksResponse, Method, Missing, Set,
+        ExchangeRate, FieldState, FundsCheck, GetChecksParams, GetChecksResponse, Method, Missing, PositiveAmount, Set,
     },
-    validation::{validate_amount, validate_count, ContextValidate, FieldValidate, ValidationContext},
+    validation::{validate_amount, validate_positive_amount, ContextValidate, FieldValidate, ValidationContext},
 };
 
-use super::CheckAPI;
-use crate::api::ExchangeRateAPI;
+use super::{fetch_by_ids, paginate, BalanceAPI, CheckAPI, DEFAULT_PAGE_SIZE};
 
 pub struct DeleteCheckBuilder<'a> {
     client: &'a CryptoBot,
+    options: RequestOptions,
     check_id: u64,
 }
 
 impl<'a> DeleteCheckBuilder<'a> {
     pub fn new(client: &'a CryptoBot, check_id: u64) -> Self {
-        Self { client, check_id }
+        Self {
+            client,
+            check_id,
+            options: RequestOptions::default(),
+        }
+    }
+
+    /// Sends the request to `base_url` instead of the client's base URL, e.g. a mirror of the API.
+    /// Optional. The URL is validated when the request is executed.
+    pub fn base_url_override(mut self, base_url: impl Into<String>) -> Self {
+        self.options.base_url = Some(base_url.into());
+        self
     }
 
     /// Executes the request to delete the check
@@ -35,7 +48,8 @@ impl<'a> DeleteCheckBuilder<'a> {
         };
 
         self.client
-            .make_request(
+            .make_request_with(
+                &self.options,
                 &APIMethod {
                     endpoint: APIEndpoint::DeleteCheck,
                     method: Method::DELETE,
@@ -48,17 +62,30 @@ impl<'a> DeleteCheckBuilder<'a> {
 
 pub struct GetChecksBuilder<'a> {
     client: &'a CryptoBot,
+    options: RequestOptions,
     params: GetChecksParams,
+    clamp_counts: Option<bool>,
+    limit: Option<usize>,
 }
 
 impl<'a> GetChecksBuilder<'a> {
     pub fn new(client: &'a CryptoBot) -> Self {
         Self {
             client,
+            options: RequestOptions::default(),
             params: GetChecksParams::default(),
+            clamp_counts: None,
+            limit: None,
         }
     }
 
+    /// Sends the request to `base_url` instead of the client's base URL, e.g. a mirror of the API.
+    /// Optional. The URL is validated when the request is executed.
+    pub fn base_url_override(mut self, base_url: impl Into<String>) -> Self {
+        self.options.base_url = Some(base_url.into());
+        self
+    }
+
     /// Set the asset for the checks.
     /// Optional. Defaults to all currencies.
     pub fn asset(mut self, asset: CryptoCurrencyCode) -> Self {
@@ -96,15 +123,60 @@ impl<'a> GetChecksBuilder<'a> {
         self
     }
 
+    /// Clamp the count into the range 1-1000 instead of rejecting it.
+    /// Optional. Overrides the client setting.
+    pub fn clamp_counts(mut self, clamp_counts: bool) -> Self {
+        self.clamp_counts = Some(clamp_counts);
+        self
+    }
+
+    /// Set the maximum number of checks yielded by `into_stream`.
+    /// Optional. Defaults to all checks.
+    pub fn limit(mut self, limit: usize) -> Self {
+        self.limit = Some(limit);
+        self
+    }
+
+    /// Returns the asset filter, if set.
+    pub fn get_asset(&self) -> Option<&CryptoCurrencyCode> {
+        self.params.asset.as_ref()
+    }
+
+    /// Returns the check IDs filter, if set.
+    pub fn get_check_ids(&self) -> Option<&[u64]> {
+        self.params.check_ids.as_deref()
+    }
+
+    /// Returns the status filter, if set.
+    pub fn get_status(&self) -> Option<&CheckStatus> {
+        self.params.status.as_ref()
+    }
+
+    /// Returns the offset, if set.
+    pub fn get_offset(&self) -> Option<u32> {
+        self.params.offset
+    }
+
+    /// Returns the count, if set.
+    pub fn get_count(&self) -> Option<u16> {
+        self.params.count
+    }
+
+    /// Returns the limit of `into_stream`, if set.
+    pub fn get_limit(&self) -> Option<usize> {
+        self.limit
+    }
+
     /// Executes the request to get checks
-    pub async fn execute(self) -> CryptoBotResult<Vec<Check>> {
+    pub async fn execute(mut self) -> CryptoBotResult<Vec<Check>> {
         if let Some(count) = self.params.count {
-            validate_count(count)?;
+            self.params.count = Some(self.client.resolve_count(count, self.clamp_counts)?);
         }
 
         let response: GetChecksResponse = self
             .client
-            .make_request(
+            .make_request_with(
+                &self.options,
                 &APIMethod {
                     endpoint: APIEndpoint::GetChecks,
                     method: Method::GET,
@@ -115,14 +187,66 @@ impl<'a> GetChecksBuilder<'a> {
 
         Ok(response.items)
     }
+
+    /// Executes the request, retrying it with `retry` instead of the client's retry configuration
+    #[cfg(feature = "tokio")]
+    pub async fn execute_with_retry(mut self, retry: RetryConfig) -> CryptoBotResult<Vec<Check>> {
+        self.options.retry = Some(retry);
+        self.execute().await
+    }
+
+    /// Returns a stream of the checks, fetching one page of `count` checks at a time as it is consumed
+    ///
+    /// Pages start at `offset` and hold 100 checks unless `count` is set. The stream ends after the
+    /// last page, after `limit` checks or after the first error, which is yielded as its last item.
+    /// Checks shifted into the next page by new ones are yielded once.
+    pub fn into_stream(self) -> impl Stream<Item = CryptoBotResult<Check>> + 'a {
+        let page_size = self
+            .client
+            .resolve_count(self.params.count.unwrap_or(DEFAULT_PAGE_SIZE), self.clamp_counts);
+        let offset = self.params.offset.unwrap_or(0);
+        let (client, options, params) = (self.client, self.options, self.params);
+
+        paginate(
+            offset,
+            page_size,
+            self.limit,
+            |check: &Check| check.check_id,
+            move |offset, count| {
+                let options = options.clone();
+                let params = GetChecksParams {
+                    offset: Some(offset),
+                    count: Some(count),
+                    ..params.clone()
+                };
+                async move {
+                    let response: GetChecksResponse = client
+                        .make_request_with(
+                            &options,
+                            &APIMethod {
+                                endpoint: APIEndpoint::GetChecks,
+                                method: Method::GET,
+                            },
+                            Some(&params),
+                        )
+                        .await?;
+                    Ok(response.items)
+                }
+            },
+        )
+    }
 }
 
 pub struct CreateCheckBuilder<'a, A = Missing, M = Missing> {
     client: &'a CryptoBot,
+    options: RequestOptions,
     asset: CryptoCurrencyCode,
     amount: Decimal,
     pin_to_user_id: Option<u64>,
     pin_to_username: Option<String>,
+    description: Option<String>,
+    metadata: BTreeMap<String, String>,
+    balance_reserve: Option<Decimal>,
     _state: PhantomData<(A, M)>,
 }
 
@@ -130,10 +254,14 @@ impl<'a> CreateCheckBuilder<'a, Missing, Missing> {
     pub fn new(client: &'a CryptoBot) -> Self {
         Self {
             client,
+            options: RequestOptions::default(),
             asset: CryptoCurrencyCode::Ton,
             amount: dec!(0),
             pin_to_user_id: None,
             pin_to_username: None,
+            description: None,
+            metadata: BTreeMap::new(),
+            balance_reserve: None,
             _state: PhantomData,
         }
     }
@@ -172,32 +300,112 @@ impl<'a, A, M> CreateCheckBuilder<'a, A, M> {
         self
     }
 
+    /// Set a description kept with the created check.
+    /// Optional. The API has no check description, so it is only stored locally in `Check::description`.
+    pub fn description(mut self, description: impl Into<String>) -> Self {
+        self.description = Some(description.into());
+        self
+    }
+
+    /// Add a metadata entry kept with the created check, e.g. an order ID.
+    /// Optional. Only stored locally in `Check::metadata`, never sent to the API.
+    pub fn metadata(mut self, key: impl Into<String>, value: impl Into<String>) -> Self {
+        self.metadata.insert(key.into(), value.into());
+        self
+    }
+
+    /// Verify the available balance of the asset covers the check before creating it.
+    /// Optional. Fails with `CryptoBotError::InsufficientBalance` instead of an opaque API error.
+    pub fn verify_balance(mut self) -> Self {
+        self.balance_reserve.get_or_insert(Decimal::ZERO);
+        self
+    }
+
+    /// Set an amount of the asset the check must leave untouched, e.g. for pending payouts.
+    /// Optional. Implies `verify_balance`. Must not be negative.
+    pub fn balance_reserve(mut self, reserve: impl IntoDecimal) -> Self {
+        self.balance_reserve = Some(reserve.into_decimal());
+        self
+    }
+
+    /// Sends the request to `base_url` instead of the client's base URL, e.g. a mirror of the API.
+    /// Optional. The URL is validated when the request is executed.
+    pub fn base_url_override(mut self, base_url: impl Into<String>) -> Self {
+        self.options.base_url = Some(base_url.into());
+        self
+    }
+
     fn transform<A2, M2>(self) -> CreateCheckBuilder<'a, A2, M2> {
         CreateCheckBuilder {
             client: self.client,
+            options: self.options,
             asset: self.asset,
             amount: self.amount,
             pin_to_user_id: self.pin_to_user_id,
             pin_to_username: self.pin_to_username,
+            description: self.description,
+            metadata: self.metadata,
+            balance_reserve: self.balance_reserve,
             _state: PhantomData,
         }
     }
 }
 
+/// Getters for the current draft state, prefixed with `get_` because the setters use the plain field names.
+impl<'a, A: FieldState, M: FieldState> CreateCheckBuilder<'a, A, M> {
+    /// Returns the asset, if set.
+    pub fn get_asset(&self) -> Option<&CryptoCurrencyCode> {
+        A::IS_SET.then_some(&self.asset)
+    }
+
+    /// Returns the amount, if set.
+    pub fn get_amount(&self) -> Option<Decimal> {
+        M::IS_SET.then_some(self.amount)
+    }
+
+    /// Returns the user ID the check is pinned to, if set.
+    pub fn get_pin_to_user_id(&self) -> Option<u64> {
+        self.pin_to_user_id
+    }
+
+    /// Returns the username the check is pinned to, if set.
+    pub fn get_pin_to_username(&self) -> Option<&str> {
+        self.pin_to_username.as_deref()
+    }
+
+    /// Returns the local description, if set.
+    pub fn get_description(&self) -> Option<&str> {
+        self.description.as_deref()
+    }
+
+    /// Returns the local metadata.
+    pub fn get_metadata(&self) -> &BTreeMap<String, String> {
+        &self.metadata
+    }
+
+    /// Returns the balance reserve, if the balance is verified.
+    pub fn get_balance_reserve(&self) -> Option<Decimal> {
+        self.balance_reserve
+    }
+}
+
 impl<'a> FieldValidate for CreateCheckBuilder<'a, Set, Set> {
     fn validate(&self) -> CryptoBotResult<()> {
-        if self.amount <= Decimal::ZERO {
+        validate_positive_amount(self.amount)?;
+
+        if self.balance_reserve.is_some_and(|reserve| reserve < Decimal::ZERO) {
             return Err(CryptoBotError::ValidationError {
                 kind: ValidationErrorKind::Range,
-                message: "Amount must be greater than 0".to_string(),
-                field: Some("amount".to_string()),
+                code: ValidationCode::AmountRange,
+                message: "Balance reserve must not be negative".to_string(),
+                field: Some("balance_reserve".to_string()),
             });
         }
+
         Ok(())
     }
 }
 
-#[async_trait]
 impl<'a> ContextValidate for CreateCheckBuilder<'a, Set, Set> {
     async fn validate_with_context(&self, ctx: &ValidationContext) -> CryptoBotResult<()> {
         validate_amount(&self.amount, &self.asset, ctx).await
@@ -208,31 +416,57 @@ impl<'a> CreateCheckBuilder<'a, Set, Set> {
     /// Executes the request to create the check
     pub async fn execute(self) -> CryptoBotResult<Check> {
         self.validate()?;
+        self.client.check_assets([&self.asset])?;
 
-        let exchange_rates = self.client.get_exchange_rates().execute().await?;
+        let exchange_rates = self.client.exchange_rates().await?;
         let ctx = ValidationContext { exchange_rates };
         self.validate_with_context(&ctx).await?;
 
+        if let Some(reserve) = self.balance_reserve {
+            self.verify_available_balance(reserve, &ctx.exchange_rates).await?;
+        }
+
+        let amount = PositiveAmount::for_asset(self.amount, &self.asset)?;
+
         let params = CreateCheckParams {
             asset: self.asset,
-            amount: self.amount,
+            amount,
             pin_to_user_id: self.pin_to_user_id,
             pin_to_username: self.pin_to_username,
         };
 
-        self.client
-            .make_request(
+        let mut check: Check = self
+            .client
+            .make_request_with(
+                &self.options,
                 &APIMethod {
                     endpoint: APIEndpoint::CreateCheck,
                     method: Method::POST,
                 },
                 Some(&params),
             )
-            .await
+            .await?;
+
+        check.description = self.description;
+        check.metadata = self.metadata;
+        Ok(check)
+    }
+
+    async fn verify_available_balance(&self, reserve: Decimal, rates: &[ExchangeRate]) -> CryptoBotResult<()> {
+        let balances = self.client.get_balance().execute().await?;
+        FundsCheck::new(
+            self.asset.clone(),
+            self.amount,
+            None,
+            reserve,
+            &balances,
+            rates,
+            self.client.rounding,
+        )
+        .ensure_sufficient()
     }
 }
 
-#[async_trait]
 impl CheckAPI for CryptoBot {
     /// Creates a new cryptocurrency check
     ///
@@ -259,16 +493,32 @@ impl CheckAPI for CryptoBot {
     fn get_checks(&self) -> GetChecksBuilder<'_> {
         GetChecksBuilder::new(self)
     }
+
+    /// Fetches checks by ID, splitting long ID lists into several concurrent requests
+    ///
+    /// # Returns
+    /// The found checks in the order of `check_ids`, without duplicates
+    async fn get_checks_by_ids(&self, check_ids: Vec<u64>) -> CryptoBotResult<Vec<Check>> {
+        fetch_by_ids(
+            check_ids,
+            |check: &Check| check.check_id,
+            |chunk| {
+                let count = chunk.len() as u16;
+                self.get_checks().check_ids(chunk).count(count).execute()
+            },
+        )
+        .await
+    }
 }
 
 #[cfg(test)]
 mod tests {
-    use futures::executor::block_on;
+    use futures::{executor::block_on, StreamExt, TryStreamExt};
     use mockito::{Matcher, Mock};
     use rust_decimal_macros::dec;
     use serde_json::json;
 
-    use crate::{models::CryptoCurrencyCode, utils::test_utils::TestContext};
+    use crate::{error::ValidationErrorKind, fixtures, models::CryptoCurrencyCode, utils::test_utils::TestContext};
 
     use super::*;
 
@@ -464,6 +714,66 @@ mod tests {
         assert_eq!(check.amount, dec!(10.0));
     }
 
+    #[test]
+    fn test_create_check_verifies_balance() {
+        let mut ctx = TestContext::new();
+        let _rates = ctx.mock_exchange_rates_response();
+        let _balance = ctx.mock_balance_response();
+        let create = ctx.mock_create_check_response().expect(1);
+
+        let client = CryptoBot::builder()
+            .api_token("test_token")
+            .base_url(ctx.server.url())
+            .build()
+            .unwrap();
+
+        let result = ctx.run(async {
+            client
+                .create_check()
+                .asset(CryptoCurrencyCode::Ton)
+                .amount(dec!(10))
+                .balance_reserve(dec!(95))
+                .execute()
+                .await
+        });
+
+        match result {
+            Err(CryptoBotError::InsufficientBalance {
+                asset,
+                required,
+                available,
+            }) => {
+                assert_eq!(asset, CryptoCurrencyCode::Ton);
+                assert_eq!(required, dec!(105));
+                assert_eq!(available, dec!(100.5));
+            }
+            _ => panic!("Expected InsufficientBalance error"),
+        }
+
+        let result = ctx.run(async {
+            client
+                .create_check()
+                .asset(CryptoCurrencyCode::Ton)
+                .amount(dec!(10))
+                .verify_balance()
+                .execute()
+                .await
+        });
+        assert!(result.is_ok());
+        create.assert();
+
+        let result = ctx.run(async {
+            client
+                .create_check()
+                .asset(CryptoCurrencyCode::Ton)
+                .amount(dec!(10))
+                .balance_reserve(dec!(-1))
+                .execute()
+                .await
+        });
+        assert_eq!(result.unwrap_err().validation_code(), Some(ValidationCode::AmountRange));
+    }
+
     #[test]
     fn test_get_checks_without_params() {
         let mut ctx = TestContext::new();
@@ -577,6 +887,43 @@ mod tests {
         assert_eq!(check.check_id, 321);
     }
 
+    #[test]
+    fn test_create_check_keeps_local_metadata() {
+        let mut ctx = TestContext::new();
+        let _rates = ctx.mock_exchange_rates_response();
+        // Only the API fields are sent
+        let m = ctx
+            .server
+            .mock("POST", "/createCheck")
+            .match_body(Matcher::Json(json!({ "asset": "TON", "amount": "10" })))
+            .with_body(fixtures::api_response(fixtures::CHECK))
+            .create();
+
+        let client = CryptoBot::builder()
+            .api_token("test_token")
+            .base_url(ctx.server.url())
+            .build()
+            .unwrap();
+
+        let check = ctx
+            .run(async {
+                client
+                    .create_check()
+                    .asset(CryptoCurrencyCode::Ton)
+                    .amount(dec!(10))
+                    .description("Referral bonus")
+                    .metadata("order_id", "42")
+                    .execute()
+                    .await
+            })
+            .unwrap();
+
+        m.assert();
+        assert_eq!(check.description.as_deref(), Some("Referral bonus"));
+        assert_eq!(check.metadata.get("order_id").map(String::as_str), Some("42"));
+        assert!(fixtures::check().metadata.is_empty());
+    }
+
     #[test]
     fn test_get_checks_invalid_count() {
         let ctx = TestContext::new();
@@ -597,6 +944,94 @@ mod tests {
         ));
     }
 
+    #[test]
+    fn test_get_checks_stream_pages_until_limit() {
+        let mut ctx = TestContext::new();
+        let mut page = |offset: u32, count: u16, check_ids: &[u64]| {
+            let items: Vec<serde_json::Value> = check_ids
+                .iter()
+                .map(|check_id| {
+                    let mut check: serde_json::Value = serde_json::from_str(fixtures::CHECK).unwrap();
+                    check["check_id"] = json!(check_id);
+                    check
+                })
+                .collect();
+            ctx.server
+                .mock("GET", "/getChecks")
+                .match_body(Matcher::JsonString(
+                    json!({ "status": "active", "offset": offset, "count": count }).to_string(),
+                ))
+                .with_body(json!({ "ok": true, "result": { "items": items } }).to_string())
+                .expect(1)
+                .create()
+        };
+        let pages = [page(1, 2, &[1, 2]), page(3, 2, &[3, 4]), page(5, 1, &[5])];
+
+        let client = CryptoBot::builder()
+            .api_token("test_token")
+            .base_url(ctx.server.url())
+            .build()
+            .unwrap();
+
+        let checks: Vec<u64> = ctx.run(async {
+            let stream = client
+                .get_checks()
+                .status(CheckStatus::Active)
+                .offset(1)
+                .count(2)
+                .limit(5)
+                .into_stream();
+            stream.map_ok(|check| check.check_id).try_collect().await.unwrap()
+        });
+        assert_eq!(checks, [1, 2, 3, 4, 5]);
+        pages.iter().for_each(Mock::assert);
+
+        let invalid: Vec<_> = ctx.run(client.get_checks().count(0).into_stream().collect());
+        assert!(matches!(
+            invalid.as_slice(),
+            [Err(CryptoBotError::ValidationError {
+                kind: ValidationErrorKind::Range,
+                ..
+            })]
+        ));
+    }
+
+    #[test]
+    fn test_get_checks_stream_skips_checks_shifted_by_new_ones() {
+        let mut ctx = TestContext::new();
+        let mut page = |offset: u32, check_ids: &[u64]| {
+            let items: Vec<serde_json::Value> = check_ids
+                .iter()
+                .map(|check_id| {
+                    let mut check: serde_json::Value = serde_json::from_str(fixtures::CHECK).unwrap();
+                    check["check_id"] = json!(check_id);
+                    check
+                })
+                .collect();
+            ctx.server
+                .mock("GET", "/getChecks")
+                .match_body(Matcher::JsonString(json!({ "offset": offset, "count": 2 }).to_string()))
+                .with_body(json!({ "ok": true, "result": { "items": items } }).to_string())
+                .expect(1)
+                .create()
+        };
+        // Check 4 is created after the first page, shifting check 2 into the second one
+        let pages = [page(0, &[3, 2]), page(2, &[2, 1]), page(4, &[])];
+
+        let client = CryptoBot::builder()
+            .api_token("test_token")
+            .base_url(ctx.server.url())
+            .build()
+            .unwrap();
+
+        let checks: Vec<u64> = ctx.run(async {
+            let stream = client.get_checks().count(2).into_stream();
+            stream.map_ok(|check| check.check_id).try_collect().await.unwrap()
+        });
+        assert_eq!(checks, [3, 2, 1]);
+        pages.iter().for_each(Mock::assert);
+    }
+
     #[test]
     fn test_create_check_rejects_non_positive_amount() {
         let ctx = TestContext::new();
@@ -636,4 +1071,23 @@ mod tests {
             })
         ));
     }
+
+    #[test]
+    fn test_create_check_builder_getters() {
+        let client = CryptoBot::test_client();
+
+        let builder = client.create_check();
+        assert_eq!(builder.get_asset(), None);
+        assert_eq!(builder.get_amount(), None);
+
+        let builder = builder
+            .asset(CryptoCurrencyCode::Usdt)
+            .amount(dec!(5))
+            .pin_to_username("user");
+
+        assert_eq!(builder.get_asset(), Some(&CryptoCurrencyCode::Usdt));
+        assert_eq!(builder.get_amount(), Some(dec!(5)));
+        assert_eq!(builder.get_pin_to_user_id(), None);
+        assert_eq!(builder.get_pin_to_username(), Some("user"));
+    }
 }
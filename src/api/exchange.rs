@@ -1,7 +1,9 @@
-use async_trait::async_trait;
+use chrono::Utc;
 
+#[cfg(feature = "tokio")]
+use crate::client::RetryConfig;
 use crate::{
-    client::CryptoBot,
+    client::{CryptoBot, RequestOptions},
     error::CryptoBotResult,
     models::{APIEndpoint, APIMethod, ExchangeRate, Method},
 };
@@ -10,11 +12,22 @@ use super::ExchangeRateAPI;
 
 pub struct GetExchangeRatesBuilder<'a> {
     client: &'a CryptoBot,
+    options: RequestOptions,
 }
 
 impl<'a> GetExchangeRatesBuilder<'a> {
     pub fn new(client: &'a CryptoBot) -> Self {
-        Self { client }
+        Self {
+            client,
+            options: RequestOptions::default(),
+        }
+    }
+
+    /// Sends the request to `base_url` instead of the client's base URL, e.g. a mirror of the API.
+    /// Optional. The URL is validated when the request is executed.
+    pub fn base_url_override(mut self, base_url: impl Into<String>) -> Self {
+        self.options.base_url = Some(base_url.into());
+        self
     }
 
     /// Executes the request to get current exchange rates
@@ -24,19 +37,35 @@ impl<'a> GetExchangeRatesBuilder<'a> {
             return Ok(rates.clone());
         }
 
-        self.client
-            .make_request(
+        let mut rates: Vec<ExchangeRate> = self
+            .client
+            .make_request_with(
+                &self.options,
                 &APIMethod {
                     endpoint: APIEndpoint::GetExchangeRates,
                     method: Method::GET,
                 },
                 None::<&()>,
             )
-            .await
+            .await?;
+
+        let fetched_at = Utc::now();
+        for rate in &mut rates {
+            rate.fetched_at = Some(fetched_at);
+        }
+
+        self.client.metadata.set_exchange_rates(rates.clone());
+        Ok(rates)
+    }
+
+    /// Executes the request, retrying it with `retry` instead of the client's retry configuration
+    #[cfg(feature = "tokio")]
+    pub async fn execute_with_retry(mut self, retry: RetryConfig) -> CryptoBotResult<Vec<ExchangeRate>> {
+        self.options.retry = Some(retry);
+        self.execute().await
     }
 }
 
-#[async_trait]
 impl ExchangeRateAPI for CryptoBot {
     /// Gets current exchange rates for all supported cryptocurrencies
     ///
@@ -135,6 +164,7 @@ mod tests {
         assert_eq!(exchange_rates[0].source, CryptoCurrencyCode::Ton);
         assert_eq!(exchange_rates[0].target, FiatCurrencyCode::Usd);
         assert_eq!(exchange_rates[0].rate, dec!(3.70824926));
+        assert!(exchange_rates.iter().all(|rate| rate.fetched_at().is_some()));
     }
 
     #[test]
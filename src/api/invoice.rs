@@ -1,31 +1,48 @@
-use async_trait::async_trait;
+use chrono::{DateTime, Utc};
 use std::marker::PhantomData;
 
 use rust_decimal::Decimal;
 
+#[cfg(feature = "tokio")]
+use crate::client::RetryConfig;
 use crate::utils::types::IntoDecimal;
 use crate::{
-    client::CryptoBot,
-    error::{CryptoBotError, CryptoBotResult, ValidationErrorKind},
+    client::{CryptoBot, IdKind, RequestOptions},
+    error::{CryptoBotError, CryptoBotResult, NetworkPhase, ValidationCode, ValidationErrorKind},
     models::{
-        APIEndpoint, APIMethod, CreateInvoiceParams, CryptoCurrencyCode, CurrencyType, DeleteInvoiceParams,
-        FiatCurrencyCode, GetInvoicesParams, GetInvoicesResponse, Invoice, InvoiceStatus, Method, Missing,
-        PayButtonName, Set, SwapToAssets,
+        recovery_marker, APIEndpoint, APIMethod, CreateInvoiceParams, CreatedInvoice, CryptoCurrencyCode, CurrencyType,
+        DeleteInvoiceParams, ExchangeRate, ExchangeRates, FiatCurrencyCode, FieldState, GetInvoicesParams,
+        GetInvoicesResponse, Invoice, InvoiceOrderBy, InvoiceStatus, Method, Missing, PayButtonName, PositiveAmount,
+        Receipt, Rounding, Set, SortDirection, SwapToAssets,
     },
-    validation::{validate_amount, validate_count, ContextValidate, FieldValidate, ValidationContext},
+    validation::{
+        validate_amount, validate_fiat_amount, ContextValidate, FieldValidate, InvoiceFieldRules, ValidationContext,
+    },
+    webhook::sign_payload,
 };
 
-use super::ExchangeRateAPI;
-use super::InvoiceAPI;
+use super::{fetch_by_ids, InvoiceAPI};
 
 pub struct DeleteInvoiceBuilder<'a> {
     client: &'a CryptoBot,
+    options: RequestOptions,
     invoice_id: u64,
 }
 
 impl<'a> DeleteInvoiceBuilder<'a> {
     pub fn new(client: &'a CryptoBot, invoice_id: u64) -> Self {
-        Self { client, invoice_id }
+        Self {
+            client,
+            invoice_id,
+            options: RequestOptions::default(),
+        }
+    }
+
+    /// Sends the request to `base_url` instead of the client's base URL, e.g. a mirror of the API.
+    /// Optional. The URL is validated when the request is executed.
+    pub fn base_url_override(mut self, base_url: impl Into<String>) -> Self {
+        self.options.base_url = Some(base_url.into());
+        self
     }
 
     /// Executes the request to delete the invoice
@@ -34,7 +51,8 @@ impl<'a> DeleteInvoiceBuilder<'a> {
             invoice_id: self.invoice_id,
         };
         self.client
-            .make_request(
+            .make_request_with(
+                &self.options,
                 &APIMethod {
                     endpoint: APIEndpoint::DeleteInvoice,
                     method: Method::DELETE,
@@ -47,17 +65,58 @@ impl<'a> DeleteInvoiceBuilder<'a> {
 
 pub struct GetInvoicesBuilder<'a> {
     client: &'a CryptoBot,
+    options: RequestOptions,
     params: GetInvoicesParams,
+    clamp_counts: Option<bool>,
+    after_invoice_id: Option<u64>,
+    filters: InvoiceFilters,
+    order: Option<(InvoiceOrderBy, SortDirection)>,
+}
+
+/// Filters applied to fetched invoices, for fields the API cannot filter on
+#[derive(Debug, Default)]
+struct InvoiceFilters {
+    only_with_comments: bool,
+    anonymous: Option<bool>,
+    paid_between: Option<(DateTime<Utc>, DateTime<Utc>)>,
+}
+
+impl InvoiceFilters {
+    fn matches(&self, invoice: &Invoice) -> bool {
+        (!self.only_with_comments
+            || invoice
+                .comment
+                .as_deref()
+                .is_some_and(|comment| !comment.trim().is_empty()))
+            && self
+                .anonymous
+                .is_none_or(|anonymous| invoice.paid_anonymously == Some(anonymous))
+            && self
+                .paid_between
+                .is_none_or(|(from, to)| invoice.paid_at.is_some_and(|paid_at| from <= paid_at && paid_at < to))
+    }
 }
 
 impl<'a> GetInvoicesBuilder<'a> {
     pub fn new(client: &'a CryptoBot) -> Self {
         Self {
             client,
+            options: RequestOptions::default(),
             params: GetInvoicesParams::default(),
+            clamp_counts: None,
+            after_invoice_id: None,
+            filters: InvoiceFilters::default(),
+            order: None,
         }
     }
 
+    /// Sends the request to `base_url` instead of the client's base URL, e.g. a mirror of the API.
+    /// Optional. The URL is validated when the request is executed.
+    pub fn base_url_override(mut self, base_url: impl Into<String>) -> Self {
+        self.options.base_url = Some(base_url.into());
+        self
+    }
+
     /// Set the asset for the invoices.
     /// Optional. Defaults to all currencies.
     pub fn asset(mut self, asset: CryptoCurrencyCode) -> Self {
@@ -101,15 +160,140 @@ impl<'a> GetInvoicesBuilder<'a> {
         self
     }
 
+    /// Clamp the count into the range 1-1000 instead of rejecting it.
+    /// Optional. Overrides the client setting.
+    pub fn clamp_counts(mut self, clamp_counts: bool) -> Self {
+        self.clamp_counts = Some(clamp_counts);
+        self
+    }
+
+    /// Only return invoices with an ID greater than `invoice_id`, e.g. the last one seen by a sync job.
+    ///
+    /// Emulates keyset pagination on top of the offset based API: pages of `count` invoices
+    /// (100 by default) are fetched until one reaches an invoice at or below the cursor, relying on
+    /// the API listing newest invoices first. Invoices shifted into the next page by new arrivals are
+    /// returned once. The result is sorted by ascending ID, so its last invoice is the next cursor.
+    pub fn after_invoice_id(mut self, invoice_id: u64) -> Self {
+        self.after_invoice_id = Some(invoice_id);
+        self
+    }
+
+    /// Only return invoices paid with a comment from the user.
+    /// Optional. Applied to the fetched invoices, so fewer than `count` invoices may be returned.
+    pub fn only_with_comments(mut self) -> Self {
+        self.filters.only_with_comments = true;
+        self
+    }
+
+    /// Only return invoices paid anonymously, or only those paid with the user's name if `false`.
+    /// Optional. Applied to the fetched invoices, so fewer than `count` invoices may be returned.
+    /// Unpaid invoices never match.
+    pub fn only_anonymous(mut self, anonymous: bool) -> Self {
+        self.filters.anonymous = Some(anonymous);
+        self
+    }
+
+    /// Only return invoices paid at or after `from` and before `to`.
+    /// Optional. Applied to the fetched invoices, so fewer than `count` invoices may be returned.
+    pub fn paid_between(mut self, from: DateTime<Utc>, to: DateTime<Utc>) -> Self {
+        self.filters.paid_between = Some((from, to));
+        self
+    }
+
+    /// Order the returned invoices by `field` in `direction`.
+    /// Optional. Applied to the fetched invoices, as the API lists newest invoices first. Overrides
+    /// the ascending ID order of `after_invoice_id`.
+    pub fn order_by(mut self, field: InvoiceOrderBy, direction: SortDirection) -> Self {
+        self.order = Some((field, direction));
+        self
+    }
+
+    /// Returns the asset filter, if set.
+    pub fn get_asset(&self) -> Option<&CryptoCurrencyCode> {
+        self.params.asset.as_ref()
+    }
+
+    /// Returns whether only invoices with comments are returned.
+    pub fn get_only_with_comments(&self) -> bool {
+        self.filters.only_with_comments
+    }
+
+    /// Returns the anonymity filter, if set.
+    pub fn get_only_anonymous(&self) -> Option<bool> {
+        self.filters.anonymous
+    }
+
+    /// Returns the payment date range, if set.
+    pub fn get_paid_between(&self) -> Option<(DateTime<Utc>, DateTime<Utc>)> {
+        self.filters.paid_between
+    }
+
+    /// Returns the keyset cursor, if set.
+    pub fn get_after_invoice_id(&self) -> Option<u64> {
+        self.after_invoice_id
+    }
+
+    /// Returns the ordering, if set.
+    pub fn get_order_by(&self) -> Option<(InvoiceOrderBy, SortDirection)> {
+        self.order
+    }
+
+    /// Returns the fiat filter, if set.
+    pub fn get_fiat(&self) -> Option<&FiatCurrencyCode> {
+        self.params.fiat.as_ref()
+    }
+
+    /// Returns the invoice IDs filter, if set.
+    pub fn get_invoice_ids(&self) -> Option<&[u64]> {
+        self.params.invoice_ids.as_deref()
+    }
+
+    /// Returns the status filter, if set.
+    pub fn get_status(&self) -> Option<&InvoiceStatus> {
+        self.params.status.as_ref()
+    }
+
+    /// Returns the offset, if set.
+    pub fn get_offset(&self) -> Option<u32> {
+        self.params.offset
+    }
+
+    /// Returns the count, if set.
+    pub fn get_count(&self) -> Option<u16> {
+        self.params.count
+    }
+
     /// Executes the request to get invoices
-    pub async fn execute(self) -> CryptoBotResult<Vec<Invoice>> {
+    pub async fn execute(mut self) -> CryptoBotResult<Vec<Invoice>> {
         if let Some(count) = self.params.count {
-            validate_count(count)?;
+            self.params.count = Some(self.client.resolve_count(count, self.clamp_counts)?);
         }
 
+        let filters = std::mem::take(&mut self.filters);
+        let order = self.order;
+        let mut invoices = match self.after_invoice_id {
+            Some(cursor) => self.fetch_after(cursor).await?,
+            None => self.fetch_page().await?,
+        };
+        invoices.retain(|invoice| filters.matches(invoice));
+        if let Some((field, direction)) = order {
+            field.sort(&mut invoices, direction);
+        }
+        Ok(invoices)
+    }
+
+    /// Executes the request, retrying it with `retry` instead of the client's retry configuration
+    #[cfg(feature = "tokio")]
+    pub async fn execute_with_retry(mut self, retry: RetryConfig) -> CryptoBotResult<Vec<Invoice>> {
+        self.options.retry = Some(retry);
+        self.execute().await
+    }
+
+    async fn fetch_page(&self) -> CryptoBotResult<Vec<Invoice>> {
         let response: GetInvoicesResponse = self
             .client
-            .make_request(
+            .make_request_with(
+                &self.options,
                 &APIMethod {
                     endpoint: APIEndpoint::GetInvoices,
                     method: Method::GET,
@@ -120,15 +304,43 @@ impl<'a> GetInvoicesBuilder<'a> {
 
         Ok(response.items)
     }
+
+    async fn fetch_after(mut self, cursor: u64) -> CryptoBotResult<Vec<Invoice>> {
+        let page_size = self.params.count.unwrap_or(100);
+        self.params.count = Some(page_size);
+
+        let mut invoices = std::collections::BTreeMap::new();
+        loop {
+            let page = self.fetch_page().await?;
+            let fetched = page.len();
+            let reached_cursor = page.iter().any(|invoice| invoice.invoice_id <= cursor);
+
+            for invoice in page.into_iter().filter(|invoice| invoice.invoice_id > cursor) {
+                invoices.entry(invoice.invoice_id).or_insert(invoice);
+            }
+
+            if reached_cursor || fetched < page_size as usize {
+                break;
+            }
+
+            self.params.offset = Some(self.params.offset.unwrap_or(0) + fetched as u32);
+        }
+
+        Ok(invoices.into_values().collect())
+    }
 }
 
 pub struct CreateInvoiceBuilder<'a, A = Missing, C = Missing, P = Missing, U = Missing> {
     client: &'a CryptoBot,
+    options: RequestOptions,
     currency_type: Option<CurrencyType>,
     asset: Option<CryptoCurrencyCode>,
     fiat: Option<FiatCurrencyCode>,
     accept_asset: Option<Vec<CryptoCurrencyCode>>,
     amount: Decimal,
+    fiat_price: Option<(Decimal, FiatCurrencyCode)>,
+    markup_percent: Decimal,
+    rounding: Option<Rounding>,
     description: Option<String>,
     hidden_message: Option<String>,
     paid_btn_name: Option<PayButtonName>,
@@ -138,6 +350,7 @@ pub struct CreateInvoiceBuilder<'a, A = Missing, C = Missing, P = Missing, U = M
     allow_comments: Option<bool>,
     allow_anonymous: Option<bool>,
     expires_in: Option<u32>,
+    recovery_key: Option<String>,
     _state: PhantomData<(A, C, P, U)>,
 }
 
@@ -145,11 +358,15 @@ impl<'a> CreateInvoiceBuilder<'a, Missing, Missing, Missing, Missing> {
     pub fn new(client: &'a CryptoBot) -> Self {
         Self {
             client,
+            options: RequestOptions::default(),
             currency_type: Some(CurrencyType::Crypto),
             asset: None,
             fiat: None,
             accept_asset: None,
             amount: Decimal::ZERO,
+            fiat_price: None,
+            markup_percent: Decimal::ZERO,
+            rounding: None,
             description: None,
             hidden_message: None,
             paid_btn_name: None,
@@ -159,6 +376,7 @@ impl<'a> CreateInvoiceBuilder<'a, Missing, Missing, Missing, Missing> {
             allow_comments: None,
             allow_anonymous: None,
             expires_in: None,
+            recovery_key: None,
             _state: PhantomData,
         }
     }
@@ -170,6 +388,36 @@ impl<'a, C, P, U> CreateInvoiceBuilder<'a, Missing, C, P, U> {
         self.amount = amount.into_decimal();
         self.transform()
     }
+
+    /// Set the price in a fiat currency, converted to the amount of the crypto `asset` when the invoice is created.
+    ///
+    /// For shops that price in fiat but settle in crypto. The conversion uses the cached exchange rates,
+    /// plus the `markup_percent` if set, and is rounded to the precision of the asset with the `rounding`
+    /// strategy, which defaults to the client's and rounds up unless configured otherwise.
+    /// Fails with `ValidationCode::MissingField` if the invoice has no crypto asset.
+    ///
+    /// # Example
+    /// ```no_run
+    /// # use crypto_pay_api::prelude::*;
+    /// # async fn example(client: CryptoBot) -> Result<(), CryptoBotError> {
+    /// let invoice = client
+    ///     .create_invoice()
+    ///     .asset(CryptoCurrencyCode::Ton)
+    ///     .amount_fiat(dec!(19.99), FiatCurrencyCode::Eur)
+    ///     .markup_percent(dec!(1.5))
+    ///     .execute()
+    ///     .await?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn amount_fiat(
+        mut self,
+        amount: impl IntoDecimal,
+        fiat: FiatCurrencyCode,
+    ) -> CreateInvoiceBuilder<'a, Set, C, P, U> {
+        self.fiat_price = Some((amount.into_decimal(), fiat));
+        self.transform()
+    }
 }
 
 impl<'a, A, P, U> CreateInvoiceBuilder<'a, A, Missing, P, U> {
@@ -205,12 +453,28 @@ impl<'a, A, C> CreateInvoiceBuilder<'a, A, C, Set, Missing> {
 }
 
 impl<'a, A, C, P, U> CreateInvoiceBuilder<'a, A, C, P, U> {
-    /// Set the accepted assets for the invoice.
+    /// Set the assets that can pay an invoice priced in fiat, e.g. from `CryptoBot::supported_accept_assets`.
+    ///
+    /// Only available for fiat invoices: executing a crypto invoice with accepted assets fails with
+    /// `ValidationCode::FieldConflict` instead of sending a parameter the API ignores.
     pub fn accept_asset(mut self, accept_asset: Vec<CryptoCurrencyCode>) -> Self {
         self.accept_asset = Some(accept_asset);
         self
     }
 
+    /// Set the markup added to a price set with `amount_fiat`, in percent, e.g. `dec!(2)` to cover volatility.
+    pub fn markup_percent(mut self, markup_percent: impl IntoDecimal) -> Self {
+        self.markup_percent = markup_percent.into_decimal();
+        self
+    }
+
+    /// Set how a price set with `amount_fiat` is rounded to the precision of the asset.
+    /// Optional. Defaults to the client's `rounding`.
+    pub fn rounding(mut self, rounding: Rounding) -> Self {
+        self.rounding = Some(rounding);
+        self
+    }
+
     /// Set the description for the invoice.
     pub fn description(mut self, description: impl Into<String>) -> Self {
         self.description = Some(description.into());
@@ -229,6 +493,25 @@ impl<'a, A, C, P, U> CreateInvoiceBuilder<'a, A, C, P, U> {
         self
     }
 
+    /// Set the payload for the invoice, signed with your own secret using `sign_payload`.
+    /// Webhook handlers built with `with_payload_hmac(secret)` verify and strip the signature.
+    pub fn signed_payload(mut self, payload: &str, secret: &str) -> Self {
+        self.payload = Some(sign_payload(secret, payload));
+        self
+    }
+
+    /// Recover the invoice after an ambiguous failure, such as a timeout, using `key` as its marker.
+    ///
+    /// The marker is appended to the payload, counting towards its 4096 characters; read the payload
+    /// without it with `Invoice::app_payload`. When the request fails after it may have reached the
+    /// API, the 100 newest invoices are searched for the marker and the invoice found is returned
+    /// instead of the error. If none is found, the error is returned and creating the invoice again
+    /// is safe. Reuse the key when retrying, so an invoice created in the meantime is still found.
+    pub fn recovery_key(mut self, key: impl Into<String>) -> Self {
+        self.recovery_key = Some(key.into());
+        self
+    }
+
     /// Set the allow comments for the invoice.
     pub fn allow_comments(mut self, allow_comments: bool) -> Self {
         self.allow_comments = Some(allow_comments);
@@ -247,14 +530,63 @@ impl<'a, A, C, P, U> CreateInvoiceBuilder<'a, A, C, P, U> {
         self
     }
 
+    /// Sends the request to `base_url` instead of the client's base URL, e.g. a mirror of the API.
+    /// Optional. The URL is validated when the request is executed.
+    pub fn base_url_override(mut self, base_url: impl Into<String>) -> Self {
+        self.options.base_url = Some(base_url.into());
+        self
+    }
+
+    /// Converts the price set with `amount_fiat` to the amount of the asset
+    fn apply_fiat_price(&mut self, rates: &[ExchangeRate]) -> CryptoBotResult<()> {
+        let Some((price, fiat)) = &self.fiat_price else {
+            return Ok(());
+        };
+
+        let asset = self.asset.as_ref().ok_or_else(|| CryptoBotError::ValidationError {
+            kind: ValidationErrorKind::Missing,
+            code: ValidationCode::MissingField,
+            message: "amount_fiat requires a crypto asset".to_string(),
+            field: Some("asset".to_string()),
+        })?;
+
+        let price = price * (Decimal::ONE_HUNDRED + self.markup_percent) / Decimal::ONE_HUNDRED;
+        let rounding = self.rounding.unwrap_or(self.client.rounding);
+        self.amount = rates
+            .find_rate(asset, fiat)
+            .and_then(|rate| rate.convert_to_source(price, rounding))
+            .ok_or_else(|| CryptoBotError::ValidationError {
+                kind: ValidationErrorKind::Missing,
+                code: ValidationCode::ExchangeRateMissing,
+                message: "exchange_rate_not_found".to_string(),
+                field: Some("exchange_rate".to_string()),
+            })?;
+        Ok(())
+    }
+
+    /// Appends the marker of the recovery key, if set, to the payload sent to the API
+    fn append_recovery_marker(&mut self) {
+        if let Some(key) = &self.recovery_key {
+            self.payload = Some(format!(
+                "{}{}",
+                self.payload.take().unwrap_or_default(),
+                recovery_marker(key)
+            ));
+        }
+    }
+
     fn transform<A2, C2, P2, U2>(self) -> CreateInvoiceBuilder<'a, A2, C2, P2, U2> {
         CreateInvoiceBuilder {
             client: self.client,
+            options: self.options,
             currency_type: self.currency_type,
             asset: self.asset,
             fiat: self.fiat,
             accept_asset: self.accept_asset,
             amount: self.amount,
+            fiat_price: self.fiat_price,
+            markup_percent: self.markup_percent,
+            rounding: self.rounding,
             description: self.description,
             hidden_message: self.hidden_message,
             paid_btn_name: self.paid_btn_name,
@@ -264,89 +596,152 @@ impl<'a, A, C, P, U> CreateInvoiceBuilder<'a, A, C, P, U> {
             allow_comments: self.allow_comments,
             allow_anonymous: self.allow_anonymous,
             expires_in: self.expires_in,
+            recovery_key: self.recovery_key,
             _state: PhantomData,
         }
     }
 }
 
-impl<'a, A, C, P, U> FieldValidate for CreateInvoiceBuilder<'a, A, C, P, U> {
-    fn validate(&self) -> CryptoBotResult<()> {
-        if self.amount <= Decimal::ZERO {
-            return Err(CryptoBotError::ValidationError {
-                kind: ValidationErrorKind::Range,
-                message: "Amount must be greater than 0".to_string(),
-                field: Some("amount".to_string()),
-            });
-        }
+/// Getters for the current draft state, e.g. to display it in an interactive flow.
+/// They are prefixed with `get_` because the setters already use the plain field names.
+impl<'a, A: FieldState, C, P, U> CreateInvoiceBuilder<'a, A, C, P, U> {
+    /// Returns the rounding of a price set with `amount_fiat`, if set.
+    pub fn get_rounding(&self) -> Option<Rounding> {
+        self.rounding
+    }
 
-        if let Some(desc) = &self.description {
-            if desc.chars().count() > 1024 {
-                return Err(CryptoBotError::ValidationError {
-                    kind: ValidationErrorKind::Range,
-                    message: "description too long".to_string(),
-                    field: Some("description".to_string()),
-                });
-            }
-        }
+    /// Returns the recovery key, if set.
+    pub fn get_recovery_key(&self) -> Option<&str> {
+        self.recovery_key.as_deref()
+    }
 
-        if let Some(msg) = &self.hidden_message {
-            if msg.chars().count() > 2048 {
-                return Err(CryptoBotError::ValidationError {
-                    kind: ValidationErrorKind::Range,
-                    message: "hidden_message_too_long".to_string(),
-                    field: Some("hidden_message".to_string()),
-                });
-            }
-        }
+    /// Returns the amount, if set.
+    pub fn get_amount(&self) -> Option<Decimal> {
+        A::IS_SET.then_some(self.amount)
+    }
 
-        if let Some(payload) = &self.payload {
-            if payload.chars().count() > 4096 {
-                return Err(CryptoBotError::ValidationError {
-                    kind: ValidationErrorKind::Range,
-                    message: "payload_too_long".to_string(),
-                    field: Some("payload".to_string()),
-                });
-            }
-        }
+    /// Returns the fiat price set with `amount_fiat`, if set.
+    pub fn get_amount_fiat(&self) -> Option<(Decimal, &FiatCurrencyCode)> {
+        self.fiat_price.as_ref().map(|(amount, fiat)| (*amount, fiat))
+    }
 
-        if let Some(expires_in) = &self.expires_in {
-            if !(1..=2_678_400u32).contains(expires_in) {
-                return Err(CryptoBotError::ValidationError {
-                    kind: ValidationErrorKind::Range,
-                    message: "expires_in_invalid".to_string(),
-                    field: Some("expires_in".to_string()),
-                });
-            }
+    /// Returns the currency type of the invoice.
+    pub fn get_currency_type(&self) -> Option<&CurrencyType> {
+        self.currency_type.as_ref()
+    }
+
+    /// Returns the asset, if set.
+    pub fn get_asset(&self) -> Option<&CryptoCurrencyCode> {
+        self.asset.as_ref()
+    }
+
+    /// Returns the fiat currency, if set.
+    pub fn get_fiat(&self) -> Option<&FiatCurrencyCode> {
+        self.fiat.as_ref()
+    }
+
+    /// Returns the accepted assets, if set.
+    pub fn get_accept_asset(&self) -> Option<&[CryptoCurrencyCode]> {
+        self.accept_asset.as_deref()
+    }
+
+    /// Returns the description, if set.
+    pub fn get_description(&self) -> Option<&str> {
+        self.description.as_deref()
+    }
+
+    /// Returns the hidden message, if set.
+    pub fn get_hidden_message(&self) -> Option<&str> {
+        self.hidden_message.as_deref()
+    }
+
+    /// Returns the paid button name, if set.
+    pub fn get_paid_btn_name(&self) -> Option<&PayButtonName> {
+        self.paid_btn_name.as_ref()
+    }
+
+    /// Returns the paid button URL, if set.
+    pub fn get_paid_btn_url(&self) -> Option<&str> {
+        self.paid_btn_url.as_deref()
+    }
+
+    /// Returns the swap target asset, if set.
+    pub fn get_swap_to(&self) -> Option<&SwapToAssets> {
+        self.swap_to.as_ref()
+    }
+
+    /// Returns the payload, if set.
+    pub fn get_payload(&self) -> Option<&str> {
+        self.payload.as_deref()
+    }
+
+    /// Returns whether comments are allowed, if set.
+    pub fn get_allow_comments(&self) -> Option<bool> {
+        self.allow_comments
+    }
+
+    /// Returns whether anonymous payments are allowed, if set.
+    pub fn get_allow_anonymous(&self) -> Option<bool> {
+        self.allow_anonymous
+    }
+
+    /// Returns the expiration time in seconds, if set.
+    pub fn get_expires_in(&self) -> Option<u32> {
+        self.expires_in
+    }
+}
+
+impl<'a, A, C, P, U> FieldValidate for CreateInvoiceBuilder<'a, A, C, P, U> {
+    fn validate(&self) -> CryptoBotResult<()> {
+        InvoiceFieldRules {
+            amount: self.fiat_price.as_ref().map_or(self.amount, |(price, _)| *price),
+            currency_type: self.currency_type,
+            accept_asset: self.accept_asset.as_deref(),
+            description: self.description.as_deref(),
+            hidden_message: self.hidden_message.as_deref(),
+            paid_btn_url: self.paid_btn_url.as_deref(),
+            payload: self.payload.as_deref(),
+            expires_in: self.expires_in,
         }
-        Ok(())
+        .validate()
     }
 }
 
-#[async_trait]
 impl<'a, C: Sync, P: Sync, U: Sync> ContextValidate for CreateInvoiceBuilder<'a, Set, C, P, U> {
     async fn validate_with_context(&self, ctx: &ValidationContext) -> CryptoBotResult<()> {
-        if let Some(asset) = &self.asset {
-            validate_amount(&self.amount, asset, ctx).await?;
+        match (&self.asset, &self.fiat) {
+            (Some(asset), _) => validate_amount(&self.amount, asset, ctx).await,
+            (None, Some(fiat)) => validate_fiat_amount(&self.amount, fiat, ctx).await,
+            (None, None) => Ok(()),
         }
-        Ok(())
     }
 }
 
 impl<'a> CreateInvoiceBuilder<'a, Set, Set, Missing, Missing> {
     /// Executes the request to create the invoice
-    pub async fn execute(self) -> CryptoBotResult<Invoice> {
+    pub async fn execute(mut self) -> CryptoBotResult<CreatedInvoice> {
+        self.append_recovery_marker();
         self.validate()?;
+        self.client
+            .check_assets(self.asset.iter().chain(self.accept_asset.iter().flatten()))?;
 
-        let exchange_rates = self.client.get_exchange_rates().execute().await?;
+        let exchange_rates = self.client.exchange_rates().await?;
+        self.apply_fiat_price(&exchange_rates)?;
         let ctx = ValidationContext { exchange_rates };
         self.validate_with_context(&ctx).await?;
 
+        let amount = match (&self.asset, &self.fiat) {
+            (Some(asset), _) => PositiveAmount::for_asset(self.amount, asset)?,
+            (None, Some(fiat)) => PositiveAmount::for_fiat(self.amount, fiat)?,
+            (None, None) => PositiveAmount::new(self.amount)?,
+        };
+
         let params = CreateInvoiceParams {
             currency_type: self.currency_type,
             asset: self.asset,
             fiat: self.fiat,
             accept_asset: self.accept_asset,
-            amount: self.amount,
+            amount,
             description: self.description,
             hidden_message: self.hidden_message,
             paid_btn_name: self.paid_btn_name,
@@ -357,43 +752,52 @@ impl<'a> CreateInvoiceBuilder<'a, Set, Set, Missing, Missing> {
             allow_anonymous: self.allow_anonymous,
             expires_in: self.expires_in,
         };
-        self.client
-            .make_request(
-                &APIMethod {
-                    endpoint: APIEndpoint::CreateInvoice,
-                    method: Method::POST,
-                },
-                Some(&params),
-            )
-            .await
+        self.client.run_before_hook(
+            self.client.before_create_invoice.as_ref(),
+            APIEndpoint::CreateInvoice,
+            &params,
+        )?;
+
+        send_create_invoice(self.client, &self.options, &params, self.recovery_key.as_deref()).await
+    }
+
+    /// Creates the invoice, then re-fetches it to confirm the defaults applied by the server
+    ///
+    /// Use it for auditing when optional fields such as `allow_comments` or `allow_anonymous`
+    /// are left unset. Costs an extra `getInvoices` request.
+    pub async fn execute_and_refresh(self) -> CryptoBotResult<CreatedInvoice> {
+        let client = self.client;
+        let options = self.options.clone();
+        let created = self.execute().await?;
+        refresh_invoice(client, options, created.invoice_id).await
     }
 }
 
 impl<'a> CreateInvoiceBuilder<'a, Set, Set, Set, Set> {
     /// Executes the request to create the invoice
-    pub async fn execute(self) -> CryptoBotResult<Invoice> {
+    pub async fn execute(mut self) -> CryptoBotResult<CreatedInvoice> {
+        self.append_recovery_marker();
         self.validate()?;
+        self.client
+            .check_assets(self.asset.iter().chain(self.accept_asset.iter().flatten()))?;
 
-        if let Some(url) = &self.paid_btn_url {
-            if !url.starts_with("https://") && !url.starts_with("http://") {
-                return Err(CryptoBotError::ValidationError {
-                    kind: ValidationErrorKind::Format,
-                    message: "paid_btn_url_invalid".to_string(),
-                    field: Some("paid_btn_url".to_string()),
-                });
-            }
-        }
-
-        let exchange_rates = self.client.get_exchange_rates().execute().await?;
+        let exchange_rates = self.client.exchange_rates().await?;
+        self.apply_fiat_price(&exchange_rates)?;
         let ctx = ValidationContext { exchange_rates };
         self.validate_with_context(&ctx).await?;
 
+        let amount = match (&self.asset, &self.fiat) {
+            (Some(asset), _) => PositiveAmount::for_asset(self.amount, asset)?,
+            (None, Some(fiat)) => PositiveAmount::for_fiat(self.amount, fiat)?,
+            (None, None) => PositiveAmount::new(self.amount)?,
+        };
+
         let params = CreateInvoiceParams {
             currency_type: self.currency_type,
             asset: self.asset,
             fiat: self.fiat,
             accept_asset: self.accept_asset,
-            amount: self.amount,
+            amount,
             description: self.description,
             hidden_message: self.hidden_message,
             paid_btn_name: self.paid_btn_name,
@@ -404,20 +808,126 @@ impl<'a> CreateInvoiceBuilder<'a, Set, Set, Set, Set> {
             allow_anonymous: self.allow_anonymous,
             expires_in: self.expires_in,
         };
+        self.client.run_before_hook(
+            self.client.before_create_invoice.as_ref(),
+            APIEndpoint::CreateInvoice,
+            &params,
+        )?;
 
-        self.client
-            .make_request(
-                &APIMethod {
-                    endpoint: APIEndpoint::CreateInvoice,
-                    method: Method::POST,
-                },
-                Some(&params),
-            )
-            .await
+        send_create_invoice(self.client, &self.options, &params, self.recovery_key.as_deref()).await
+    }
+
+    /// Creates the invoice, then re-fetches it to confirm the defaults applied by the server
+    ///
+    /// Use it for auditing when optional fields such as `allow_comments` or `allow_anonymous`
+    /// are left unset. Costs an extra `getInvoices` request.
+    pub async fn execute_and_refresh(self) -> CryptoBotResult<CreatedInvoice> {
+        let client = self.client;
+        let options = self.options.clone();
+        let created = self.execute().await?;
+        refresh_invoice(client, options, created.invoice_id).await
+    }
+}
+
+/// Sends `createInvoice`, looking up the invoice by its recovery marker when the outcome is unknown
+async fn send_create_invoice(
+    client: &CryptoBot,
+    options: &RequestOptions,
+    params: &CreateInvoiceParams,
+    recovery_key: Option<&str>,
+) -> CryptoBotResult<CreatedInvoice> {
+    let result: CryptoBotResult<(Invoice, _)> = client
+        .make_receipted_request(
+            options,
+            &APIMethod {
+                endpoint: APIEndpoint::CreateInvoice,
+                method: Method::POST,
+            },
+            Some(params),
+        )
+        .await;
+
+    let (mut invoice, receipt) = match (result, recovery_key) {
+        (Ok(created), _) => created,
+        (Err(error), Some(key)) if may_have_been_processed(&error) => {
+            match find_by_recovery_key(client, options, key).await {
+                Some(recovered) => recovered,
+                None => return Err(error),
+            }
+        }
+        (Err(error), _) => return Err(error),
+    };
+    invoice.receipt = receipt;
+
+    Ok(client.created_invoice(invoice).await)
+}
+
+/// Returns true if a failed request may still have been processed by the API
+fn may_have_been_processed(error: &CryptoBotError) -> bool {
+    match error {
+        CryptoBotError::HttpError(_) => error.network_phase() != Some(NetworkPhase::Connect),
+        CryptoBotError::ApiError { code, .. } => *code >= 500,
+        CryptoBotError::ResponseTooLarge { .. } => true,
+        _ => false,
     }
 }
 
-#[async_trait]
+/// Searches the newest invoices for the one carrying the recovery marker of `key`
+///
+/// The recovered invoice is signed as a `getInvoices` result if a receipt key is configured.
+/// A failed search counts as not found, so the original error is returned.
+async fn find_by_recovery_key(
+    client: &CryptoBot,
+    options: &RequestOptions,
+    key: &str,
+) -> Option<(Invoice, Option<Receipt>)> {
+    let marker = recovery_marker(key);
+    let params = GetInvoicesParams {
+        count: Some(RECOVERY_SEARCH_COUNT),
+        ..Default::default()
+    };
+    let response: serde_json::Value = client
+        .make_request_with(
+            options,
+            &APIMethod {
+                endpoint: APIEndpoint::GetInvoices,
+                method: Method::GET,
+            },
+            Some(&params),
+        )
+        .await
+        .ok()?;
+
+    let item = response["items"].as_array()?.iter().find(|item| {
+        item["payload"]
+            .as_str()
+            .is_some_and(|payload| payload.ends_with(&marker))
+    })?;
+    let invoice: Invoice = serde_json::from_value(item.clone()).ok()?;
+    Some((invoice, client.sign_receipt(APIEndpoint::GetInvoices, item)))
+}
+
+/// Number of newest invoices searched for a recovery marker
+const RECOVERY_SEARCH_COUNT: u16 = 100;
+
+/// Fetches a single invoice by ID, failing with `NoResult` if it is not returned
+async fn refresh_invoice(
+    client: &CryptoBot,
+    options: RequestOptions,
+    invoice_id: u64,
+) -> CryptoBotResult<CreatedInvoice> {
+    let mut builder = client.get_invoices().invoice_ids(vec![invoice_id]);
+    builder.options = options;
+    let invoice = builder
+        .execute()
+        .await?
+        .into_iter()
+        .find(|invoice| invoice.invoice_id == invoice_id)
+        .ok_or(CryptoBotError::NoResult)?;
+
+    Ok(client.created_invoice(invoice).await)
+}
+
 impl InvoiceAPI for CryptoBot {
     /// Creates a new cryptocurrency invoice
     ///
@@ -430,6 +940,15 @@ impl InvoiceAPI for CryptoBot {
         CreateInvoiceBuilder::new(self)
     }
 
+    /// Creates an invoice that is recovered instead of duplicated after an ambiguous failure
+    ///
+    /// Same as `create_invoice` with a `recovery_key` generated by the client's `IdGenerator`.
+    /// Keep the key with `get_recovery_key` to retry the creation safely.
+    fn create_invoice_recoverable(&self) -> CreateInvoiceBuilder<'_> {
+        let key = self.generate_id(IdKind::IdempotencyKey);
+        CreateInvoiceBuilder::new(self).recovery_key(key)
+    }
+
     fn delete_invoice(&self, invoice_id: u64) -> DeleteInvoiceBuilder<'_> {
         DeleteInvoiceBuilder::new(self, invoice_id)
     }
@@ -444,6 +963,22 @@ impl InvoiceAPI for CryptoBot {
     fn get_invoices(&self) -> GetInvoicesBuilder<'_> {
         GetInvoicesBuilder::new(self)
     }
+
+    /// Fetches invoices by ID, splitting long ID lists into several concurrent requests
+    ///
+    /// # Returns
+    /// The found invoices in the order of `invoice_ids`, without duplicates
+    async fn get_invoices_by_ids(&self, invoice_ids: Vec<u64>) -> CryptoBotResult<Vec<Invoice>> {
+        fetch_by_ids(
+            invoice_ids,
+            |invoice: &Invoice| invoice.invoice_id,
+            |chunk| {
+                let count = chunk.len() as u16;
+                self.get_invoices().invoice_ids(chunk).count(count).execute()
+            },
+        )
+        .await
+    }
 }
 
 #[cfg(test)]
@@ -454,7 +989,8 @@ mod tests {
     use serde_json::json;
 
     use super::*;
-    use crate::models::{CryptoCurrencyCode, PayButtonName, SwapToAssets};
+    use crate::fixtures;
+    use crate::models::{CryptoCurrencyCode, PayButtonName, PayUrlPreference, SwapToAssets};
     use crate::utils::test_utils::TestContext;
 
     impl TestContext {
@@ -580,8 +1116,8 @@ mod tests {
                 .mock("POST", "/createInvoice")
                 .match_body(Matcher::JsonString(
                     json!({
-                        "currency_type": "crypto",
-                        "asset": "TON",
+                        "currency_type": "fiat",
+                        "fiat": "USD",
                         "amount": "2",
                         "accept_asset": ["TON", "USDT"],
                         "payload": "payload",
@@ -696,60 +1232,304 @@ mod tests {
     }
 
     #[test]
-    fn test_get_invoices_without_params() {
+    fn test_create_invoice_recoverable_finds_invoice_after_server_error() {
         let mut ctx = TestContext::new();
-        let _m = ctx.mock_get_invoices_response();
+        let _rates = ctx.mock_exchange_rates_response();
+        let create = ctx
+            .server
+            .mock("POST", "/createInvoice")
+            .with_status(500)
+            .with_body(r#"{"ok":false,"error":{"code":500,"name":"INTERNAL_SERVER_ERROR"}}"#)
+            .expect(2)
+            .create();
+        let mut invoice: serde_json::Value = serde_json::from_str(fixtures::PAID_INVOICE).unwrap();
+        invoice["payload"] = json!("order_42\u{1e}recovery:key-1");
+        let search = ctx
+            .server
+            .mock("GET", "/getInvoices")
+            .with_body(json!({ "ok": true, "result": { "items": [invoice] } }).to_string())
+            .expect(2)
+            .create();
+
         let client = CryptoBot::builder()
             .api_token("test_token")
             .base_url(ctx.server.url())
+            .receipt_key("audit_key")
             .build()
             .unwrap();
-        let result = ctx.run(async { client.get_invoices().execute().await });
+        let create_invoice = |key: &str| {
+            client
+                .create_invoice()
+                .asset(CryptoCurrencyCode::Ton)
+                .amount(dec!(10.5))
+                .payload("order_42")
+                .recovery_key(key)
+                .execute()
+        };
 
-        println!("result:{:?}", result);
+        let recovered = ctx.run(create_invoice("key-1")).unwrap();
+        assert_eq!(recovered.invoice_id, 528890);
+        assert_eq!(recovered.app_payload(), Some("order_42"));
+        let receipt = recovered.receipt.as_ref().unwrap();
+        assert_eq!(receipt.method, "getInvoices");
+        assert!(receipt.verify(b"audit_key"));
 
-        assert!(result.is_ok());
+        // Without a matching marker the original error is returned
+        let result = ctx.run(create_invoice("key-2"));
+        assert!(matches!(result, Err(CryptoBotError::HttpError(_))), "{result:?}");
 
-        let invoices = result.unwrap();
-        assert!(!invoices.is_empty());
-        assert_eq!(invoices.len(), 1);
+        create.assert();
+        search.assert();
     }
 
     #[test]
-    fn test_get_invoices_with_params() {
+    fn test_create_invoice_with_fiat_price() {
         let mut ctx = TestContext::new();
-        let _m = ctx.mock_get_invoices_response_with_invoice_ids();
+        let _rates = ctx.mock_exchange_rates_response();
+        let create = ctx
+            .server
+            .mock("POST", "/createInvoice")
+            .match_body(Matcher::PartialJson(json!({
+                "currency_type": "crypto",
+                "asset": "TON",
+                "amount": "2.750624159"
+            })))
+            .with_body(fixtures::api_response(fixtures::PAID_INVOICE))
+            .create();
+
         let client = CryptoBot::builder()
             .api_token("test_token")
             .base_url(ctx.server.url())
             .build()
             .unwrap();
 
-        let result = ctx.run(async { client.get_invoices().invoice_ids(vec![530195]).execute().await });
-
-        println!("result: {:?}", result);
+        // 10 USD plus 2% at 3.70824926 USD per TON, rounded up to 9 decimal places
+        let result = ctx.run(
+            client
+                .create_invoice()
+                .asset(CryptoCurrencyCode::Ton)
+                .amount_fiat(dec!(10), FiatCurrencyCode::Usd)
+                .markup_percent(dec!(2))
+                .execute(),
+        );
 
         assert!(result.is_ok());
+        create.assert();
 
-        let invoices = result.unwrap();
-        assert!(!invoices.is_empty());
-        assert_eq!(invoices.len(), 1);
-        assert_eq!(invoices[0].invoice_id, 530195);
+        let missing_rate = ctx.run(
+            client
+                .create_invoice()
+                .asset(CryptoCurrencyCode::Btc)
+                .amount_fiat(dec!(10), FiatCurrencyCode::Usd)
+                .execute(),
+        );
+        assert!(matches!(
+            missing_rate,
+            Err(CryptoBotError::ValidationError {
+                code: ValidationCode::ExchangeRateMissing,
+                ..
+            })
+        ));
+
+        let fiat_invoice = ctx.run(
+            client
+                .create_invoice()
+                .fiat(FiatCurrencyCode::Usd)
+                .amount_fiat(dec!(10), FiatCurrencyCode::Usd)
+                .execute(),
+        );
+        assert!(matches!(
+            fiat_invoice,
+            Err(CryptoBotError::ValidationError {
+                code: ValidationCode::MissingField,
+                ..
+            })
+        ));
+
+        // Fiat prices are rejected with more decimal places than the fiat supports
+        let fiat_precision = ctx.run(
+            client
+                .create_invoice()
+                .fiat(FiatCurrencyCode::Usd)
+                .amount(dec!(10.555))
+                .execute(),
+        );
+        assert_eq!(
+            fiat_precision.unwrap_err().validation_code(),
+            Some(ValidationCode::AmountPrecision)
+        );
     }
 
     #[test]
-    fn test_delete_invoice() {
+    fn test_create_invoice_fiat_price_rounding() {
         let mut ctx = TestContext::new();
-        let _m = ctx.mock_delete_invoice_response();
+        let _rates = ctx.mock_exchange_rates_response();
+        let mut create = |amount: &str| {
+            ctx.server
+                .mock("POST", "/createInvoice")
+                .match_body(Matcher::PartialJson(json!({ "amount": amount })))
+                .with_body(fixtures::api_response(fixtures::PAID_INVOICE))
+                .expect(1)
+                .create()
+        };
+        let (floor, ceiling) = (create("2.750624158"), create("2.750624159"));
 
         let client = CryptoBot::builder()
             .api_token("test_token")
             .base_url(ctx.server.url())
+            .rounding(Rounding::Floor)
             .build()
             .unwrap();
+        let create_invoice = |rounding: Option<Rounding>| {
+            let builder = client
+                .create_invoice()
+                .asset(CryptoCurrencyCode::Ton)
+                .amount_fiat(dec!(10), FiatCurrencyCode::Usd)
+                .markup_percent(dec!(2));
+            match rounding {
+                Some(rounding) => builder.rounding(rounding).execute(),
+                None => builder.execute(),
+            }
+        };
+
+        // The client's strategy applies unless the invoice overrides it
+        ctx.run(create_invoice(None)).unwrap();
+        ctx.run(create_invoice(Some(Rounding::Ceiling))).unwrap();
+        floor.assert();
+        ceiling.assert();
+    }
+
+    #[test]
+    fn test_create_invoice_applies_link_decorator() {
+        struct Utm;
+
+        #[async_trait::async_trait]
+        impl crate::client::LinkDecorator for Utm {
+            async fn decorate(&self, url: &str, kind: PayUrlPreference, invoice: &Invoice) -> String {
+                format!("{url}&utm_source={kind:?}&id={}", invoice.invoice_id)
+            }
+        }
+
+        let mut ctx = TestContext::new();
+        let _m = ctx.mock_exchange_rates_response();
+        let _m = ctx.mock_create_invoice_response();
+
+        let client = CryptoBot::builder()
+            .api_token("test_token")
+            .base_url(ctx.server.url())
+            .link_decorator(Utm)
+            .build()
+            .unwrap();
+
+        let invoice = ctx
+            .run(async {
+                client
+                    .create_invoice()
+                    .asset(CryptoCurrencyCode::Ton)
+                    .amount(dec!(10.5))
+                    .execute()
+                    .await
+            })
+            .unwrap();
+
+        assert!(invoice
+            .pay_url(PayUrlPreference::Bot)
+            .ends_with("&utm_source=Bot&id=528890"));
+        assert!(invoice
+            .pay_url(PayUrlPreference::MiniApp)
+            .ends_with("&utm_source=MiniApp&id=528890"));
+        assert!(invoice
+            .pay_url(PayUrlPreference::WebApp)
+            .ends_with("&utm_source=WebApp&id=528890"));
+    }
+
+    #[test]
+    fn test_before_create_invoice_hook_receives_params() {
+        let mut ctx = TestContext::new();
+        let _m = ctx.mock_exchange_rates_response();
+        let create = ctx.mock_create_invoice_response().expect(0);
+
+        let client = CryptoBot::builder()
+            .api_token("test_token")
+            .base_url(ctx.server.url())
+            .before_create_invoice(|params| {
+                assert_eq!(params["amount"], "10.5");
+                Err("amount above risk limit")
+            })
+            .build()
+            .unwrap();
+
+        let result = ctx.run(async {
+            client
+                .create_invoice()
+                .asset(CryptoCurrencyCode::Ton)
+                .amount(dec!(10.5))
+                .execute()
+                .await
+        });
+
+        assert!(matches!(
+            result,
+            Err(CryptoBotError::Rejected { endpoint, .. }) if endpoint == "createInvoice"
+        ));
+        create.assert();
+    }
+
+    #[test]
+    fn test_get_invoices_without_params() {
+        let mut ctx = TestContext::new();
+        let _m = ctx.mock_get_invoices_response();
+        let client = CryptoBot::builder()
+            .api_token("test_token")
+            .base_url(ctx.server.url())
+            .build()
+            .unwrap();
+        let result = ctx.run(async { client.get_invoices().execute().await });
+
+        println!("result:{:?}", result);
+
+        assert!(result.is_ok());
+
+        let invoices = result.unwrap();
+        assert!(!invoices.is_empty());
+        assert_eq!(invoices.len(), 1);
+    }
+
+    #[test]
+    fn test_get_invoices_with_params() {
+        let mut ctx = TestContext::new();
+        let _m = ctx.mock_get_invoices_response_with_invoice_ids();
+        let client = CryptoBot::builder()
+            .api_token("test_token")
+            .base_url(ctx.server.url())
+            .build()
+            .unwrap();
+
+        let result = ctx.run(async { client.get_invoices().invoice_ids(vec![530195]).execute().await });
+
+        println!("result: {:?}", result);
+
+        assert!(result.is_ok());
+
+        let invoices = result.unwrap();
+        assert!(!invoices.is_empty());
+        assert_eq!(invoices.len(), 1);
+        assert_eq!(invoices[0].invoice_id, 530195);
+    }
+
+    #[test]
+    fn test_delete_invoice() {
+        let mut ctx = TestContext::new();
+        let _m = ctx.mock_delete_invoice_response();
+
+        let client = CryptoBot::builder()
+            .api_token("test_token")
+            .base_url(ctx.server.url())
+            .build()
+            .unwrap();
+
+        let result = ctx.run(async { client.delete_invoice(528890).execute().await });
 
-        let result = ctx.run(async { client.delete_invoice(528890).execute().await });
-
         assert!(result.is_ok());
         assert!(result.unwrap());
     }
@@ -799,6 +1579,71 @@ mod tests {
         }
     }
 
+    #[test]
+    fn test_get_invoices_clamps_count() {
+        let mut ctx = TestContext::new();
+        let m = ctx
+            .server
+            .mock("GET", "/getInvoices")
+            .match_body(Matcher::JsonString(json!({ "count": 1000 }).to_string()))
+            .with_header("content-type", "application/json")
+            .with_body(json!({ "ok": true, "result": { "items": [] } }).to_string())
+            .create();
+
+        let warnings = std::sync::Arc::new(std::sync::Mutex::new(Vec::new()));
+        let captured = warnings.clone();
+
+        let client = CryptoBot::builder()
+            .api_token("test_token")
+            .base_url(ctx.server.url())
+            .clamp_counts(true)
+            .on_warning(move |warning| captured.lock().unwrap().push(warning.to_string()))
+            .build()
+            .unwrap();
+
+        let result = ctx.run(async { client.get_invoices().count(5000).execute().await });
+
+        assert!(result.is_ok());
+        m.assert();
+        assert_eq!(
+            *warnings.lock().unwrap(),
+            vec!["count 5000 is out of range, clamped to 1000".to_string()]
+        );
+
+        // The builder setting overrides the client setting
+        let result = ctx.run(async { client.get_invoices().count(0).clamp_counts(false).execute().await });
+        assert!(matches!(
+            result,
+            Err(CryptoBotError::ValidationError {
+                kind: ValidationErrorKind::Range,
+                ..
+            })
+        ));
+    }
+
+    #[test]
+    fn test_get_invoices_builder_clamps_count() {
+        let mut ctx = TestContext::new();
+        let m = ctx
+            .server
+            .mock("GET", "/getInvoices")
+            .match_body(Matcher::JsonString(json!({ "count": 1 }).to_string()))
+            .with_header("content-type", "application/json")
+            .with_body(json!({ "ok": true, "result": { "items": [] } }).to_string())
+            .create();
+
+        let client = CryptoBot::builder()
+            .api_token("test_token")
+            .base_url(ctx.server.url())
+            .build()
+            .unwrap();
+
+        let result = ctx.run(async { client.get_invoices().count(0).clamp_counts(true).execute().await });
+
+        assert!(result.is_ok());
+        m.assert();
+    }
+
     #[test]
     fn test_create_invoice_with_all_optional_params() {
         let mut ctx = TestContext::new();
@@ -871,6 +1716,33 @@ mod tests {
         assert_eq!(invoice.swapped_to, Some(SwapToAssets::Usdt));
     }
 
+    #[test]
+    fn test_invoice_empty_strings_deserialize_as_none() {
+        let invoice: Invoice = serde_json::from_value(json!({
+            "invoice_id": 123,
+            "hash": "hash-value",
+            "currency_type": "crypto",
+            "asset": "TON",
+            "amount": "10.00",
+            "bot_invoice_url": "https://t.me/CryptoTestnetBot?start=hash-value",
+            "mini_app_invoice_url": "https://t.me/CryptoTestnetBot/app?startapp=invoice-hash-value",
+            "web_app_invoice_url": "https://testnet-app.send.tg/invoices/hash-value",
+            "status": "paid",
+            "allow_comments": true,
+            "allow_anonymous": false,
+            "created_at": "2025-02-08T12:11:01.341Z",
+            "comment": "",
+            "payload": null,
+            "description": "Test invoice"
+        }))
+        .unwrap();
+
+        assert_eq!(invoice.comment, None);
+        assert_eq!(invoice.payload, None);
+        assert_eq!(invoice.hidden_message, None);
+        assert_eq!(invoice.description, Some("Test invoice".to_string()));
+    }
+
     #[test]
     fn test_create_invoice_rejects_negative_amount() {
         let ctx = TestContext::new();
@@ -909,13 +1781,263 @@ mod tests {
         let result = builder.validate();
         assert!(result.is_err());
         match result {
-            Err(CryptoBotError::ValidationError { field, .. }) => {
-                assert_eq!(field, Some("description".to_string()))
+            Err(CryptoBotError::ValidationError { field, code, .. }) => {
+                assert_eq!(field, Some("description".to_string()));
+                assert_eq!(code, ValidationCode::DescriptionLength);
             }
             _ => panic!("Expected validation error for long description"),
         }
     }
 
+    fn invoice_page(ids: &[u64]) -> String {
+        let items: Vec<serde_json::Value> = ids
+            .iter()
+            .map(|id| {
+                let mut invoice: serde_json::Value = serde_json::from_str(fixtures::PAID_INVOICE).unwrap();
+                invoice["invoice_id"] = json!(id);
+                invoice
+            })
+            .collect();
+
+        json!({ "ok": true, "result": { "items": items } }).to_string()
+    }
+
+    #[test]
+    fn test_get_invoices_post_filters() {
+        let mut ctx = TestContext::new();
+        let invoice = |id: u64, anonymous: bool, comment: Option<&str>, paid_at: &str| {
+            let mut invoice: serde_json::Value = serde_json::from_str(fixtures::PAID_INVOICE).unwrap();
+            invoice["invoice_id"] = json!(id);
+            invoice["paid_anonymously"] = json!(anonymous);
+            invoice["comment"] = json!(comment);
+            invoice["paid_at"] = json!(paid_at);
+            invoice
+        };
+        let items = vec![
+            invoice(1, true, Some("thanks!"), "2025-02-03T10:00:00Z"),
+            invoice(2, true, None, "2025-02-04T10:00:00Z"),
+            invoice(3, false, Some("hi"), "2025-02-05T10:00:00Z"),
+            invoice(4, true, Some("late"), "2025-02-12T10:00:00Z"),
+        ];
+        let _m = ctx
+            .server
+            .mock("GET", "/getInvoices")
+            .with_body(json!({ "ok": true, "result": { "items": items } }).to_string())
+            .create();
+
+        let client = CryptoBot::builder()
+            .api_token("test_token")
+            .base_url(ctx.server.url())
+            .build()
+            .unwrap();
+
+        let from = "2025-02-01T00:00:00Z".parse().unwrap();
+        let to = "2025-02-08T00:00:00Z".parse().unwrap();
+        let builder = client
+            .get_invoices()
+            .only_anonymous(true)
+            .only_with_comments()
+            .paid_between(from, to);
+        assert_eq!(builder.get_paid_between(), Some((from, to)));
+
+        let result = ctx.run(builder.execute());
+
+        let ids: Vec<u64> = result.unwrap().iter().map(|invoice| invoice.invoice_id).collect();
+        assert_eq!(ids, vec![1]);
+    }
+
+    #[test]
+    fn test_get_invoices_order_by() {
+        let mut ctx = TestContext::new();
+        let items: Vec<_> = [(1, "5"), (2, "20"), (3, "1.5")]
+            .into_iter()
+            .map(|(id, amount)| {
+                let mut invoice: serde_json::Value = serde_json::from_str(fixtures::PAID_INVOICE).unwrap();
+                invoice["invoice_id"] = json!(id);
+                invoice["amount"] = json!(amount);
+                invoice
+            })
+            .collect();
+        let _m = ctx
+            .server
+            .mock("GET", "/getInvoices")
+            .with_body(json!({ "ok": true, "result": { "items": items } }).to_string())
+            .create();
+
+        let client = CryptoBot::builder()
+            .api_token("test_token")
+            .base_url(ctx.server.url())
+            .build()
+            .unwrap();
+
+        let builder = client
+            .get_invoices()
+            .order_by(InvoiceOrderBy::Amount, SortDirection::Desc);
+        assert_eq!(
+            builder.get_order_by(),
+            Some((InvoiceOrderBy::Amount, SortDirection::Desc))
+        );
+
+        let ids: Vec<u64> = ctx
+            .run(builder.execute())
+            .unwrap()
+            .iter()
+            .map(|invoice| invoice.invoice_id)
+            .collect();
+        assert_eq!(ids, vec![2, 1, 3]);
+    }
+
+    #[test]
+    fn test_get_invoices_by_ids_chunks_and_keeps_order() {
+        let mut ctx = TestContext::new();
+        let m = ctx
+            .server
+            .mock("GET", "/getInvoices")
+            .with_body_from_request(|request| {
+                let body: serde_json::Value = serde_json::from_slice(request.body().unwrap()).unwrap();
+                let ids: Vec<u64> = body["invoice_ids"]
+                    .as_str()
+                    .unwrap()
+                    .split(',')
+                    .map(|id| id.parse().unwrap())
+                    .collect();
+                assert!(ids.len() <= 100);
+                assert_eq!(body["count"], json!(ids.len()));
+                // The API omits unknown IDs
+                let found: Vec<u64> = ids.into_iter().filter(|id| *id != 9999).collect();
+                invoice_page(&found).into_bytes()
+            })
+            .expect(2)
+            .create();
+        let client = CryptoBot::builder()
+            .api_token("test_token")
+            .base_url(ctx.server.url())
+            .build()
+            .unwrap();
+
+        let mut ids: Vec<u64> = (1..=150).rev().collect();
+        ids.extend([3, 9999]);
+        let invoices = ctx.run(client.get_invoices_by_ids(ids)).unwrap();
+
+        m.assert();
+        let returned: Vec<u64> = invoices.iter().map(|invoice| invoice.invoice_id).collect();
+        assert_eq!(returned, (1..=150).rev().collect::<Vec<u64>>());
+    }
+
+    #[test]
+    fn test_get_invoices_after_invoice_id() {
+        let mut ctx = TestContext::new();
+        let _first = ctx
+            .server
+            .mock("GET", "/getInvoices")
+            .match_body(Matcher::Json(json!({ "count": 2 })))
+            .with_body(invoice_page(&[6, 5]))
+            .create();
+        // A new invoice arrived meanwhile, shifting 5 into the second page
+        let _second = ctx
+            .server
+            .mock("GET", "/getInvoices")
+            .match_body(Matcher::Json(json!({ "offset": 2, "count": 2 })))
+            .with_body(invoice_page(&[5, 4]))
+            .create();
+        let _third = ctx
+            .server
+            .mock("GET", "/getInvoices")
+            .match_body(Matcher::Json(json!({ "offset": 4, "count": 2 })))
+            .with_body(invoice_page(&[3, 2]))
+            .create();
+
+        let client = CryptoBot::builder()
+            .api_token("test_token")
+            .base_url(ctx.server.url())
+            .build()
+            .unwrap();
+
+        let result = ctx.run(async { client.get_invoices().count(2).after_invoice_id(3).execute().await });
+
+        let ids: Vec<u64> = result.unwrap().iter().map(|invoice| invoice.invoice_id).collect();
+        assert_eq!(ids, vec![4, 5, 6]);
+    }
+
+    #[test]
+    fn test_get_invoices_after_invoice_id_stops_on_short_page() {
+        let mut ctx = TestContext::new();
+        let _m = ctx
+            .server
+            .mock("GET", "/getInvoices")
+            .match_body(Matcher::Json(json!({ "count": 100 })))
+            .with_body(invoice_page(&[12, 11]))
+            .expect(1)
+            .create();
+
+        let client = CryptoBot::builder()
+            .api_token("test_token")
+            .base_url(ctx.server.url())
+            .build()
+            .unwrap();
+
+        let result = ctx.run(async { client.get_invoices().after_invoice_id(10).execute().await });
+
+        let ids: Vec<u64> = result.unwrap().iter().map(|invoice| invoice.invoice_id).collect();
+        assert_eq!(ids, vec![11, 12]);
+        _m.assert();
+    }
+
+    #[test]
+    fn test_create_invoice_execute_and_refresh() {
+        let mut ctx = TestContext::new();
+        let _m = ctx.mock_exchange_rates_response();
+        let _m = ctx.mock_create_invoice_response();
+        let _m = ctx
+            .server
+            .mock("GET", "/getInvoices")
+            .match_body(json!({ "invoice_ids": "528890" }).to_string().as_str())
+            .with_header("content-type", "application/json")
+            .with_body(
+                json!({
+                    "ok": true,
+                    "result": {
+                        "items": [{
+                            "invoice_id": 528890,
+                            "hash": "IVDoTcNBYEfk",
+                            "currency_type": "crypto",
+                            "asset": "TON",
+                            "amount": "10.5",
+                            "bot_invoice_url": "https://t.me/CryptoTestnetBot?start=IVDoTcNBYEfk",
+                            "mini_app_invoice_url": "https://t.me/CryptoTestnetBot/app?startapp=invoice-IVDoTcNBYEfk",
+                            "web_app_invoice_url": "https://testnet-app.send.tg/invoices/IVDoTcNBYEfk",
+                            "status": "active",
+                            "created_at": "2025-02-08T12:11:01.341Z",
+                            "allow_comments": false,
+                            "allow_anonymous": true
+                        }]
+                    }
+                })
+                .to_string(),
+            )
+            .create();
+
+        let client = CryptoBot::builder()
+            .api_token("test_token")
+            .base_url(ctx.server.url())
+            .build()
+            .unwrap();
+
+        let result = ctx.run(async {
+            client
+                .create_invoice()
+                .asset(CryptoCurrencyCode::Ton)
+                .amount(dec!(10.5))
+                .execute_and_refresh()
+                .await
+        });
+
+        let invoice = result.unwrap();
+        assert_eq!(invoice.invoice_id, 528890);
+        assert!(!invoice.allow_comments);
+        assert!(invoice.allow_anonymous);
+    }
+
     #[test]
     fn test_create_invoice_invalid_paid_button_url() {
         let ctx = TestContext::new();
@@ -941,6 +2063,22 @@ mod tests {
             Err(CryptoBotError::ValidationError { field, .. }) => assert_eq!(field, Some("paid_btn_url".to_string())),
             _ => panic!("Expected validation error for invalid paid_btn_url"),
         }
+
+        let result = ctx.run(async {
+            client
+                .create_invoice()
+                .asset(CryptoCurrencyCode::Ton)
+                .amount(dec!(5))
+                .paid_btn_name(PayButtonName::ViewItem)
+                .paid_btn_url("https://exa mple.com")
+                .execute()
+                .await
+        });
+
+        assert!(matches!(
+            result,
+            Err(CryptoBotError::ValidationError { field: Some(field), .. }) if field == "paid_btn_url"
+        ));
     }
 
     #[test]
@@ -1038,7 +2176,7 @@ mod tests {
         let result = ctx.run(async {
             client
                 .create_invoice()
-                .asset(CryptoCurrencyCode::Ton)
+                .fiat(FiatCurrencyCode::Usd)
                 .amount(dec!(2))
                 .accept_asset(vec![CryptoCurrencyCode::Ton, CryptoCurrencyCode::Usdt])
                 .payload("payload")
@@ -1056,6 +2194,27 @@ mod tests {
         assert!(!invoice.allow_comments);
     }
 
+    #[test]
+    fn test_create_crypto_invoice_rejects_accept_asset() {
+        let ctx = TestContext::new();
+        let client = CryptoBot::test_client();
+
+        let result = ctx.run(async {
+            client
+                .create_invoice()
+                .asset(CryptoCurrencyCode::Ton)
+                .amount(dec!(2))
+                .accept_asset(vec![CryptoCurrencyCode::Usdt])
+                .execute()
+                .await
+        });
+
+        assert_eq!(
+            result.unwrap_err().validation_code(),
+            Some(ValidationCode::FieldConflict)
+        );
+    }
+
     #[test]
     fn test_get_invoices_serializes_filters() {
         let mut ctx = TestContext::new();
@@ -1099,7 +2258,7 @@ mod tests {
     }
 
     #[test]
-    fn test_invoice_validate_with_context_fiat_skips_amount_check() {
+    fn test_invoice_validate_with_context_fiat_checks_amount() {
         let client = CryptoBot::test_client();
         let builder = client.create_invoice().fiat(FiatCurrencyCode::Usd).amount(dec!(5));
         let ctx = ValidationContext {
@@ -1108,5 +2267,59 @@ mod tests {
 
         let result = block_on(async { builder.validate_with_context(&ctx).await });
         assert!(result.is_ok());
+
+        // Converted through the TON cross rate, 0.5 EUR is worth less than 1 USD
+        let builder = client.create_invoice().fiat(FiatCurrencyCode::Eur).amount(dec!(0.5));
+        let result = block_on(async { builder.validate_with_context(&ctx).await });
+        assert_eq!(result.unwrap_err().validation_code(), Some(ValidationCode::AmountRange));
+
+        let builder = client.create_invoice().fiat(FiatCurrencyCode::Eur).amount(dec!(5));
+        assert!(block_on(async { builder.validate_with_context(&ctx).await }).is_ok());
+    }
+
+    #[test]
+    fn test_create_invoice_builder_getters() {
+        let client = CryptoBot::test_client();
+
+        let builder = client.create_invoice();
+        assert_eq!(builder.get_amount(), None);
+        assert_eq!(builder.get_asset(), None);
+        assert_eq!(builder.get_description(), None);
+
+        let builder = builder
+            .amount(dec!(10.5))
+            .asset(CryptoCurrencyCode::Ton)
+            .description("Draft")
+            .paid_btn_name(PayButtonName::ViewItem)
+            .paid_btn_url("https://example.com")
+            .expires_in(60);
+
+        assert_eq!(builder.get_amount(), Some(dec!(10.5)));
+        assert_eq!(builder.get_asset(), Some(&CryptoCurrencyCode::Ton));
+        assert_eq!(builder.get_currency_type(), Some(&CurrencyType::Crypto));
+        assert_eq!(builder.get_description(), Some("Draft"));
+        assert_eq!(builder.get_paid_btn_name(), Some(&PayButtonName::ViewItem));
+        assert_eq!(builder.get_paid_btn_url(), Some("https://example.com"));
+        assert_eq!(builder.get_expires_in(), Some(60));
+        assert_eq!(builder.get_payload(), None);
+    }
+
+    #[test]
+    fn test_get_invoices_builder_getters() {
+        let client = CryptoBot::test_client();
+
+        let builder = client
+            .get_invoices()
+            .asset(CryptoCurrencyCode::Ton)
+            .invoice_ids(vec![1, 2])
+            .status(InvoiceStatus::Paid)
+            .count(10);
+
+        assert_eq!(builder.get_asset(), Some(&CryptoCurrencyCode::Ton));
+        assert_eq!(builder.get_fiat(), None);
+        assert_eq!(builder.get_invoice_ids(), Some(&[1, 2][..]));
+        assert_eq!(builder.get_status(), Some(&InvoiceStatus::Paid));
+        assert_eq!(builder.get_offset(), None);
+        assert_eq!(builder.get_count(), Some(10));
     }
 }
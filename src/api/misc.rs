@@ -1,105 +1,183 @@
 use chrono::{DateTime, Utc};
 
+#[cfg(feature = "tokio")]
+use crate::client::RetryConfig;
 use crate::{
-    client::CryptoBot,
-    error::{CryptoBotError, CryptoBotResult, ValidationErrorKind},
-    models::{APIEndpoint, APIMethod, AppStats, Currency, GetMeResponse, GetStatsParams, Method},
+    client::{CryptoBot, RequestOptions},
+    error::{CryptoBotError, CryptoBotResult, ValidationCode, ValidationErrorKind},
+    models::{
+        APIEndpoint, APIMethod, AppStats, Capability, ConfigMismatch, CryptoCurrencyCode, Currency, CurrencyCode,
+        ExchangeRates, ExpectedAppConfig, FiatCurrencyCode, GetMeResponse, GetStatsParams, Method, StatsTime,
+    },
+    validation::validate_url,
 };
-use async_trait::async_trait;
 
-use super::MiscAPI;
+use super::{CheckAPI, InvoiceAPI, MiscAPI, TransferAPI};
 
 pub struct GetMeBuilder<'a> {
     client: &'a CryptoBot,
+    options: RequestOptions,
 }
 
 impl<'a> GetMeBuilder<'a> {
     pub fn new(client: &'a CryptoBot) -> Self {
-        Self { client }
+        Self {
+            client,
+            options: RequestOptions::default(),
+        }
+    }
+
+    /// Sends the request to `base_url` instead of the client's base URL, e.g. a mirror of the API.
+    /// Optional. The URL is validated when the request is executed.
+    pub fn base_url_override(mut self, base_url: impl Into<String>) -> Self {
+        self.options.base_url = Some(base_url.into());
+        self
     }
 
     /// Executes the request to get application information
     pub async fn execute(self) -> CryptoBotResult<GetMeResponse> {
-        self.client
-            .make_request(
+        let me: GetMeResponse = self
+            .client
+            .make_request_with(
+                &self.options,
                 &APIMethod {
                     endpoint: APIEndpoint::GetMe,
                     method: Method::GET,
                 },
                 None::<&()>,
             )
-            .await
+            .await?;
+
+        self.client.metadata.set_me(me.clone());
+        Ok(me)
+    }
+
+    /// Executes the request, retrying it with `retry` instead of the client's retry configuration
+    #[cfg(feature = "tokio")]
+    pub async fn execute_with_retry(mut self, retry: RetryConfig) -> CryptoBotResult<GetMeResponse> {
+        self.options.retry = Some(retry);
+        self.execute().await
     }
 }
 
 pub struct GetCurrenciesBuilder<'a> {
     client: &'a CryptoBot,
+    options: RequestOptions,
 }
 
 impl<'a> GetCurrenciesBuilder<'a> {
     pub fn new(client: &'a CryptoBot) -> Self {
-        Self { client }
+        Self {
+            client,
+            options: RequestOptions::default(),
+        }
+    }
+
+    /// Sends the request to `base_url` instead of the client's base URL, e.g. a mirror of the API.
+    /// Optional. The URL is validated when the request is executed.
+    pub fn base_url_override(mut self, base_url: impl Into<String>) -> Self {
+        self.options.base_url = Some(base_url.into());
+        self
     }
 
     /// Executes the request to get supported currencies
     pub async fn execute(self) -> CryptoBotResult<Vec<Currency>> {
-        self.client
-            .make_request(
+        let currencies: Vec<Currency> = self
+            .client
+            .make_request_with(
+                &self.options,
                 &APIMethod {
                     endpoint: APIEndpoint::GetCurrencies,
                     method: Method::GET,
                 },
                 None::<&()>,
             )
-            .await
+            .await?;
+
+        self.client.metadata.set_currencies(currencies.clone());
+        Ok(currencies)
+    }
+
+    /// Executes the request, retrying it with `retry` instead of the client's retry configuration
+    #[cfg(feature = "tokio")]
+    pub async fn execute_with_retry(mut self, retry: RetryConfig) -> CryptoBotResult<Vec<Currency>> {
+        self.options.retry = Some(retry);
+        self.execute().await
     }
 }
 
 pub struct GetStatsBuilder<'a> {
     client: &'a CryptoBot,
-    params: GetStatsParams,
+    options: RequestOptions,
+    start_at: Option<StatsTime>,
+    end_at: Option<StatsTime>,
 }
 
 impl<'a> GetStatsBuilder<'a> {
     pub fn new(client: &'a CryptoBot) -> Self {
         Self {
             client,
-            params: GetStatsParams::default(),
+            options: RequestOptions::default(),
+            start_at: None,
+            end_at: None,
         }
     }
 
-    /// Set the start date for the statistics.
+    /// Sends the request to `base_url` instead of the client's base URL, e.g. a mirror of the API.
+    /// Optional. The URL is validated when the request is executed.
+    pub fn base_url_override(mut self, base_url: impl Into<String>) -> Self {
+        self.options.base_url = Some(base_url.into());
+        self
+    }
+
+    /// Set the start date for the statistics, e.g. a `DateTime`, a `NaiveDate` or unix seconds.
     /// Optional. Defaults is current date minus 24 hours.
-    pub fn start_at(mut self, start_at: DateTime<Utc>) -> Self {
-        self.params.start_at = Some(start_at);
+    pub fn start_at(mut self, start_at: impl Into<StatsTime>) -> Self {
+        self.start_at = Some(start_at.into());
         self
     }
 
-    /// Set the end date for the statistics.
+    /// Set the end date for the statistics, e.g. a `DateTime`, a `NaiveDate` or unix seconds.
     /// Optional. Defaults is current date.
-    pub fn end_at(mut self, end_at: DateTime<Utc>) -> Self {
-        self.params.end_at = Some(end_at);
+    pub fn end_at(mut self, end_at: impl Into<StatsTime>) -> Self {
+        self.end_at = Some(end_at.into());
         self
     }
 
+    /// Returns the start date, if set to a valid time.
+    pub fn get_start_at(&self) -> Option<DateTime<Utc>> {
+        self.start_at.and_then(StatsTime::to_datetime)
+    }
+
+    /// Returns the end date, if set to a valid time.
+    pub fn get_end_at(&self) -> Option<DateTime<Utc>> {
+        self.end_at.and_then(StatsTime::to_datetime)
+    }
+
     /// Executes the request to get application statistics
     pub async fn execute(self) -> CryptoBotResult<AppStats> {
         let now = Utc::now();
+        let params = GetStatsParams {
+            start_at: resolve_stats_time(self.start_at, "start_at")?,
+            end_at: resolve_stats_time(self.end_at, "end_at")?,
+        };
 
-        if let Some(start) = self.params.start_at {
+        if let Some(start) = params.start_at {
             if start > now {
                 return Err(CryptoBotError::ValidationError {
                     kind: ValidationErrorKind::Range,
+                    code: ValidationCode::DateRange,
                     message: "start_at cannot be in the future".to_string(),
                     field: Some("start_at".to_string()),
                 });
             }
         }
 
-        if let (Some(start), Some(end)) = (self.params.start_at, self.params.end_at) {
+        if let (Some(start), Some(end)) = (params.start_at, params.end_at) {
             if end < start {
                 return Err(CryptoBotError::ValidationError {
                     kind: ValidationErrorKind::Range,
+                    code: ValidationCode::DateRange,
                     message: "end_at cannot be earlier than start_at".to_string(),
                     field: Some("end_at".to_string()),
                 });
@@ -107,18 +185,37 @@ impl<'a> GetStatsBuilder<'a> {
         }
 
         self.client
-            .make_request(
+            .make_request_with(
+                &self.options,
                 &APIMethod {
                     endpoint: APIEndpoint::GetStats,
                     method: Method::GET,
                 },
-                Some(&self.params),
+                Some(&params),
             )
             .await
     }
+
+    /// Executes the request, retrying it with `retry` instead of the client's retry configuration
+    #[cfg(feature = "tokio")]
+    pub async fn execute_with_retry(mut self, retry: RetryConfig) -> CryptoBotResult<AppStats> {
+        self.options.retry = Some(retry);
+        self.execute().await
+    }
+}
+
+fn resolve_stats_time(time: Option<StatsTime>, field: &str) -> CryptoBotResult<Option<DateTime<Utc>>> {
+    time.map(|time| {
+        time.to_datetime().ok_or_else(|| CryptoBotError::ValidationError {
+            kind: ValidationErrorKind::Range,
+            code: ValidationCode::DateRange,
+            message: format!("{field} is not a valid unix timestamp"),
+            field: Some(field.to_string()),
+        })
+    })
+    .transpose()
 }
 
-#[async_trait]
 impl MiscAPI for CryptoBot {
     /// Gets basic information about your application
     ///
@@ -152,6 +249,137 @@ impl MiscAPI for CryptoBot {
     fn get_stats(&self) -> GetStatsBuilder<'_> {
         GetStatsBuilder::new(self)
     }
+
+    /// Checks on startup that the application can use the given capabilities
+    ///
+    /// The API token is verified with `getMe`, then each capability is probed with its
+    /// read-only listing method (`getInvoices`, `getTransfers`, `getChecks`). The API does not
+    /// report app permissions directly, so this is a best-effort check: a capability counts as
+    /// missing when its probe is rejected with a `*_DISABLED` error.
+    ///
+    /// # Errors
+    /// * `CryptoBotError::MissingCapability` - If a capability is disabled for the application
+    /// * Any other error returned by the probes, e.g. an invalid token
+    ///
+    /// # Example
+    /// ```no_run
+    /// use crypto_pay_api::prelude::*;
+    ///
+    /// #[tokio::main]
+    /// async fn main() -> Result<(), CryptoBotError> {
+    ///     let client = CryptoBot::builder().api_token("YOUR_API_TOKEN").build()?;
+    ///     client.ensure_capabilities(&[Capability::Transfers, Capability::Checks]).await?;
+    ///     Ok(())
+    /// }
+    /// ```
+    async fn ensure_capabilities(&self, capabilities: &[Capability]) -> CryptoBotResult<()> {
+        self.get_me().execute().await?;
+
+        for capability in capabilities {
+            let probe = match capability {
+                Capability::Invoices => self.get_invoices().count(1).execute().await.map(|_| ()),
+                Capability::Transfers => self.get_transfers().count(1).execute().await.map(|_| ()),
+                Capability::Checks => self.get_checks().count(1).execute().await.map(|_| ()),
+            };
+
+            match probe {
+                Err(CryptoBotError::ApiError { message, .. }) if message.to_uppercase().contains("DISABLED") => {
+                    return Err(CryptoBotError::MissingCapability {
+                        capability: *capability,
+                        message,
+                    });
+                }
+                other => other?,
+            }
+        }
+
+        Ok(())
+    }
+}
+
+impl CryptoBot {
+    /// Checks on startup that the app's webhook endpoint is `url`
+    ///
+    /// The API has no method to set or delete the webhook: it is enabled and its URL is set in
+    /// @CryptoBot, under Crypto Pay > My Apps > Webhooks. This verifies that setting with `getMe`, so
+    /// a deployment serving webhooks on another URL fails early instead of never receiving updates.
+    /// URLs are compared like in `GetMeResponse::diff_config`, after normalization, e.g. of the host
+    /// case and default port.
+    ///
+    /// # Errors
+    /// * `CryptoBotError::ValidationError` - If `url` is not an http or https URL
+    /// * `CryptoBotError::ConfigError` - If webhooks are disabled or set to another URL
+    /// * Any error returned by `getMe`
+    ///
+    /// # Example
+    /// ```no_run
+    /// use crypto_pay_api::prelude::*;
+    ///
+    /// # async fn example(client: CryptoBot) -> Result<(), CryptoBotError> {
+    /// client.ensure_webhook("https://example.com/webhook").await?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub async fn ensure_webhook(&self, url: &str) -> CryptoBotResult<()> {
+        validate_url(url, "webhook_url")?;
+
+        let me = self.get_me().execute().await?;
+        match me.diff_config(&ExpectedAppConfig::new().webhook_url(url)).pop() {
+            None => Ok(()),
+            Some(ConfigMismatch {
+                actual: Some(actual), ..
+            }) => Err(CryptoBotError::ConfigError {
+                message: format!("webhook endpoint of {} is {actual}, expected {url}", me.name),
+            }),
+            Some(_) => Err(CryptoBotError::ConfigError {
+                message: format!(
+                    "webhooks are not enabled for {}, set the endpoint {url} in @CryptoBot",
+                    me.name
+                ),
+            }),
+        }
+    }
+
+    /// Returns the crypto assets that can pay an invoice priced in `fiat`, in `getCurrencies` order
+    ///
+    /// An asset is listed if it is a supported crypto currency, is allowed in the client's
+    /// environment and has a valid exchange rate to `fiat`. The API does not expose per-app asset
+    /// settings, so the exchange rates stand in for them. Cached currencies and exchange rates are
+    /// reused while they are fresh. The result can be passed to `CreateInvoiceBuilder::accept_asset`.
+    ///
+    /// # Example
+    /// ```no_run
+    /// use crypto_pay_api::prelude::*;
+    ///
+    /// # async fn example(client: CryptoBot) -> Result<(), CryptoBotError> {
+    /// let assets = client.supported_accept_assets(FiatCurrencyCode::Eur).await?;
+    /// let invoice = client
+    ///     .create_invoice()
+    ///     .fiat(FiatCurrencyCode::Eur)
+    ///     .amount(dec!(25))
+    ///     .accept_asset(assets)
+    ///     .execute()
+    ///     .await?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub async fn supported_accept_assets(&self, fiat: FiatCurrencyCode) -> CryptoBotResult<Vec<CryptoCurrencyCode>> {
+        let (currencies, rates) = futures_util::future::try_join(self.currencies(), self.exchange_rates()).await?;
+
+        let mut assets: Vec<CryptoCurrencyCode> = Vec::new();
+        for currency in currencies {
+            let CurrencyCode::Crypto(asset) = currency.code else {
+                continue;
+            };
+            if asset == CryptoCurrencyCode::Unknown || assets.contains(&asset) || self.check_assets([&asset]).is_err() {
+                continue;
+            }
+            if rates.find_rate(&asset, &fiat).is_some_and(|rate| rate.is_valid) {
+                assets.push(asset);
+            }
+        }
+        Ok(assets)
+    }
 }
 
 #[cfg(test)]
@@ -164,8 +392,9 @@ mod tests {
     use crate::{
         api::MiscAPI,
         client::CryptoBot,
-        models::{CryptoCurrencyCode, CurrencyCode},
-        prelude::{CryptoBotError, ValidationErrorKind},
+        fixtures,
+        models::{Capability, CryptoCurrencyCode, CurrencyCode, FiatCurrencyCode},
+        prelude::{CryptoBotError, ValidationCode, ValidationErrorKind},
         utils::test_utils::TestContext,
     };
 
@@ -271,6 +500,66 @@ mod tests {
         assert_eq!(me.webhook_endpoint, None);
     }
 
+    #[test]
+    fn test_ensure_webhook() {
+        let mut ctx = TestContext::new();
+        let with_webhook = ctx
+            .server
+            .mock("GET", "/getMe")
+            .with_body(fixtures::api_response(
+                r#"{"app_id":28692,"name":"Stated Seaslug App","payment_processing_bot_username":"CryptoTestnetBot","webhook_endpoint":"https://Example.com:443/webhook"}"#,
+            ))
+            .create();
+
+        let client = CryptoBot::builder()
+            .api_token("test_token")
+            .base_url(ctx.server.url())
+            .build()
+            .unwrap();
+
+        ctx.run(async {
+            assert!(client.ensure_webhook("https://example.com/webhook").await.is_ok());
+            assert!(matches!(
+                client.ensure_webhook("https://example.com/other").await,
+                Err(CryptoBotError::ConfigError { .. })
+            ));
+            assert!(matches!(
+                client.ensure_webhook("ftp://example.com/webhook").await,
+                Err(CryptoBotError::ValidationError {
+                    code: ValidationCode::InvalidUrl,
+                    ..
+                })
+            ));
+        });
+
+        drop(with_webhook);
+        let _m = ctx.mock_get_me_response();
+        let result = ctx.run(client.ensure_webhook("https://example.com/webhook"));
+        assert!(matches!(result, Err(CryptoBotError::ConfigError { .. })));
+    }
+
+    #[test]
+    fn test_get_me_with_base_url_override() {
+        let mut ctx = TestContext::new();
+        let _m = ctx.mock_get_me_response();
+
+        let client = CryptoBot::builder()
+            .api_token("test_token")
+            .base_url("http://unreachable.invalid/api")
+            .build()
+            .unwrap();
+
+        let url = format!("{}/", ctx.server.url());
+        let result = ctx.run(async { client.get_me().base_url_override(url).execute().await });
+        assert_eq!(result.unwrap().app_id, 28692);
+
+        let result = ctx.run(async { client.get_me().base_url_override("not a url").execute().await });
+        assert!(matches!(
+            result,
+            Err(CryptoBotError::ValidationError { field: Some(field), .. }) if field == "base_url"
+        ));
+    }
+
     #[test]
     fn test_get_currencies() {
         let mut ctx = TestContext::new();
@@ -339,6 +628,116 @@ mod tests {
         assert_eq!(stats.conversion, Decimal::from(0));
     }
 
+    #[test]
+    fn test_ensure_capabilities_ok() {
+        let mut ctx = TestContext::new();
+        let _m = ctx.mock_get_me_response();
+        let _m = ctx
+            .server
+            .mock("GET", "/getChecks")
+            .with_header("content-type", "application/json")
+            .with_body(json!({ "ok": true, "result": { "items": [] } }).to_string())
+            .create();
+
+        let client = CryptoBot::builder()
+            .api_token("test_token")
+            .base_url(ctx.server.url())
+            .build()
+            .unwrap();
+
+        let result = ctx.run(async { client.ensure_capabilities(&[Capability::Checks]).await });
+
+        assert!(result.is_ok());
+    }
+
+    #[test]
+    fn test_ensure_capabilities_missing() {
+        let mut ctx = TestContext::new();
+        let _m = ctx.mock_get_me_response();
+        let _m = ctx
+            .server
+            .mock("GET", "/getTransfers")
+            .with_status(403)
+            .with_header("content-type", "application/json")
+            .with_body(json!({ "ok": false, "error": "METHOD_DISABLED", "error_code": 403 }).to_string())
+            .create();
+
+        let client = CryptoBot::builder()
+            .api_token("test_token")
+            .base_url(ctx.server.url())
+            .build()
+            .unwrap();
+
+        let result = ctx.run(async { client.ensure_capabilities(&[Capability::Transfers]).await });
+
+        assert!(matches!(
+            result,
+            Err(CryptoBotError::MissingCapability {
+                capability: Capability::Transfers,
+                message,
+            }) if message == "METHOD_DISABLED"
+        ));
+    }
+
+    #[test]
+    fn test_ensure_capabilities_propagates_other_errors() {
+        let mut ctx = TestContext::new();
+        let _m = ctx
+            .server
+            .mock("GET", "/getMe")
+            .with_header("content-type", "application/json")
+            .with_body(json!({ "ok": false, "error": "UNAUTHORIZED", "error_code": 401 }).to_string())
+            .create();
+
+        let client = CryptoBot::builder()
+            .api_token("test_token")
+            .base_url(ctx.server.url())
+            .build()
+            .unwrap();
+
+        let result = ctx.run(async { client.ensure_capabilities(&[Capability::Invoices]).await });
+
+        assert!(matches!(result, Err(CryptoBotError::ApiError { code: 401, .. })));
+    }
+
+    #[test]
+    fn test_get_stats_accepts_dates_and_unix_timestamps() {
+        let mut ctx = TestContext::new();
+        let stats = ctx
+            .server
+            .mock("GET", "/getStats")
+            .match_body(mockito::Matcher::JsonString(
+                json!({ "start_at": "2025-01-31T00:00:00Z", "end_at": "2025-02-01T00:00:00Z" }).to_string(),
+            ))
+            .with_body(fixtures::api_response(
+                r#"{"volume":0,"conversion":0,"unique_users_count":0,"created_invoice_count":0,"paid_invoice_count":0,"start_at":"2025-01-31T00:00:00Z","end_at":"2025-02-01T00:00:00Z"}"#,
+            ))
+            .expect(1)
+            .create();
+
+        let client = CryptoBot::builder()
+            .api_token("test_token")
+            .base_url(ctx.server.url())
+            .build()
+            .unwrap();
+
+        let offset = chrono::DateTime::parse_from_rfc3339("2025-02-01T03:00:00+03:00").unwrap();
+        let builder = client
+            .get_stats()
+            .start_at(chrono::NaiveDate::from_ymd_opt(2025, 1, 31).unwrap())
+            .end_at(offset);
+        assert_eq!(
+            builder.get_end_at(),
+            client.get_stats().end_at(1_738_368_000).get_end_at()
+        );
+
+        ctx.run(builder.execute()).unwrap();
+        stats.assert();
+
+        let result = ctx.run(client.get_stats().start_at(i64::MAX).execute());
+        assert_eq!(result.unwrap_err().validation_code(), Some(ValidationCode::DateRange));
+    }
+
     #[test]
     fn test_get_stats_start_date_in_future_rejected() {
         let ctx = TestContext::new();
@@ -384,4 +783,31 @@ mod tests {
             }) if field == Some("end_at".to_string())
         ));
     }
+
+    #[test]
+    fn test_supported_accept_assets() {
+        let mut ctx = TestContext::new();
+        let currencies = ctx.mock_currencies_response();
+        let rates = ctx.mock_exchange_rates_response();
+
+        let client = CryptoBot::builder()
+            .api_token("test_token")
+            .base_url(ctx.server.url())
+            .build()
+            .unwrap();
+
+        let (usd, rub, gbp) = ctx.run(async {
+            (
+                client.supported_accept_assets(FiatCurrencyCode::Usd).await.unwrap(),
+                client.supported_accept_assets(FiatCurrencyCode::Rub).await.unwrap(),
+                client.supported_accept_assets(FiatCurrencyCode::Gbp).await.unwrap(),
+            )
+        });
+
+        assert_eq!(usd, vec![CryptoCurrencyCode::Ton]);
+        assert_eq!(rub, vec![CryptoCurrencyCode::Usdt]);
+        assert!(gbp.is_empty());
+        currencies.assert();
+        rates.assert();
+    }
 }
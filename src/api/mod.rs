@@ -1,44 +1,175 @@
 mod balance;
+mod batch;
 mod check;
 mod exchange;
 mod invoice;
 mod misc;
+mod sweep;
 mod transfer;
 
-use async_trait::async_trait;
+use std::{collections::HashMap, future::Future};
+
+use futures_util::{stream, Stream, StreamExt, TryStreamExt};
+
+pub use batch::BatchResults;
+
+use crate::{
+    error::{CryptoBotError, CryptoBotResult},
+    models::{Capability, Check, Invoice, Transfer},
+};
+
+/// Number of IDs sent per request by the `get_*_by_ids` helpers.
+const IDS_PER_REQUEST: usize = 100;
+/// Number of requests the `get_*_by_ids` helpers run at once.
+const CONCURRENT_ID_REQUESTS: usize = 4;
+/// Page size of the `into_stream` adapters when no count is set.
+const DEFAULT_PAGE_SIZE: u16 = 100;
 
-#[async_trait]
 pub trait MiscAPI {
     fn get_me(&self) -> misc::GetMeBuilder<'_>;
     fn get_currencies(&self) -> misc::GetCurrenciesBuilder<'_>;
     fn get_stats(&self) -> misc::GetStatsBuilder<'_>;
+    fn ensure_capabilities(&self, capabilities: &[Capability]) -> impl Future<Output = CryptoBotResult<()>> + Send;
 }
 
-#[async_trait]
 pub trait BalanceAPI {
     fn get_balance(&self) -> balance::GetBalanceBuilder<'_>;
 }
 
-#[async_trait]
 pub trait CheckAPI {
     fn create_check(&self) -> check::CreateCheckBuilder<'_>;
     fn delete_check(&self, check_id: u64) -> check::DeleteCheckBuilder<'_>;
     fn get_checks(&self) -> check::GetChecksBuilder<'_>;
+    fn get_checks_by_ids(&self, check_ids: Vec<u64>) -> impl Future<Output = CryptoBotResult<Vec<Check>>> + Send;
 }
 
-#[async_trait]
 pub trait ExchangeRateAPI {
     fn get_exchange_rates(&self) -> exchange::GetExchangeRatesBuilder<'_>;
 }
-#[async_trait]
 pub trait TransferAPI {
     fn transfer(&self) -> transfer::TransferBuilder<'_>;
     fn get_transfers(&self) -> transfer::GetTransfersBuilder<'_>;
+    fn get_transfers_by_ids(
+        &self,
+        transfer_ids: Vec<u64>,
+    ) -> impl Future<Output = CryptoBotResult<Vec<Transfer>>> + Send;
+    fn sweep_to(&self, user_id: u64) -> sweep::SweepBuilder<'_>;
 }
 
-#[async_trait]
 pub trait InvoiceAPI {
     fn create_invoice(&self) -> invoice::CreateInvoiceBuilder<'_>;
+    fn create_invoice_recoverable(&self) -> invoice::CreateInvoiceBuilder<'_>;
     fn delete_invoice(&self, invoice_id: u64) -> invoice::DeleteInvoiceBuilder<'_>;
     fn get_invoices(&self) -> invoice::GetInvoicesBuilder<'_>;
+    fn get_invoices_by_ids(&self, invoice_ids: Vec<u64>) -> impl Future<Output = CryptoBotResult<Vec<Invoice>>> + Send;
+}
+
+/// Fetches items by ID in chunks of [`IDS_PER_REQUEST`], running up to [`CONCURRENT_ID_REQUESTS`] requests at once
+///
+/// Duplicate IDs are fetched once. Items are returned in the order of their first ID in `ids`,
+/// and IDs the API returns nothing for are skipped.
+async fn fetch_by_ids<T, F, Fut>(ids: Vec<u64>, id_of: fn(&T) -> u64, fetch: F) -> CryptoBotResult<Vec<T>>
+where
+    F: Fn(Vec<u64>) -> Fut,
+    Fut: Future<Output = CryptoBotResult<Vec<T>>>,
+{
+    let mut unique = Vec::with_capacity(ids.len());
+    let mut seen = std::collections::HashSet::with_capacity(ids.len());
+    unique.extend(ids.into_iter().filter(|id| seen.insert(*id)));
+
+    let requests: Vec<Fut> = unique
+        .chunks(IDS_PER_REQUEST)
+        .map(|chunk| fetch(chunk.to_vec()))
+        .collect();
+    let chunks: Vec<Vec<T>> = stream::iter(requests)
+        .buffered(CONCURRENT_ID_REQUESTS)
+        .try_collect()
+        .await?;
+
+    let mut items: HashMap<u64, T> = chunks.into_iter().flatten().map(|item| (id_of(&item), item)).collect();
+    Ok(unique.iter().filter_map(|id| items.remove(id)).collect())
+}
+
+/// Lazily pages through an offset-paginated method, starting at `offset`
+///
+/// `fetch` is called with the offset and count of each page once the previous page was consumed.
+/// Items shifted into a later page by items created meanwhile are yielded once, by their `id_of`.
+/// The stream ends after a short page, after `limit` items or after the first error, which is
+/// yielded as its last item. A page size that failed validation is yielded as the only item.
+fn paginate<'a, T, F, Fut>(
+    offset: u32,
+    page_size: CryptoBotResult<u16>,
+    limit: Option<usize>,
+    id_of: fn(&T) -> u64,
+    fetch: F,
+) -> impl Stream<Item = CryptoBotResult<T>> + 'a
+where
+    T: 'a,
+    F: Fn(u32, u16) -> Fut + 'a,
+    Fut: Future<Output = CryptoBotResult<Vec<T>>> + 'a,
+{
+    struct Pages<T, F> {
+        fetch: F,
+        page_size: u16,
+        error: Option<CryptoBotError>,
+        offset: u32,
+        remaining: Option<usize>,
+        id_of: fn(&T) -> u64,
+        seen: std::collections::HashSet<u64>,
+        page: std::vec::IntoIter<T>,
+        done: bool,
+    }
+
+    let (page_size, error) = match page_size {
+        Ok(page_size) => (page_size, None),
+        Err(error) => (0, Some(error)),
+    };
+    let pages = Pages {
+        fetch,
+        page_size,
+        error,
+        offset,
+        remaining: limit,
+        id_of,
+        seen: std::collections::HashSet::new(),
+        page: Vec::new().into_iter(),
+        done: false,
+    };
+
+    stream::unfold(pages, |mut pages| async move {
+        loop {
+            if let Some(error) = pages.error.take() {
+                pages.done = true;
+                return Some((Err(error), pages));
+            }
+            if pages.remaining == Some(0) {
+                return None;
+            }
+            if let Some(item) = pages.page.next() {
+                if let Some(remaining) = pages.remaining.as_mut() {
+                    *remaining -= 1;
+                }
+                return Some((Ok(item), pages));
+            }
+            if pages.done {
+                return None;
+            }
+
+            // Don't fetch more than the limit leaves room for
+            let count = match pages.remaining {
+                Some(remaining) => pages.page_size.min(u16::try_from(remaining).unwrap_or(u16::MAX)),
+                None => pages.page_size,
+            };
+            match (pages.fetch)(pages.offset, count).await {
+                Ok(items) => {
+                    pages.done = items.len() < count as usize;
+                    pages.offset += items.len() as u32;
+                    let (id_of, seen) = (pages.id_of, &mut pages.seen);
+                    let items: Vec<T> = items.into_iter().filter(|item| seen.insert(id_of(item))).collect();
+                    pages.page = items.into_iter();
+                }
+                Err(error) => pages.error = Some(error),
+            }
+        }
+    })
 }
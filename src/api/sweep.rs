@@ -0,0 +1,270 @@
+use rust_decimal::{Decimal, RoundingStrategy};
+
+use crate::{
+    client::{CryptoBot, IdKind},
+    error::CryptoBotResult,
+    models::{CryptoCurrencyCode, SweepItem, SweepOutcome},
+    validation::MAX_SPEND_ID_LENGTH,
+};
+
+use super::{BalanceAPI, TransferAPI};
+
+pub struct SweepBuilder<'a> {
+    client: &'a CryptoBot,
+    user_id: u64,
+    assets: Option<Vec<CryptoCurrencyCode>>,
+    min_amount: Decimal,
+    sweep_id: String,
+    comment: Option<String>,
+    dry_run: bool,
+}
+
+impl<'a> SweepBuilder<'a> {
+    pub fn new(client: &'a CryptoBot, user_id: u64) -> Self {
+        Self {
+            client,
+            user_id,
+            assets: None,
+            min_amount: Decimal::ZERO,
+            sweep_id: client.generate_id(IdKind::IdempotencyKey),
+            comment: None,
+            dry_run: false,
+        }
+    }
+
+    /// Only sweeps the given assets. Defaults to every asset with an available balance.
+    pub fn assets(mut self, assets: Vec<CryptoCurrencyCode>) -> Self {
+        self.assets = Some(assets);
+        self
+    }
+
+    /// Skips assets whose available balance is below this amount. Defaults to zero.
+    pub fn min_amount(mut self, min_amount: Decimal) -> Self {
+        self.min_amount = min_amount;
+        self
+    }
+
+    /// Identifies the sweep in the spend IDs of its transfers.
+    /// Defaults to an ID generated by the client's `IdGenerator`.
+    ///
+    /// Spend IDs are built from the sweep ID, the user ID and the asset, so running a sweep again
+    /// with the same ID cannot transfer an asset twice. Keep the ID with `get_sweep_id` to retry.
+    pub fn sweep_id(mut self, sweep_id: impl Into<String>) -> Self {
+        self.sweep_id = sweep_id.into();
+        self
+    }
+
+    /// Returns the sweep ID.
+    pub fn get_sweep_id(&self) -> &str {
+        &self.sweep_id
+    }
+
+    /// Set the comment of the transfers.
+    pub fn comment(mut self, comment: impl Into<String>) -> Self {
+        self.comment = Some(comment.into());
+        self
+    }
+
+    /// Only plans the transfers without sending them.
+    pub fn dry_run(mut self, dry_run: bool) -> Self {
+        self.dry_run = dry_run;
+        self
+    }
+
+    /// Reads the balances and transfers every selected asset above the threshold to the user
+    ///
+    /// Transfers are made one after the other. A failed transfer is reported in its item and does
+    /// not stop the sweep.
+    ///
+    /// # Errors
+    /// Returns an error only if the balances cannot be fetched.
+    pub async fn execute(self) -> CryptoBotResult<Vec<SweepItem>> {
+        let balances = self.client.get_balance().execute().await?;
+        let mut items = Vec::new();
+
+        for balance in balances {
+            if let Some(assets) = &self.assets {
+                if !assets.contains(&balance.currency_code) {
+                    continue;
+                }
+            }
+
+            let amount = balance
+                .available
+                .round_dp_with_strategy(balance.currency_code.max_scale(), RoundingStrategy::ToZero)
+                .normalize();
+            if amount <= Decimal::ZERO || amount < self.min_amount {
+                continue;
+            }
+
+            let spend_id = sweep_spend_id(&self.sweep_id, self.user_id, &balance.currency_code);
+            let outcome = if self.dry_run {
+                SweepOutcome::Planned
+            } else {
+                self.transfer(&balance.currency_code, amount, &spend_id).await
+            };
+
+            items.push(SweepItem {
+                asset: balance.currency_code,
+                amount,
+                spend_id,
+                outcome,
+            });
+        }
+
+        Ok(items)
+    }
+
+    async fn transfer(&self, asset: &CryptoCurrencyCode, amount: Decimal, spend_id: &str) -> SweepOutcome {
+        let builder = self
+            .client
+            .transfer()
+            .user_id(self.user_id)
+            .asset(asset.clone())
+            .amount(amount)
+            .spend_id(spend_id);
+
+        let result = match &self.comment {
+            Some(comment) => builder.comment(comment.clone()).execute().await,
+            None => builder.execute().await,
+        };
+
+        match result {
+            Ok(transfer) => SweepOutcome::Transferred(transfer),
+            Err(error) => SweepOutcome::Failed(error),
+        }
+    }
+}
+
+/// Builds a deterministic spend ID, truncated to the maximum length accepted by the API
+fn sweep_spend_id(sweep_id: &str, user_id: u64, asset: &CryptoCurrencyCode) -> String {
+    let suffix = format!("-{user_id}-{}", asset.to_string().to_uppercase());
+    let prefix: String = format!("sweep-{sweep_id}")
+        .chars()
+        .take(MAX_SPEND_ID_LENGTH.saturating_sub(suffix.len()))
+        .collect();
+    format!("{prefix}{suffix}")
+}
+
+#[cfg(test)]
+mod tests {
+    use mockito::Matcher;
+    use rust_decimal_macros::dec;
+    use serde_json::json;
+
+    use super::*;
+    use crate::utils::test_utils::TestContext;
+
+    #[test]
+    fn test_sweep_spend_id_is_deterministic_and_bounded() {
+        assert_eq!(
+            sweep_spend_id("20250208", 123, &CryptoCurrencyCode::Ton),
+            "sweep-20250208-123-TON"
+        );
+
+        let long = sweep_spend_id(&"x".repeat(100), 123, &CryptoCurrencyCode::Usdt);
+        assert_eq!(long.len(), MAX_SPEND_ID_LENGTH);
+        assert!(long.ends_with("-123-USDT"));
+    }
+
+    #[test]
+    fn test_sweep_dry_run() {
+        let mut ctx = TestContext::new();
+        let _m = ctx.mock_balance_response();
+        let transfer = ctx.server.mock("POST", "/transfer").expect(0).create();
+
+        let client = CryptoBot::builder()
+            .api_token("test_token")
+            .base_url(ctx.server.url())
+            .build()
+            .unwrap();
+
+        let items = ctx
+            .run(async { client.sweep_to(42).sweep_id("daily").dry_run(true).execute().await })
+            .unwrap();
+
+        let planned: Vec<_> = items.iter().map(|item| (item.asset.clone(), item.amount)).collect();
+        assert_eq!(
+            planned,
+            vec![
+                (CryptoCurrencyCode::Ton, dec!(100.5)),
+                (CryptoCurrencyCode::Send, dec!(10.5))
+            ]
+        );
+        assert!(items.iter().all(|item| matches!(item.outcome, SweepOutcome::Planned)));
+        assert_eq!(items[0].to_string(), "TON 100.5 -> planned (sweep-daily-42-TON)");
+        transfer.assert();
+    }
+
+    #[test]
+    fn test_sweep_id_defaults_to_generated_id() {
+        let client = CryptoBot::builder()
+            .api_token("test_token")
+            .id_generator(crate::client::SeededIdGenerator::new(7))
+            .build()
+            .unwrap();
+        let expected = {
+            use crate::client::IdGenerator;
+            crate::client::SeededIdGenerator::new(7).generate(IdKind::IdempotencyKey)
+        };
+
+        assert_eq!(client.sweep_to(42).get_sweep_id(), expected);
+        assert_ne!(client.sweep_to(42).get_sweep_id(), expected);
+    }
+
+    #[test]
+    fn test_sweep_selected_assets_above_threshold() {
+        let mut ctx = TestContext::new();
+        let _m = ctx.mock_balance_response();
+        let _m = ctx.mock_exchange_rates_response();
+        let transfer = ctx
+            .server
+            .mock("POST", "/transfer")
+            .match_body(Matcher::PartialJson(json!({
+                "user_id": 42,
+                "asset": "TON",
+                "amount": "100.5",
+                "spend_id": "sweep-daily-42-TON"
+            })))
+            .with_header("content-type", "application/json")
+            .with_body(
+                json!({
+                    "ok": true,
+                    "result": {
+                        "transfer_id": 7,
+                        "spend_id": "sweep-daily-42-TON",
+                        "user_id": 42,
+                        "asset": "TON",
+                        "amount": "100.5",
+                        "status": "completed",
+                        "completed_at": "2025-02-08T12:11:01.341Z"
+                    }
+                })
+                .to_string(),
+            )
+            .expect(1)
+            .create();
+
+        let client = CryptoBot::builder()
+            .api_token("test_token")
+            .base_url(ctx.server.url())
+            .build()
+            .unwrap();
+
+        let items = ctx
+            .run(async {
+                client
+                    .sweep_to(42)
+                    .assets(vec![CryptoCurrencyCode::Ton, CryptoCurrencyCode::Send])
+                    .min_amount(dec!(50))
+                    .sweep_id("daily")
+                    .execute()
+                    .await
+            })
+            .unwrap();
+
+        assert_eq!(items.len(), 1);
+        assert!(matches!(&items[0].outcome, SweepOutcome::Transferred(transfer) if transfer.transfer_id == 7));
+        transfer.assert();
+    }
+}
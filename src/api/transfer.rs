@@ -1,35 +1,51 @@
-use async_trait::async_trait;
 use std::marker::PhantomData;
 
+use futures_util::Stream;
 use rust_decimal::Decimal;
 
+#[cfg(feature = "tokio")]
+use crate::client::RetryConfig;
 use crate::utils::types::IntoDecimal;
 use crate::{
-    client::CryptoBot,
-    error::{CryptoBotError, CryptoBotResult, ValidationErrorKind},
+    client::{CryptoBot, IdKind, RequestOptions},
+    error::CryptoBotResult,
     models::{
-        APIEndpoint, APIMethod, CryptoCurrencyCode, GetTransfersParams, GetTransfersResponse, Method, Missing, Set,
-        Transfer, TransferParams,
+        APIEndpoint, APIMethod, CryptoCurrencyCode, FieldState, FundsCheck, GetTransfersParams, GetTransfersResponse,
+        Method, Missing, PositiveAmount, Set, Transfer, TransferParams,
+    },
+    validation::{
+        validate_amount, validate_comment, validate_spend_id, ContextValidate, FieldValidate, ValidationContext,
     },
-    validation::{validate_amount, validate_count, ContextValidate, FieldValidate, ValidationContext},
 };
 
-use super::TransferAPI;
-use crate::api::ExchangeRateAPI;
+use super::{fetch_by_ids, paginate, sweep::SweepBuilder, BalanceAPI, TransferAPI, DEFAULT_PAGE_SIZE};
 
 pub struct GetTransfersBuilder<'a> {
     client: &'a CryptoBot,
+    options: RequestOptions,
     params: GetTransfersParams,
+    clamp_counts: Option<bool>,
+    limit: Option<usize>,
 }
 
 impl<'a> GetTransfersBuilder<'a> {
     pub fn new(client: &'a CryptoBot) -> Self {
         Self {
             client,
+            options: RequestOptions::default(),
             params: GetTransfersParams::default(),
+            clamp_counts: None,
+            limit: None,
         }
     }
 
+    /// Sends the request to `base_url` instead of the client's base URL, e.g. a mirror of the API.
+    /// Optional. The URL is validated when the request is executed.
+    pub fn base_url_override(mut self, base_url: impl Into<String>) -> Self {
+        self.options.base_url = Some(base_url.into());
+        self
+    }
+
     /// Set the asset for the transfers.
     /// Optional. Defaults to all currencies.
     pub fn asset(mut self, asset: CryptoCurrencyCode) -> Self {
@@ -66,15 +82,60 @@ impl<'a> GetTransfersBuilder<'a> {
         self
     }
 
+    /// Clamp the count into the range 1-1000 instead of rejecting it.
+    /// Optional. Overrides the client setting.
+    pub fn clamp_counts(mut self, clamp_counts: bool) -> Self {
+        self.clamp_counts = Some(clamp_counts);
+        self
+    }
+
+    /// Set the maximum number of transfers yielded by `into_stream`.
+    /// Optional. Defaults to all transfers.
+    pub fn limit(mut self, limit: usize) -> Self {
+        self.limit = Some(limit);
+        self
+    }
+
+    /// Returns the asset filter, if set.
+    pub fn get_asset(&self) -> Option<&CryptoCurrencyCode> {
+        self.params.asset.as_ref()
+    }
+
+    /// Returns the transfer IDs filter, if set.
+    pub fn get_transfer_ids(&self) -> Option<&[u64]> {
+        self.params.transfer_ids.as_deref()
+    }
+
+    /// Returns the spend ID filter, if set.
+    pub fn get_spend_id(&self) -> Option<&str> {
+        self.params.spend_id.as_deref()
+    }
+
+    /// Returns the offset, if set.
+    pub fn get_offset(&self) -> Option<u32> {
+        self.params.offset
+    }
+
+    /// Returns the count, if set.
+    pub fn get_count(&self) -> Option<u16> {
+        self.params.count
+    }
+
+    /// Returns the limit of `into_stream`, if set.
+    pub fn get_limit(&self) -> Option<usize> {
+        self.limit
+    }
+
     /// Executes the request to get transfers
-    pub async fn execute(self) -> CryptoBotResult<Vec<Transfer>> {
+    pub async fn execute(mut self) -> CryptoBotResult<Vec<Transfer>> {
         if let Some(count) = self.params.count {
-            validate_count(count)?;
+            self.params.count = Some(self.client.resolve_count(count, self.clamp_counts)?);
         }
 
         let response: GetTransfersResponse = self
             .client
-            .make_request(
+            .make_request_with(
+                &self.options,
                 &APIMethod {
                     endpoint: APIEndpoint::GetTransfers,
                     method: Method::GET,
@@ -85,10 +146,59 @@ impl<'a> GetTransfersBuilder<'a> {
 
         Ok(response.items)
     }
+
+    /// Executes the request, retrying it with `retry` instead of the client's retry configuration
+    #[cfg(feature = "tokio")]
+    pub async fn execute_with_retry(mut self, retry: RetryConfig) -> CryptoBotResult<Vec<Transfer>> {
+        self.options.retry = Some(retry);
+        self.execute().await
+    }
+
+    /// Returns a stream of the transfers, fetching one page of `count` transfers at a time as it is consumed
+    ///
+    /// Pages start at `offset` and hold 100 transfers unless `count` is set. The stream ends after the
+    /// last page, after `limit` transfers or after the first error, which is yielded as its last item.
+    /// Transfers shifted into the next page by new ones are yielded once.
+    pub fn into_stream(self) -> impl Stream<Item = CryptoBotResult<Transfer>> + 'a {
+        let page_size = self
+            .client
+            .resolve_count(self.params.count.unwrap_or(DEFAULT_PAGE_SIZE), self.clamp_counts);
+        let offset = self.params.offset.unwrap_or(0);
+        let (client, options, params) = (self.client, self.options, self.params);
+
+        paginate(
+            offset,
+            page_size,
+            self.limit,
+            |transfer: &Transfer| transfer.transfer_id,
+            move |offset, count| {
+                let options = options.clone();
+                let params = GetTransfersParams {
+                    offset: Some(offset),
+                    count: Some(count),
+                    ..params.clone()
+                };
+                async move {
+                    let response: GetTransfersResponse = client
+                        .make_request_with(
+                            &options,
+                            &APIMethod {
+                                endpoint: APIEndpoint::GetTransfers,
+                                method: Method::GET,
+                            },
+                            Some(&params),
+                        )
+                        .await?;
+                    Ok(response.items)
+                }
+            },
+        )
+    }
 }
 
 pub struct TransferBuilder<'a, U = Missing, A = Missing, M = Missing, S = Missing> {
     client: &'a CryptoBot,
+    options: RequestOptions,
     user_id: u64,
     asset: CryptoCurrencyCode,
     amount: Decimal,
@@ -102,6 +212,7 @@ impl<'a> TransferBuilder<'a, Missing, Missing, Missing, Missing> {
     pub fn new(client: &'a CryptoBot) -> Self {
         Self {
             client,
+            options: RequestOptions::default(),
             user_id: 0,
             asset: CryptoCurrencyCode::Ton,
             amount: Decimal::ZERO,
@@ -147,6 +258,13 @@ impl<'a, U, A, M> TransferBuilder<'a, U, A, M, Missing> {
         self.spend_id = spend_id.into();
         self.transform()
     }
+
+    /// Set a spend ID generated by the client's `IdGenerator`, random unless configured otherwise.
+    /// Keep the generated ID with `get_spend_id` to retry the transfer safely.
+    pub fn generate_spend_id(self) -> TransferBuilder<'a, U, A, M, Set> {
+        let spend_id = self.client.generate_id(IdKind::SpendId);
+        self.spend_id(spend_id)
+    }
 }
 
 impl<'a, U, A, M, S> TransferBuilder<'a, U, A, M, S> {
@@ -161,15 +279,23 @@ impl<'a, U, A, M, S> TransferBuilder<'a, U, A, M, S> {
 
     /// Set the disable send notification for the transfer.
     /// Optional. Pass true to not send to the user the notification about the transfer.
-    /// Defaults to false.
+    /// Defaults to the client's `TransferDefaults`, or false if unset.
     pub fn disable_send_notification(mut self, disable: bool) -> Self {
         self.disable_send_notification = Some(disable);
         self
     }
 
+    /// Sends the request to `base_url` instead of the client's base URL, e.g. a mirror of the API.
+    /// Optional. The URL is validated when the request is executed.
+    pub fn base_url_override(mut self, base_url: impl Into<String>) -> Self {
+        self.options.base_url = Some(base_url.into());
+        self
+    }
+
     fn transform<U2, A2, M2, S2>(self) -> TransferBuilder<'a, U2, A2, M2, S2> {
         TransferBuilder {
             client: self.client,
+            options: self.options,
             user_id: self.user_id,
             asset: self.asset,
             amount: self.amount,
@@ -181,31 +307,75 @@ impl<'a, U, A, M, S> TransferBuilder<'a, U, A, M, S> {
     }
 }
 
+/// Getters for the current draft state, prefixed with `get_` because the setters use the plain field names.
+impl<'a, U: FieldState, A: FieldState, M: FieldState, S: FieldState> TransferBuilder<'a, U, A, M, S> {
+    /// Returns the Telegram user ID, if set.
+    pub fn get_user_id(&self) -> Option<u64> {
+        U::IS_SET.then_some(self.user_id)
+    }
+
+    /// Returns the asset, if set.
+    pub fn get_asset(&self) -> Option<&CryptoCurrencyCode> {
+        A::IS_SET.then_some(&self.asset)
+    }
+
+    /// Returns the amount, if set.
+    pub fn get_amount(&self) -> Option<Decimal> {
+        M::IS_SET.then_some(self.amount)
+    }
+
+    /// Returns the spend ID, if set.
+    pub fn get_spend_id(&self) -> Option<&str> {
+        S::IS_SET.then_some(self.spend_id.as_str())
+    }
+
+    /// Returns the comment set on this builder. Client transfer defaults are not included.
+    pub fn get_comment(&self) -> Option<&str> {
+        self.comment.as_deref()
+    }
+
+    /// Returns the notification setting of this builder. Client transfer defaults are not included.
+    pub fn get_disable_send_notification(&self) -> Option<bool> {
+        self.disable_send_notification
+    }
+}
+
+impl<'a, U, S> TransferBuilder<'a, U, Set, Set, S> {
+    /// Checks whether the app balance can cover the transfer, without executing it
+    ///
+    /// Fetches the balance and exchange rates and validates the amount against the asset limits,
+    /// so payout jobs can skip transfers that would fail.
+    pub async fn check_funds(&self) -> CryptoBotResult<FundsCheck> {
+        let (balances, rates) =
+            futures_util::future::try_join(self.client.get_balance().execute(), self.client.exchange_rates()).await?;
+
+        let ctx = ValidationContext { exchange_rates: rates };
+        validate_amount(&self.amount, &self.asset, &ctx).await?;
+
+        Ok(FundsCheck::new(
+            self.asset.clone(),
+            self.amount,
+            Some(Decimal::ZERO),
+            Decimal::ZERO,
+            &balances,
+            &ctx.exchange_rates,
+            self.client.rounding,
+        ))
+    }
+}
+
 impl<'a> FieldValidate for TransferBuilder<'a, Set, Set, Set, Set> {
     fn validate(&self) -> CryptoBotResult<()> {
-        if self.spend_id.chars().count() > 64 {
-            return Err(CryptoBotError::ValidationError {
-                kind: ValidationErrorKind::Range,
-                message: "Spend ID must be at most 64 symbols".to_string(),
-                field: Some("spend_id".to_string()),
-            });
-        }
+        validate_spend_id(&self.spend_id)?;
 
         if let Some(comment) = &self.comment {
-            if comment.chars().count() > 1024 {
-                return Err(CryptoBotError::ValidationError {
-                    kind: ValidationErrorKind::Range,
-                    message: "Comment must be at most 1024 symbols".to_string(),
-                    field: Some("comment".to_string()),
-                });
-            }
+            validate_comment(comment)?;
         }
 
         Ok(())
     }
 }
 
-#[async_trait]
 impl<'a> ContextValidate for TransferBuilder<'a, Set, Set, Set, Set> {
     async fn validate_with_context(&self, ctx: &ValidationContext) -> CryptoBotResult<()> {
         validate_amount(&self.amount, &self.asset, ctx).await
@@ -216,33 +386,42 @@ impl<'a> TransferBuilder<'a, Set, Set, Set, Set> {
     /// Executes the request to transfer cryptocurrency
     pub async fn execute(self) -> CryptoBotResult<Transfer> {
         self.validate()?;
+        self.client.check_assets([&self.asset])?;
 
-        let rates = self.client.get_exchange_rates().execute().await?;
+        let rates = self.client.exchange_rates().await?;
         let ctx = ValidationContext { exchange_rates: rates };
         self.validate_with_context(&ctx).await?;
 
+        let amount = PositiveAmount::for_asset(self.amount, &self.asset)?;
+        let defaults = &self.client.transfer_defaults;
+
         let params = TransferParams {
             user_id: self.user_id,
             asset: self.asset,
-            amount: self.amount,
+            amount,
             spend_id: self.spend_id,
-            comment: self.comment,
-            disable_send_notification: self.disable_send_notification,
+            comment: self.comment.or_else(|| defaults.comment.clone()),
+            disable_send_notification: self.disable_send_notification.or(defaults.disable_send_notification),
         };
-
         self.client
-            .make_request(
+            .run_before_hook(self.client.before_transfer.as_ref(), APIEndpoint::Transfer, &params)?;
+
+        let (mut transfer, receipt): (Transfer, _) = self
+            .client
+            .make_receipted_request(
+                &self.options,
                 &APIMethod {
                     endpoint: APIEndpoint::Transfer,
                     method: Method::POST,
                 },
                 Some(&params),
             )
-            .await
+            .await?;
+        transfer.receipt = receipt;
+        Ok(transfer)
     }
 }
 
-#[async_trait]
 impl TransferAPI for CryptoBot {
     /// Transfer cryptocurrency to a user
     ///
@@ -259,10 +438,35 @@ impl TransferAPI for CryptoBot {
     fn get_transfers(&self) -> GetTransfersBuilder<'_> {
         GetTransfersBuilder::new(self)
     }
+
+    /// Fetches transfers by ID, splitting long ID lists into several concurrent requests
+    ///
+    /// # Returns
+    /// The found transfers in the order of `transfer_ids`, without duplicates
+    async fn get_transfers_by_ids(&self, transfer_ids: Vec<u64>) -> CryptoBotResult<Vec<Transfer>> {
+        fetch_by_ids(
+            transfer_ids,
+            |transfer: &Transfer| transfer.transfer_id,
+            |chunk| {
+                let count = chunk.len() as u16;
+                self.get_transfers().transfer_ids(chunk).count(count).execute()
+            },
+        )
+        .await
+    }
+
+    /// Transfer the available balances of the app to a user, e.g. to consolidate funds
+    ///
+    /// # Returns
+    /// * `SweepBuilder` - A builder to select the assets and run or plan the sweep
+    fn sweep_to(&self, user_id: u64) -> SweepBuilder<'_> {
+        SweepBuilder::new(self, user_id)
+    }
 }
 
 #[cfg(test)]
 mod tests {
+    use futures::{StreamExt, TryStreamExt};
     use mockito::{Matcher, Mock};
     use rust_decimal_macros::dec;
     use serde_json::json;
@@ -270,7 +474,8 @@ mod tests {
     use crate::{
         api::TransferAPI,
         client::CryptoBot,
-        models::{CryptoCurrencyCode, TransferStatus},
+        fixtures,
+        models::{CryptoCurrencyCode, TransferDefaults, TransferStatus},
         prelude::{CryptoBotError, ValidationErrorKind},
         utils::test_utils::TestContext,
         validation::FieldValidate,
@@ -467,6 +672,78 @@ mod tests {
         assert_eq!(transfer.asset, CryptoCurrencyCode::Ton);
         assert_eq!(transfer.amount, dec!(10.5));
         assert_eq!(transfer.status, TransferStatus::Completed);
+        assert!(transfer.receipt.is_none());
+    }
+
+    #[test]
+    fn test_transfer_with_receipt() {
+        let mut ctx = TestContext::new();
+        let _m = ctx.mock_exchange_rates_response();
+        let _m = ctx.mock_transfer_response();
+
+        let client = CryptoBot::builder()
+            .api_token("test_token")
+            .base_url(ctx.server.url())
+            .receipt_key("audit_key")
+            .build()
+            .unwrap();
+
+        let transfer = ctx
+            .run(
+                client
+                    .transfer()
+                    .user_id(123456789)
+                    .asset(CryptoCurrencyCode::Ton)
+                    .amount(dec!(10.5))
+                    .spend_id("test_spend_id")
+                    .execute(),
+            )
+            .unwrap();
+
+        assert_eq!(transfer.transfer_id, 1);
+        let receipt = transfer.receipt.unwrap();
+        assert_eq!(receipt.method, "transfer");
+        assert!(receipt
+            .body
+            .starts_with(r#"{"amount":"10.5","asset":"TON","comment":"test_comment""#));
+        assert!(receipt.verify(b"audit_key"));
+        assert!(!receipt.verify(b"other_key"));
+    }
+
+    #[test]
+    fn test_before_transfer_hook_can_veto() {
+        let mut ctx = TestContext::new();
+        let _m = ctx.mock_exchange_rates_response();
+        let transfer = ctx.mock_transfer_response().expect(1);
+
+        let client = CryptoBot::builder()
+            .api_token("test_token")
+            .base_url(ctx.server.url())
+            .before_transfer(|params| match params["user_id"].as_u64() {
+                Some(666) => Err("user is blocked"),
+                _ => Ok(()),
+            })
+            .build()
+            .unwrap();
+
+        let transfer_to = |user_id| {
+            client
+                .transfer()
+                .user_id(user_id)
+                .asset(CryptoCurrencyCode::Ton)
+                .amount(dec!(10.5))
+                .spend_id(format!("spend_{user_id}"))
+                .execute()
+        };
+
+        let blocked = ctx.run(transfer_to(666));
+        assert!(matches!(
+            &blocked,
+            Err(CryptoBotError::Rejected { endpoint, reason }) if endpoint == "transfer" && reason.to_string() == "user is blocked"
+        ));
+
+        assert!(ctx.run(transfer_to(123456789)).is_ok());
+        transfer.assert();
     }
 
     #[test]
@@ -558,6 +835,104 @@ mod tests {
         ));
     }
 
+    #[test]
+    fn test_get_transfers_stream_stops_after_short_page_or_error() {
+        let mut ctx = TestContext::new();
+        let transfers = |transfer_ids: &[u64]| {
+            let items: Vec<serde_json::Value> = transfer_ids
+                .iter()
+                .map(|transfer_id| {
+                    let mut transfer: serde_json::Value = serde_json::from_str(fixtures::TRANSFER).unwrap();
+                    transfer["transfer_id"] = json!(transfer_id);
+                    transfer
+                })
+                .collect();
+            json!({ "ok": true, "result": { "items": items } }).to_string()
+        };
+        let page = |ctx: &mut TestContext, asset: &str, offset: u32, body: String| {
+            ctx.server
+                .mock("GET", "/getTransfers")
+                .match_body(Matcher::JsonString(
+                    json!({ "asset": asset, "offset": offset, "count": 2 }).to_string(),
+                ))
+                .with_body(body)
+                .expect(1)
+                .create()
+        };
+        let pages = [
+            page(&mut ctx, "USDT", 0, transfers(&[1, 2])),
+            page(&mut ctx, "USDT", 2, transfers(&[3])),
+            page(&mut ctx, "TON", 0, transfers(&[4, 5])),
+            page(
+                &mut ctx,
+                "TON",
+                2,
+                r#"{"ok":false,"error":{"code":400,"name":"COUNT_INVALID"}}"#.to_string(),
+            ),
+        ];
+
+        let client = CryptoBot::builder()
+            .api_token("test_token")
+            .base_url(ctx.server.url())
+            .build()
+            .unwrap();
+        let stream = |asset| client.get_transfers().asset(asset).count(2).into_stream();
+
+        let usdt: Vec<u64> = ctx
+            .run(
+                stream(CryptoCurrencyCode::Usdt)
+                    .map_ok(|transfer| transfer.transfer_id)
+                    .try_collect(),
+            )
+            .unwrap();
+        assert_eq!(usdt, [1, 2, 3]);
+
+        let ton: Vec<_> = ctx.run(stream(CryptoCurrencyCode::Ton).collect());
+        assert_eq!(ton.len(), 3);
+        assert_eq!(ton[1].as_ref().unwrap().transfer_id, 5);
+        assert!(matches!(ton[2], Err(CryptoBotError::ApiError { .. })));
+        pages.iter().for_each(Mock::assert);
+    }
+
+    #[test]
+    fn test_transfer_check_funds() {
+        let mut ctx = TestContext::new();
+        let _m = ctx.mock_exchange_rates_response();
+        let _m = ctx.mock_balance_response();
+
+        let client = CryptoBot::builder()
+            .api_token("test_token")
+            .base_url(ctx.server.url())
+            .build()
+            .unwrap();
+
+        let result = ctx.run(async {
+            let enough = client
+                .transfer()
+                .asset(CryptoCurrencyCode::Ton)
+                .amount(dec!(10.5))
+                .check_funds()
+                .await?;
+            let too_much = client
+                .transfer()
+                .asset(CryptoCurrencyCode::Ton)
+                .amount(dec!(200))
+                .check_funds()
+                .await?;
+            Ok::<_, CryptoBotError>((enough, too_much))
+        });
+
+        let (enough, too_much) = result.unwrap();
+        assert!(enough.sufficient);
+        assert_eq!(enough.available, dec!(100.5));
+        assert_eq!(enough.fee, Some(dec!(0)));
+
+        assert!(!too_much.sufficient);
+        assert_eq!(too_much.shortfall, dec!(99.5));
+        // 368.9708... USD, rounded up by the default rounding of the client
+        assert_eq!(too_much.shortfall_usd, Some(dec!(368.98)));
+    }
+
     #[test]
     fn test_transfer_rejects_long_spend_id() {
         let ctx = TestContext::new();
@@ -615,6 +990,68 @@ mod tests {
         ));
     }
 
+    #[test]
+    fn test_transfer_uses_client_defaults() {
+        let mut ctx = TestContext::new();
+        let _m = ctx.mock_exchange_rates_response();
+        let _m = ctx.mock_transfer_with_optional_fields_response();
+
+        let client = CryptoBot::builder()
+            .api_token("test_token")
+            .base_url(ctx.server.url())
+            .transfer_defaults(TransferDefaults {
+                comment: Some("optional".to_string()),
+                disable_send_notification: Some(true),
+            })
+            .build()
+            .unwrap();
+
+        let result = ctx.run(async {
+            client
+                .transfer()
+                .user_id(999)
+                .asset(CryptoCurrencyCode::Ton)
+                .amount(dec!(2))
+                .spend_id("long_spend")
+                .execute()
+                .await
+        });
+
+        assert!(result.is_ok());
+    }
+
+    #[test]
+    fn test_transfer_overrides_client_defaults() {
+        let mut ctx = TestContext::new();
+        let _m = ctx.mock_exchange_rates_response();
+        let _m = ctx.mock_transfer_with_optional_fields_response();
+
+        let client = CryptoBot::builder()
+            .api_token("test_token")
+            .base_url(ctx.server.url())
+            .transfer_defaults(TransferDefaults {
+                comment: Some("default".to_string()),
+                disable_send_notification: Some(false),
+            })
+            .build()
+            .unwrap();
+
+        let result = ctx.run(async {
+            client
+                .transfer()
+                .user_id(999)
+                .asset(CryptoCurrencyCode::Ton)
+                .amount(dec!(2))
+                .spend_id("long_spend")
+                .comment("optional")
+                .disable_send_notification(true)
+                .execute()
+                .await
+        });
+
+        assert!(result.is_ok());
+    }
+
     #[test]
     fn test_transfer_with_disable_notification_flag() {
         let mut ctx = TestContext::new();
@@ -643,4 +1080,48 @@ mod tests {
         assert!(result.is_ok());
         assert!(result.is_ok());
     }
+
+    #[test]
+    fn test_transfer_builder_getters() {
+        let client = CryptoBot::test_client();
+
+        let builder = client.transfer().user_id(123456789).comment("Draft");
+        assert_eq!(builder.get_user_id(), Some(123456789));
+        assert_eq!(builder.get_asset(), None);
+        assert_eq!(builder.get_amount(), None);
+        assert_eq!(builder.get_spend_id(), None);
+        assert_eq!(builder.get_comment(), Some("Draft"));
+
+        let builder = builder
+            .asset(CryptoCurrencyCode::Ton)
+            .amount(dec!(1.5))
+            .spend_id("spend_1");
+
+        assert_eq!(builder.get_asset(), Some(&CryptoCurrencyCode::Ton));
+        assert_eq!(builder.get_amount(), Some(dec!(1.5)));
+        assert_eq!(builder.get_spend_id(), Some("spend_1"));
+        assert_eq!(builder.get_disable_send_notification(), None);
+    }
+
+    #[test]
+    fn test_transfer_generate_spend_id_uses_client_generator() {
+        use crate::{
+            client::{IdGenerator, IdKind, SeededIdGenerator},
+            validation::validate_spend_id,
+        };
+
+        let client = CryptoBot::builder()
+            .api_token("test_token")
+            .id_generator(SeededIdGenerator::new(42))
+            .build()
+            .unwrap();
+        let expected = SeededIdGenerator::new(42);
+
+        let first = client.transfer().generate_spend_id();
+        let second = client.transfer().generate_spend_id();
+
+        assert_eq!(first.get_spend_id(), Some(expected.generate(IdKind::SpendId).as_str()));
+        assert_eq!(second.get_spend_id(), Some(expected.generate(IdKind::SpendId).as_str()));
+        assert!(validate_spend_id(second.get_spend_id().unwrap()).is_ok());
+    }
 }
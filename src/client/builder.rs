@@ -1,17 +1,72 @@
 use reqwest::header::{HeaderName, HeaderValue};
-use std::time::Duration;
+use std::{path::PathBuf, sync::Arc, time::Duration};
 
-use crate::error::CryptoBotResult;
+use crate::{
+    config::{
+        DEFAULT_API_URL, DEFAULT_FAILOVER_COOLDOWN, DEFAULT_METADATA_CACHE_TTL, DEFAULT_TIMEOUT,
+        DEFAULT_WEBHOOK_EXPIRATION_TIME,
+    },
+    error::{CryptoBotError, CryptoBotResult},
+    models::{ReceiptKey, ResponseEnvelope, Rounding, TransferDefaults},
+    validation::validate_url,
+};
 
-use super::{CryptoBot, DEFAULT_API_URL, DEFAULT_TIMEOUT};
+#[cfg(feature = "disk-cache")]
+use super::disk_cache::{DiskCache, DiskCacheLocation};
+use super::{
+    circuit_breaker::{CircuitBreaker, CircuitBreakerConfig},
+    environment::Environment,
+    failover::Failover,
+    id_generator::{IdGenerator, IdGeneratorHandle},
+    link_decorator::{LinkDecorator, LinkDecoratorHandle},
+    metadata::MetadataCache,
+    transport::{HttpTransport, RecordingTransport, Transport, TransportHandle},
+    BeforeRequestHook, CryptoBot, ResponseHook, WarningHook,
+};
+#[cfg(feature = "tokio")]
+use super::{RateLimiter, RetryConfig};
+#[cfg(feature = "disk-cache")]
+use crate::config::DEFAULT_DISK_CACHE_TTL;
 
 pub struct NoAPIToken;
 
 pub struct ClientBuilder<T> {
     api_token: T,
     base_url: String,
+    base_urls: Option<Vec<String>>,
+    failover_cooldown: Duration,
     headers: Option<Vec<(HeaderName, HeaderValue)>>,
     timeout: Duration,
+    connect_timeout: Option<Duration>,
+    read_timeout: Option<Duration>,
+    webhook_expiration_time: Duration,
+    transfer_defaults: TransferDefaults,
+    circuit_breaker: Option<CircuitBreakerConfig>,
+    response_hook: Option<ResponseHook>,
+    clamp_counts: bool,
+    rounding: Rounding,
+    warning_hook: Option<WarningHook>,
+    prefetch_metadata: bool,
+    transport: Option<Arc<dyn Transport>>,
+    id_generator: Option<IdGeneratorHandle>,
+    record_path: Option<PathBuf>,
+    max_response_size: Option<usize>,
+    http2_prior_knowledge: bool,
+    http2_keep_alive_interval: Option<Duration>,
+    link_decorator: Option<LinkDecoratorHandle>,
+    receipt_key: Option<ReceiptKey>,
+    before_create_invoice: Option<BeforeRequestHook>,
+    before_transfer: Option<BeforeRequestHook>,
+    environment: Option<Environment>,
+    allow_testnet_assets: bool,
+    #[cfg(feature = "disk-cache")]
+    disk_cache: Option<DiskCacheLocation>,
+    #[cfg(feature = "disk-cache")]
+    disk_cache_ttl: Duration,
+    #[cfg(feature = "tokio")]
+    retry: Option<RetryConfig>,
+    #[cfg(feature = "tokio")]
+    rate_limiter: Option<RateLimiter>,
 }
 
 impl<T> ClientBuilder<T> {
@@ -20,6 +75,26 @@ impl<T> ClientBuilder<T> {
         self
     }
 
+    /// Sets several base URLs to fail over between, in order of preference. Replaces `base_url`.
+    ///
+    /// A URL that fails with a connect error or a 5xx response is skipped for the `failover_cooldown`,
+    /// and the request is sent again to the next URL unless it may already have taken effect (a
+    /// non-idempotent endpoint answered with a 5xx). After the cool-down, the URL is probed again by the next request.
+    pub fn base_urls<I, S>(mut self, base_urls: I) -> Self
+    where
+        I: IntoIterator<Item = S>,
+        S: Into<String>,
+    {
+        self.base_urls = Some(base_urls.into_iter().map(Into::into).collect());
+        self
+    }
+
+    /// Sets how long a failing base URL is skipped. Optional. Defaults to 30 seconds.
+    pub fn failover_cooldown(mut self, cooldown: Duration) -> Self {
+        self.failover_cooldown = cooldown;
+        self
+    }
+
     pub fn headers(mut self, headers: Vec<(HeaderName, HeaderValue)>) -> Self {
         self.headers = Some(headers);
         self
@@ -29,6 +104,222 @@ impl<T> ClientBuilder<T> {
         self.timeout = timeout;
         self
     }
+
+    /// Sets the timeout for establishing a connection, including TLS.
+    /// Optional. Only the total `timeout` applies if unset.
+    pub fn connect_timeout(mut self, timeout: Duration) -> Self {
+        self.connect_timeout = Some(timeout);
+        self
+    }
+
+    /// Sets the timeout for each read of the response, reset after every successful read.
+    /// Optional. Only the total `timeout` applies if unset.
+    pub fn read_timeout(mut self, timeout: Duration) -> Self {
+        self.read_timeout = Some(timeout);
+        self
+    }
+
+    /// Speaks HTTP/2 without negotiating it first, e.g. for a self-hosted gateway that only accepts
+    /// cleartext HTTP/2. Optional. Ignored with a custom `transport`.
+    pub fn http2_prior_knowledge(mut self) -> Self {
+        self.http2_prior_knowledge = true;
+        self
+    }
+
+    /// Sends HTTP/2 pings at this interval to keep idle connections alive.
+    /// Optional. Disabled by default and ignored with a custom `transport`.
+    pub fn http2_keep_alive_interval(mut self, interval: Duration) -> Self {
+        self.http2_keep_alive_interval = Some(interval);
+        self
+    }
+
+    /// Sets the default expiration time of webhook handlers created with `webhook_handler()`.
+    pub fn webhook_expiration_time(mut self, expiration_time: Duration) -> Self {
+        self.webhook_expiration_time = expiration_time;
+        self
+    }
+
+    /// Sets default options applied to every transfer made with the client.
+    /// Options set on an individual `TransferBuilder` take precedence.
+    pub fn transfer_defaults(mut self, transfer_defaults: TransferDefaults) -> Self {
+        self.transfer_defaults = transfer_defaults;
+        self
+    }
+
+    /// Enables a per-endpoint circuit breaker.
+    /// While the circuit of an endpoint is open, requests to it fail fast with `CryptoBotError::CircuitOpen`.
+    pub fn circuit_breaker(mut self, config: CircuitBreakerConfig) -> Self {
+        self.circuit_breaker = Some(config);
+        self
+    }
+
+    /// Retries failed requests, optionally within a retry budget shared by every request.
+    /// Requires the `tokio` feature for the delays between attempts.
+    #[cfg(feature = "tokio")]
+    pub fn retry(mut self, config: RetryConfig) -> Self {
+        self.retry = Some(config);
+        self
+    }
+
+    /// Throttles outgoing requests to `requests_per_second` on average, in bursts of up to one second of requests.
+    /// Requests over the limit wait for their turn. Requires the `tokio` feature.
+    #[cfg(feature = "tokio")]
+    pub fn rate_limit(mut self, requests_per_second: f64) -> Self {
+        let burst = requests_per_second.ceil().clamp(1.0, u32::MAX as f64) as u32;
+        self.rate_limiter = Some(RateLimiter::new(requests_per_second, burst));
+        self
+    }
+
+    /// Throttles outgoing requests with `limiter`, e.g. a clone shared with other clients of the same app.
+    /// Requires the `tokio` feature.
+    #[cfg(feature = "tokio")]
+    pub fn rate_limiter(mut self, limiter: RateLimiter) -> Self {
+        self.rate_limiter = Some(limiter);
+        self
+    }
+
+    /// Keeps fetched exchange rates and currencies on disk, so they survive process restarts,
+    /// e.g. for CLI tools and serverless functions. Stored in the cache directory of the platform,
+    /// such as `~/.cache/crypto-pay-api` on Linux. Requires the `disk-cache` feature.
+    #[cfg(feature = "disk-cache")]
+    pub fn disk_cache(mut self) -> Self {
+        self.disk_cache = Some(DiskCacheLocation::Default);
+        self
+    }
+
+    /// Same as `disk_cache`, but stores the files in `dir`. Requires the `disk-cache` feature.
+    #[cfg(feature = "disk-cache")]
+    pub fn disk_cache_dir(mut self, dir: impl Into<PathBuf>) -> Self {
+        self.disk_cache = Some(DiskCacheLocation::Dir(dir.into()));
+        self
+    }
+
+    /// Sets how long data from the disk cache is reused. Optional. Defaults to 5 minutes.
+    #[cfg(feature = "disk-cache")]
+    pub fn disk_cache_ttl(mut self, ttl: Duration) -> Self {
+        self.disk_cache_ttl = ttl;
+        self
+    }
+
+    /// Registers a hook that receives the envelope (`ok`, `error_code`, `error` and the raw JSON)
+    /// of every API response before its `result` is extracted, e.g. for logging.
+    pub fn on_response<F>(mut self, hook: F) -> Self
+    where
+        F: Fn(&ResponseEnvelope) + Send + Sync + 'static,
+    {
+        self.response_hook = Some(ResponseHook::new(hook));
+        self
+    }
+
+    /// Clamps `count` values of list requests into the range 1-1000 instead of rejecting them.
+    /// A warning is passed to the `on_warning` hook whenever a value is adjusted.
+    pub fn clamp_counts(mut self, clamp_counts: bool) -> Self {
+        self.clamp_counts = clamp_counts;
+        self
+    }
+
+    /// Sets how `amount_fiat` prices are rounded to the precision of the asset. Defaults to
+    /// `Rounding::Ceiling`, so converted prices are rounded up. Overridable per invoice.
+    pub fn rounding(mut self, rounding: Rounding) -> Self {
+        self.rounding = rounding;
+        self
+    }
+
+    /// Registers a hook that receives warnings about requests that were adjusted instead of rejected.
+    pub fn on_warning<F>(mut self, hook: F) -> Self
+    where
+        F: Fn(&str) + Send + Sync + 'static,
+    {
+        self.warning_hook = Some(WarningHook::new(hook));
+        self
+    }
+
+    /// Sets the environment used to validate assets, e.g. for a gateway in front of the testnet API.
+    /// Optional. Detected from the base URL by default.
+    pub fn environment(mut self, environment: Environment) -> Self {
+        self.environment = Some(environment);
+        self
+    }
+
+    /// Allows testnet-only assets such as JET on mainnet instead of rejecting them before the request.
+    /// Optional. Use it if such an asset becomes available on mainnet before this library is updated.
+    pub fn allow_testnet_assets(mut self, allow_testnet_assets: bool) -> Self {
+        self.allow_testnet_assets = allow_testnet_assets;
+        self
+    }
+
+    /// Fetches exchange rates and currencies concurrently when the client is built with `build_async()`.
+    /// Failures are not fatal, they are passed to the `on_warning` hook and the data is fetched on first use.
+    /// With `build()`, the prefetch is spawned on the current tokio runtime if there is one (requires the
+    /// `tokio` feature), otherwise a warning is passed to the `on_warning` hook.
+    pub fn prefetch_metadata(mut self, prefetch_metadata: bool) -> Self {
+        self.prefetch_metadata = prefetch_metadata;
+        self
+    }
+
+    /// Rejects responses larger than `bytes` with `CryptoBotError::ResponseTooLarge`.
+    /// The HTTP transport stops reading the body as soon as the limit is exceeded.
+    pub fn max_response_size(mut self, bytes: usize) -> Self {
+        self.max_response_size = Some(bytes);
+        self
+    }
+
+    /// Sends requests with a custom transport instead of HTTP, e.g. a `ReplayTransport`.
+    pub fn transport(mut self, transport: impl Transport + 'static) -> Self {
+        self.transport = Some(Arc::new(transport));
+        self
+    }
+
+    /// Generates spend IDs and other IDs with `generator` instead of randomly.
+    /// Use a `SeededIdGenerator` in tests so request bodies and recorded journals stay stable.
+    pub fn id_generator(mut self, generator: impl IdGenerator + 'static) -> Self {
+        self.id_generator = Some(IdGeneratorHandle(Arc::new(generator)));
+        self
+    }
+
+    /// Appends every request and its response or error to a JSONL journal at `path`.
+    /// Entries are written in the background and flushed when the last clone of the client is dropped.
+    /// Replay the journal offline with `ReplayTransport::from_file`.
+    pub fn record_mode(mut self, path: impl Into<PathBuf>) -> Self {
+        self.record_path = Some(path.into());
+        self
+    }
+
+    /// Registers a hook called with the serialized params of every `createInvoice` request before it is sent.
+    /// Returning an error vetoes the request, which then fails with `CryptoBotError::Rejected`.
+    pub fn before_create_invoice<F, E>(mut self, hook: F) -> Self
+    where
+        F: Fn(&serde_json::Value) -> Result<(), E> + Send + Sync + 'static,
+        E: Into<Box<dyn std::error::Error + Send + Sync>>,
+    {
+        self.before_create_invoice = Some(BeforeRequestHook::new(hook));
+        self
+    }
+
+    /// Registers a hook called with the serialized params of every `transfer` request before it is sent.
+    /// Returning an error vetoes the request, which then fails with `CryptoBotError::Rejected`.
+    pub fn before_transfer<F, E>(mut self, hook: F) -> Self
+    where
+        F: Fn(&serde_json::Value) -> Result<(), E> + Send + Sync + 'static,
+        E: Into<Box<dyn std::error::Error + Send + Sync>>,
+    {
+        self.before_transfer = Some(BeforeRequestHook::new(hook));
+        self
+    }
+
+    /// Transforms the payment URLs of created invoices, e.g. to add UTM parameters or shorten them.
+    pub fn link_decorator(mut self, decorator: impl LinkDecorator + 'static) -> Self {
+        self.link_decorator = Some(LinkDecoratorHandle(Arc::new(decorator)));
+        self
+    }
+
+    /// Signs every created invoice and transfer with HMAC-SHA-256 keyed with `key`, see [`Receipt`](crate::models::Receipt).
+    ///
+    /// The receipts are attached as `Invoice::receipt` and `Transfer::receipt`.
+    pub fn receipt_key(mut self, key: impl AsRef<[u8]>) -> Self {
+        self.receipt_key = Some(ReceiptKey(key.as_ref().to_vec()));
+        self
+    }
 }
 
 impl ClientBuilder<NoAPIToken> {
@@ -36,8 +327,40 @@ impl ClientBuilder<NoAPIToken> {
         Self {
             api_token: NoAPIToken,
             base_url: DEFAULT_API_URL.to_string(),
+            base_urls: None,
+            failover_cooldown: Duration::from_secs(DEFAULT_FAILOVER_COOLDOWN),
             headers: None,
             timeout: Duration::from_secs(DEFAULT_TIMEOUT),
+            connect_timeout: None,
+            read_timeout: None,
+            webhook_expiration_time: Duration::from_secs(DEFAULT_WEBHOOK_EXPIRATION_TIME),
+            transfer_defaults: TransferDefaults::default(),
+            circuit_breaker: None,
+            response_hook: None,
+            clamp_counts: false,
+            rounding: Rounding::default(),
+            warning_hook: None,
+            prefetch_metadata: false,
+            transport: None,
+            id_generator: None,
+            record_path: None,
+            max_response_size: None,
+            http2_prior_knowledge: false,
+            http2_keep_alive_interval: None,
+            link_decorator: None,
+            receipt_key: None,
+            before_create_invoice: None,
+            before_transfer: None,
+            environment: None,
+            allow_testnet_assets: false,
+            #[cfg(feature = "disk-cache")]
+            disk_cache: None,
+            #[cfg(feature = "disk-cache")]
+            disk_cache_ttl: Duration::from_secs(DEFAULT_DISK_CACHE_TTL),
+            #[cfg(feature = "tokio")]
+            retry: None,
+            #[cfg(feature = "tokio")]
+            rate_limiter: None,
         }
     }
 
@@ -45,8 +368,40 @@ impl ClientBuilder<NoAPIToken> {
         ClientBuilder {
             api_token: api_token.into(),
             base_url: self.base_url,
+            base_urls: self.base_urls,
+            failover_cooldown: self.failover_cooldown,
             headers: self.headers,
             timeout: self.timeout,
+            connect_timeout: self.connect_timeout,
+            read_timeout: self.read_timeout,
+            webhook_expiration_time: self.webhook_expiration_time,
+            transfer_defaults: self.transfer_defaults,
+            circuit_breaker: self.circuit_breaker,
+            response_hook: self.response_hook,
+            clamp_counts: self.clamp_counts,
+            rounding: self.rounding,
+            warning_hook: self.warning_hook,
+            prefetch_metadata: self.prefetch_metadata,
+            transport: self.transport,
+            id_generator: self.id_generator,
+            record_path: self.record_path,
+            max_response_size: self.max_response_size,
+            http2_prior_knowledge: self.http2_prior_knowledge,
+            http2_keep_alive_interval: self.http2_keep_alive_interval,
+            link_decorator: self.link_decorator,
+            receipt_key: self.receipt_key,
+            before_create_invoice: self.before_create_invoice,
+            before_transfer: self.before_transfer,
+            environment: self.environment,
+            allow_testnet_assets: self.allow_testnet_assets,
+            #[cfg(feature = "disk-cache")]
+            disk_cache: self.disk_cache,
+            #[cfg(feature = "disk-cache")]
+            disk_cache_ttl: self.disk_cache_ttl,
+            #[cfg(feature = "tokio")]
+            retry: self.retry,
+            #[cfg(feature = "tokio")]
+            rate_limiter: self.rate_limiter,
         }
     }
 }
@@ -59,24 +414,169 @@ impl Default for ClientBuilder<NoAPIToken> {
 
 impl ClientBuilder<String> {
     pub fn build(self) -> CryptoBotResult<CryptoBot> {
-        let client = reqwest::Client::builder().timeout(self.timeout).build()?;
+        let prefetch_metadata = self.prefetch_metadata;
+        let client = self.build_client()?;
+
+        if prefetch_metadata {
+            spawn_prefetch(&client);
+        }
+
+        Ok(client)
+    }
+
+    /// Builds the client and, if `prefetch_metadata(true)` is set, prefetches exchange rates and currencies
+    pub async fn build_async(self) -> CryptoBotResult<CryptoBot> {
+        let prefetch_metadata = self.prefetch_metadata;
+        let client = self.build_client()?;
+
+        if prefetch_metadata {
+            if let Err(e) = client.prefetch_metadata().await {
+                client.warn(&format!("failed to prefetch metadata: {e}"));
+            }
+        }
+
+        Ok(client)
+    }
+
+    fn build_client(mut self) -> CryptoBotResult<CryptoBot> {
+        let mut failover = None;
+        if let Some(base_urls) = self.base_urls.take() {
+            for base_url in &base_urls {
+                validate_url(base_url, "base_urls")?;
+            }
+            let base_urls: Vec<String> = base_urls
+                .iter()
+                .map(|url| url.trim_end_matches('/').to_string())
+                .collect();
+
+            self.base_url = base_urls.first().cloned().ok_or_else(|| CryptoBotError::ConfigError {
+                message: "base_urls must contain at least one URL".to_string(),
+            })?;
+            if base_urls.len() > 1 {
+                failover = Some(Arc::new(Failover::new(base_urls, self.failover_cooldown)));
+            }
+        }
+
+        validate_url(&self.base_url, "base_url")?;
+
+        #[cfg(feature = "tokio")]
+        if let Some(limiter) = &self.rate_limiter {
+            let requests_per_second = limiter.requests_per_second();
+            if !(requests_per_second.is_finite() && requests_per_second > 0.0) {
+                return Err(CryptoBotError::ConfigError {
+                    message: format!(
+                        "rate limit must be a positive number of requests per second, got {requests_per_second}"
+                    ),
+                });
+            }
+        }
+
+        let mut transport = match self.transport {
+            Some(transport) => transport,
+            None => {
+                let mut client = reqwest::Client::builder().timeout(self.timeout);
+                #[cfg(feature = "native-tls")]
+                {
+                    client = client.tls_backend_native();
+                }
+                #[cfg(all(feature = "rustls-tls", not(feature = "native-tls")))]
+                {
+                    client = client.tls_backend_rustls();
+                }
+                if let Some(timeout) = self.connect_timeout {
+                    client = client.connect_timeout(timeout);
+                }
+                if let Some(timeout) = self.read_timeout {
+                    client = client.read_timeout(timeout);
+                }
+                if self.http2_prior_knowledge {
+                    client = client.http2_prior_knowledge();
+                }
+                if let Some(interval) = self.http2_keep_alive_interval {
+                    client = client.http2_keep_alive_interval(interval);
+                }
+                Arc::new(HttpTransport::new(client.build()?, self.max_response_size))
+            }
+        };
+
+        if let Some(path) = &self.record_path {
+            transport = Arc::new(RecordingTransport::open(transport, path)?);
+        }
+
+        let environment = self
+            .environment
+            .unwrap_or_else(|| Environment::from_base_url(&self.base_url));
+
+        #[allow(unused_mut)]
+        let mut metadata = MetadataCache::new(Duration::from_secs(DEFAULT_METADATA_CACHE_TTL));
+        #[cfg(feature = "disk-cache")]
+        if let Some(location) = self.disk_cache {
+            metadata = metadata.with_disk_cache(DiskCache::new(location, &self.base_url, self.disk_cache_ttl)?);
+        }
+
         Ok(CryptoBot {
             api_token: self.api_token,
-            client,
+            transport: TransportHandle(transport),
             base_url: self.base_url,
+            failover,
             headers: self.headers,
+            transfer_defaults: self.transfer_defaults,
+            circuit_breaker: self.circuit_breaker.map(|config| Arc::new(CircuitBreaker::new(config))),
+            response_hook: self.response_hook,
+            clamp_counts: self.clamp_counts,
+            rounding: self.rounding,
+            warning_hook: self.warning_hook,
+            webhook_expiration_time: self.webhook_expiration_time,
+            metadata: Arc::new(metadata),
+            max_response_size: self.max_response_size,
+            response_sizes: Arc::default(),
+            request_stats: Arc::default(),
+            id_generator: self.id_generator.unwrap_or_default(),
+            link_decorator: self.link_decorator,
+            receipt_key: self.receipt_key,
+            before_create_invoice: self.before_create_invoice,
+            before_transfer: self.before_transfer,
+            environment,
+            allow_testnet_assets: self.allow_testnet_assets,
+            #[cfg(feature = "tokio")]
+            retry: self.retry,
+            #[cfg(feature = "tokio")]
+            rate_limiter: self.rate_limiter,
             #[cfg(test)]
             test_rates: None,
         })
     }
 }
 
+/// Prefetches metadata in the background for `build()`, as it cannot wait for the requests
+fn spawn_prefetch(client: &CryptoBot) {
+    #[cfg(feature = "tokio")]
+    if let Ok(runtime) = tokio::runtime::Handle::try_current() {
+        let client = client.shared();
+        runtime.spawn(async move {
+            if let Err(e) = client.prefetch_metadata().await {
+                client.warn(&format!("failed to prefetch metadata: {e}"));
+            }
+        });
+        return;
+    }
+
+    client.warn("prefetch_metadata requires build_async() or a tokio runtime, metadata will be fetched on first use");
+}
+
 #[cfg(test)]
 mod tests {
     use reqwest::header::HeaderName;
     use std::str::FromStr;
 
-    use crate::{api::ExchangeRateAPI, utils::test_utils::TestContext};
+    use rust_decimal_macros::dec;
+    use std::sync::{Arc, Mutex};
+
+    use crate::{
+        api::{ExchangeRateAPI, InvoiceAPI},
+        models::CryptoCurrencyCode,
+        utils::test_utils::TestContext,
+    };
 
     use super::*;
 
@@ -125,4 +625,157 @@ mod tests {
             )))
             .unwrap_or(false));
     }
+
+    #[test]
+    fn test_builder_base_urls_fail_over_on_connect_error() {
+        let mut ctx = TestContext::new();
+        let rates = ctx.mock_exchange_rates_response().expect(2);
+
+        let client = ClientBuilder::new()
+            .base_urls(["http://127.0.0.1:1", ctx.server.url().as_str()])
+            .api_token("test_token")
+            .build()
+            .unwrap();
+        assert_eq!(client.active_base_url(), "http://127.0.0.1:1");
+
+        assert!(ctx.run(async { client.get_exchange_rates().execute().await }).is_ok());
+        assert_eq!(client.active_base_url(), ctx.server.url());
+
+        // The unreachable URL is skipped while cooling down
+        assert!(ctx.run(async { client.get_exchange_rates().execute().await }).is_ok());
+        rates.assert();
+    }
+
+    #[test]
+    fn test_builder_base_urls_fail_over_on_server_error() {
+        let mut primary = TestContext::new();
+        let failing = primary
+            .server
+            .mock("GET", "/getExchangeRates")
+            .with_status(502)
+            .expect(1)
+            .create();
+        let mut ctx = TestContext::new();
+        let _rates = ctx.mock_exchange_rates_response();
+
+        let client = ClientBuilder::new()
+            .base_urls([primary.server.url(), ctx.server.url()])
+            .failover_cooldown(Duration::ZERO)
+            .api_token("test_token")
+            .build()
+            .unwrap();
+
+        assert!(ctx.run(async { client.get_exchange_rates().execute().await }).is_ok());
+        failing.assert();
+
+        // Without a cool-down, the primary URL is probed again by the next request
+        assert_eq!(client.active_base_url(), primary.server.url());
+    }
+
+    #[test]
+    fn test_builder_base_urls_must_not_be_empty() {
+        let result = ClientBuilder::new()
+            .base_urls(Vec::<String>::new())
+            .api_token("test_token")
+            .build();
+        assert!(matches!(result, Err(CryptoBotError::ConfigError { .. })));
+    }
+
+    #[test]
+    fn test_build_async_prefetches_metadata() {
+        let mut ctx = TestContext::new();
+        let rates = ctx.mock_exchange_rates_response().expect(1);
+        let currencies = ctx.mock_currencies_response().expect(1);
+        let _invoice = ctx.mock_create_invoice_response();
+
+        let client = ctx
+            .run(async {
+                ClientBuilder::new()
+                    .base_url(ctx.server.url())
+                    .prefetch_metadata(true)
+                    .api_token("test_token")
+                    .build_async()
+                    .await
+            })
+            .unwrap();
+
+        assert!(client.cached_currencies().is_some());
+
+        // The invoice validation reuses the prefetched exchange rates
+        let result = ctx.run(async {
+            client
+                .create_invoice()
+                .asset(CryptoCurrencyCode::Ton)
+                .amount(dec!(10))
+                .execute()
+                .await
+        });
+
+        assert!(result.is_ok());
+        rates.assert();
+        currencies.assert();
+    }
+
+    #[test]
+    fn test_build_async_prefetch_failure_is_not_fatal() {
+        let ctx = TestContext::new();
+        let warnings = Arc::new(Mutex::new(Vec::new()));
+        let captured = warnings.clone();
+
+        let client = ctx.run(async {
+            ClientBuilder::new()
+                .base_url(ctx.server.url())
+                .prefetch_metadata(true)
+                .on_warning(move |warning| captured.lock().unwrap().push(warning.to_string()))
+                .api_token("test_token")
+                .build_async()
+                .await
+        });
+
+        assert!(client.is_ok());
+        assert!(client.unwrap().cached_currencies().is_none());
+        assert_eq!(warnings.lock().unwrap().len(), 1);
+        assert!(warnings.lock().unwrap()[0].starts_with("failed to prefetch metadata"));
+    }
+
+    #[test]
+    fn test_build_warns_when_prefetch_requires_async() {
+        let warnings = Arc::new(Mutex::new(Vec::new()));
+        let captured = warnings.clone();
+
+        let client = ClientBuilder::new()
+            .prefetch_metadata(true)
+            .on_warning(move |warning| captured.lock().unwrap().push(warning.to_string()))
+            .api_token("test_token")
+            .build();
+
+        assert!(client.is_ok());
+        assert_eq!(warnings.lock().unwrap().len(), 1);
+    }
+
+    #[cfg(feature = "tokio")]
+    #[test]
+    fn test_build_spawns_prefetch_on_runtime() {
+        let mut ctx = TestContext::new();
+        let rates = ctx.mock_exchange_rates_response().expect(1);
+        let currencies = ctx.mock_currencies_response().expect(1);
+
+        let client = ctx.run(async {
+            let client = ClientBuilder::new()
+                .prefetch_metadata(true)
+                .api_token("test_token")
+                .base_url(ctx.server.url())
+                .build()
+                .unwrap();
+            while client.metadata.exchange_rates().is_none() || client.metadata.currencies().is_none() {
+                tokio::task::yield_now().await;
+            }
+            client
+        });
+
+        // The cached metadata is reused instead of being fetched again
+        ctx.run(async { client.exchange_rates().await }).unwrap();
+        rates.assert();
+        currencies.assert();
+    }
 }
@@ -0,0 +1,306 @@
+use std::{
+    collections::HashMap,
+    sync::Mutex,
+    time::{Duration, Instant},
+};
+
+use crate::{
+    error::{CryptoBotError, CryptoBotResult},
+    models::APIEndpoint,
+};
+
+/// Configuration of the per-endpoint circuit breaker
+///
+/// # Example
+/// ```
+/// use crypto_pay_api::prelude::*;
+/// use std::time::Duration;
+///
+/// let client = CryptoBot::builder()
+///     .api_token("YOUR_API_TOKEN")
+///     .circuit_breaker(CircuitBreakerConfig {
+///         failure_threshold: 3,
+///         reset_timeout: Duration::from_secs(10),
+///     })
+///     .build()
+///     .unwrap();
+///
+/// assert_eq!(client.circuit_state(APIEndpoint::GetMe), Some(CircuitState::Closed));
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct CircuitBreakerConfig {
+    /// Number of consecutive failures after which the circuit opens.
+    pub failure_threshold: u32,
+    /// How long the circuit stays open before a single probe request is let through.
+    pub reset_timeout: Duration,
+}
+
+impl Default for CircuitBreakerConfig {
+    fn default() -> Self {
+        Self {
+            failure_threshold: 5,
+            reset_timeout: Duration::from_secs(30),
+        }
+    }
+}
+
+/// State of the circuit for a single endpoint
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CircuitState {
+    /// Requests are sent normally.
+    Closed,
+    /// Requests fail fast with `CryptoBotError::CircuitOpen`.
+    Open,
+    /// A probe request is in flight; its outcome closes or reopens the circuit.
+    HalfOpen,
+}
+
+impl std::fmt::Display for CircuitState {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{self:?}")
+    }
+}
+
+#[derive(Debug, Default)]
+struct EndpointCircuit {
+    consecutive_failures: u32,
+    opened_at: Option<Instant>,
+    probing: bool,
+}
+
+impl EndpointCircuit {
+    fn state(&self) -> CircuitState {
+        match (self.opened_at, self.probing) {
+            (None, _) => CircuitState::Closed,
+            (Some(_), true) => CircuitState::HalfOpen,
+            (Some(_), false) => CircuitState::Open,
+        }
+    }
+}
+
+#[derive(Debug)]
+pub(crate) struct CircuitBreaker {
+    config: CircuitBreakerConfig,
+    circuits: Mutex<HashMap<APIEndpoint, EndpointCircuit>>,
+}
+
+impl CircuitBreaker {
+    pub(crate) fn new(config: CircuitBreakerConfig) -> Self {
+        Self {
+            config,
+            circuits: Mutex::new(HashMap::new()),
+        }
+    }
+
+    /// Checks whether a request to `endpoint` may be sent.
+    ///
+    /// Once the reset timeout has elapsed on an open circuit, the first caller is let through
+    /// as a probe and the circuit becomes half-open until the outcome is recorded with
+    /// [`CircuitPermit::record`]. A probe dropped without an outcome, e.g. a cancelled request,
+    /// leaves the circuit open so the next request becomes the probe.
+    pub(crate) fn acquire(&self, endpoint: APIEndpoint) -> CryptoBotResult<CircuitPermit<'_>> {
+        let mut circuits = self.circuits.lock().unwrap_or_else(|e| e.into_inner());
+        let circuit = circuits.entry(endpoint).or_default();
+
+        let Some(opened_at) = circuit.opened_at else {
+            return Ok(CircuitPermit {
+                breaker: self,
+                endpoint,
+                probe: false,
+            });
+        };
+
+        let elapsed = opened_at.elapsed();
+        if !circuit.probing && elapsed >= self.config.reset_timeout {
+            circuit.probing = true;
+            return Ok(CircuitPermit {
+                breaker: self,
+                endpoint,
+                probe: true,
+            });
+        }
+
+        Err(CryptoBotError::CircuitOpen {
+            endpoint: endpoint.as_str().to_string(),
+            retry_after: self.config.reset_timeout.saturating_sub(elapsed),
+        })
+    }
+
+    /// Records the outcome of a request that was let through by [`CircuitBreaker::acquire`].
+    fn record<T>(&self, endpoint: APIEndpoint, result: &CryptoBotResult<T>) {
+        let mut circuits = self.circuits.lock().unwrap_or_else(|e| e.into_inner());
+        let circuit = circuits.entry(endpoint).or_default();
+
+        match result {
+            Err(error) if is_service_failure(error) => {
+                circuit.consecutive_failures = circuit.consecutive_failures.saturating_add(1);
+                if circuit.probing || circuit.consecutive_failures >= self.config.failure_threshold {
+                    circuit.opened_at = Some(Instant::now());
+                }
+                circuit.probing = false;
+            }
+            _ => *circuit = EndpointCircuit::default(),
+        }
+    }
+
+    pub(crate) fn state(&self, endpoint: APIEndpoint) -> CircuitState {
+        let circuits = self.circuits.lock().unwrap_or_else(|e| e.into_inner());
+        circuits
+            .get(&endpoint)
+            .map_or(CircuitState::Closed, EndpointCircuit::state)
+    }
+
+    pub(crate) fn states(&self) -> Vec<(APIEndpoint, CircuitState)> {
+        let circuits = self.circuits.lock().unwrap_or_else(|e| e.into_inner());
+        circuits
+            .iter()
+            .map(|(endpoint, circuit)| (*endpoint, circuit.state()))
+            .collect()
+    }
+}
+
+/// Permission to send one request, returned by [`CircuitBreaker::acquire`]
+pub(crate) struct CircuitPermit<'a> {
+    breaker: &'a CircuitBreaker,
+    endpoint: APIEndpoint,
+    probe: bool,
+}
+
+impl CircuitPermit<'_> {
+    /// Records the outcome of the request
+    pub(crate) fn record<T>(mut self, result: &CryptoBotResult<T>) {
+        self.probe = false;
+        self.breaker.record(self.endpoint, result);
+    }
+}
+
+impl Drop for CircuitPermit<'_> {
+    fn drop(&mut self) {
+        if self.probe {
+            let mut circuits = self.breaker.circuits.lock().unwrap_or_else(|e| e.into_inner());
+            if let Some(circuit) = circuits.get_mut(&self.endpoint) {
+                circuit.probing = false;
+            }
+        }
+    }
+}
+
+/// Only failures that indicate the service is unavailable trip the breaker. API errors such as
+/// invalid parameters are answered by a healthy service and must not open the circuit.
+fn is_service_failure(error: &CryptoBotError) -> bool {
+    match error {
+        CryptoBotError::HttpError(e) => e.status().is_none_or(|status| status.is_server_error()),
+        CryptoBotError::ApiError { code, .. } => *code == -1 || *code >= 500,
+        _ => false,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use futures::FutureExt;
+
+    use super::*;
+
+    fn service_failure() -> CryptoBotResult<()> {
+        Err(CryptoBotError::ApiError {
+            code: 500,
+            message: "Internal Server Error".to_string(),
+            details: None,
+        })
+    }
+
+    fn breaker(failure_threshold: u32, reset_timeout: Duration) -> CircuitBreaker {
+        CircuitBreaker::new(CircuitBreakerConfig {
+            failure_threshold,
+            reset_timeout,
+        })
+    }
+
+    #[test]
+    fn test_circuit_opens_after_consecutive_failures() {
+        let breaker = breaker(2, Duration::from_secs(60));
+
+        breaker.record(APIEndpoint::GetMe, &service_failure());
+        assert_eq!(breaker.state(APIEndpoint::GetMe), CircuitState::Closed);
+        assert!(breaker.acquire(APIEndpoint::GetMe).is_ok());
+
+        breaker.record(APIEndpoint::GetMe, &service_failure());
+        assert_eq!(breaker.state(APIEndpoint::GetMe), CircuitState::Open);
+
+        assert!(matches!(
+            breaker.acquire(APIEndpoint::GetMe),
+            Err(CryptoBotError::CircuitOpen { endpoint, .. }) if endpoint == "getMe"
+        ));
+
+        // Other endpoints are not affected
+        assert!(breaker.acquire(APIEndpoint::GetBalance).is_ok());
+        assert_eq!(breaker.state(APIEndpoint::GetBalance), CircuitState::Closed);
+    }
+
+    #[test]
+    fn test_success_resets_failure_count() {
+        let breaker = breaker(2, Duration::from_secs(60));
+
+        breaker.record(APIEndpoint::GetMe, &service_failure());
+        breaker.record(APIEndpoint::GetMe, &Ok(()));
+        breaker.record(APIEndpoint::GetMe, &service_failure());
+
+        assert_eq!(breaker.state(APIEndpoint::GetMe), CircuitState::Closed);
+    }
+
+    #[test]
+    fn test_client_errors_do_not_trip_circuit() {
+        let breaker = breaker(1, Duration::from_secs(60));
+
+        let result: CryptoBotResult<()> = Err(CryptoBotError::ApiError {
+            code: 400,
+            message: "AMOUNT_TOO_SMALL".to_string(),
+            details: None,
+        });
+        breaker.record(APIEndpoint::CreateInvoice, &result);
+
+        assert_eq!(breaker.state(APIEndpoint::CreateInvoice), CircuitState::Closed);
+    }
+
+    #[test]
+    fn test_half_open_probe() {
+        let breaker = breaker(1, Duration::ZERO);
+
+        breaker.record(APIEndpoint::GetMe, &service_failure());
+        assert_eq!(breaker.state(APIEndpoint::GetMe), CircuitState::Open);
+
+        // The first request after the reset timeout is a probe, concurrent ones fail fast
+        let probe = breaker.acquire(APIEndpoint::GetMe).unwrap();
+        assert_eq!(breaker.state(APIEndpoint::GetMe), CircuitState::HalfOpen);
+        assert!(breaker.acquire(APIEndpoint::GetMe).is_err());
+
+        // A failed probe reopens the circuit
+        probe.record(&service_failure());
+        assert_eq!(breaker.state(APIEndpoint::GetMe), CircuitState::Open);
+
+        // A successful probe closes it
+        breaker.acquire(APIEndpoint::GetMe).unwrap().record(&Ok(()));
+        assert_eq!(breaker.state(APIEndpoint::GetMe), CircuitState::Closed);
+        assert_eq!(breaker.states(), vec![(APIEndpoint::GetMe, CircuitState::Closed)]);
+    }
+
+    #[test]
+    fn test_dropped_probe_lets_the_next_request_probe() {
+        let breaker = breaker(1, Duration::ZERO);
+        breaker.record(APIEndpoint::CreateInvoice, &service_failure());
+
+        // A probe cancelled before its outcome is known, e.g. by a caller timeout
+        let probe = async {
+            let _permit = breaker.acquire(APIEndpoint::CreateInvoice).unwrap();
+            std::future::pending::<()>().await;
+        };
+        let mut probe = Box::pin(probe);
+        assert!((&mut probe).now_or_never().is_none());
+        assert_eq!(breaker.state(APIEndpoint::CreateInvoice), CircuitState::HalfOpen);
+        drop(probe);
+
+        assert_eq!(breaker.state(APIEndpoint::CreateInvoice), CircuitState::Open);
+        breaker.acquire(APIEndpoint::CreateInvoice).unwrap().record(&Ok(()));
+        assert_eq!(breaker.state(APIEndpoint::CreateInvoice), CircuitState::Closed);
+    }
+}
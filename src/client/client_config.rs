@@ -0,0 +1,307 @@
+use std::time::Duration;
+
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    config::ENV_API_TOKEN,
+    error::{CryptoBotError, CryptoBotResult},
+    models::{Rounding, TransferDefaults},
+};
+
+use super::{CircuitBreakerConfig, CryptoBot};
+
+/// Client settings that can be loaded from a file, so deployments can tune the client without code changes
+///
+/// Every field is optional and unset fields keep the `ClientBuilder` defaults. The file never holds
+/// the API token itself, only the name of the environment variable it is read from.
+///
+/// # Example
+/// A TOML file read with `CryptoBot::from_config_file` (`config-file` feature):
+/// ```toml
+/// api_token_env = "SHOP_CRYPTO_PAY_TOKEN"
+/// base_urls = ["https://pay.crypt.bot/api", "https://mirror.example.com/api"]
+/// timeout_secs = 20
+/// circuit_breaker_failure_threshold = 5
+/// rate_limit_per_second = 10
+/// retry_max_retries = 3
+///
+/// [transfer_defaults]
+/// comment = "Thanks for playing!"
+/// ```
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct ClientConfig {
+    /// Environment variable holding the API token. Defaults to `CRYPTO_PAY_API_TOKEN`.
+    pub api_token_env: Option<String>,
+    /// Base URL of the API.
+    pub base_url: Option<String>,
+    /// Base URLs to fail over between, in order of preference. Takes precedence over `base_url`.
+    pub base_urls: Option<Vec<String>>,
+    /// How long a failing base URL is skipped, in seconds.
+    pub failover_cooldown_secs: Option<u64>,
+    /// Request timeout in seconds.
+    pub timeout_secs: Option<u64>,
+    /// Connect timeout in seconds.
+    pub connect_timeout_secs: Option<u64>,
+    /// Read timeout in seconds.
+    pub read_timeout_secs: Option<u64>,
+    /// Default expiration time of webhook handlers in seconds.
+    pub webhook_expiration_time_secs: Option<u64>,
+    /// Maximum size of a response body in bytes.
+    pub max_response_size: Option<usize>,
+    /// Whether out of range `count` values are clamped instead of rejected.
+    pub clamp_counts: Option<bool>,
+    /// Rounding of converted `amount_fiat` prices: `bankers`, `floor`, `ceiling` or `half_up`.
+    pub rounding: Option<Rounding>,
+    /// Enables the circuit breaker, opening after this many consecutive failures.
+    pub circuit_breaker_failure_threshold: Option<u32>,
+    /// Enables the circuit breaker, keeping circuits open for this many seconds.
+    pub circuit_breaker_reset_timeout_secs: Option<u64>,
+    /// Throttles requests to this many per second. Requires the `tokio` feature.
+    pub rate_limit_per_second: Option<u32>,
+    /// Enables retries, retrying each request up to this many times. Requires the `tokio` feature.
+    pub retry_max_retries: Option<u32>,
+    /// Enables retries, waiting this many milliseconds before the first one. Requires the `tokio` feature.
+    pub retry_base_delay_ms: Option<u64>,
+    /// Defaults applied to transfers.
+    pub transfer_defaults: Option<TransferDefaults>,
+}
+
+impl CryptoBot {
+    /// Creates a new client from a [`ClientConfig`], reading the API token from the configured environment variable
+    ///
+    /// # Errors
+    /// * `CryptoBotError::ConfigError` - If the token is missing, a timeout or the rate limit is zero, or
+    ///   the rate limit or retries are set without the `tokio` feature
+    /// * `CryptoBotError::ValidationError` - If a base URL is invalid
+    ///
+    /// # Example
+    /// ```no_run
+    /// use crypto_pay_api::prelude::*;
+    ///
+    /// let client = CryptoBot::from_config(ClientConfig {
+    ///     timeout_secs: Some(10),
+    ///     ..ClientConfig::default()
+    /// })?;
+    /// # Ok::<(), CryptoBotError>(())
+    /// ```
+    pub fn from_config(config: ClientConfig) -> CryptoBotResult<Self> {
+        Self::from_config_with(config, |key| std::env::var(key).ok())
+    }
+
+    /// Creates a new client from a TOML or JSON [`ClientConfig`] file, picked by the file extension
+    ///
+    /// Available with the `config-file` feature.
+    ///
+    /// # Errors
+    /// * `CryptoBotError::ConfigError` - If the file cannot be read or parsed, or the settings are invalid
+    #[cfg(feature = "config-file")]
+    pub fn from_config_file(path: impl AsRef<std::path::Path>) -> CryptoBotResult<Self> {
+        Self::from_config(read_config_file(path.as_ref())?)
+    }
+
+    pub(crate) fn from_config_with<F>(config: ClientConfig, lookup: F) -> CryptoBotResult<Self>
+    where
+        F: Fn(&str) -> Option<String>,
+    {
+        let token_env = config.api_token_env.as_deref().unwrap_or(ENV_API_TOKEN);
+        let api_token = lookup(token_env)
+            .map(|token| token.trim().to_string())
+            .filter(|token| !token.is_empty())
+            .ok_or_else(|| CryptoBotError::ConfigError {
+                message: format!("{token_env} is not set"),
+            })?;
+
+        let mut builder = Self::builder().api_token(api_token);
+
+        if let Some(base_url) = config.base_url {
+            builder = builder.base_url(base_url.trim_end_matches('/'));
+        }
+        if let Some(base_urls) = config.base_urls {
+            builder = builder.base_urls(base_urls);
+        }
+        if let Some(secs) = config.failover_cooldown_secs {
+            builder = builder.failover_cooldown(Duration::from_secs(secs));
+        }
+        if let Some(secs) = config.timeout_secs {
+            builder = builder.timeout(positive_secs("timeout_secs", secs)?);
+        }
+        if let Some(secs) = config.connect_timeout_secs {
+            builder = builder.connect_timeout(positive_secs("connect_timeout_secs", secs)?);
+        }
+        if let Some(secs) = config.read_timeout_secs {
+            builder = builder.read_timeout(positive_secs("read_timeout_secs", secs)?);
+        }
+        if let Some(secs) = config.webhook_expiration_time_secs {
+            builder = builder.webhook_expiration_time(positive_secs("webhook_expiration_time_secs", secs)?);
+        }
+        if let Some(bytes) = config.max_response_size {
+            builder = builder.max_response_size(bytes);
+        }
+        if let Some(clamp_counts) = config.clamp_counts {
+            builder = builder.clamp_counts(clamp_counts);
+        }
+        if let Some(rounding) = config.rounding {
+            builder = builder.rounding(rounding);
+        }
+        if config.circuit_breaker_failure_threshold.is_some() || config.circuit_breaker_reset_timeout_secs.is_some() {
+            let defaults = CircuitBreakerConfig::default();
+            builder = builder.circuit_breaker(CircuitBreakerConfig {
+                failure_threshold: config
+                    .circuit_breaker_failure_threshold
+                    .unwrap_or(defaults.failure_threshold),
+                reset_timeout: config
+                    .circuit_breaker_reset_timeout_secs
+                    .map_or(defaults.reset_timeout, Duration::from_secs),
+            });
+        }
+        if let Some(transfer_defaults) = config.transfer_defaults {
+            builder = builder.transfer_defaults(transfer_defaults);
+        }
+
+        #[cfg(feature = "tokio")]
+        {
+            if let Some(requests_per_second) = config.rate_limit_per_second {
+                if requests_per_second == 0 {
+                    return Err(CryptoBotError::ConfigError {
+                        message: "rate_limit_per_second must be positive".to_string(),
+                    });
+                }
+                builder = builder.rate_limit(requests_per_second as f64);
+            }
+            if config.retry_max_retries.is_some() || config.retry_base_delay_ms.is_some() {
+                let defaults = super::RetryConfig::default();
+                builder = builder.retry(super::RetryConfig {
+                    max_retries: config.retry_max_retries.unwrap_or(defaults.max_retries),
+                    base_delay: config
+                        .retry_base_delay_ms
+                        .map_or(defaults.base_delay, Duration::from_millis),
+                    ..defaults
+                });
+            }
+        }
+        #[cfg(not(feature = "tokio"))]
+        if config.rate_limit_per_second.is_some()
+            || config.retry_max_retries.is_some()
+            || config.retry_base_delay_ms.is_some()
+        {
+            return Err(CryptoBotError::ConfigError {
+                message: "rate_limit_per_second and retries require the tokio feature".to_string(),
+            });
+        }
+
+        builder.build()
+    }
+}
+
+/// Reads a TOML or JSON [`ClientConfig`] file, picked by the file extension
+#[cfg(feature = "config-file")]
+fn read_config_file(path: &std::path::Path) -> CryptoBotResult<ClientConfig> {
+    let config_error = |message: String| CryptoBotError::ConfigError {
+        message: format!("{}: {message}", path.display()),
+    };
+
+    let content = std::fs::read_to_string(path).map_err(|e| config_error(e.to_string()))?;
+    match path.extension().and_then(|ext| ext.to_str()) {
+        Some("toml") => toml::from_str(&content).map_err(|e| config_error(e.to_string())),
+        Some("json") => serde_json::from_str(&content).map_err(|e| config_error(e.to_string())),
+        _ => Err(config_error("expected a .toml or .json file".to_string())),
+    }
+}
+
+fn positive_secs(field: &str, secs: u64) -> CryptoBotResult<Duration> {
+    if secs == 0 {
+        return Err(CryptoBotError::ConfigError {
+            message: format!("{field} must be a positive number of seconds"),
+        });
+    }
+    Ok(Duration::from_secs(secs))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn lookup(key: &str) -> Option<String> {
+        (key == "SHOP_TOKEN").then(|| "test_token".to_string())
+    }
+
+    #[test]
+    fn test_from_config() {
+        let config: ClientConfig = serde_json::from_str(
+            r#"{
+                "api_token_env": "SHOP_TOKEN",
+                "base_url": "https://gateway.example.com/api/",
+                "timeout_secs": 5,
+                "circuit_breaker_failure_threshold": 3,
+                "transfer_defaults": { "comment": "Thanks!" }
+            }"#,
+        )
+        .unwrap();
+
+        let client = CryptoBot::from_config_with(config, lookup).unwrap();
+
+        assert_eq!(client.api_token, "test_token");
+        assert_eq!(client.base_url, "https://gateway.example.com/api");
+        assert!(client.circuit_breaker.is_some());
+        assert_eq!(client.transfer_defaults.comment.as_deref(), Some("Thanks!"));
+    }
+
+    #[test]
+    fn test_from_config_errors() {
+        let missing_token = CryptoBot::from_config_with(ClientConfig::default(), lookup);
+        assert!(
+            matches!(missing_token, Err(CryptoBotError::ConfigError { message }) if message.contains(ENV_API_TOKEN))
+        );
+
+        let zero_timeout = CryptoBot::from_config_with(
+            ClientConfig {
+                api_token_env: Some("SHOP_TOKEN".to_string()),
+                timeout_secs: Some(0),
+                ..ClientConfig::default()
+            },
+            lookup,
+        );
+        assert!(matches!(zero_timeout, Err(CryptoBotError::ConfigError { .. })));
+
+        let unknown_field = serde_json::from_str::<ClientConfig>(r#"{ "timeout": 5 }"#);
+        assert!(unknown_field.is_err());
+    }
+
+    #[cfg(feature = "tokio")]
+    #[test]
+    fn test_from_config_rate_limit_and_retry() {
+        let config = |rate_limit_per_second| ClientConfig {
+            api_token_env: Some("SHOP_TOKEN".to_string()),
+            rate_limit_per_second,
+            retry_max_retries: Some(5),
+            ..ClientConfig::default()
+        };
+
+        let client = CryptoBot::from_config_with(config(Some(10)), lookup).unwrap();
+        assert!(client.rate_limiter.is_some());
+        let retry = client.retry.unwrap();
+        assert_eq!(retry.max_retries, 5);
+        assert_eq!(retry.base_delay, crate::client::RetryConfig::default().base_delay);
+
+        let zero_rate = CryptoBot::from_config_with(config(Some(0)), lookup);
+        assert!(matches!(zero_rate, Err(CryptoBotError::ConfigError { .. })));
+    }
+
+    #[cfg(feature = "config-file")]
+    #[test]
+    fn test_from_config_file() {
+        let path = std::env::temp_dir().join(format!("crypto-pay-config-{}.toml", std::process::id()));
+        std::fs::write(&path, "api_token_env = \"SHOP_TOKEN\"\ntimeout_secs = 5\n").unwrap();
+
+        let config = read_config_file(&path);
+        std::fs::remove_file(&path).unwrap();
+
+        let client = CryptoBot::from_config_with(config.unwrap(), lookup).unwrap();
+        assert_eq!(client.api_token, "test_token");
+        assert!(matches!(
+            CryptoBot::from_config_file(path.with_extension("yaml")),
+            Err(CryptoBotError::ConfigError { .. })
+        ));
+    }
+}
@@ -0,0 +1,132 @@
+use std::{fs, path::PathBuf, time::Duration};
+
+use chrono::{DateTime, Utc};
+use serde::{de::DeserializeOwned, Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+
+use crate::error::{CryptoBotError, CryptoBotResult};
+
+pub(crate) const EXCHANGE_RATES: &str = "exchange_rates";
+pub(crate) const CURRENCIES: &str = "currencies";
+
+/// Where the disk cache is kept, set with `ClientBuilder::disk_cache` or `ClientBuilder::disk_cache_dir`
+#[derive(Debug, Clone)]
+pub(crate) enum DiskCacheLocation {
+    /// The cache directory of the platform, e.g. `~/.cache/crypto-pay-api` on Linux.
+    Default,
+    Dir(PathBuf),
+}
+
+impl DiskCacheLocation {
+    fn resolve(self) -> CryptoBotResult<PathBuf> {
+        match self {
+            DiskCacheLocation::Dir(dir) => Ok(dir),
+            DiskCacheLocation::Default => directories::ProjectDirs::from("", "", "crypto-pay-api")
+                .map(|dirs| dirs.cache_dir().to_path_buf())
+                .ok_or_else(|| CryptoBotError::ConfigError {
+                    message: "no cache directory found for the disk cache, set one with disk_cache_dir".to_string(),
+                }),
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize)]
+struct Entry<T> {
+    fetched_at: DateTime<Utc>,
+    value: T,
+}
+
+/// Currencies and exchange rates kept in JSON files between process restarts
+///
+/// Files are named after a hash of the base URL, so mainnet and testnet data never mix. Entries
+/// older than `ttl` are ignored. Errors are never fatal: the data is fetched from the API instead.
+#[derive(Debug)]
+pub(crate) struct DiskCache {
+    dir: PathBuf,
+    key: String,
+    ttl: Duration,
+}
+
+impl DiskCache {
+    pub(crate) fn new(location: DiskCacheLocation, base_url: &str, ttl: Duration) -> CryptoBotResult<Self> {
+        let key = hex::encode(&Sha256::digest(base_url.as_bytes())[..8]);
+        Ok(Self {
+            dir: location.resolve()?,
+            key,
+            ttl,
+        })
+    }
+
+    fn path(&self, name: &str) -> PathBuf {
+        self.dir.join(format!("{}-{name}.json", self.key))
+    }
+
+    /// Returns the stored value with the time it was fetched, if it is younger than the TTL
+    pub(crate) fn load<T: DeserializeOwned>(&self, name: &str) -> Option<(T, DateTime<Utc>)> {
+        let content = fs::read(self.path(name)).ok()?;
+        let entry: Entry<T> = serde_json::from_slice(&content).ok()?;
+
+        let age = (Utc::now() - entry.fetched_at).to_std().unwrap_or_default();
+        (age < self.ttl).then_some((entry.value, entry.fetched_at))
+    }
+
+    /// Stores a value fetched now, replacing the file atomically so concurrent processes never read a partial file
+    pub(crate) fn store<T: Serialize>(&self, name: &str, value: &T) {
+        let entry = Entry {
+            fetched_at: Utc::now(),
+            value,
+        };
+        let Ok(content) = serde_json::to_vec(&entry) else {
+            return;
+        };
+
+        let path = self.path(name);
+        let tmp = path.with_extension(format!("{}.tmp", std::process::id()));
+        let written = fs::create_dir_all(&self.dir)
+            .and_then(|()| fs::write(&tmp, content))
+            .and_then(|()| fs::rename(&tmp, &path));
+        if written.is_err() {
+            let _ = fs::remove_file(&tmp);
+        }
+    }
+
+    pub(crate) fn clear(&self) {
+        for name in [EXCHANGE_RATES, CURRENCIES] {
+            let _ = fs::remove_file(self.path(name));
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn cache(name: &str, ttl: Duration) -> DiskCache {
+        let dir = std::env::temp_dir().join(format!("crypto-pay-disk-cache-{name}-{}", std::process::id()));
+        DiskCache::new(DiskCacheLocation::Dir(dir), "https://pay.crypt.bot/api", ttl).unwrap()
+    }
+
+    #[test]
+    fn test_disk_cache_round_trip() {
+        let cache = cache("round-trip", Duration::from_secs(60));
+        assert!(cache.load::<Vec<u32>>(CURRENCIES).is_none());
+
+        cache.store(CURRENCIES, &vec![1, 2, 3]);
+        let (value, fetched_at) = cache.load::<Vec<u32>>(CURRENCIES).unwrap();
+        assert_eq!(value, vec![1, 2, 3]);
+        assert!(fetched_at <= Utc::now());
+
+        cache.clear();
+        assert!(cache.load::<Vec<u32>>(CURRENCIES).is_none());
+        fs::remove_dir_all(&cache.dir).unwrap();
+    }
+
+    #[test]
+    fn test_disk_cache_ignores_expired_entries() {
+        let cache = cache("expired", Duration::ZERO);
+        cache.store(EXCHANGE_RATES, &vec![1]);
+
+        assert!(cache.load::<Vec<u32>>(EXCHANGE_RATES).is_none());
+        fs::remove_dir_all(&cache.dir).unwrap();
+    }
+}
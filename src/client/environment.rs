@@ -0,0 +1,144 @@
+use url::Url;
+
+use crate::{
+    error::{CryptoBotError, CryptoBotResult, ValidationCode, ValidationErrorKind},
+    models::CryptoCurrencyCode,
+};
+
+use super::CryptoBot;
+
+/// Crypto Pay network a client talks to.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Environment {
+    /// The production API at `pay.crypt.bot`.
+    Mainnet,
+    /// The test API at `testnet-pay.crypt.bot`.
+    Testnet,
+}
+
+impl Environment {
+    /// Detects the environment from the API base URL
+    ///
+    /// Hosts starting with `testnet` are treated as testnet, anything else as mainnet.
+    pub fn from_base_url(base_url: &str) -> Self {
+        let is_testnet = Url::parse(base_url)
+            .ok()
+            .and_then(|url| url.host_str().map(|host| host.starts_with("testnet")))
+            .unwrap_or(false);
+
+        if is_testnet {
+            Environment::Testnet
+        } else {
+            Environment::Mainnet
+        }
+    }
+}
+
+impl CryptoBot {
+    /// Returns the environment of the client, set with `ClientBuilder::environment` or detected from the base URL
+    pub fn environment(&self) -> Environment {
+        self.environment
+    }
+
+    /// Rejects testnet-only assets such as JET on mainnet, unless `allow_testnet_assets` is set
+    pub(crate) fn check_assets<'a>(
+        &self,
+        assets: impl IntoIterator<Item = &'a CryptoCurrencyCode>,
+    ) -> CryptoBotResult<()> {
+        if self.environment == Environment::Testnet || self.allow_testnet_assets {
+            return Ok(());
+        }
+
+        match assets.into_iter().find(|asset| asset.is_testnet_only()) {
+            Some(asset) => Err(CryptoBotError::ValidationError {
+                kind: ValidationErrorKind::Currency,
+                code: ValidationCode::TestnetAsset,
+                message: format!("{} is only available on testnet", asset.to_string().to_uppercase()),
+                field: Some("asset".to_string()),
+            }),
+            None => Ok(()),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use rust_decimal_macros::dec;
+
+    use super::*;
+    use crate::api::{CheckAPI, InvoiceAPI, TransferAPI};
+
+    #[test]
+    fn test_environment_from_base_url() {
+        assert_eq!(
+            Environment::from_base_url("https://pay.crypt.bot/api"),
+            Environment::Mainnet
+        );
+        assert_eq!(
+            Environment::from_base_url("https://testnet-pay.crypt.bot/api"),
+            Environment::Testnet
+        );
+        assert_eq!(Environment::from_base_url("not a url"), Environment::Mainnet);
+    }
+
+    #[test]
+    fn test_environment_override() {
+        let client = CryptoBot::builder()
+            .api_token("test_token")
+            .base_url("https://gateway.example.com/api")
+            .environment(Environment::Testnet)
+            .build()
+            .unwrap();
+
+        assert_eq!(client.environment(), Environment::Testnet);
+        assert!(client.check_assets([&CryptoCurrencyCode::Jet]).is_ok());
+    }
+
+    #[tokio::test]
+    async fn test_jet_rejected_on_mainnet() {
+        let client = CryptoBot::test_client();
+        assert_eq!(client.environment(), Environment::Mainnet);
+
+        let is_testnet_asset_error =
+            |error: CryptoBotError| error.validation_code() == Some(ValidationCode::TestnetAsset);
+
+        let result = client
+            .create_invoice()
+            .amount(dec!(1))
+            .fiat(crate::models::FiatCurrencyCode::Usd)
+            .accept_asset(vec![CryptoCurrencyCode::Ton, CryptoCurrencyCode::Jet])
+            .execute()
+            .await;
+        assert!(is_testnet_asset_error(result.unwrap_err()));
+
+        let result = client
+            .create_check()
+            .asset(CryptoCurrencyCode::Jet)
+            .amount(dec!(1))
+            .execute()
+            .await;
+        assert!(is_testnet_asset_error(result.unwrap_err()));
+
+        let result = client
+            .transfer()
+            .user_id(1)
+            .asset(CryptoCurrencyCode::Jet)
+            .amount(dec!(1))
+            .spend_id("spend")
+            .execute()
+            .await;
+        assert!(is_testnet_asset_error(result.unwrap_err()));
+    }
+
+    #[test]
+    fn test_allow_testnet_assets() {
+        let client = CryptoBot::builder()
+            .api_token("test_token")
+            .allow_testnet_assets(true)
+            .build()
+            .unwrap();
+
+        assert_eq!(client.environment(), Environment::Mainnet);
+        assert!(client.check_assets([&CryptoCurrencyCode::Jet]).is_ok());
+    }
+}
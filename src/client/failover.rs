@@ -0,0 +1,124 @@
+use std::{
+    sync::Mutex,
+    time::{Duration, Instant},
+};
+
+use crate::{
+    error::{CryptoBotError, NetworkPhase},
+    models::APIEndpoint,
+};
+
+/// Ordered list of base URLs, failing over to the next one while a URL is cooling down
+///
+/// A URL that fails with a connect error or a 5xx response is skipped for `cooldown`. Once the
+/// cool-down has elapsed the URL is preferred again by its position in the list, so the next request
+/// probes it: a success keeps it in use, a failure starts a new cool-down.
+#[derive(Debug)]
+pub(crate) struct Failover {
+    urls: Vec<String>,
+    cooldown: Duration,
+    down_until: Mutex<Vec<Option<Instant>>>,
+}
+
+impl Failover {
+    pub(crate) fn new(urls: Vec<String>, cooldown: Duration) -> Self {
+        let down_until = Mutex::new(vec![None; urls.len()]);
+        Self {
+            urls,
+            cooldown,
+            down_until,
+        }
+    }
+
+    /// Returns the indices of the URLs in the order they should be tried
+    ///
+    /// Available URLs come first in list order, followed by cooling down URLs by the end of their
+    /// cool-down, so a request is still attempted when every URL is down.
+    pub(crate) fn candidates(&self) -> Vec<usize> {
+        let now = Instant::now();
+        let down_until = self.down_until.lock().unwrap_or_else(|e| e.into_inner());
+
+        let (mut available, mut cooling): (Vec<usize>, Vec<usize>) =
+            (0..self.urls.len()).partition(|&i| down_until[i].is_none_or(|until| until <= now));
+        cooling.sort_by_key(|&i| down_until[i]);
+        available.extend(cooling);
+        available
+    }
+
+    pub(crate) fn url(&self, index: usize) -> &str {
+        &self.urls[index]
+    }
+
+    /// Returns the URL the next request is sent to first
+    pub(crate) fn active(&self) -> &str {
+        self.url(self.candidates()[0])
+    }
+
+    pub(crate) fn mark_up(&self, index: usize) {
+        let mut down_until = self.down_until.lock().unwrap_or_else(|e| e.into_inner());
+        down_until[index] = None;
+    }
+
+    pub(crate) fn mark_down(&self, index: usize) {
+        let mut down_until = self.down_until.lock().unwrap_or_else(|e| e.into_inner());
+        down_until[index] = Some(Instant::now() + self.cooldown);
+    }
+}
+
+/// Returns whether the URL that produced `error` should be put on cool-down
+pub(crate) fn is_url_failure(error: &CryptoBotError) -> bool {
+    let CryptoBotError::HttpError(http_error) = error else {
+        return false;
+    };
+
+    error.network_phase() == Some(NetworkPhase::Connect)
+        || http_error.status().is_some_and(|status| status.is_server_error())
+}
+
+/// Returns whether the request can be sent again to the next URL after `error`
+///
+/// Like retries, a request to a non-idempotent endpoint that failed with a 5xx response may already
+/// have taken effect, so only requests that never reached the server are re-sent for every endpoint.
+pub(crate) fn can_fail_over(endpoint: APIEndpoint, error: &CryptoBotError) -> bool {
+    is_url_failure(error) && (endpoint.is_idempotent() || error.network_phase() == Some(NetworkPhase::Connect))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn failover(cooldown: Duration) -> Failover {
+        Failover::new(
+            vec!["https://a.example.com".to_string(), "https://b.example.com".to_string()],
+            cooldown,
+        )
+    }
+
+    #[test]
+    fn test_failover_prefers_list_order() {
+        let failover = failover(Duration::from_secs(30));
+        assert_eq!(failover.candidates(), vec![0, 1]);
+        assert_eq!(failover.active(), "https://a.example.com");
+    }
+
+    #[test]
+    fn test_failover_skips_url_during_cooldown() {
+        let failover = failover(Duration::from_secs(30));
+        failover.mark_down(0);
+        assert_eq!(failover.candidates(), vec![1, 0]);
+        assert_eq!(failover.active(), "https://b.example.com");
+
+        failover.mark_down(1);
+        assert_eq!(failover.candidates(), vec![0, 1]);
+
+        failover.mark_up(1);
+        assert_eq!(failover.candidates(), vec![1, 0]);
+    }
+
+    #[test]
+    fn test_failover_reprobes_after_cooldown() {
+        let failover = failover(Duration::ZERO);
+        failover.mark_down(0);
+        assert_eq!(failover.active(), "https://a.example.com");
+    }
+}
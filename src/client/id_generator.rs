@@ -0,0 +1,153 @@
+use std::{
+    collections::hash_map::RandomState,
+    hash::{BuildHasher, Hasher},
+    sync::{
+        atomic::{AtomicU64, Ordering},
+        Arc, Mutex,
+    },
+    time::{SystemTime, UNIX_EPOCH},
+};
+
+use sha2::{Digest, Sha256};
+
+/// Purpose of a generated ID
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum IdKind {
+    /// Spend ID of a transfer, see `TransferBuilder::generate_spend_id`.
+    SpendId,
+    /// ID correlating a request in application logs.
+    RequestId,
+    /// Key deduplicating a retried operation in the application.
+    IdempotencyKey,
+}
+
+impl IdKind {
+    fn index(self) -> usize {
+        match self {
+            IdKind::SpendId => 0,
+            IdKind::RequestId => 1,
+            IdKind::IdempotencyKey => 2,
+        }
+    }
+}
+
+/// Source of the IDs generated by the client, set with `ClientBuilder::id_generator`
+///
+/// IDs must be unique per kind and at most 64 characters long, the limit of spend IDs.
+pub trait IdGenerator: Send + Sync {
+    /// Returns a new ID of the given kind
+    fn generate(&self, kind: IdKind) -> String;
+}
+
+/// Generates 32 random hexadecimal characters. Used by default.
+#[derive(Debug, Default)]
+pub struct RandomIdGenerator {
+    counter: AtomicU64,
+}
+
+impl IdGenerator for RandomIdGenerator {
+    fn generate(&self, _kind: IdKind) -> String {
+        let nanos = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map_or(0, |elapsed| elapsed.as_nanos());
+        let count = self.counter.fetch_add(1, Ordering::Relaxed);
+
+        // Every `RandomState` is seeded with random keys by the standard library
+        let random = |salt: u64| {
+            let mut hasher = RandomState::new().build_hasher();
+            hasher.write_u128(nanos);
+            hasher.write_u64(count);
+            hasher.write_u64(salt);
+            hasher.finish()
+        };
+        format!("{:016x}{:016x}", random(0), random(1))
+    }
+}
+
+/// Generates the same sequence of IDs for the same seed, e.g. for stable request bodies in tests
+///
+/// Each kind has its own sequence, so generating IDs of one kind does not shift the others.
+///
+/// # Example
+/// ```
+/// use crypto_pay_api::prelude::*;
+///
+/// let first = SeededIdGenerator::new(42);
+/// let second = SeededIdGenerator::new(42);
+/// assert_eq!(first.generate(IdKind::SpendId), second.generate(IdKind::SpendId));
+/// ```
+#[derive(Debug)]
+pub struct SeededIdGenerator {
+    seed: u64,
+    counters: Mutex<[u64; 3]>,
+}
+
+impl SeededIdGenerator {
+    pub fn new(seed: u64) -> Self {
+        Self {
+            seed,
+            counters: Mutex::new([0; 3]),
+        }
+    }
+}
+
+impl IdGenerator for SeededIdGenerator {
+    fn generate(&self, kind: IdKind) -> String {
+        let count = {
+            let mut counters = self.counters.lock().unwrap_or_else(|e| e.into_inner());
+            let counter = &mut counters[kind.index()];
+            *counter += 1;
+            *counter
+        };
+
+        let digest = Sha256::new()
+            .chain_update(self.seed.to_be_bytes())
+            .chain_update([kind.index() as u8])
+            .chain_update(count.to_be_bytes())
+            .finalize();
+        hex::encode(&digest[..16])
+    }
+}
+
+#[derive(Clone)]
+pub(crate) struct IdGeneratorHandle(pub(crate) Arc<dyn IdGenerator>);
+
+impl Default for IdGeneratorHandle {
+    fn default() -> Self {
+        Self(Arc::new(RandomIdGenerator::default()))
+    }
+}
+
+impl std::fmt::Debug for IdGeneratorHandle {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str("IdGenerator")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_random_id_generator() {
+        let generator = RandomIdGenerator::default();
+        let first = generator.generate(IdKind::SpendId);
+        let second = generator.generate(IdKind::SpendId);
+
+        assert_eq!(first.len(), 32);
+        assert_ne!(first, second);
+    }
+
+    #[test]
+    fn test_seeded_id_generator_is_deterministic_per_kind() {
+        let generator = SeededIdGenerator::new(7);
+        let spend_ids = [generator.generate(IdKind::SpendId), generator.generate(IdKind::SpendId)];
+        assert_ne!(spend_ids[0], spend_ids[1]);
+
+        let other = SeededIdGenerator::new(7);
+        assert_ne!(other.generate(IdKind::RequestId), spend_ids[0]);
+        assert_eq!(other.generate(IdKind::SpendId), spend_ids[0]);
+        assert_eq!(other.generate(IdKind::SpendId), spend_ids[1]);
+        assert_ne!(SeededIdGenerator::new(8).generate(IdKind::SpendId), spend_ids[0]);
+    }
+}
@@ -0,0 +1,55 @@
+use async_trait::async_trait;
+
+use crate::models::{CreatedInvoice, Invoice, PayUrlPreference};
+
+/// Transforms the payment URLs of every invoice created through the client.
+///
+/// Register it with `ClientBuilder::link_decorator` to e.g. add UTM parameters or shorten the
+/// links. It is applied to the bot, mini app and web app URLs of the `CreatedInvoice` returned by
+/// `execute()` and `execute_and_refresh()`.
+///
+/// # Example
+/// ```
+/// use crypto_pay_api::prelude::*;
+///
+/// struct Utm;
+///
+/// #[async_trait::async_trait]
+/// impl LinkDecorator for Utm {
+///     async fn decorate(&self, url: &str, _kind: PayUrlPreference, _invoice: &Invoice) -> String {
+///         format!("{url}&utm_source=bot")
+///     }
+/// }
+///
+/// let client = CryptoBot::builder().api_token("YOUR_API_TOKEN").link_decorator(Utm).build();
+/// ```
+#[async_trait]
+pub trait LinkDecorator: Send + Sync {
+    /// Returns the URL to use instead of `url`, which is the `kind` payment URL of `invoice`
+    async fn decorate(&self, url: &str, kind: PayUrlPreference, invoice: &Invoice) -> String;
+}
+
+/// Applies the decorator to the three payment URLs of an invoice
+pub(crate) async fn decorate_invoice(decorator: &dyn LinkDecorator, mut invoice: Invoice) -> CreatedInvoice {
+    invoice.bot_invoice_url = decorator
+        .decorate(&invoice.bot_invoice_url, PayUrlPreference::Bot, &invoice)
+        .await;
+    invoice.mini_app_invoice_url = decorator
+        .decorate(&invoice.mini_app_invoice_url, PayUrlPreference::MiniApp, &invoice)
+        .await;
+    invoice.web_app_invoice_url = decorator
+        .decorate(&invoice.web_app_invoice_url, PayUrlPreference::WebApp, &invoice)
+        .await;
+
+    CreatedInvoice::new(invoice)
+}
+
+/// Link decorator registered on the client
+#[derive(Clone)]
+pub(crate) struct LinkDecoratorHandle(pub(crate) std::sync::Arc<dyn LinkDecorator>);
+
+impl std::fmt::Debug for LinkDecoratorHandle {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str("LinkDecorator")
+    }
+}
@@ -0,0 +1,316 @@
+use std::{
+    sync::RwLock,
+    time::{Duration, Instant},
+};
+
+use chrono::{DateTime, Utc};
+
+#[cfg(feature = "disk-cache")]
+use super::disk_cache::{DiskCache, CURRENCIES, EXCHANGE_RATES};
+use crate::{
+    api::{ExchangeRateAPI, MiscAPI},
+    client::CryptoBot,
+    error::CryptoBotResult,
+    models::{Currency, ExchangeRate, GetMeResponse},
+};
+
+/// Application metadata cached by the client.
+#[derive(Debug, Clone)]
+pub struct AppMetadata {
+    pub me: GetMeResponse,
+    pub currencies: Vec<Currency>,
+    pub exchange_rates: Vec<ExchangeRate>,
+    /// When the oldest part of the metadata was fetched.
+    pub fetched_at: DateTime<Utc>,
+}
+
+/// Access to the application metadata cached by the client, returned by [`CryptoBot::metadata`].
+pub struct Metadata<'a> {
+    client: &'a CryptoBot,
+}
+
+impl<'a> Metadata<'a> {
+    pub(crate) fn new(client: &'a CryptoBot) -> Self {
+        Self { client }
+    }
+
+    /// Returns the cached metadata if all of it is fresh, otherwise fetches it again
+    pub async fn get(&self) -> CryptoBotResult<AppMetadata> {
+        match self.cached() {
+            Some(metadata) => Ok(metadata),
+            None => self.refresh().await,
+        }
+    }
+
+    /// Returns the cached metadata without making any request, if all of it is fresh
+    pub fn cached(&self) -> Option<AppMetadata> {
+        self.client.metadata.snapshot()
+    }
+
+    /// Fetches the app info, currencies and exchange rates concurrently and caches them
+    pub async fn refresh(&self) -> CryptoBotResult<AppMetadata> {
+        let (me, currencies, exchange_rates) = futures_util::future::try_join3(
+            self.client.get_me().execute(),
+            self.client.get_currencies().execute(),
+            self.client.get_exchange_rates().execute(),
+        )
+        .await?;
+
+        Ok(AppMetadata {
+            me,
+            currencies,
+            exchange_rates,
+            fetched_at: Utc::now(),
+        })
+    }
+
+    /// Drops all cached metadata, including the disk cache, so the next use fetches it again
+    pub fn invalidate(&self) {
+        self.client.metadata.invalidate();
+    }
+}
+
+#[derive(Debug)]
+struct Cached<T> {
+    value: T,
+    stored_at: Instant,
+    fetched_at: DateTime<Utc>,
+}
+
+/// App info, exchange rates and currencies fetched by the client, reused while they are fresh.
+#[derive(Debug)]
+pub(crate) struct MetadataCache {
+    ttl: Duration,
+    me: RwLock<Option<Cached<GetMeResponse>>>,
+    exchange_rates: RwLock<Option<Cached<Vec<ExchangeRate>>>>,
+    currencies: RwLock<Option<Cached<Vec<Currency>>>>,
+    #[cfg(feature = "disk-cache")]
+    disk: Option<DiskCache>,
+}
+
+impl MetadataCache {
+    pub(crate) fn new(ttl: Duration) -> Self {
+        Self {
+            ttl,
+            me: RwLock::new(None),
+            exchange_rates: RwLock::new(None),
+            currencies: RwLock::new(None),
+            #[cfg(feature = "disk-cache")]
+            disk: None,
+        }
+    }
+
+    /// Falls back to `disk` for exchange rates and currencies missing in memory, and stores fetched ones there
+    #[cfg(feature = "disk-cache")]
+    pub(crate) fn with_disk_cache(mut self, disk: DiskCache) -> Self {
+        self.disk = Some(disk);
+        self
+    }
+
+    pub(crate) fn set_me(&self, me: GetMeResponse) {
+        Self::store(&self.me, me);
+    }
+
+    pub(crate) fn exchange_rates(&self) -> Option<Vec<ExchangeRate>> {
+        let rates = self.fresh(&self.exchange_rates);
+        #[cfg(feature = "disk-cache")]
+        let rates = rates.or_else(|| {
+            let (mut rates, fetched_at) = self.disk.as_ref()?.load::<Vec<ExchangeRate>>(EXCHANGE_RATES)?;
+            for rate in &mut rates {
+                rate.fetched_at = Some(fetched_at);
+            }
+            Self::store_at(&self.exchange_rates, rates.clone(), fetched_at);
+            Some(rates)
+        });
+        rates
+    }
+
+    pub(crate) fn set_exchange_rates(&self, rates: Vec<ExchangeRate>) {
+        #[cfg(feature = "disk-cache")]
+        if let Some(disk) = &self.disk {
+            disk.store(EXCHANGE_RATES, &rates);
+        }
+        Self::store(&self.exchange_rates, rates);
+    }
+
+    pub(crate) fn currencies(&self) -> Option<Vec<Currency>> {
+        let currencies = self.fresh(&self.currencies);
+        #[cfg(feature = "disk-cache")]
+        let currencies = currencies.or_else(|| {
+            let (currencies, fetched_at) = self.disk.as_ref()?.load::<Vec<Currency>>(CURRENCIES)?;
+            Self::store_at(&self.currencies, currencies.clone(), fetched_at);
+            Some(currencies)
+        });
+        currencies
+    }
+
+    pub(crate) fn set_currencies(&self, currencies: Vec<Currency>) {
+        #[cfg(feature = "disk-cache")]
+        if let Some(disk) = &self.disk {
+            disk.store(CURRENCIES, &currencies);
+        }
+        Self::store(&self.currencies, currencies);
+    }
+
+    /// Returns all metadata if every part of it is fresh
+    pub(crate) fn snapshot(&self) -> Option<AppMetadata> {
+        let (me, me_fetched_at) = self.fresh_with_time(&self.me)?;
+        let (exchange_rates, rates_fetched_at) = self.fresh_with_time(&self.exchange_rates)?;
+        let (currencies, currencies_fetched_at) = self.fresh_with_time(&self.currencies)?;
+
+        Some(AppMetadata {
+            me,
+            currencies,
+            exchange_rates,
+            fetched_at: me_fetched_at.min(rates_fetched_at).min(currencies_fetched_at),
+        })
+    }
+
+    pub(crate) fn invalidate(&self) {
+        *self.me.write().unwrap_or_else(|e| e.into_inner()) = None;
+        *self.exchange_rates.write().unwrap_or_else(|e| e.into_inner()) = None;
+        *self.currencies.write().unwrap_or_else(|e| e.into_inner()) = None;
+        #[cfg(feature = "disk-cache")]
+        if let Some(disk) = &self.disk {
+            disk.clear();
+        }
+    }
+
+    fn fresh<T: Clone>(&self, slot: &RwLock<Option<Cached<T>>>) -> Option<T> {
+        self.fresh_with_time(slot).map(|(value, _)| value)
+    }
+
+    fn fresh_with_time<T: Clone>(&self, slot: &RwLock<Option<Cached<T>>>) -> Option<(T, DateTime<Utc>)> {
+        let slot = slot.read().unwrap_or_else(|e| e.into_inner());
+        slot.as_ref()
+            .filter(|cached| cached.stored_at.elapsed() < self.ttl)
+            .map(|cached| (cached.value.clone(), cached.fetched_at))
+    }
+
+    fn store<T>(slot: &RwLock<Option<Cached<T>>>, value: T) {
+        Self::store_at(slot, value, Utc::now());
+    }
+
+    fn store_at<T>(slot: &RwLock<Option<Cached<T>>>, value: T, fetched_at: DateTime<Utc>) {
+        *slot.write().unwrap_or_else(|e| e.into_inner()) = Some(Cached {
+            value,
+            stored_at: Instant::now(),
+            fetched_at,
+        });
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::utils::test_utils::TestContext;
+
+    #[test]
+    fn test_metadata_cache_returns_fresh_values() {
+        let cache = MetadataCache::new(Duration::from_secs(60));
+        assert!(cache.exchange_rates().is_none());
+        assert!(cache.currencies().is_none());
+
+        cache.set_exchange_rates(TestContext::mock_exchange_rates());
+        cache.set_currencies(Vec::new());
+
+        assert_eq!(cache.exchange_rates().map(|rates| rates.len()), Some(2));
+        assert_eq!(cache.currencies().map(|currencies| currencies.len()), Some(0));
+    }
+
+    #[test]
+    fn test_metadata_cache_expires() {
+        let cache = MetadataCache::new(Duration::ZERO);
+        cache.set_exchange_rates(TestContext::mock_exchange_rates());
+
+        assert!(cache.exchange_rates().is_none());
+    }
+
+    #[test]
+    fn test_metadata_snapshot_requires_every_part() {
+        let cache = MetadataCache::new(Duration::from_secs(60));
+        cache.set_exchange_rates(TestContext::mock_exchange_rates());
+        cache.set_currencies(Vec::new());
+        assert!(cache.snapshot().is_none());
+
+        cache.set_me(GetMeResponse {
+            app_id: 1,
+            name: "app".to_string(),
+            payment_processing_bot_username: "CryptoBot".to_string(),
+            webhook_endpoint: None,
+            extra: Default::default(),
+        });
+        let snapshot = cache.snapshot().unwrap();
+        assert_eq!(snapshot.me.app_id, 1);
+        assert_eq!(snapshot.exchange_rates.len(), 2);
+
+        cache.invalidate();
+        assert!(cache.snapshot().is_none());
+        assert!(cache.exchange_rates().is_none());
+    }
+
+    #[cfg(feature = "disk-cache")]
+    #[test]
+    fn test_metadata_survives_restart_with_disk_cache() {
+        let mut ctx = TestContext::new();
+        let rates = ctx.mock_exchange_rates_response();
+        let dir = std::env::temp_dir().join(format!("crypto-pay-disk-cache-restart-{}", std::process::id()));
+        let build = || {
+            CryptoBot::builder()
+                .api_token("test_token")
+                .base_url(ctx.server.url())
+                .disk_cache_dir(&dir)
+                .build()
+                .unwrap()
+        };
+
+        let client = build();
+        ctx.run(client.get_exchange_rates().execute()).unwrap();
+        drop(client);
+        rates.remove();
+
+        // A new client reads the rates from disk without a request
+        let restarted = build();
+        let cached = restarted.metadata.exchange_rates().unwrap();
+        assert_eq!(cached.len(), 4);
+        assert!(cached.iter().all(|rate| rate.fetched_at().is_some()));
+
+        restarted.metadata().invalidate();
+        assert!(build().metadata.exchange_rates().is_none());
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn test_metadata_refresh_and_invalidate() {
+        let mut ctx = TestContext::new();
+        let me = ctx.mock_get_me_response().expect(2);
+        let currencies = ctx.mock_currencies_response().expect(2);
+        let rates = ctx.mock_exchange_rates_response().expect(2);
+
+        let client = CryptoBot::builder()
+            .api_token("test_token")
+            .base_url(ctx.server.url())
+            .build()
+            .unwrap();
+
+        ctx.run(async {
+            assert!(client.metadata().cached().is_none());
+
+            let metadata = client.metadata().get().await.unwrap();
+            assert_eq!(metadata.me.app_id, 28692);
+            assert_eq!(metadata.exchange_rates.len(), 4);
+
+            // Served from the cache
+            let cached = client.metadata().get().await.unwrap();
+            assert_eq!(cached.currencies.len(), metadata.currencies.len());
+
+            client.metadata().invalidate();
+            assert!(client.metadata().cached().is_none());
+            client.metadata().get().await.unwrap();
+        });
+
+        me.assert();
+        currencies.assert();
+        rates.assert();
+    }
+}
@@ -1,33 +1,199 @@
 mod builder;
-
-use std::str::FromStr;
+mod circuit_breaker;
+mod client_config;
+#[cfg(feature = "disk-cache")]
+mod disk_cache;
+mod environment;
+mod failover;
+mod id_generator;
+mod link_decorator;
+mod metadata;
+mod monitor;
+#[cfg(feature = "tokio")]
+mod rate_limit;
+mod request_stats;
+mod response_size;
+#[cfg(feature = "tokio")]
+mod retry;
+mod transport;
+
+use std::{
+    str::FromStr,
+    sync::Arc,
+    time::{Duration, Instant},
+};
 
 use crate::{
+    api::{ExchangeRateAPI, MiscAPI},
+    config::{ENV_API_TIMEOUT, ENV_API_TOKEN, ENV_API_URL, ENV_WEBHOOK_EXPIRATION_TIME},
     error::{CryptoBotError, CryptoBotResult},
-    models::{APIMethod, ApiResponse, Method},
+    models::{
+        APIEndpoint, APIMethod, ApiResponse, CreatedInvoice, Currency, ExchangeRate, Invoice, Receipt, ReceiptKey,
+        ResponseEnvelope, Rounding, TransferDefaults,
+    },
+    utils::redact,
+    validation::{clamp_count, validate_count, validate_url},
 };
 
-#[cfg(test)]
-use crate::models::ExchangeRate;
-
 use builder::{ClientBuilder, NoAPIToken};
+use circuit_breaker::CircuitBreaker;
+pub use circuit_breaker::{CircuitBreakerConfig, CircuitState};
+pub use client_config::ClientConfig;
+pub use environment::Environment;
+use failover::{can_fail_over, is_url_failure, Failover};
+use id_generator::IdGeneratorHandle;
+pub use id_generator::{IdGenerator, IdKind, RandomIdGenerator, SeededIdGenerator};
+pub use link_decorator::LinkDecorator;
+use link_decorator::{decorate_invoice, LinkDecoratorHandle};
+use metadata::MetadataCache;
+pub use metadata::{AppMetadata, Metadata};
+pub use monitor::{AlertState, BalanceAlert, BalanceMonitor, ThresholdDirection};
+#[cfg(feature = "tokio")]
+pub use rate_limit::RateLimiter;
+use request_stats::RequestStatsTracker;
+pub use request_stats::{EndpointStats, ErrorCounts, StatsSnapshot};
 use reqwest::header::{HeaderMap, HeaderName, HeaderValue};
+pub use response_size::ResponseSizeStats;
+use response_size::ResponseSizeTracker;
+#[cfg(feature = "tokio")]
+pub use retry::{RetryBudget, RetryConfig};
 use serde::{de::DeserializeOwned, Serialize};
+use transport::{response_too_large, TransportHandle};
+pub use transport::{JournalEntry, RecordedError, ReplayTransport, Transport, TransportRequest};
+
+/// Per-request settings of a request builder, overriding the client's settings
+#[derive(Debug, Clone, Default)]
+pub(crate) struct RequestOptions {
+    pub(crate) base_url: Option<String>,
+    #[cfg(feature = "tokio")]
+    pub(crate) retry: Option<RetryConfig>,
+}
+
+/// Callback invoked with the envelope of every parsed API response
+#[derive(Clone)]
+pub(crate) struct ResponseHook(Arc<dyn Fn(&ResponseEnvelope) + Send + Sync>);
+
+impl ResponseHook {
+    pub(crate) fn new<F>(hook: F) -> Self
+    where
+        F: Fn(&ResponseEnvelope) + Send + Sync + 'static,
+    {
+        Self(Arc::new(hook))
+    }
+}
 
-pub const DEFAULT_API_URL: &str = "https://pay.crypt.bot/api";
-pub const DEFAULT_TIMEOUT: u64 = 30;
-pub const DEFAULT_WEBHOOK_EXPIRATION_TIME: u64 = 600;
+impl std::fmt::Debug for ResponseHook {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str("ResponseHook")
+    }
+}
+
+/// Callback invoked with warnings about requests that were adjusted instead of rejected
+#[derive(Clone)]
+pub(crate) struct WarningHook(Arc<dyn Fn(&str) + Send + Sync>);
+
+impl WarningHook {
+    pub(crate) fn new<F>(hook: F) -> Self
+    where
+        F: Fn(&str) + Send + Sync + 'static,
+    {
+        Self(Arc::new(hook))
+    }
+}
+
+impl std::fmt::Debug for WarningHook {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str("WarningHook")
+    }
+}
 
-#[derive(Debug)]
+type BeforeRequestHookFn =
+    dyn Fn(&serde_json::Value) -> Result<(), Box<dyn std::error::Error + Send + Sync>> + Send + Sync;
+
+/// Callback invoked with the serialized params of a money-moving request before it is sent
+#[derive(Clone)]
+pub(crate) struct BeforeRequestHook(Arc<BeforeRequestHookFn>);
+
+impl BeforeRequestHook {
+    pub(crate) fn new<F, E>(hook: F) -> Self
+    where
+        F: Fn(&serde_json::Value) -> Result<(), E> + Send + Sync + 'static,
+        E: Into<Box<dyn std::error::Error + Send + Sync>>,
+    {
+        Self(Arc::new(move |params| hook(params).map_err(Into::into)))
+    }
+}
+
+impl std::fmt::Debug for BeforeRequestHook {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str("BeforeRequestHook")
+    }
+}
+
+/// Client for the Crypto Pay API.
+///
+/// `CryptoBot` is `Send + Sync` and the futures returned by its request builders are `Send`, so one
+/// client can be shared by reference or in an `Arc` across the tasks of a multi-threaded runtime.
+/// Caches and circuit breaker state are behind locks and safe to use concurrently.
+///
+/// The `Debug` output redacts the API token and omits custom header values.
 pub struct CryptoBot {
     pub(crate) api_token: String,
-    pub(crate) client: reqwest::Client,
+    pub(crate) transport: TransportHandle,
     pub(crate) base_url: String,
+    pub(crate) failover: Option<Arc<Failover>>,
     pub(crate) headers: Option<Vec<(HeaderName, HeaderValue)>>,
+    pub(crate) transfer_defaults: TransferDefaults,
+    pub(crate) circuit_breaker: Option<Arc<CircuitBreaker>>,
+    pub(crate) response_hook: Option<ResponseHook>,
+    pub(crate) clamp_counts: bool,
+    pub(crate) rounding: Rounding,
+    pub(crate) warning_hook: Option<WarningHook>,
+    pub(crate) webhook_expiration_time: Duration,
+    pub(crate) metadata: Arc<MetadataCache>,
+    pub(crate) max_response_size: Option<usize>,
+    pub(crate) response_sizes: Arc<ResponseSizeTracker>,
+    pub(crate) request_stats: Arc<RequestStatsTracker>,
+    pub(crate) id_generator: IdGeneratorHandle,
+    pub(crate) link_decorator: Option<LinkDecoratorHandle>,
+    pub(crate) receipt_key: Option<ReceiptKey>,
+    pub(crate) before_create_invoice: Option<BeforeRequestHook>,
+    pub(crate) before_transfer: Option<BeforeRequestHook>,
+    pub(crate) environment: Environment,
+    pub(crate) allow_testnet_assets: bool,
+    #[cfg(feature = "tokio")]
+    pub(crate) retry: Option<RetryConfig>,
+    #[cfg(feature = "tokio")]
+    pub(crate) rate_limiter: Option<RateLimiter>,
     #[cfg(test)]
     pub(crate) test_rates: Option<Vec<ExchangeRate>>,
 }
 
+impl std::fmt::Debug for CryptoBot {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("CryptoBot")
+            .field("api_token", &redact(&self.api_token))
+            .field("base_url", &self.base_url)
+            .field("environment", &self.environment)
+            .field(
+                "headers",
+                &self.headers.iter().flatten().map(|(name, _)| name).collect::<Vec<_>>(),
+            )
+            .finish_non_exhaustive()
+    }
+}
+
+fn parse_env_secs(key: &str, value: &str) -> CryptoBotResult<Duration> {
+    value
+        .parse::<u64>()
+        .ok()
+        .filter(|secs| *secs > 0)
+        .map(Duration::from_secs)
+        .ok_or_else(|| CryptoBotError::ConfigError {
+            message: format!("{key} must be a positive number of seconds, got '{value}'"),
+        })
+}
+
 impl CryptoBot {
     /// Returns a new builder for creating a customized CryptoBot client
     ///
@@ -38,7 +204,14 @@ impl CryptoBot {
     /// * `api_token` - Required, the API token from [@CryptoBot](https://t.me/CryptoBot)
     /// * `base_url` - Optional, defaults to "https://pay.crypt.bot/api"
     /// * `timeout` - Optional, defaults to 30 seconds
+    /// * `webhook_expiration_time` - Optional, default expiration of webhook handlers, defaults to 10 minutes
     /// * `headers` - Optional, custom headers for all requests
+    /// * `transfer_defaults` - Optional, default options applied to every transfer
+    /// * `circuit_breaker` - Optional, fail fast on endpoints that keep failing
+    /// * `on_response` - Optional, hook receiving the raw response envelope of every call
+    /// * `clamp_counts` - Optional, clamp out of range `count` values instead of rejecting them
+    /// * `rounding` - Optional, rounding of converted `amount_fiat` prices, defaults to rounding up
+    /// * `on_warning` - Optional, hook receiving warnings such as clamped counts
     ///
     /// # Example
     /// ```
@@ -67,27 +240,225 @@ impl CryptoBot {
         ClientBuilder::new()
     }
 
+    /// Creates a new client from environment variables
+    ///
+    /// # Environment Variables
+    /// * `CRYPTO_PAY_API_TOKEN` - Required, the API token
+    /// * `CRYPTO_PAY_API_URL` - Optional, defaults to "https://pay.crypt.bot/api"
+    /// * `CRYPTO_PAY_API_TIMEOUT` - Optional, request timeout in seconds, defaults to 30
+    /// * `CRYPTO_PAY_WEBHOOK_EXPIRATION_TIME` - Optional, webhook expiration time in seconds, defaults to 600
+    ///
+    /// With the `dotenv` feature enabled, variables from a `.env` file in the current
+    /// directory (or its parents) are loaded first. Variables already set in the
+    /// environment take precedence.
+    ///
+    /// # Errors
+    /// * `CryptoBotError::ConfigError` - If the token is missing or a value is invalid
+    ///
+    /// # Example
+    /// ```no_run
+    /// use crypto_pay_api::prelude::*;
+    ///
+    /// let client = CryptoBot::from_env()?;
+    /// # Ok::<(), CryptoBotError>(())
+    /// ```
+    pub fn from_env() -> CryptoBotResult<Self> {
+        #[cfg(feature = "dotenv")]
+        dotenvy::dotenv().ok();
+
+        Self::from_env_with(|key| std::env::var(key).ok())
+    }
+
+    pub(crate) fn from_env_with<F>(lookup: F) -> CryptoBotResult<Self>
+    where
+        F: Fn(&str) -> Option<String>,
+    {
+        let lookup = |key: &str| lookup(key).map(|v| v.trim().to_string()).filter(|v| !v.is_empty());
+
+        let api_token = lookup(ENV_API_TOKEN).ok_or_else(|| CryptoBotError::ConfigError {
+            message: format!("{ENV_API_TOKEN} is not set"),
+        })?;
+
+        let mut builder = Self::builder().api_token(api_token);
+
+        if let Some(url) = lookup(ENV_API_URL) {
+            if let Err(CryptoBotError::ValidationError { message, .. }) = validate_url(&url, ENV_API_URL) {
+                return Err(CryptoBotError::ConfigError { message });
+            }
+            builder = builder.base_url(url.trim_end_matches('/'));
+        }
+
+        if let Some(timeout) = lookup(ENV_API_TIMEOUT) {
+            builder = builder.timeout(parse_env_secs(ENV_API_TIMEOUT, &timeout)?);
+        }
+
+        if let Some(expiration) = lookup(ENV_WEBHOOK_EXPIRATION_TIME) {
+            builder = builder.webhook_expiration_time(parse_env_secs(ENV_WEBHOOK_EXPIRATION_TIME, &expiration)?);
+        }
+
+        builder.build()
+    }
+
     /// Makes a request to the CryptoBot API
     ///
     /// # Arguments
+    /// * `options` - Overrides the client's base URL or retry configuration for this request
     /// * `method` - The method to call, must be one of the ApiMethod enum values
     /// * `params` - The parameters to pass to the method
     ///
     /// # Returns
     /// * `Ok(R)` - The response from the API
     /// * `Err(CryptoBotError)` - If the request fails or the response is not valid
-    pub(crate) async fn make_request<T, R>(&self, method: &APIMethod, params: Option<&T>) -> CryptoBotResult<R>
+    pub(crate) async fn make_request_with<T, R>(
+        &self,
+        options: &RequestOptions,
+        method: &APIMethod,
+        params: Option<&T>,
+    ) -> CryptoBotResult<R>
     where
         T: Serialize + ?Sized,
         R: DeserializeOwned,
     {
-        let url = format!("{}/{}", self.base_url, method.endpoint.as_str());
+        let started = Instant::now();
+        let result = self.send_with_options(options, method, params).await;
+        self.request_stats
+            .record(method.endpoint, started.elapsed(), result.as_ref().err());
+        result
+    }
+
+    /// Signs `result` as returned by `endpoint`, or returns `None` if no receipt key is configured
+    pub(crate) fn sign_receipt(&self, endpoint: APIEndpoint, result: &serde_json::Value) -> Option<Receipt> {
+        let ReceiptKey(key) = self.receipt_key.as_ref()?;
+        Some(Receipt::sign(key, endpoint, result))
+    }
+
+    /// Makes a request creating a record, attaching a signed receipt if a receipt key is set
+    pub(crate) async fn make_receipted_request<T, R>(
+        &self,
+        options: &RequestOptions,
+        method: &APIMethod,
+        params: Option<&T>,
+    ) -> CryptoBotResult<(R, Option<Receipt>)>
+    where
+        T: Serialize + ?Sized,
+        R: DeserializeOwned,
+    {
+        let Some(ReceiptKey(key)) = &self.receipt_key else {
+            return Ok((self.make_request_with(options, method, params).await?, None));
+        };
+
+        let result: serde_json::Value = self.make_request_with(options, method, params).await?;
+        let receipt = Receipt::sign(key, method.endpoint, &result);
+        let record = R::deserialize(&result).map_err(|e| CryptoBotError::ApiError {
+            code: -1,
+            message: "Failed to parse API response".to_string(),
+            details: Some(serde_json::json!({ "error": e.to_string() })),
+        })?;
+        Ok((record, Some(receipt)))
+    }
+
+    async fn send_with_options<T, R>(
+        &self,
+        options: &RequestOptions,
+        method: &APIMethod,
+        params: Option<&T>,
+    ) -> CryptoBotResult<R>
+    where
+        T: Serialize + ?Sized,
+        R: DeserializeOwned,
+    {
+        let base_url = match &options.base_url {
+            Some(base_url) => {
+                validate_url(base_url, "base_url")?;
+                Some(base_url.trim_end_matches('/'))
+            }
+            None => None,
+        };
+
+        #[cfg(feature = "tokio")]
+        if let Some(retry) = options.retry.as_ref().or(self.retry.as_ref()) {
+            return retry
+                .run(method.endpoint, || self.send_through_breaker(base_url, method, params))
+                .await;
+        }
+
+        self.send_through_breaker(base_url, method, params).await
+    }
+
+    async fn send_through_breaker<T, R>(
+        &self,
+        base_url: Option<&str>,
+        method: &APIMethod,
+        params: Option<&T>,
+    ) -> CryptoBotResult<R>
+    where
+        T: Serialize + ?Sized,
+        R: DeserializeOwned,
+    {
+        let Some(breaker) = &self.circuit_breaker else {
+            return self.send_with_failover(base_url, method, params).await;
+        };
+
+        let permit = breaker.acquire(method.endpoint)?;
+        let result = self.send_with_failover(base_url, method, params).await;
+        permit.record(&result);
+        result
+    }
+
+    /// Sends the request to the overriding base URL, or through the failover list if one is configured
+    async fn send_with_failover<T, R>(
+        &self,
+        base_url: Option<&str>,
+        method: &APIMethod,
+        params: Option<&T>,
+    ) -> CryptoBotResult<R>
+    where
+        T: Serialize + ?Sized,
+        R: DeserializeOwned,
+    {
+        let (Some(failover), None) = (&self.failover, base_url) else {
+            return self
+                .send_request(base_url.unwrap_or(&self.base_url), method, params)
+                .await;
+        };
+
+        let candidates = failover.candidates();
+        for (attempt, &index) in candidates.iter().enumerate() {
+            let result = self.send_request(failover.url(index), method, params).await;
+            match &result {
+                Err(error) if is_url_failure(error) => {
+                    failover.mark_down(index);
+                    if attempt + 1 < candidates.len() && can_fail_over(method.endpoint, error) {
+                        continue;
+                    }
+                }
+                _ => failover.mark_up(index),
+            }
+            return result;
+        }
+
+        unreachable!("failover is only configured with several base URLs")
+    }
+
+    async fn send_request<T, R>(&self, base_url: &str, method: &APIMethod, params: Option<&T>) -> CryptoBotResult<R>
+    where
+        T: Serialize + ?Sized,
+        R: DeserializeOwned,
+    {
+        #[cfg(feature = "tokio")]
+        if let Some(limiter) = &self.rate_limiter {
+            limiter.acquire().await;
+        }
+
+        let url = format!("{base_url}/{}", method.endpoint.as_str());
 
         let mut request_headers = HeaderMap::new();
 
         let token_header = HeaderName::from_str("Crypto-Pay-Api-Token")?;
 
-        request_headers.insert(token_header, HeaderValue::from_str(&self.api_token)?);
+        let mut token = HeaderValue::from_str(&self.api_token)?;
+        token.set_sensitive(true);
+        request_headers.insert(token_header, token);
 
         if let Some(custom_headers) = &self.headers {
             for (name, value) in custom_headers.iter() {
@@ -95,24 +466,45 @@ impl CryptoBot {
             }
         }
 
-        let mut request = match method.method {
-            Method::POST => self.client.post(&url).headers(request_headers),
-            Method::GET => self.client.get(&url).headers(request_headers),
-            Method::DELETE => self.client.delete(&url).headers(request_headers),
-        };
-
-        if let Some(params) = params {
-            request = request.json(params);
+        let body = params
+            .map(serde_json::to_string)
+            .transpose()
+            .map_err(|e| CryptoBotError::ValidationError {
+                kind: crate::error::ValidationErrorKind::Invalid,
+                code: crate::error::ValidationCode::InvalidParams,
+                message: format!("Failed to serialize request parameters: {e}"),
+                field: None,
+            })?;
+
+        let TransportHandle(transport) = &self.transport;
+        let text = transport
+            .send(TransportRequest {
+                endpoint: method.endpoint,
+                method: method.method,
+                url,
+                headers: request_headers,
+                body,
+            })
+            .await?;
+
+        self.response_sizes.record(method.endpoint, text.len());
+
+        if let Some(limit) = self.max_response_size.filter(|limit| text.len() > *limit) {
+            return Err(response_too_large(method.endpoint, limit));
         }
 
-        let response = request.send().await?;
-
-        if !response.status().is_success() {
-            return Err(CryptoBotError::HttpError(response.error_for_status().unwrap_err()));
+        if let Some(ResponseHook(hook)) = &self.response_hook {
+            if let Ok(envelope) = serde_json::from_str::<ApiResponse<serde::de::IgnoredAny>>(&text) {
+                hook(&ResponseEnvelope {
+                    endpoint: method.endpoint,
+                    ok: envelope.ok,
+                    error_code: envelope.error_code,
+                    error: envelope.error,
+                    raw: text.clone(),
+                });
+            }
         }
 
-        let text = response.text().await?;
-
         let api_response: ApiResponse<R> = serde_json::from_str(&text).map_err(|e| CryptoBotError::ApiError {
             code: -1,
             message: "Failed to parse API response".to_string(),
@@ -130,17 +522,211 @@ impl CryptoBot {
         api_response.result.ok_or(CryptoBotError::NoResult)
     }
 
+    /// Fetches exchange rates and currencies concurrently and caches them
+    ///
+    /// Requests that validate amounts reuse the cached exchange rates while they are fresh, so
+    /// the first invoice creation does not pay for an extra request. Called by
+    /// `ClientBuilder::build_async` when `prefetch_metadata(true)` is set.
+    ///
+    /// # Errors
+    /// Returns the first error if either request fails. Whatever was fetched successfully is cached.
+    pub async fn prefetch_metadata(&self) -> CryptoBotResult<()> {
+        let (rates, currencies) =
+            futures_util::future::join(self.get_exchange_rates().execute(), self.get_currencies().execute()).await;
+
+        rates.and(currencies).map(|_| ())
+    }
+
+    /// Returns the cached exchange rates if they are fresh, otherwise fetches them
+    pub(crate) async fn exchange_rates(&self) -> CryptoBotResult<Vec<ExchangeRate>> {
+        match self.metadata.exchange_rates() {
+            Some(rates) => Ok(rates),
+            None => self.get_exchange_rates().execute().await,
+        }
+    }
+
+    /// Returns the cached currencies if they are fresh, otherwise fetches them
+    pub(crate) async fn currencies(&self) -> CryptoBotResult<Vec<Currency>> {
+        match self.metadata.currencies() {
+            Some(currencies) => Ok(currencies),
+            None => self.get_currencies().execute().await,
+        }
+    }
+
+    /// Returns the cached app info, currencies and exchange rates, with explicit refresh and invalidation
+    ///
+    /// # Example
+    /// ```no_run
+    /// # use crypto_pay_api::prelude::*;
+    /// # async fn example(client: CryptoBot) -> Result<(), CryptoBotError> {
+    /// let metadata = client.metadata().get().await?;
+    /// println!("{} supports {} currencies", metadata.me.name, metadata.currencies.len());
+    ///
+    /// client.metadata().invalidate();
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn metadata(&self) -> Metadata<'_> {
+        Metadata::new(self)
+    }
+
+    /// Runs a before-request hook with the serialized params, failing with `Rejected` if it vetoes the request
+    pub(crate) fn run_before_hook<P: Serialize>(
+        &self,
+        hook: Option<&BeforeRequestHook>,
+        endpoint: APIEndpoint,
+        params: &P,
+    ) -> CryptoBotResult<()> {
+        let Some(BeforeRequestHook(hook)) = hook else {
+            return Ok(());
+        };
+
+        let params = serde_json::to_value(params).map_err(|e| CryptoBotError::ValidationError {
+            kind: crate::error::ValidationErrorKind::Invalid,
+            code: crate::error::ValidationCode::InvalidParams,
+            message: format!("Failed to serialize request parameters: {e}"),
+            field: None,
+        })?;
+
+        hook(&params).map_err(|reason| CryptoBotError::Rejected {
+            endpoint: endpoint.as_str().to_string(),
+            reason,
+        })
+    }
+
+    /// Wraps a created invoice, applying the link decorator if one is registered
+    pub(crate) async fn created_invoice(&self, invoice: Invoice) -> CreatedInvoice {
+        match &self.link_decorator {
+            Some(LinkDecoratorHandle(decorator)) => decorate_invoice(decorator.as_ref(), invoice).await,
+            None => CreatedInvoice::new(invoice),
+        }
+    }
+
+    /// Returns the cached currencies, if they were fetched recently
+    pub fn cached_currencies(&self) -> Option<Vec<Currency>> {
+        self.metadata.currencies()
+    }
+
+    /// Validates a `count` parameter, or clamps it into the accepted range if clamping is enabled
+    ///
+    /// `clamp` overrides the client setting for a single request.
+    pub(crate) fn resolve_count(&self, count: u16, clamp: Option<bool>) -> CryptoBotResult<u16> {
+        if !clamp.unwrap_or(self.clamp_counts) {
+            validate_count(count)?;
+            return Ok(count);
+        }
+
+        let clamped = clamp_count(count);
+        if clamped != count {
+            self.warn(&format!("count {count} is out of range, clamped to {clamped}"));
+        }
+        Ok(clamped)
+    }
+
+    pub(crate) fn warn(&self, message: &str) {
+        if let Some(WarningHook(hook)) = &self.warning_hook {
+            hook(message);
+        }
+    }
+
+    /// Returns a client sharing the state of this one, e.g. for a spawned task
+    ///
+    /// Caches, statistics, circuit breaker and failover state are shared, so requests of the returned client
+    /// count for both.
+    #[cfg(feature = "tokio")]
+    pub(crate) fn shared(&self) -> CryptoBot {
+        CryptoBot {
+            api_token: self.api_token.clone(),
+            transport: self.transport.clone(),
+            base_url: self.base_url.clone(),
+            failover: self.failover.clone(),
+            headers: self.headers.clone(),
+            transfer_defaults: self.transfer_defaults.clone(),
+            circuit_breaker: self.circuit_breaker.clone(),
+            response_hook: self.response_hook.clone(),
+            clamp_counts: self.clamp_counts,
+            rounding: self.rounding,
+            warning_hook: self.warning_hook.clone(),
+            webhook_expiration_time: self.webhook_expiration_time,
+            metadata: self.metadata.clone(),
+            max_response_size: self.max_response_size,
+            response_sizes: self.response_sizes.clone(),
+            request_stats: self.request_stats.clone(),
+            id_generator: self.id_generator.clone(),
+            link_decorator: self.link_decorator.clone(),
+            receipt_key: self.receipt_key.clone(),
+            before_create_invoice: self.before_create_invoice.clone(),
+            before_transfer: self.before_transfer.clone(),
+            environment: self.environment,
+            allow_testnet_assets: self.allow_testnet_assets,
+            retry: self.retry.clone(),
+            rate_limiter: self.rate_limiter.clone(),
+            #[cfg(test)]
+            test_rates: self.test_rates.clone(),
+        }
+    }
+
+    /// Returns the base URL the next request is sent to first
+    ///
+    /// With `ClientBuilder::base_urls`, this is the first URL of the list that is not cooling down after a failure.
+    pub fn active_base_url(&self) -> &str {
+        self.failover
+            .as_ref()
+            .map_or(&self.base_url, |failover| failover.active())
+    }
+
+    /// Returns the circuit state of an endpoint, or `None` if no circuit breaker is configured
+    pub fn circuit_state(&self, endpoint: APIEndpoint) -> Option<CircuitState> {
+        self.circuit_breaker.as_ref().map(|breaker| breaker.state(endpoint))
+    }
+
+    /// Returns the response size statistics of every endpoint that has been called
+    ///
+    /// Responses rejected by the HTTP transport for exceeding `max_response_size` are not counted.
+    pub fn response_size_stats(&self) -> Vec<(APIEndpoint, ResponseSizeStats)> {
+        self.response_sizes.stats()
+    }
+
+    /// Returns a new ID from the client's `IdGenerator`, e.g. to correlate requests in application logs
+    pub fn generate_id(&self, kind: IdKind) -> String {
+        let IdGeneratorHandle(generator) = &self.id_generator;
+        generator.generate(kind)
+    }
+
+    /// Returns the request counters of every endpoint that has been called, without any external metrics system
+    ///
+    /// # Example
+    /// ```no_run
+    /// # use crypto_pay_api::prelude::*;
+    /// # fn example(client: CryptoBot) {
+    /// // e.g. the reply to an "/admin api_stats" command
+    /// let stats = client.stats_snapshot();
+    /// println!("{} requests, {} errors\n{stats}", stats.requests(), stats.errors());
+    /// # }
+    /// ```
+    pub fn stats_snapshot(&self) -> StatsSnapshot {
+        self.request_stats.snapshot()
+    }
+
+    /// Returns the circuit state of every endpoint that has been called, e.g. for health endpoints
+    pub fn circuit_states(&self) -> Vec<(APIEndpoint, CircuitState)> {
+        self.circuit_breaker
+            .as_ref()
+            .map(|breaker| breaker.states())
+            .unwrap_or_default()
+    }
+
     #[cfg(test)]
     pub fn test_client() -> Self {
         use crate::utils::test_utils::TestContext;
 
-        Self {
-            api_token: "test_token".to_string(),
-            client: reqwest::Client::new(),
-            base_url: "http://test.example.com".to_string(),
-            headers: None,
-            test_rates: Some(TestContext::mock_exchange_rates()),
-        }
+        let mut client = Self::builder()
+            .api_token("test_token")
+            .base_url("http://test.example.com")
+            .build()
+            .expect("Failed to build test client");
+        client.test_rates = Some(TestContext::mock_exchange_rates());
+        client
     }
 }
 
@@ -152,12 +738,13 @@ mod tests {
     use serde_json::json;
 
     use crate::{
-        api::BalanceAPI,
-        models::{APIEndpoint, Balance},
+        api::{BalanceAPI, InvoiceAPI, MiscAPI},
+        models::{APIEndpoint, Balance, Method},
         utils::test_utils::TestContext,
     };
 
     use super::*;
+    use crate::config::DEFAULT_API_URL;
 
     #[derive(Debug, Serialize)]
     struct DummyPayload {
@@ -182,6 +769,240 @@ mod tests {
         }
     }
 
+    fn env(vars: &[(&str, &str)]) -> impl Fn(&str) -> Option<String> {
+        let vars: Vec<(String, String)> = vars.iter().map(|(k, v)| (k.to_string(), v.to_string())).collect();
+        move |key| vars.iter().find(|(k, _)| k == key).map(|(_, v)| v.clone())
+    }
+
+    #[test]
+    fn test_from_env_with_defaults() {
+        let client = CryptoBot::from_env_with(env(&[(ENV_API_TOKEN, "token")])).unwrap();
+
+        assert_eq!(client.api_token, "token");
+        assert_eq!(client.base_url, DEFAULT_API_URL);
+    }
+
+    #[test]
+    fn test_from_env_with_all_values() {
+        let client = CryptoBot::from_env_with(env(&[
+            (ENV_API_TOKEN, "token"),
+            (ENV_API_URL, "https://testnet-pay.crypt.bot/api/"),
+            (ENV_API_TIMEOUT, "60"),
+            (ENV_WEBHOOK_EXPIRATION_TIME, "120"),
+        ]))
+        .unwrap();
+
+        assert_eq!(client.base_url, "https://testnet-pay.crypt.bot/api");
+        assert_eq!(client.webhook_expiration_time, Duration::from_secs(120));
+    }
+
+    #[test]
+    fn test_from_env_missing_token() {
+        let result = CryptoBot::from_env_with(env(&[(ENV_API_TOKEN, "  ")]));
+
+        assert!(matches!(
+            result,
+            Err(CryptoBotError::ConfigError { message }) if message.contains(ENV_API_TOKEN)
+        ));
+    }
+
+    #[test]
+    fn test_from_env_invalid_values() {
+        let result = CryptoBot::from_env_with(env(&[(ENV_API_TOKEN, "token"), (ENV_API_TIMEOUT, "soon")]));
+        assert!(matches!(
+            result,
+            Err(CryptoBotError::ConfigError { message }) if message.contains(ENV_API_TIMEOUT)
+        ));
+
+        let result = CryptoBot::from_env_with(env(&[(ENV_API_TOKEN, "token"), (ENV_WEBHOOK_EXPIRATION_TIME, "-1")]));
+        assert!(matches!(
+            result,
+            Err(CryptoBotError::ConfigError { message }) if message.contains(ENV_WEBHOOK_EXPIRATION_TIME)
+        ));
+
+        let result = CryptoBot::from_env_with(env(&[(ENV_API_TOKEN, "token"), (ENV_API_TIMEOUT, "0")]));
+        assert!(matches!(result, Err(CryptoBotError::ConfigError { .. })));
+
+        let result = CryptoBot::from_env_with(env(&[(ENV_API_TOKEN, "token"), (ENV_API_URL, "pay.crypt.bot")]));
+        assert!(matches!(
+            result,
+            Err(CryptoBotError::ConfigError { message }) if message.contains(ENV_API_URL)
+        ));
+    }
+
+    #[test]
+    fn test_debug_redacts_secrets() {
+        struct CapturingTransport(Arc<std::sync::Mutex<Vec<String>>>);
+
+        #[async_trait::async_trait]
+        impl Transport for CapturingTransport {
+            async fn send(&self, request: TransportRequest) -> CryptoBotResult<String> {
+                self.0.lock().unwrap().push(format!("{request:?}"));
+                Ok(r#"{"ok":true,"result":[]}"#.to_string())
+            }
+        }
+
+        let token = "12345:AAzQcZWQqQAbsfgPnOLr4FHC8Doa4L7KryC";
+        let requests = Arc::new(std::sync::Mutex::new(Vec::new()));
+        let client = CryptoBot::builder()
+            .api_token(token)
+            .transport(CapturingTransport(requests.clone()))
+            .build()
+            .unwrap();
+
+        let debug = format!("{client:?}");
+        assert!(!debug.contains(token));
+        assert!(debug.contains("1234…[redacted]"));
+
+        let ctx = TestContext::new();
+        ctx.run(client.get_balance().execute()).unwrap();
+        let requests = requests.lock().unwrap();
+        assert!(!requests[0].contains(token));
+    }
+
+    #[test]
+    fn test_network_phase_connect() {
+        let ctx = TestContext::new();
+        let client = CryptoBot::builder()
+            .api_token("test_token")
+            .base_url("http://127.0.0.1:1")
+            .connect_timeout(Duration::from_secs(1))
+            .build()
+            .unwrap();
+
+        let error = ctx.run(async { client.get_balance().execute().await }).unwrap_err();
+
+        assert_eq!(error.network_phase(), Some(crate::error::NetworkPhase::Connect));
+        assert_eq!(CryptoBotError::NoResult.network_phase(), None);
+    }
+
+    #[test]
+    fn test_network_phase_read_timeout() {
+        let mut ctx = TestContext::new();
+        let _m = ctx
+            .server
+            .mock("GET", "/getBalance")
+            .with_header("content-type", "application/json")
+            .with_chunked_body(|w| {
+                std::thread::sleep(Duration::from_millis(500));
+                w.write_all(br#"{"ok":true,"result":[]}"#)
+            })
+            .create();
+
+        let client = CryptoBot::builder()
+            .api_token("test_token")
+            .base_url(ctx.server.url())
+            .read_timeout(Duration::from_millis(100))
+            .build()
+            .unwrap();
+
+        let error = ctx.run(async { client.get_balance().execute().await }).unwrap_err();
+
+        assert!(error.is_timeout());
+        assert_eq!(error.network_phase(), Some(crate::error::NetworkPhase::Read));
+    }
+
+    #[test]
+    fn test_response_size_stats() {
+        let mut ctx = TestContext::new();
+        let _m = ctx.mock_balance_response();
+
+        let client = CryptoBot::builder()
+            .api_token("test_token")
+            .base_url(ctx.server.url())
+            .build()
+            .unwrap();
+
+        ctx.run(async {
+            client.get_balance().execute().await.unwrap();
+            client.get_balance().execute().await.unwrap();
+        });
+
+        let stats = client.response_size_stats();
+        assert_eq!(stats.len(), 1);
+        let (endpoint, stats) = stats[0];
+        assert_eq!(endpoint, APIEndpoint::GetBalance);
+        assert_eq!(stats.responses, 2);
+        assert!(stats.last_bytes > 0);
+        assert_eq!(stats.total_bytes, 2 * stats.last_bytes as u64);
+    }
+
+    #[test]
+    fn test_stats_snapshot() {
+        let mut ctx = TestContext::new();
+        let _balance = ctx.mock_balance_response();
+        let _me = ctx
+            .server
+            .mock("GET", "/getMe")
+            .with_status(401)
+            .with_body(r#"{"ok":false,"error_code":401,"error":"UNAUTHORIZED"}"#)
+            .create();
+
+        let client = CryptoBot::builder()
+            .api_token("test_token")
+            .base_url(ctx.server.url())
+            .build()
+            .unwrap();
+
+        ctx.run(async {
+            client.get_balance().execute().await.unwrap();
+            client.get_me().execute().await.unwrap_err();
+        });
+
+        let stats = client.stats_snapshot();
+        assert_eq!(stats.requests(), 2);
+        assert_eq!(stats.endpoint(APIEndpoint::GetBalance).unwrap().successes(), 1);
+        assert_eq!(stats.endpoint(APIEndpoint::GetMe).unwrap().errors.api, 1);
+        assert!(stats.to_string().starts_with("getBalance: 1 requests, 0 errors, avg "));
+    }
+
+    #[test]
+    fn test_max_response_size() {
+        let mut ctx = TestContext::new();
+        let _m = ctx.mock_balance_response();
+        let _chunked = ctx
+            .server
+            .mock("GET", "/getMe")
+            .with_chunked_body(|w| {
+                for _ in 0..10 {
+                    w.write_all(&[b' '; 64])?;
+                }
+                w.write_all(br#"{"ok":true,"result":{}}"#)
+            })
+            .create();
+
+        let build = |limit: usize| {
+            CryptoBot::builder()
+                .api_token("test_token")
+                .base_url(ctx.server.url())
+                .max_response_size(limit)
+                .build()
+                .unwrap()
+        };
+
+        let client = build(50);
+        let result = ctx.run(async { client.get_balance().execute().await });
+        assert!(matches!(
+            result,
+            Err(CryptoBotError::ResponseTooLarge { endpoint, limit: 50 }) if endpoint == "getBalance"
+        ));
+
+        let result = ctx.run(async { client.get_me().execute().await });
+        assert!(matches!(result, Err(CryptoBotError::ResponseTooLarge { .. })));
+
+        let client = build(10_000);
+        assert!(ctx.run(async { client.get_balance().execute().await }).is_ok());
+    }
+
+    #[test]
+    fn test_builder_rejects_invalid_base_url() {
+        let result = CryptoBot::builder().api_token("token").base_url("https://").build();
+        assert!(matches!(
+            result,
+            Err(CryptoBotError::ValidationError { field: Some(field), .. }) if field == "base_url"
+        ));
+    }
+
     #[test]
     fn test_malformed_json_response() {
         let mut ctx = TestContext::new();
@@ -275,14 +1096,11 @@ mod tests {
 
     #[test]
     fn test_invalid_api_token_header() {
-        let client = CryptoBot {
-            api_token: "invalid\u{0000}token".to_string(),
-            client: reqwest::Client::new(),
-            base_url: "http://test.example.com".to_string(),
-            headers: None,
-            #[cfg(test)]
-            test_rates: None,
-        };
+        let client = CryptoBot::builder()
+            .api_token("invalid\u{0000}token")
+            .base_url("http://test.example.com")
+            .build()
+            .unwrap();
 
         let method = APIMethod {
             endpoint: APIEndpoint::GetBalance,
@@ -290,7 +1108,11 @@ mod tests {
         };
         let ctx = TestContext::new();
 
-        let result = ctx.run(async { client.make_request::<(), Vec<Balance>>(&method, None).await });
+        let result = ctx.run(async {
+            client
+                .make_request_with::<(), Vec<Balance>>(&RequestOptions::default(), &method, None)
+                .await
+        });
 
         assert!(matches!(result, Err(CryptoBotError::InvalidHeaderValue(_))));
     }
@@ -425,6 +1247,91 @@ mod tests {
         assert!(matches!(result, Err(CryptoBotError::HttpError(_))));
     }
 
+    #[test]
+    fn test_circuit_breaker_fails_fast() {
+        let mut ctx = TestContext::new();
+        let m = ctx
+            .server
+            .mock("GET", "/getBalance")
+            .with_status(503)
+            .expect(1)
+            .create();
+
+        let client = CryptoBot::builder()
+            .api_token("test")
+            .base_url(ctx.server.url())
+            .circuit_breaker(CircuitBreakerConfig {
+                failure_threshold: 1,
+                reset_timeout: Duration::from_secs(60),
+            })
+            .build()
+            .unwrap();
+
+        let first = ctx.run(async { client.get_balance().execute().await });
+        assert!(matches!(first, Err(CryptoBotError::HttpError(_))));
+        assert_eq!(client.circuit_state(APIEndpoint::GetBalance), Some(CircuitState::Open));
+
+        let second = ctx.run(async { client.get_balance().execute().await });
+        assert!(matches!(
+            second,
+            Err(CryptoBotError::CircuitOpen { endpoint, .. }) if endpoint == "getBalance"
+        ));
+
+        m.assert();
+        assert_eq!(
+            client.circuit_states(),
+            vec![(APIEndpoint::GetBalance, CircuitState::Open)]
+        );
+    }
+
+    #[test]
+    fn test_on_response_hook_receives_envelopes() {
+        let mut ctx = TestContext::new();
+        let _balance = ctx.mock_balance_response();
+        let _error = ctx
+            .server
+            .mock("GET", "/getInvoices")
+            .with_header("content-type", "application/json")
+            .with_body(json!({ "ok": false, "error": "UNAUTHORIZED", "error_code": 401 }).to_string())
+            .create();
+
+        let envelopes = Arc::new(std::sync::Mutex::new(Vec::new()));
+        let captured = envelopes.clone();
+
+        let client = CryptoBot::builder()
+            .api_token("test")
+            .base_url(ctx.server.url())
+            .on_response(move |envelope| captured.lock().unwrap().push(envelope.clone()))
+            .build()
+            .unwrap();
+
+        let balance = ctx.run(async { client.get_balance().execute().await });
+        assert!(balance.is_ok());
+
+        let invoices = ctx.run(async { client.get_invoices().execute().await });
+        assert!(invoices.is_err());
+
+        let envelopes = envelopes.lock().unwrap();
+        assert_eq!(envelopes.len(), 2);
+
+        assert_eq!(envelopes[0].endpoint, APIEndpoint::GetBalance);
+        assert!(envelopes[0].ok);
+        assert_eq!(envelopes[0].error_code, None);
+        assert!(envelopes[0].json().unwrap()["result"].is_array());
+
+        assert_eq!(envelopes[1].endpoint, APIEndpoint::GetInvoices);
+        assert!(!envelopes[1].ok);
+        assert_eq!(envelopes[1].error_code, Some(401));
+        assert_eq!(envelopes[1].error.as_deref(), Some("UNAUTHORIZED"));
+    }
+
+    #[test]
+    fn test_circuit_state_without_breaker() {
+        let client = CryptoBot::test_client();
+        assert_eq!(client.circuit_state(APIEndpoint::GetMe), None);
+        assert!(client.circuit_states().is_empty());
+    }
+
     #[test]
     fn test_make_request_with_custom_headers_and_body() {
         let mut ctx = TestContext::new();
@@ -471,7 +1378,11 @@ mod tests {
             value: "payload".to_string(),
         };
 
-        let result: Result<DummyResponse, _> = ctx.run(async { client.make_request(&method, Some(&payload)).await });
+        let result: Result<DummyResponse, _> = ctx.run(async {
+            client
+                .make_request_with(&RequestOptions::default(), &method, Some(&payload))
+                .await
+        });
 
         assert_eq!(
             result.unwrap(),
@@ -516,7 +1427,11 @@ mod tests {
 
         let payload = DeletePayload { invoice_id: 7 };
 
-        let result: Result<bool, _> = ctx.run(async { client.make_request(&method, Some(&payload)).await });
-        assert_eq!(result.unwrap(), true);
+        let result: Result<bool, _> = ctx.run(async {
+            client
+                .make_request_with(&RequestOptions::default(), &method, Some(&payload))
+                .await
+        });
+        assert!(result.unwrap());
     }
 }
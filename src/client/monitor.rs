@@ -0,0 +1,299 @@
+use std::sync::{Arc, Mutex};
+
+use rust_decimal::Decimal;
+
+use crate::{
+    api::BalanceAPI,
+    error::CryptoBotResult,
+    models::{Balance, Balances, CryptoCurrencyCode},
+};
+
+use super::CryptoBot;
+
+/// Side of a threshold an alert watches.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ThresholdDirection {
+    /// Alert when the available balance drops below the threshold.
+    Below,
+    /// Alert when the available balance rises above the threshold.
+    Above,
+}
+
+/// Whether an alert was just raised or cleared.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum AlertState {
+    /// The balance crossed the threshold.
+    Triggered,
+    /// The balance moved back past the threshold and the hysteresis margin.
+    Recovered,
+}
+
+/// Balance alert passed to the `BalanceMonitor::on_alert` callback.
+#[derive(Debug, Clone, PartialEq)]
+pub struct BalanceAlert {
+    pub asset: CryptoCurrencyCode,
+    pub direction: ThresholdDirection,
+    pub state: AlertState,
+    pub threshold: Decimal,
+    /// Available balance that caused the alert.
+    pub available: Decimal,
+}
+
+#[derive(Debug)]
+struct Threshold {
+    asset: CryptoCurrencyCode,
+    direction: ThresholdDirection,
+    value: Decimal,
+}
+
+type AlertCallback = dyn Fn(&BalanceAlert) + Send + Sync;
+
+/// Watches the app balances and raises alerts when they cross configured thresholds.
+///
+/// An alert is raised once when a balance crosses its threshold and cleared once it moves back
+/// past the threshold by more than the hysteresis margin, so a balance hovering around the
+/// threshold does not flap.
+///
+/// # Example
+/// ```no_run
+/// use crypto_pay_api::prelude::*;
+/// use std::time::Duration;
+///
+/// # async fn example(client: CryptoBot) -> Result<(), CryptoBotError> {
+/// let monitor = BalanceMonitor::new()
+///     .below(CryptoCurrencyCode::Ton, dec!(50))
+///     .hysteresis(dec!(0.1))
+///     .on_alert(|alert| eprintln!("{:?} {} balance: {}", alert.state, alert.asset, alert.available));
+///
+/// loop {
+///     monitor.check(&client).await?;
+///     tokio::time::sleep(Duration::from_secs(60)).await;
+/// }
+/// # }
+/// ```
+pub struct BalanceMonitor {
+    thresholds: Vec<Threshold>,
+    hysteresis: Decimal,
+    triggered: Mutex<Vec<bool>>,
+    on_alert: Option<Arc<AlertCallback>>,
+}
+
+impl BalanceMonitor {
+    pub fn new() -> Self {
+        Self {
+            thresholds: Vec::new(),
+            hysteresis: Decimal::ZERO,
+            triggered: Mutex::new(Vec::new()),
+            on_alert: None,
+        }
+    }
+
+    /// Raises an alert when the available balance of `asset` drops below `threshold`
+    pub fn below(self, asset: CryptoCurrencyCode, threshold: Decimal) -> Self {
+        self.threshold(asset, ThresholdDirection::Below, threshold)
+    }
+
+    /// Raises an alert when the available balance of `asset` rises above `threshold`
+    pub fn above(self, asset: CryptoCurrencyCode, threshold: Decimal) -> Self {
+        self.threshold(asset, ThresholdDirection::Above, threshold)
+    }
+
+    fn threshold(mut self, asset: CryptoCurrencyCode, direction: ThresholdDirection, value: Decimal) -> Self {
+        self.thresholds.push(Threshold {
+            asset,
+            direction,
+            value,
+        });
+        self.triggered.get_mut().unwrap_or_else(|e| e.into_inner()).push(false);
+        self
+    }
+
+    /// Sets the hysteresis margin as a fraction of each threshold, e.g. `dec!(0.1)` for 10%
+    ///
+    /// A `below` alert on 50 TON with a 10% margin is only cleared once the balance exceeds 55 TON.
+    /// Defaults to zero.
+    pub fn hysteresis(mut self, ratio: Decimal) -> Self {
+        self.hysteresis = ratio.max(Decimal::ZERO);
+        self
+    }
+
+    /// Registers the callback receiving raised and cleared alerts
+    pub fn on_alert<F>(mut self, callback: F) -> Self
+    where
+        F: Fn(&BalanceAlert) + Send + Sync + 'static,
+    {
+        self.on_alert = Some(Arc::new(callback));
+        self
+    }
+
+    /// Fetches the balances and evaluates the thresholds against them
+    ///
+    /// # Returns
+    /// The alerts raised or cleared by this check, which were also passed to the callback.
+    pub async fn check(&self, client: &CryptoBot) -> CryptoBotResult<Vec<BalanceAlert>> {
+        let balances = client.get_balance().execute().await?;
+        Ok(self.observe(&balances))
+    }
+
+    /// Evaluates the thresholds against the given balances
+    ///
+    /// Assets missing from `balances` are treated as a zero balance.
+    pub fn observe(&self, balances: &[Balance]) -> Vec<BalanceAlert> {
+        let mut triggered = self.triggered.lock().unwrap_or_else(|e| e.into_inner());
+        let mut alerts = Vec::new();
+
+        for (threshold, triggered) in self.thresholds.iter().zip(triggered.iter_mut()) {
+            let available = balances
+                .find(&threshold.asset)
+                .map_or(Decimal::ZERO, |balance| balance.available);
+            let margin = threshold.value * self.hysteresis;
+
+            let state = match threshold.direction {
+                ThresholdDirection::Below if !*triggered && available < threshold.value => AlertState::Triggered,
+                ThresholdDirection::Below if *triggered && available > threshold.value + margin => {
+                    AlertState::Recovered
+                }
+                ThresholdDirection::Above if !*triggered && available > threshold.value => AlertState::Triggered,
+                ThresholdDirection::Above if *triggered && available < threshold.value - margin => {
+                    AlertState::Recovered
+                }
+                _ => continue,
+            };
+
+            *triggered = state == AlertState::Triggered;
+            alerts.push(BalanceAlert {
+                asset: threshold.asset.clone(),
+                direction: threshold.direction,
+                state,
+                threshold: threshold.value,
+                available,
+            });
+        }
+        drop(triggered);
+
+        if let Some(callback) = &self.on_alert {
+            alerts.iter().for_each(|alert| callback(alert));
+        }
+        alerts
+    }
+
+    /// Checks the balances every `interval`, forever
+    ///
+    /// Errors are passed to `on_error` and do not stop the monitor.
+    #[cfg(feature = "tokio")]
+    pub async fn run<F>(&self, client: &CryptoBot, interval: std::time::Duration, on_error: F)
+    where
+        F: Fn(crate::error::CryptoBotError),
+    {
+        loop {
+            if let Err(error) = self.check(client).await {
+                on_error(error);
+            }
+            tokio::time::sleep(interval).await;
+        }
+    }
+}
+
+impl Default for BalanceMonitor {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl std::fmt::Debug for BalanceMonitor {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("BalanceMonitor")
+            .field("thresholds", &self.thresholds)
+            .field("hysteresis", &self.hysteresis)
+            .finish()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::atomic::{AtomicUsize, Ordering};
+
+    use rust_decimal_macros::dec;
+
+    use super::*;
+    use crate::utils::test_utils::TestContext;
+
+    fn ton(available: Decimal) -> Vec<Balance> {
+        vec![Balance {
+            currency_code: CryptoCurrencyCode::Ton,
+            available,
+            onhold: Decimal::ZERO,
+        }]
+    }
+
+    fn states(alerts: Vec<BalanceAlert>) -> Vec<(ThresholdDirection, AlertState)> {
+        alerts.into_iter().map(|alert| (alert.direction, alert.state)).collect()
+    }
+
+    #[test]
+    fn test_below_threshold_with_hysteresis() {
+        let monitor = BalanceMonitor::new()
+            .below(CryptoCurrencyCode::Ton, dec!(50))
+            .hysteresis(dec!(0.1));
+
+        assert!(monitor.observe(&ton(dec!(60))).is_empty());
+        assert_eq!(
+            states(monitor.observe(&ton(dec!(49)))),
+            vec![(ThresholdDirection::Below, AlertState::Triggered)]
+        );
+        // Hovering around the threshold neither re-triggers nor clears the alert
+        assert!(monitor.observe(&ton(dec!(48))).is_empty());
+        assert!(monitor.observe(&ton(dec!(54))).is_empty());
+        assert_eq!(
+            states(monitor.observe(&ton(dec!(56)))),
+            vec![(ThresholdDirection::Below, AlertState::Recovered)]
+        );
+        assert!(monitor.observe(&ton(dec!(56))).is_empty());
+    }
+
+    #[test]
+    fn test_above_threshold_and_missing_asset() {
+        let monitor = BalanceMonitor::new()
+            .above(CryptoCurrencyCode::Ton, dec!(1000))
+            .below(CryptoCurrencyCode::Usdt, dec!(10));
+
+        let alerts = monitor.observe(&ton(dec!(1500)));
+        assert_eq!(
+            states(alerts.clone()),
+            vec![
+                (ThresholdDirection::Above, AlertState::Triggered),
+                (ThresholdDirection::Below, AlertState::Triggered),
+            ]
+        );
+        assert_eq!(alerts[1].available, Decimal::ZERO);
+
+        assert_eq!(
+            states(monitor.observe(&ton(dec!(900)))),
+            vec![(ThresholdDirection::Above, AlertState::Recovered)]
+        );
+    }
+
+    #[test]
+    fn test_check_fetches_balances_and_calls_back() {
+        let mut ctx = TestContext::new();
+        let _m = ctx.mock_balance_response();
+        let client = CryptoBot::builder()
+            .api_token("test_token")
+            .base_url(ctx.server.url())
+            .build()
+            .unwrap();
+
+        let calls = Arc::new(AtomicUsize::new(0));
+        let counter = calls.clone();
+        let monitor = BalanceMonitor::new()
+            .below(CryptoCurrencyCode::Send, dec!(20))
+            .on_alert(move |_| {
+                counter.fetch_add(1, Ordering::SeqCst);
+            });
+
+        let alerts = ctx.run(monitor.check(&client)).unwrap();
+        assert_eq!(alerts.len(), 1);
+        assert_eq!(alerts[0].available, dec!(10.5));
+        assert_eq!(calls.load(Ordering::SeqCst), 1);
+    }
+}
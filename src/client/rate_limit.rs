@@ -0,0 +1,187 @@
+use std::{
+    sync::{Arc, Mutex},
+    time::Duration,
+};
+
+use tokio::time::Instant;
+
+/// Token bucket throttling outgoing requests, set with `ClientBuilder::rate_limit` or `ClientBuilder::rate_limiter`
+///
+/// Every request sent to the API takes a token, including retries and failover attempts, and tokens
+/// are refilled continuously up to the burst size. When the bucket is empty, the request waits for
+/// its turn instead of failing, so bursts of invoice creation are spread out rather than rejected by
+/// the API. Clones share the same bucket, so clients built with clones of one limiter stay under the
+/// limit together. Requires the `tokio` feature.
+///
+/// # Example
+/// ```
+/// use crypto_pay_api::prelude::*;
+///
+/// // Two clients of the same app, together sending at most 5 requests per second
+/// let limiter = RateLimiter::new(5.0, 5);
+/// let invoices = CryptoBot::builder()
+///     .api_token("YOUR_API_TOKEN")
+///     .rate_limiter(limiter.clone())
+///     .build()
+///     .unwrap();
+/// let payouts = CryptoBot::builder()
+///     .api_token("YOUR_API_TOKEN")
+///     .rate_limiter(limiter)
+///     .build()
+///     .unwrap();
+/// ```
+#[derive(Debug, Clone)]
+pub struct RateLimiter {
+    bucket: Arc<Mutex<Bucket>>,
+}
+
+#[derive(Debug)]
+struct Bucket {
+    /// Available tokens, negative while requests are waiting for a token.
+    tokens: f64,
+    burst: f64,
+    requests_per_second: f64,
+    refilled_at: Instant,
+}
+
+impl RateLimiter {
+    /// Allows `requests_per_second` requests on average, in bursts of up to `burst` requests
+    ///
+    /// A `burst` of 0 is treated as 1. The rate must be positive, or building the client fails.
+    pub fn new(requests_per_second: f64, burst: u32) -> Self {
+        let burst = burst.max(1) as f64;
+        Self {
+            bucket: Arc::new(Mutex::new(Bucket {
+                tokens: burst,
+                burst,
+                requests_per_second,
+                refilled_at: Instant::now(),
+            })),
+        }
+    }
+
+    /// Returns the average number of requests allowed per second
+    pub fn requests_per_second(&self) -> f64 {
+        self.lock().requests_per_second
+    }
+
+    /// Waits until the request may be sent
+    ///
+    /// The token is taken before waiting and given back if the returned future is dropped while
+    /// waiting, e.g. when the request is cancelled or times out.
+    pub(crate) async fn acquire(&self) {
+        let wait = {
+            let mut bucket = self.lock();
+            bucket.refill();
+
+            // Taking the token up front reserves a turn, so waiting requests are served in order
+            bucket.tokens -= 1.0;
+            (bucket.tokens < 0.0).then(|| Duration::from_secs_f64(-bucket.tokens / bucket.requests_per_second))
+        };
+
+        if let Some(wait) = wait {
+            let reservation = Reservation { limiter: Some(self) };
+            tokio::time::sleep(wait).await;
+            reservation.keep();
+        }
+    }
+
+    fn lock(&self) -> std::sync::MutexGuard<'_, Bucket> {
+        self.bucket.lock().unwrap_or_else(|e| e.into_inner())
+    }
+}
+
+/// Token taken by a waiting request, refunded unless the request got its turn
+struct Reservation<'a> {
+    limiter: Option<&'a RateLimiter>,
+}
+
+impl Reservation<'_> {
+    fn keep(mut self) {
+        self.limiter = None;
+    }
+}
+
+impl Drop for Reservation<'_> {
+    fn drop(&mut self) {
+        if let Some(limiter) = self.limiter {
+            let mut bucket = limiter.lock();
+            bucket.refill();
+            bucket.tokens = (bucket.tokens + 1.0).min(bucket.burst);
+        }
+    }
+}
+
+impl Bucket {
+    fn refill(&mut self) {
+        let now = Instant::now();
+        let elapsed = now.duration_since(self.refilled_at).as_secs_f64();
+        self.tokens = (self.tokens + elapsed * self.requests_per_second).min(self.burst);
+        self.refilled_at = now;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{api::MiscAPI, client::CryptoBot, error::CryptoBotError, utils::test_utils::TestContext};
+
+    #[tokio::test(start_paused = true)]
+    async fn test_rate_limiter_spreads_requests_across_clones() {
+        let limiter = RateLimiter::new(50.0, 2);
+        let shared = limiter.clone();
+
+        let start = Instant::now();
+        for _ in 0..3 {
+            limiter.acquire().await;
+            shared.acquire().await;
+        }
+
+        // The burst covers two requests, the other four wait 20ms each
+        assert_eq!(start.elapsed(), Duration::from_millis(80));
+        assert_eq!(shared.requests_per_second(), 50.0);
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn test_rate_limiter_refunds_cancelled_wait() {
+        let limiter = RateLimiter::new(10.0, 1);
+        limiter.acquire().await;
+
+        // Cancelled after 50ms of its 100ms wait
+        let cancelled = tokio::time::timeout(Duration::from_millis(50), limiter.acquire()).await;
+        assert!(cancelled.is_err());
+
+        // The refunded turn is not charged to the next request, which waits for the remaining 50ms only
+        let start = Instant::now();
+        limiter.acquire().await;
+        assert_eq!(start.elapsed(), Duration::from_millis(50));
+    }
+
+    #[test]
+    fn test_client_rate_limit() {
+        let mut ctx = TestContext::new();
+        let _m = ctx.mock_get_me_response();
+
+        let client = CryptoBot::builder()
+            .api_token("test_token")
+            .base_url(ctx.server.url())
+            .rate_limit(10.0)
+            .build()
+            .unwrap();
+
+        let elapsed = ctx.run(async {
+            let start = Instant::now();
+            client.get_me().execute().await.unwrap();
+            client.get_me().execute().await.unwrap();
+            start.elapsed()
+        });
+        assert!(elapsed < Duration::from_millis(500), "the burst covers both requests");
+
+        for rate in [0.0, -1.0, f64::NAN] {
+            assert!(matches!(
+                CryptoBot::builder().api_token("test_token").rate_limit(rate).build(),
+                Err(CryptoBotError::ConfigError { .. })
+            ));
+        }
+    }
+}
@@ -0,0 +1,211 @@
+use std::{collections::HashMap, fmt, sync::Mutex, time::Duration};
+
+use crate::{error::CryptoBotError, models::APIEndpoint};
+
+/// Failed requests of an endpoint, by class of error
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct ErrorCounts {
+    /// Connect, read or total request timeouts.
+    pub timeout: u64,
+    /// Other network errors, e.g. refused connections.
+    pub network: u64,
+    /// 5xx responses and responses that could not be parsed.
+    pub server: u64,
+    /// Errors returned by the API for the request, e.g. invalid parameters or an invalid token.
+    pub api: u64,
+    /// Everything else, e.g. validation errors, open circuits and rejected requests.
+    pub other: u64,
+}
+
+impl ErrorCounts {
+    /// Returns the number of failed requests
+    pub fn total(&self) -> u64 {
+        self.timeout + self.network + self.server + self.api + self.other
+    }
+
+    fn add(&mut self, error: &CryptoBotError) {
+        match error {
+            e if e.is_timeout() => self.timeout += 1,
+            CryptoBotError::HttpError(e) if e.status().is_some_and(|status| status.is_server_error()) => {
+                self.server += 1
+            }
+            CryptoBotError::HttpError(e) if e.status().is_some() => self.api += 1,
+            CryptoBotError::HttpError(_) => self.network += 1,
+            CryptoBotError::ApiError { code, .. } if *code == -1 || *code >= 500 => self.server += 1,
+            CryptoBotError::ApiError { .. } | CryptoBotError::NoResult => self.api += 1,
+            _ => self.other += 1,
+        }
+    }
+}
+
+/// Requests made to an endpoint since the client was built
+///
+/// A request counts once, however many retries or failover attempts it took.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct EndpointStats {
+    /// Number of requests made.
+    pub requests: u64,
+    /// Failed requests, by class of error.
+    pub errors: ErrorCounts,
+    /// Sum of the request latencies, including retries.
+    pub total_latency: Duration,
+    /// Highest request latency.
+    pub max_latency: Duration,
+}
+
+impl EndpointStats {
+    /// Returns the number of successful requests
+    pub fn successes(&self) -> u64 {
+        self.requests - self.errors.total()
+    }
+
+    /// Returns the average request latency, or zero if no request was made
+    pub fn average_latency(&self) -> Duration {
+        u32::try_from(self.requests)
+            .ok()
+            .and_then(|requests| self.total_latency.checked_div(requests))
+            .unwrap_or_default()
+    }
+}
+
+/// In-process request counters of every endpoint that has been called, returned by `CryptoBot::stats_snapshot`
+///
+/// The `Display` output has one line per endpoint, e.g. for an admin command of a bot.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct StatsSnapshot {
+    /// Counters per endpoint, ordered by endpoint name.
+    pub endpoints: Vec<(APIEndpoint, EndpointStats)>,
+}
+
+impl StatsSnapshot {
+    /// Returns the counters of an endpoint, or `None` if it has not been called
+    pub fn endpoint(&self, endpoint: APIEndpoint) -> Option<&EndpointStats> {
+        self.endpoints
+            .iter()
+            .find(|(called, _)| *called == endpoint)
+            .map(|(_, stats)| stats)
+    }
+
+    /// Returns the number of requests made to all endpoints
+    pub fn requests(&self) -> u64 {
+        self.endpoints.iter().map(|(_, stats)| stats.requests).sum()
+    }
+
+    /// Returns the number of failed requests to all endpoints
+    pub fn errors(&self) -> u64 {
+        self.endpoints.iter().map(|(_, stats)| stats.errors.total()).sum()
+    }
+}
+
+/// E.g. `getMe: 3 requests, 1 errors (1 timeout), avg 120ms, max 310ms`.
+impl fmt::Display for StatsSnapshot {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if self.endpoints.is_empty() {
+            return f.write_str("no requests");
+        }
+
+        for (i, (endpoint, stats)) in self.endpoints.iter().enumerate() {
+            if i > 0 {
+                writeln!(f)?;
+            }
+            write!(
+                f,
+                "{}: {} requests, {} errors",
+                endpoint.as_str(),
+                stats.requests,
+                stats.errors.total()
+            )?;
+
+            let errors = stats.errors;
+            let classes: Vec<String> = [
+                ("timeout", errors.timeout),
+                ("network", errors.network),
+                ("server", errors.server),
+                ("api", errors.api),
+                ("other", errors.other),
+            ]
+            .into_iter()
+            .filter(|(_, count)| *count > 0)
+            .map(|(class, count)| format!("{count} {class}"))
+            .collect();
+            if !classes.is_empty() {
+                write!(f, " ({})", classes.join(", "))?;
+            }
+
+            write!(
+                f,
+                ", avg {}ms, max {}ms",
+                stats.average_latency().as_millis(),
+                stats.max_latency.as_millis()
+            )?;
+        }
+        Ok(())
+    }
+}
+
+#[derive(Debug, Default)]
+pub(crate) struct RequestStatsTracker {
+    stats: Mutex<HashMap<APIEndpoint, EndpointStats>>,
+}
+
+impl RequestStatsTracker {
+    pub(crate) fn record(&self, endpoint: APIEndpoint, latency: Duration, error: Option<&CryptoBotError>) {
+        let mut stats = self.stats.lock().unwrap_or_else(|e| e.into_inner());
+        let entry = stats.entry(endpoint).or_default();
+        entry.requests += 1;
+        entry.total_latency += latency;
+        entry.max_latency = entry.max_latency.max(latency);
+        if let Some(error) = error {
+            entry.errors.add(error);
+        }
+    }
+
+    pub(crate) fn snapshot(&self) -> StatsSnapshot {
+        let stats = self.stats.lock().unwrap_or_else(|e| e.into_inner());
+        let mut endpoints: Vec<(APIEndpoint, EndpointStats)> =
+            stats.iter().map(|(endpoint, stats)| (*endpoint, *stats)).collect();
+        endpoints.sort_by_key(|(endpoint, _)| endpoint.as_str());
+        StatsSnapshot { endpoints }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn api_error(code: i32) -> CryptoBotError {
+        CryptoBotError::ApiError {
+            code,
+            message: "error".to_string(),
+            details: None,
+        }
+    }
+
+    #[test]
+    fn test_request_stats_tracker() {
+        let tracker = RequestStatsTracker::default();
+        tracker.record(APIEndpoint::GetMe, Duration::from_millis(100), None);
+        tracker.record(APIEndpoint::GetMe, Duration::from_millis(300), Some(&api_error(500)));
+        tracker.record(APIEndpoint::GetMe, Duration::from_millis(200), Some(&api_error(400)));
+        tracker.record(APIEndpoint::GetBalance, Duration::from_millis(50), None);
+
+        let snapshot = tracker.snapshot();
+        assert_eq!(snapshot.requests(), 4);
+        assert_eq!(snapshot.errors(), 2);
+
+        let me = snapshot.endpoint(APIEndpoint::GetMe).unwrap();
+        assert_eq!(me.successes(), 1);
+        assert_eq!(me.errors.server, 1);
+        assert_eq!(me.errors.api, 1);
+        assert_eq!(me.average_latency(), Duration::from_millis(200));
+        assert_eq!(me.max_latency, Duration::from_millis(300));
+        assert!(snapshot.endpoint(APIEndpoint::GetStats).is_none());
+
+        assert_eq!(
+            snapshot.to_string(),
+            "getBalance: 1 requests, 0 errors, avg 50ms, max 50ms\n\
+             getMe: 3 requests, 2 errors (1 server, 1 api), avg 200ms, max 300ms"
+        );
+        assert_eq!(StatsSnapshot::default().to_string(), "no requests");
+    }
+}
@@ -0,0 +1,67 @@
+use std::{collections::HashMap, sync::Mutex};
+
+use crate::models::APIEndpoint;
+
+/// Sizes of the API responses received for an endpoint
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct ResponseSizeStats {
+    /// Number of responses received.
+    pub responses: u64,
+    /// Sum of the response sizes, in bytes.
+    pub total_bytes: u64,
+    /// Largest response size, in bytes.
+    pub max_bytes: usize,
+    /// Size of the latest response, in bytes.
+    pub last_bytes: usize,
+}
+
+impl ResponseSizeStats {
+    /// Returns the average response size in bytes, or 0 if no response was received
+    pub fn average_bytes(&self) -> u64 {
+        self.total_bytes.checked_div(self.responses).unwrap_or(0)
+    }
+}
+
+#[derive(Debug, Default)]
+pub(crate) struct ResponseSizeTracker {
+    stats: Mutex<HashMap<APIEndpoint, ResponseSizeStats>>,
+}
+
+impl ResponseSizeTracker {
+    pub(crate) fn record(&self, endpoint: APIEndpoint, size: usize) {
+        let mut stats = self.stats.lock().unwrap_or_else(|e| e.into_inner());
+        let entry = stats.entry(endpoint).or_default();
+        entry.responses += 1;
+        entry.total_bytes += size as u64;
+        entry.max_bytes = entry.max_bytes.max(size);
+        entry.last_bytes = size;
+    }
+
+    pub(crate) fn stats(&self) -> Vec<(APIEndpoint, ResponseSizeStats)> {
+        let stats = self.stats.lock().unwrap_or_else(|e| e.into_inner());
+        stats.iter().map(|(endpoint, stats)| (*endpoint, *stats)).collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_response_size_tracker() {
+        let tracker = ResponseSizeTracker::default();
+        tracker.record(APIEndpoint::GetInvoices, 100);
+        tracker.record(APIEndpoint::GetInvoices, 300);
+        tracker.record(APIEndpoint::GetMe, 50);
+
+        let stats: HashMap<_, _> = tracker.stats().into_iter().collect();
+        let invoices = stats[&APIEndpoint::GetInvoices];
+        assert_eq!(invoices.responses, 2);
+        assert_eq!(invoices.total_bytes, 400);
+        assert_eq!(invoices.max_bytes, 300);
+        assert_eq!(invoices.last_bytes, 300);
+        assert_eq!(invoices.average_bytes(), 200);
+        assert_eq!(stats[&APIEndpoint::GetMe].responses, 1);
+        assert_eq!(ResponseSizeStats::default().average_bytes(), 0);
+    }
+}
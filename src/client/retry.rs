@@ -0,0 +1,366 @@
+use std::{
+    collections::hash_map::RandomState,
+    future::Future,
+    hash::{BuildHasher, Hasher},
+    sync::{Arc, Mutex},
+    time::{Duration, Instant},
+};
+
+use crate::{
+    error::{CryptoBotError, CryptoBotResult, NetworkPhase},
+    models::APIEndpoint,
+};
+
+/// Configuration of automatic retries of failed requests
+///
+/// Requests that never reached the server (connect errors) are retried for every endpoint. Timeouts
+/// and responses with a status in `retry_on_status` are only retried for idempotent endpoints (see
+/// [`APIEndpoint::is_idempotent`]), since creating an invoice or a check may already have taken effect.
+/// The delay doubles after each attempt, starting at `base_delay`, and is randomized by `jitter`.
+///
+/// # Example
+/// ```
+/// use crypto_pay_api::prelude::*;
+///
+/// // At most 10 retries in a burst across every request of the client, refilled at 1 per second
+/// let client = CryptoBot::builder()
+///     .api_token("YOUR_API_TOKEN")
+///     .retry(RetryConfig {
+///         budget: Some(RetryBudget::new(10, 1.0)),
+///         ..RetryConfig::default()
+///     })
+///     .build()
+///     .unwrap();
+/// ```
+#[derive(Debug, Clone)]
+pub struct RetryConfig {
+    /// Maximum number of retries of a single request.
+    pub max_retries: u32,
+    /// Delay before the first retry.
+    pub base_delay: Duration,
+    /// Fraction of each delay, between 0 and 1, that is randomized so clients failing together
+    /// do not retry in lockstep. With `0.2`, a delay of 1s becomes anything between 0.8s and 1.2s.
+    pub jitter: f64,
+    /// HTTP statuses retried for idempotent endpoints.
+    pub retry_on_status: Vec<u16>,
+    /// Budget shared by every retry of the client, or of every client it is cloned into.
+    pub budget: Option<RetryBudget>,
+}
+
+impl Default for RetryConfig {
+    fn default() -> Self {
+        Self {
+            max_retries: 2,
+            base_delay: Duration::from_millis(200),
+            jitter: 0.2,
+            retry_on_status: vec![429, 500, 502, 503, 504],
+            budget: None,
+        }
+    }
+}
+
+impl RetryConfig {
+    pub(crate) async fn run<R, F, Fut>(&self, endpoint: APIEndpoint, send: F) -> CryptoBotResult<R>
+    where
+        F: Fn() -> Fut,
+        Fut: Future<Output = CryptoBotResult<R>>,
+    {
+        let mut attempt = 0;
+        loop {
+            match send().await {
+                Err(error)
+                    if attempt < self.max_retries
+                        && self.is_retryable(endpoint, &error)
+                        && self.budget.as_ref().is_none_or(RetryBudget::try_acquire) =>
+                {
+                    tokio::time::sleep(self.delay(attempt)).await;
+                    attempt += 1;
+                }
+                result => return result,
+            }
+        }
+    }
+
+    /// Returns the delay before retry number `attempt + 1`
+    fn delay(&self, attempt: u32) -> Duration {
+        let delay = self.base_delay.saturating_mul(1 << attempt.min(16));
+        let jitter = self.jitter.clamp(0.0, 1.0);
+        if jitter == 0.0 {
+            return delay;
+        }
+
+        // Every `RandomState` is seeded with random keys by the standard library
+        let mut hasher = RandomState::new().build_hasher();
+        hasher.write_u32(attempt);
+        let random = hasher.finish() as f64 / u64::MAX as f64;
+        delay.mul_f64(1.0 + jitter * (2.0 * random - 1.0))
+    }
+
+    fn is_retryable(&self, endpoint: APIEndpoint, error: &CryptoBotError) -> bool {
+        let CryptoBotError::HttpError(http_error) = error else {
+            return false;
+        };
+
+        if error.network_phase() == Some(NetworkPhase::Connect) {
+            return true;
+        }
+
+        endpoint.is_idempotent()
+            && (http_error.is_timeout()
+                || http_error
+                    .status()
+                    .is_some_and(|status| self.retry_on_status.contains(&status.as_u16())))
+    }
+}
+
+/// Token bucket bounding the aggregate number of retries
+///
+/// Every retry takes a token, and tokens are refilled continuously up to the capacity. When the
+/// bucket is empty, failed requests return their error instead of being retried, so retries cannot
+/// multiply the load during an incident no matter how many tasks share the client. Clones share
+/// the same bucket.
+#[derive(Debug, Clone)]
+pub struct RetryBudget {
+    bucket: Arc<Mutex<Bucket>>,
+}
+
+#[derive(Debug)]
+struct Bucket {
+    tokens: f64,
+    capacity: f64,
+    refill_per_second: f64,
+    refilled_at: Instant,
+}
+
+impl RetryBudget {
+    /// Creates a full budget of `capacity` retries, refilled at `refill_per_second` retries per second
+    pub fn new(capacity: u32, refill_per_second: f64) -> Self {
+        Self {
+            bucket: Arc::new(Mutex::new(Bucket {
+                tokens: capacity as f64,
+                capacity: capacity as f64,
+                refill_per_second: refill_per_second.max(0.0),
+                refilled_at: Instant::now(),
+            })),
+        }
+    }
+
+    /// Returns the number of retries currently available
+    pub fn available(&self) -> u32 {
+        let mut bucket = self.bucket.lock().unwrap_or_else(|e| e.into_inner());
+        bucket.refill();
+        bucket.tokens as u32
+    }
+
+    pub(crate) fn try_acquire(&self) -> bool {
+        let mut bucket = self.bucket.lock().unwrap_or_else(|e| e.into_inner());
+        bucket.refill();
+
+        if bucket.tokens < 1.0 {
+            return false;
+        }
+        bucket.tokens -= 1.0;
+        true
+    }
+}
+
+impl Bucket {
+    fn refill(&mut self) {
+        let now = Instant::now();
+        let elapsed = now.duration_since(self.refilled_at).as_secs_f64();
+        self.tokens = (self.tokens + elapsed * self.refill_per_second).min(self.capacity);
+        self.refilled_at = now;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        api::{BalanceAPI, InvoiceAPI},
+        client::CryptoBot,
+        utils::test_utils::TestContext,
+    };
+
+    fn client(ctx: &TestContext, retry: RetryConfig) -> CryptoBot {
+        CryptoBot::builder()
+            .api_token("test_token")
+            .base_url(ctx.server.url())
+            .retry(RetryConfig {
+                base_delay: Duration::from_millis(1),
+                ..retry
+            })
+            .build()
+            .unwrap()
+    }
+
+    #[test]
+    fn test_retry_budget_refills_up_to_capacity() {
+        let budget = RetryBudget::new(2, 0.0);
+        let shared = budget.clone();
+
+        assert!(budget.try_acquire());
+        assert!(shared.try_acquire());
+        assert!(!budget.try_acquire());
+        assert_eq!(shared.available(), 0);
+
+        let budget = RetryBudget::new(1, 1000.0);
+        assert!(budget.try_acquire());
+        std::thread::sleep(Duration::from_millis(5));
+        assert_eq!(budget.available(), 1);
+    }
+
+    #[test]
+    fn test_retry_delay_backoff_and_jitter() {
+        let exact = RetryConfig {
+            base_delay: Duration::from_millis(100),
+            jitter: 0.0,
+            ..RetryConfig::default()
+        };
+        assert_eq!(exact.delay(0), Duration::from_millis(100));
+        assert_eq!(exact.delay(2), Duration::from_millis(400));
+
+        let jittered = RetryConfig { jitter: 0.5, ..exact };
+        for _ in 0..20 {
+            let delay = jittered.delay(1);
+            assert!((Duration::from_millis(100)..=Duration::from_millis(300)).contains(&delay));
+        }
+    }
+
+    #[test]
+    fn test_retries_only_configured_statuses() {
+        for (retry_on_status, expected_attempts) in [(RetryConfig::default().retry_on_status, 1), (vec![501], 3)] {
+            let mut ctx = TestContext::new();
+            let m = ctx
+                .server
+                .mock("GET", "/getBalance")
+                .with_status(501)
+                .expect(expected_attempts)
+                .create();
+            let client = client(
+                &ctx,
+                RetryConfig {
+                    retry_on_status,
+                    ..RetryConfig::default()
+                },
+            );
+
+            let result = ctx.run(client.get_balance().execute());
+
+            m.assert();
+            assert!(result.is_err());
+        }
+    }
+
+    #[test]
+    fn test_retries_get_requests_on_server_errors() {
+        let mut ctx = TestContext::new();
+        let m = ctx
+            .server
+            .mock("GET", "/getBalance")
+            .with_status(503)
+            .expect(3)
+            .create();
+        let client = client(&ctx, RetryConfig::default());
+
+        let result = ctx.run(client.get_balance().execute());
+
+        m.assert();
+        assert!(matches!(result, Err(CryptoBotError::HttpError(_))));
+    }
+
+    #[test]
+    fn test_does_not_retry_post_requests_on_server_errors() {
+        let mut ctx = TestContext::new();
+        let _rates = ctx.mock_exchange_rates_response();
+        let m = ctx
+            .server
+            .mock("POST", "/createInvoice")
+            .with_status(503)
+            .expect(1)
+            .create();
+        let client = client(&ctx, RetryConfig::default());
+
+        let result = ctx.run(
+            client
+                .create_invoice()
+                .asset(crate::models::CryptoCurrencyCode::Ton)
+                .amount(rust_decimal_macros::dec!(1))
+                .execute(),
+        );
+
+        m.assert();
+        assert!(result.is_err());
+    }
+
+    #[test]
+    fn test_retries_idempotent_non_get_requests_on_server_errors() {
+        let mut ctx = TestContext::new();
+        let m = ctx
+            .server
+            .mock("DELETE", "/deleteInvoice")
+            .with_status(503)
+            .expect(3)
+            .create();
+        let client = client(&ctx, RetryConfig::default());
+
+        let result = ctx.run(client.delete_invoice(1).execute());
+
+        m.assert();
+        assert!(result.is_err());
+    }
+
+    #[test]
+    fn test_execute_with_retry_overrides_client_config() {
+        let mut ctx = TestContext::new();
+        let m = ctx
+            .server
+            .mock("GET", "/getBalance")
+            .with_status(503)
+            .expect(2)
+            .create();
+        let client = CryptoBot::builder()
+            .api_token("test_token")
+            .base_url(ctx.server.url())
+            .build()
+            .unwrap();
+
+        let result = ctx.run(client.get_balance().execute_with_retry(RetryConfig {
+            max_retries: 1,
+            base_delay: Duration::from_millis(1),
+            ..RetryConfig::default()
+        }));
+
+        m.assert();
+        assert!(matches!(result, Err(CryptoBotError::HttpError(_))));
+    }
+
+    #[test]
+    fn test_shared_budget_bounds_retries() {
+        let mut ctx = TestContext::new();
+        let m = ctx
+            .server
+            .mock("GET", "/getBalance")
+            .with_status(503)
+            .expect(4)
+            .create();
+        let budget = RetryBudget::new(2, 0.0);
+        let client = client(
+            &ctx,
+            RetryConfig {
+                max_retries: 5,
+                budget: Some(budget.clone()),
+                ..RetryConfig::default()
+            },
+        );
+
+        // Two requests, but only two retries in the budget
+        ctx.run(async {
+            let _ = client.get_balance().execute().await;
+            let _ = client.get_balance().execute().await;
+        });
+
+        m.assert();
+        assert_eq!(budget.available(), 0);
+    }
+}
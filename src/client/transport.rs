@@ -0,0 +1,582 @@
+use std::{
+    collections::VecDeque,
+    fs::{File, OpenOptions},
+    io::{BufRead, BufReader, Write},
+    path::Path,
+    sync::{mpsc, Arc, Mutex},
+    thread::JoinHandle,
+};
+
+use async_trait::async_trait;
+use reqwest::{header::HeaderMap, StatusCode};
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    error::{CryptoBotError, CryptoBotResult},
+    models::{APIEndpoint, Method},
+};
+
+/// A request to the Crypto Pay API, ready to be sent by a [`Transport`].
+///
+/// The API token header is marked as sensitive, and the `Debug` output only shows the size of the body.
+#[derive(Clone)]
+pub struct TransportRequest {
+    pub endpoint: APIEndpoint,
+    pub method: Method,
+    pub url: String,
+    pub headers: HeaderMap,
+    /// JSON encoded parameters, if any.
+    pub body: Option<String>,
+}
+
+impl std::fmt::Debug for TransportRequest {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("TransportRequest")
+            .field("endpoint", &self.endpoint)
+            .field("method", &self.method)
+            .field("url", &self.url)
+            .field("headers", &self.headers)
+            .field("body_len", &self.body.as_ref().map(String::len))
+            .finish()
+    }
+}
+
+/// Sends API requests and returns the raw response body.
+///
+/// The client uses an HTTP transport by default. Set a custom one with `ClientBuilder::transport`,
+/// e.g. to replay a recorded session with [`ReplayTransport`].
+#[async_trait]
+pub trait Transport: Send + Sync {
+    async fn send(&self, request: TransportRequest) -> CryptoBotResult<String>;
+}
+
+#[derive(Clone)]
+pub(crate) struct TransportHandle(pub(crate) Arc<dyn Transport>);
+
+impl std::fmt::Debug for TransportHandle {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str("Transport")
+    }
+}
+
+/// Sends requests over HTTP with reqwest.
+pub(crate) struct HttpTransport {
+    client: reqwest::Client,
+    max_response_size: Option<usize>,
+}
+
+impl HttpTransport {
+    pub(crate) fn new(client: reqwest::Client, max_response_size: Option<usize>) -> Self {
+        Self {
+            client,
+            max_response_size,
+        }
+    }
+}
+
+pub(crate) fn response_too_large(endpoint: APIEndpoint, limit: usize) -> CryptoBotError {
+    CryptoBotError::ResponseTooLarge {
+        endpoint: endpoint.as_str().to_string(),
+        limit,
+    }
+}
+
+/// Returns the API error named in the body of a client error response, if it is an error envelope
+///
+/// The API rejects calls with a 4xx status, e.g. 403 for a disabled method, and names the error
+/// either as `"error": "NAME"` with an `error_code`, or as `"error": {"code": ..., "name": "NAME"}`.
+fn api_error(status: StatusCode, body: &str) -> Option<CryptoBotError> {
+    let envelope: serde_json::Value = serde_json::from_str(body).ok()?;
+    if envelope["ok"] != false {
+        return None;
+    }
+
+    let error = &envelope["error"];
+    let (code, message) = match error.as_str() {
+        Some(name) => (&envelope["error_code"], name),
+        None => (&error["code"], error["name"].as_str()?),
+    };
+
+    Some(CryptoBotError::ApiError {
+        code: code
+            .as_i64()
+            .and_then(|code| i32::try_from(code).ok())
+            .unwrap_or(i32::from(status.as_u16())),
+        message: message.to_string(),
+        details: None,
+    })
+}
+
+#[async_trait]
+impl Transport for HttpTransport {
+    async fn send(&self, request: TransportRequest) -> CryptoBotResult<String> {
+        let endpoint = request.endpoint;
+        let mut builder = match request.method {
+            Method::POST => self.client.post(&request.url),
+            Method::GET => self.client.get(&request.url),
+            Method::DELETE => self.client.delete(&request.url),
+        }
+        .headers(request.headers);
+
+        if let Some(body) = request.body {
+            builder = builder
+                .header(reqwest::header::CONTENT_TYPE, "application/json")
+                .body(body);
+        }
+
+        let mut response = builder.send().await?;
+
+        if !response.status().is_success() {
+            let status = response.status();
+            let error = response.error_for_status_ref().unwrap_err();
+            let small = match self.max_response_size {
+                Some(limit) => response.content_length().is_some_and(|length| length <= limit as u64),
+                None => true,
+            };
+            if status.is_client_error() && status != StatusCode::TOO_MANY_REQUESTS && small {
+                if let Some(api_error) = response.text().await.ok().and_then(|body| api_error(status, &body)) {
+                    return Err(api_error);
+                }
+            }
+            return Err(CryptoBotError::HttpError(error));
+        }
+
+        let Some(limit) = self.max_response_size else {
+            return Ok(response.text().await?);
+        };
+
+        // Stop reading as soon as the cap is exceeded, instead of buffering the whole body first
+        if response.content_length().is_some_and(|length| length > limit as u64) {
+            return Err(response_too_large(endpoint, limit));
+        }
+
+        let mut body = Vec::new();
+        while let Some(chunk) = response.chunk().await? {
+            if body.len() + chunk.len() > limit {
+                return Err(response_too_large(endpoint, limit));
+            }
+            body.extend_from_slice(&chunk);
+        }
+
+        Ok(String::from_utf8_lossy(&body).into_owned())
+    }
+}
+
+/// A request and its response, as stored in a journal file, one JSON object per line.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct JournalEntry {
+    pub endpoint: String,
+    pub method: String,
+    pub request: Option<serde_json::Value>,
+    /// Response body, empty if the request failed.
+    pub response: String,
+    /// Error the request failed with, if any.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+    /// Class of `error`, replayed as the same kind of error. `None` for other errors, e.g. network failures.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub error_class: Option<RecordedError>,
+}
+
+/// Class of the error a recorded request failed with, as stored in a [`JournalEntry`]
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum RecordedError {
+    /// Replayed as `CryptoBotError::ApiError`.
+    Api { code: i32, message: String },
+    /// Replayed as `CryptoBotError::HttpError` for a response with this status.
+    Http { status: u16 },
+}
+
+impl RecordedError {
+    fn of(error: &CryptoBotError) -> Option<Self> {
+        match error {
+            CryptoBotError::ApiError { code, message, .. } => Some(Self::Api {
+                code: *code,
+                message: message.clone(),
+            }),
+            CryptoBotError::HttpError(error) => error.status().map(|status| Self::Http {
+                status: status.as_u16(),
+            }),
+            _ => None,
+        }
+    }
+
+    fn into_error(self) -> Option<CryptoBotError> {
+        match self {
+            Self::Api { code, message } => Some(CryptoBotError::ApiError {
+                code,
+                message,
+                details: None,
+            }),
+            Self::Http { status } => {
+                let response = http::Response::builder().status(status).body(String::new()).ok()?;
+                reqwest::Response::from(response)
+                    .error_for_status()
+                    .err()
+                    .map(CryptoBotError::HttpError)
+            }
+        }
+    }
+}
+
+impl JournalEntry {
+    fn matches(&self, request: &TransportRequest) -> bool {
+        self.endpoint == request.endpoint.as_str()
+            && self.method == request.method.as_str()
+            && self.request == parse_body(request.body.as_deref())
+    }
+}
+
+fn parse_body(body: Option<&str>) -> Option<serde_json::Value> {
+    body.map(|body| serde_json::from_str(body).unwrap_or_else(|_| serde_json::Value::String(body.to_string())))
+}
+
+/// Appends every request and its response or error to a JSONL journal.
+///
+/// Entries are written by a dedicated thread, so requests never wait for the disk. Failing to write the
+/// journal does not fail the request, since the API call has already been made.
+pub(crate) struct RecordingTransport {
+    inner: Arc<dyn Transport>,
+    lines: Option<mpsc::Sender<String>>,
+    writer: Option<JoinHandle<()>>,
+}
+
+impl RecordingTransport {
+    pub(crate) fn open(inner: Arc<dyn Transport>, path: &Path) -> CryptoBotResult<Self> {
+        let file =
+            OpenOptions::new()
+                .create(true)
+                .append(true)
+                .open(path)
+                .map_err(|e| CryptoBotError::ConfigError {
+                    message: format!("Failed to open journal {}: {e}", path.display()),
+                })?;
+
+        let (lines, receiver) = mpsc::channel::<String>();
+        let writer = std::thread::spawn(move || {
+            let mut file = file;
+            for line in receiver {
+                let _ = writeln!(file, "{line}");
+            }
+        });
+
+        Ok(Self {
+            inner,
+            lines: Some(lines),
+            writer: Some(writer),
+        })
+    }
+}
+
+impl Drop for RecordingTransport {
+    // Waits for the pending entries to be written
+    fn drop(&mut self) {
+        drop(self.lines.take());
+        if let Some(writer) = self.writer.take() {
+            let _ = writer.join();
+        }
+    }
+}
+
+#[async_trait]
+impl Transport for RecordingTransport {
+    async fn send(&self, request: TransportRequest) -> CryptoBotResult<String> {
+        let endpoint = request.endpoint.as_str().to_string();
+        let method = request.method.as_str().to_string();
+        let body = parse_body(request.body.as_deref());
+
+        let result = self.inner.send(request).await;
+
+        let entry = JournalEntry {
+            endpoint,
+            method,
+            request: body,
+            response: result.as_ref().map(String::clone).unwrap_or_default(),
+            error: result.as_ref().err().map(ToString::to_string),
+            error_class: result.as_ref().err().and_then(RecordedError::of),
+        };
+        if let (Ok(line), Some(lines)) = (serde_json::to_string(&entry), &self.lines) {
+            let _ = lines.send(line);
+        }
+
+        result
+    }
+}
+
+/// Serves responses from a journal written with `ClientBuilder::record_mode`, without network access.
+///
+/// Each recorded response is served once, to the first request with the same endpoint, method and
+/// parameters. A request matching an entry recorded with an API error or an HTTP error status fails
+/// with the same `CryptoBotError::ApiError` or `CryptoBotError::HttpError`, so retries, the circuit
+/// breaker and failover behave as when it was recorded. Requests without a matching entry, or
+/// matching an entry recorded with another error, fail with `CryptoBotError::ConfigError`.
+///
+/// # Example
+/// ```no_run
+/// use crypto_pay_api::prelude::*;
+///
+/// # fn main() -> Result<(), CryptoBotError> {
+/// let client = CryptoBot::builder()
+///     .api_token("YOUR_API_TOKEN")
+///     .transport(ReplayTransport::from_file("session.jsonl")?)
+///     .build()?;
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Debug)]
+pub struct ReplayTransport {
+    entries: Mutex<VecDeque<JournalEntry>>,
+}
+
+impl ReplayTransport {
+    /// Creates a replay transport from journal entries
+    pub fn new(entries: impl IntoIterator<Item = JournalEntry>) -> Self {
+        Self {
+            entries: Mutex::new(entries.into_iter().collect()),
+        }
+    }
+
+    /// Loads a journal file written with `ClientBuilder::record_mode`
+    pub fn from_file(path: impl AsRef<Path>) -> CryptoBotResult<Self> {
+        let path = path.as_ref();
+        let error = |e: &dyn std::fmt::Display| CryptoBotError::ConfigError {
+            message: format!("Failed to read journal {}: {e}", path.display()),
+        };
+
+        let file = File::open(path).map_err(|e| error(&e))?;
+        let mut entries = Vec::new();
+        for line in BufReader::new(file).lines() {
+            let line = line.map_err(|e| error(&e))?;
+            if line.trim().is_empty() {
+                continue;
+            }
+            entries.push(serde_json::from_str(&line).map_err(|e| error(&e))?);
+        }
+
+        Ok(Self::new(entries))
+    }
+
+    /// Returns the number of recorded responses that were not served yet
+    pub fn remaining(&self) -> usize {
+        self.entries.lock().unwrap_or_else(|e| e.into_inner()).len()
+    }
+}
+
+#[async_trait]
+impl Transport for ReplayTransport {
+    async fn send(&self, request: TransportRequest) -> CryptoBotResult<String> {
+        let mut entries = self.entries.lock().unwrap_or_else(|e| e.into_inner());
+        let position = entries
+            .iter()
+            .position(|entry| entry.matches(&request))
+            .ok_or_else(|| CryptoBotError::ConfigError {
+                message: format!(
+                    "No recorded response for {} {}",
+                    request.method.as_str(),
+                    request.endpoint.as_str()
+                ),
+            })?;
+
+        match entries.remove(position) {
+            Some(JournalEntry {
+                endpoint,
+                method,
+                error: Some(error),
+                error_class,
+                ..
+            }) => Err(error_class
+                .and_then(RecordedError::into_error)
+                .unwrap_or_else(|| CryptoBotError::ConfigError {
+                    message: format!("Recorded {method} {endpoint} failed: {error}"),
+                })),
+            entry => Ok(entry.map(|entry| entry.response).unwrap_or_default()),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use super::*;
+    use crate::{
+        api::{BalanceAPI, CheckAPI, InvoiceAPI, MiscAPI},
+        client::CryptoBot,
+        utils::test_utils::TestContext,
+    };
+
+    fn journal_path(name: &str) -> std::path::PathBuf {
+        let path = std::env::temp_dir().join(format!("crypto-pay-api-{name}-{}.jsonl", std::process::id()));
+        let _ = std::fs::remove_file(&path);
+        path
+    }
+
+    #[test]
+    fn test_record_and_replay_session() {
+        let path = journal_path("record");
+        let mut ctx = TestContext::new();
+        let _m = ctx.mock_balance_response();
+
+        let client = CryptoBot::builder()
+            .api_token("test_token")
+            .base_url(ctx.server.url())
+            .record_mode(&path)
+            .build()
+            .unwrap();
+        let recorded = ctx.run(async { client.get_balance().execute().await }).unwrap();
+        drop(client);
+
+        let entries: Vec<JournalEntry> = std::fs::read_to_string(&path)
+            .unwrap()
+            .lines()
+            .map(|line| serde_json::from_str(line).unwrap())
+            .collect();
+        assert_eq!(entries.len(), 1);
+        assert_eq!(entries[0].endpoint, "getBalance");
+        assert_eq!(entries[0].method, "GET");
+
+        let replay = CryptoBot::builder()
+            .api_token("test_token")
+            .base_url("http://unreachable.invalid")
+            .transport(ReplayTransport::from_file(&path).unwrap())
+            .build()
+            .unwrap();
+        let replayed = ctx.run(async { replay.get_balance().execute().await }).unwrap();
+
+        assert_eq!(replayed.len(), recorded.len());
+        assert_eq!(replayed[0].available, recorded[0].available);
+
+        let exhausted = ctx.run(async { replay.get_balance().execute().await });
+        assert!(matches!(exhausted, Err(CryptoBotError::ConfigError { .. })));
+
+        let _ = std::fs::remove_file(&path);
+    }
+
+    #[test]
+    fn test_client_error_envelope_is_api_error() {
+        let mut ctx = TestContext::new();
+        let _me = ctx
+            .server
+            .mock("GET", "/getMe")
+            .with_status(401)
+            .with_body(r#"{"ok":false,"error":{"code":401,"name":"UNAUTHORIZED"}}"#)
+            .create();
+        let _checks = ctx
+            .server
+            .mock("GET", "/getChecks")
+            .with_status(403)
+            .with_body(r#"{"ok":false,"error":"METHOD_DISABLED"}"#)
+            .create();
+        let _balance = ctx
+            .server
+            .mock("GET", "/getBalance")
+            .with_status(429)
+            .with_body(r#"{"ok":false,"error":"TOO_MANY_REQUESTS"}"#)
+            .create();
+
+        let client = CryptoBot::builder()
+            .api_token("test_token")
+            .base_url(ctx.server.url())
+            .build()
+            .unwrap();
+
+        let me = ctx.run(async { client.get_me().execute().await });
+        assert!(matches!(me, Err(CryptoBotError::ApiError { code: 401, message, .. }) if message == "UNAUTHORIZED"));
+
+        let checks = ctx.run(async { client.get_checks().execute().await });
+        assert!(
+            matches!(checks, Err(CryptoBotError::ApiError { code: 403, message, .. }) if message == "METHOD_DISABLED")
+        );
+
+        let balance = ctx.run(async { client.get_balance().execute().await });
+        assert!(matches!(balance, Err(CryptoBotError::HttpError(_))));
+    }
+
+    #[test]
+    fn test_record_failed_request() {
+        let path = journal_path("record-error");
+        let mut ctx = TestContext::new();
+        let _m = ctx.server.mock("GET", "/getBalance").with_status(500).create();
+
+        let client = CryptoBot::builder()
+            .api_token("test_token")
+            .base_url(ctx.server.url())
+            .record_mode(&path)
+            .build()
+            .unwrap();
+        let result = ctx.run(async { client.get_balance().execute().await });
+        assert!(matches!(result, Err(CryptoBotError::HttpError(_))));
+        drop(client);
+
+        let entry: JournalEntry = serde_json::from_str(std::fs::read_to_string(&path).unwrap().trim()).unwrap();
+        assert_eq!(entry.endpoint, "getBalance");
+        assert!(entry.response.is_empty());
+        assert!(entry.error.unwrap().contains("500"));
+        assert_eq!(entry.error_class, Some(RecordedError::Http { status: 500 }));
+
+        let replay = CryptoBot::builder()
+            .api_token("test_token")
+            .transport(ReplayTransport::from_file(&path).unwrap())
+            .build()
+            .unwrap();
+        let replayed = ctx.run(async { replay.get_balance().execute().await });
+        assert!(
+            matches!(&replayed, Err(CryptoBotError::HttpError(error)) if error.status().map(|s| s.as_u16()) == Some(500)),
+            "{replayed:?}"
+        );
+
+        let _ = std::fs::remove_file(&path);
+    }
+
+    #[test]
+    fn test_replay_api_error() {
+        let transport = ReplayTransport::new([JournalEntry {
+            endpoint: "getBalance".to_string(),
+            method: "GET".to_string(),
+            request: None,
+            response: String::new(),
+            error: Some("API error: 403 - METHOD_DISABLED".to_string()),
+            error_class: Some(RecordedError::Api {
+                code: 403,
+                message: "METHOD_DISABLED".to_string(),
+            }),
+        }]);
+        let client = CryptoBot::builder()
+            .api_token("test_token")
+            .transport(transport)
+            .build()
+            .unwrap();
+
+        let ctx = TestContext::new();
+        let replayed = ctx.run(async { client.get_balance().execute().await });
+
+        assert!(
+            matches!(&replayed, Err(CryptoBotError::ApiError { code: 403, message, .. }) if message == "METHOD_DISABLED"),
+            "{replayed:?}"
+        );
+    }
+
+    #[test]
+    fn test_replay_matches_request_parameters() {
+        let entry = |id: u64, ok: bool| JournalEntry {
+            endpoint: "deleteInvoice".to_string(),
+            method: "DELETE".to_string(),
+            request: Some(json!({ "invoice_id": id })),
+            response: json!({ "ok": true, "result": ok }).to_string(),
+            error: None,
+            error_class: None,
+        };
+        let transport = ReplayTransport::new([entry(1, false), entry(2, true)]);
+        let client = CryptoBot::builder()
+            .api_token("test_token")
+            .transport(transport)
+            .build()
+            .unwrap();
+
+        let ctx = TestContext::new();
+        let deleted = ctx.run(async { client.delete_invoice(2).execute().await });
+
+        assert!(deleted.unwrap());
+    }
+}
@@ -0,0 +1,101 @@
+//! Default settings and environment variable names
+//!
+//! The defaults can be overridden at compile time by setting the following environment
+//! variables when building the crate, e.g. for forks pointing at an API gateway:
+//! * `CRYPTO_PAY_DEFAULT_API_URL` - overrides [`DEFAULT_API_URL`]
+//! * `CRYPTO_PAY_DEFAULT_TIMEOUT` - overrides [`DEFAULT_TIMEOUT`], in seconds
+//! * `CRYPTO_PAY_DEFAULT_WEBHOOK_EXPIRATION_TIME` - overrides [`DEFAULT_WEBHOOK_EXPIRATION_TIME`], in seconds
+//!
+//! At runtime every default can still be overridden with `ClientBuilder` or with the
+//! environment variables read by `CryptoBot::from_env`.
+
+/// Base URL of the Crypto Pay API.
+pub const DEFAULT_API_URL: &str = match option_env!("CRYPTO_PAY_DEFAULT_API_URL") {
+    Some(url) => url,
+    None => "https://pay.crypt.bot/api",
+};
+
+/// Request timeout in seconds.
+pub const DEFAULT_TIMEOUT: u64 = match option_env!("CRYPTO_PAY_DEFAULT_TIMEOUT") {
+    Some(secs) => parse_secs(secs),
+    None => 30,
+};
+
+/// Maximum age in seconds of a webhook request accepted by the webhook handler.
+pub const DEFAULT_WEBHOOK_EXPIRATION_TIME: u64 = match option_env!("CRYPTO_PAY_DEFAULT_WEBHOOK_EXPIRATION_TIME") {
+    Some(secs) => parse_secs(secs),
+    None => 600,
+};
+
+/// Minimum webhook expiration time in seconds accepted by `WebhookHandlerConfigBuilder`.
+pub const MIN_WEBHOOK_EXPIRATION_TIME: u64 = 10;
+
+/// Smallest webhook body size limit in bytes accepted by `WebhookHandlerConfigBuilder`, enough for one update.
+pub const MIN_WEBHOOK_BODY_SIZE: usize = 64 * 1024;
+
+/// Largest webhook body size limit in bytes accepted by `WebhookHandlerConfigBuilder`.
+pub const MAX_WEBHOOK_BODY_SIZE: usize = 16 * 1024 * 1024;
+
+/// How long a failing base URL is skipped before it is tried again, in seconds.
+pub const DEFAULT_FAILOVER_COOLDOWN: u64 = 30;
+
+/// How long fetched exchange rates and currencies are reused, in seconds.
+pub const DEFAULT_METADATA_CACHE_TTL: u64 = 60;
+
+/// How long exchange rates and currencies stored in the disk cache are reused across restarts, in seconds.
+#[cfg(feature = "disk-cache")]
+pub const DEFAULT_DISK_CACHE_TTL: u64 = 300;
+
+/// Delay between polls of `CryptoBot::poll_updates`, in seconds.
+#[cfg(feature = "tokio")]
+pub const DEFAULT_POLL_INTERVAL: u64 = 5;
+
+/// Environment variable holding the API token, read by `CryptoBot::from_env`.
+pub const ENV_API_TOKEN: &str = "CRYPTO_PAY_API_TOKEN";
+/// Environment variable holding the optional base URL, read by `CryptoBot::from_env`.
+pub const ENV_API_URL: &str = "CRYPTO_PAY_API_URL";
+/// Environment variable holding the optional request timeout in seconds, read by `CryptoBot::from_env`.
+pub const ENV_API_TIMEOUT: &str = "CRYPTO_PAY_API_TIMEOUT";
+/// Environment variable holding the optional webhook expiration time in seconds, read by `CryptoBot::from_env`.
+pub const ENV_WEBHOOK_EXPIRATION_TIME: &str = "CRYPTO_PAY_WEBHOOK_EXPIRATION_TIME";
+
+/// Parses a positive number of seconds at compile time, failing the build on invalid input.
+const fn parse_secs(value: &str) -> u64 {
+    let bytes = value.as_bytes();
+    assert!(!bytes.is_empty(), "default duration must not be empty");
+
+    let mut secs: u64 = 0;
+    let mut i = 0;
+    while i < bytes.len() {
+        let digit = bytes[i];
+        assert!(digit.is_ascii_digit(), "default duration must be a number of seconds");
+        secs = secs * 10 + (digit - b'0') as u64;
+        i += 1;
+    }
+
+    assert!(secs > 0, "default duration must be positive");
+    secs
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_secs() {
+        assert_eq!(parse_secs("1"), 1);
+        assert_eq!(parse_secs("600"), 600);
+    }
+
+    #[test]
+    #[should_panic]
+    fn test_parse_secs_rejects_invalid() {
+        parse_secs("10s");
+    }
+
+    #[test]
+    #[should_panic]
+    fn test_parse_secs_rejects_zero() {
+        parse_secs("0");
+    }
+}
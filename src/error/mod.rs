@@ -1,13 +1,21 @@
 use thiserror::Error;
 
+use rust_decimal::Decimal;
+
+use crate::models::{Capability, CryptoCurrencyCode};
+
 #[derive(Error, Debug)]
+#[non_exhaustive]
 pub enum CryptoBotError {
+    #[cfg(feature = "client")]
     #[error("Invalid header name: {0}")]
     InvalidHeaderName(#[from] reqwest::header::InvalidHeaderName),
 
+    #[cfg(feature = "client")]
     #[error("Invalid header value: {0}")]
     InvalidHeaderValue(#[from] reqwest::header::InvalidHeaderValue),
 
+    #[cfg(feature = "client")]
     #[error("HTTP client error: {0}")]
     HttpError(#[from] reqwest::Error),
 
@@ -21,6 +29,7 @@ pub enum CryptoBotError {
     #[error("Validation error: {kind} - {message}")]
     ValidationError {
         kind: ValidationErrorKind,
+        code: ValidationCode,
         message: String,
         field: Option<String>,
     },
@@ -30,9 +39,163 @@ pub enum CryptoBotError {
 
     #[error("No result returned from API")]
     NoResult,
+
+    #[error("Configuration error: {message}")]
+    ConfigError { message: String },
+
+    #[error("Missing capability: {capability} - {message}")]
+    MissingCapability { capability: Capability, message: String },
+
+    #[error("Circuit open for {endpoint}, retry after {retry_after:?}")]
+    CircuitOpen {
+        endpoint: String,
+        retry_after: std::time::Duration,
+    },
+
+    #[error("Response from {endpoint} exceeds {limit} bytes")]
+    ResponseTooLarge { endpoint: String, limit: usize },
+
+    #[error("Insufficient {asset} balance: {required} required, {available} available")]
+    InsufficientBalance {
+        asset: CryptoCurrencyCode,
+        /// Amount needed, including the reserve kept untouched.
+        required: Decimal,
+        available: Decimal,
+    },
+
+    #[error("Webhook handler error: {0}")]
+    HandlerError(Box<dyn std::error::Error + Send + Sync>),
+
+    #[error("Request to {endpoint} rejected by hook: {reason}")]
+    Rejected {
+        endpoint: String,
+        reason: Box<dyn std::error::Error + Send + Sync>,
+    },
+
+    #[cfg(feature = "sqlx")]
+    #[error("Database error: {0}")]
+    DatabaseError(#[from] sqlx::Error),
+}
+
+/// Phase of an HTTP request in which a network error occurred.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum NetworkPhase {
+    /// Establishing the connection, including DNS resolution and TLS.
+    Connect,
+    /// Sending the request.
+    Request,
+    /// Reading the response.
+    Read,
+}
+
+impl std::fmt::Display for NetworkPhase {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{self:?}")
+    }
+}
+
+impl CryptoBotError {
+    /// Returns the phase in which a network error occurred, or `None` for other errors
+    pub fn network_phase(&self) -> Option<NetworkPhase> {
+        match self {
+            #[cfg(feature = "client")]
+            Self::HttpError(error) if error.is_connect() => Some(NetworkPhase::Connect),
+            #[cfg(feature = "client")]
+            Self::HttpError(error) if error.is_timeout() || error.is_body() || error.is_decode() => {
+                Some(NetworkPhase::Read)
+            }
+            #[cfg(feature = "client")]
+            Self::HttpError(error) if error.is_request() => Some(NetworkPhase::Request),
+            _ => None,
+        }
+    }
+
+    /// Returns true if the error is a connect, read or total request timeout
+    pub fn is_timeout(&self) -> bool {
+        match self {
+            #[cfg(feature = "client")]
+            Self::HttpError(error) => error.is_timeout(),
+            _ => false,
+        }
+    }
+
+    /// Returns the code of a validation error, or `None` for other errors
+    pub fn validation_code(&self) -> Option<ValidationCode> {
+        match self {
+            Self::ValidationError { code, .. } => Some(*code),
+            _ => None,
+        }
+    }
+
+    /// Returns a short message explaining the error to an end user, e.g. in a Telegram chat
+    ///
+    /// The message never contains tokens, URLs, request details or error texts from the API or
+    /// application handlers. Errors the user cannot act on share a generic message, so log the
+    /// error itself for diagnosis.
+    ///
+    /// # Example
+    /// ```
+    /// use crypto_pay_api::prelude::*;
+    ///
+    /// let error = CryptoBotError::InsufficientBalance {
+    ///     asset: CryptoCurrencyCode::Ton,
+    ///     required: 10.into(),
+    ///     available: 2.into(),
+    /// };
+    /// assert_eq!(error.user_message(), "Not enough TON is available right now. Please try again later.");
+    /// ```
+    pub fn user_message(&self) -> String {
+        const UNAVAILABLE: &str = "Payments are temporarily unavailable. Please try again later.";
+        const FAILED: &str = "Something went wrong with the payment. Please try again later.";
+
+        let message = match self {
+            #[cfg(feature = "client")]
+            Self::HttpError(_) => UNAVAILABLE,
+            Self::CircuitOpen { .. } | Self::MissingCapability { .. } => UNAVAILABLE,
+            Self::ApiError { code, message, .. } => {
+                let message = message.to_uppercase();
+                if message.contains("NOT_ENOUGH") || message.contains("INSUFFICIENT") {
+                    "There are not enough funds to complete the payment."
+                } else if message.contains("EXPIRED") {
+                    "This payment has expired."
+                } else if *code == 429 || message.contains("FLOOD") {
+                    "Too many requests. Please wait a moment and try again."
+                } else if *code == 401 || *code == 403 || *code >= 500 {
+                    UNAVAILABLE
+                } else {
+                    FAILED
+                }
+            }
+            Self::ValidationError { code, .. } => match code {
+                ValidationCode::AmountFormat => "Please enter a valid amount.",
+                ValidationCode::AmountRange => "This amount is too small or too large.",
+                ValidationCode::AmountPrecision => "This amount has too many decimal places.",
+                ValidationCode::CommentLength => "This comment is too long.",
+                ValidationCode::TestnetAsset => "This currency is not available.",
+                ValidationCode::ExchangeRateMissing => UNAVAILABLE,
+                _ => FAILED,
+            },
+            Self::InsufficientBalance { asset, .. } => {
+                let asset = asset.to_string().to_uppercase();
+                return format!("Not enough {asset} is available right now. Please try again later.");
+            }
+            _ => FAILED,
+        };
+        message.to_string()
+    }
+
+    /// Returns the application error returned by a webhook handler, if it is of type `E`
+    pub fn handler_error<E: std::error::Error + 'static>(&self) -> Option<&E> {
+        match self {
+            Self::HandlerError(source) => source.downcast_ref(),
+            _ => None,
+        }
+    }
 }
 
 #[derive(Debug, PartialEq)]
+#[non_exhaustive]
 pub enum ValidationErrorKind {
     Format,
     Range,
@@ -41,9 +204,89 @@ pub enum ValidationErrorKind {
     Invalid,
 }
 
+/// Stable code identifying a validation failure.
+///
+/// Codes never change meaning and new codes are only appended, so they can be used as keys for
+/// localized messages instead of parsing the error text.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+#[non_exhaustive]
+pub enum ValidationCode {
+    /// The amount could not be parsed.
+    AmountFormat,
+    /// The amount is zero, negative or outside the allowed USD range.
+    AmountRange,
+    /// The amount has too many significant digits or decimal places.
+    AmountPrecision,
+    /// No exchange rate is known for the asset.
+    ExchangeRateMissing,
+    /// The count is outside the allowed range.
+    CountRange,
+    /// A field required by another field is missing.
+    MissingField,
+    /// The invoice description is too long.
+    DescriptionLength,
+    /// The invoice hidden message is too long.
+    HiddenMessageLength,
+    /// The invoice payload is too long.
+    PayloadLength,
+    /// The transfer comment is too long.
+    CommentLength,
+    /// The transfer spend ID is too long.
+    SpendIdLength,
+    /// A text field is too long.
+    FieldLength,
+    /// The invoice expiration time is outside the allowed range.
+    ExpiresInRange,
+    /// The URL is invalid.
+    InvalidUrl,
+    /// The date range is invalid.
+    DateRange,
+    /// The request parameters could not be serialized.
+    InvalidParams,
+    /// The asset is only available on testnet.
+    TestnetAsset,
+    /// A field is not available in combination with the other fields.
+    FieldConflict,
+}
+
+impl ValidationCode {
+    /// Returns the code as a string, e.g. `E_AMOUNT_RANGE`
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            ValidationCode::AmountFormat => "E_AMOUNT_FORMAT",
+            ValidationCode::AmountRange => "E_AMOUNT_RANGE",
+            ValidationCode::AmountPrecision => "E_AMOUNT_PRECISION",
+            ValidationCode::ExchangeRateMissing => "E_RATE_MISSING",
+            ValidationCode::CountRange => "E_COUNT_RANGE",
+            ValidationCode::MissingField => "E_MISSING_FIELD",
+            ValidationCode::DescriptionLength => "E_DESC_LEN",
+            ValidationCode::HiddenMessageLength => "E_HIDDEN_MSG_LEN",
+            ValidationCode::PayloadLength => "E_PAYLOAD_LEN",
+            ValidationCode::CommentLength => "E_COMMENT_LEN",
+            ValidationCode::SpendIdLength => "E_SPEND_ID_LEN",
+            ValidationCode::FieldLength => "E_FIELD_LEN",
+            ValidationCode::ExpiresInRange => "E_EXPIRES_IN_RANGE",
+            ValidationCode::InvalidUrl => "E_URL",
+            ValidationCode::DateRange => "E_DATE_RANGE",
+            ValidationCode::InvalidParams => "E_PARAMS",
+            ValidationCode::TestnetAsset => "E_TESTNET_ASSET",
+            ValidationCode::FieldConflict => "E_FIELD_CONFLICT",
+        }
+    }
+}
+
+impl std::fmt::Display for ValidationCode {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
 #[derive(Debug)]
+#[non_exhaustive]
 pub enum WebhookErrorKind {
     InvalidSignature,
+    /// The request carries no signature while the handler requires one.
+    MissingSignature,
     InvalidPayload,
     DeserializationError,
     Expired,
@@ -86,6 +329,7 @@ mod tests {
     fn test_webhook_error_kind_display() {
         let test_cases = vec![
             (WebhookErrorKind::InvalidSignature, "InvalidSignature"),
+            (WebhookErrorKind::MissingSignature, "MissingSignature"),
             (WebhookErrorKind::InvalidPayload, "InvalidPayload"),
             (WebhookErrorKind::DeserializationError, "DeserializationError"),
             (WebhookErrorKind::Expired, "Expired"),
@@ -100,11 +344,20 @@ mod tests {
     fn test_validation_error_formatting() {
         let error = CryptoBotError::ValidationError {
             kind: ValidationErrorKind::Range,
+            code: ValidationCode::AmountRange,
             message: "Value out of range".to_string(),
             field: Some("amount".to_string()),
         };
 
         assert_eq!(error.to_string(), "Validation error: Range - Value out of range");
+        assert_eq!(error.validation_code(), Some(ValidationCode::AmountRange));
+    }
+
+    #[test]
+    fn test_validation_code_display() {
+        assert_eq!(ValidationCode::AmountRange.to_string(), "E_AMOUNT_RANGE");
+        assert_eq!(ValidationCode::DescriptionLength.to_string(), "E_DESC_LEN");
+        assert!(ValidationCode::AmountFormat < ValidationCode::InvalidParams);
     }
 
     #[test]
@@ -116,4 +369,51 @@ mod tests {
 
         assert_eq!(error.to_string(), "Webhook error: InvalidSignature - Invalid signature");
     }
+
+    #[test]
+    fn test_config_error_formatting() {
+        let error = CryptoBotError::ConfigError {
+            message: "CRYPTO_PAY_API_TOKEN is not set".to_string(),
+        };
+
+        assert_eq!(
+            error.to_string(),
+            "Configuration error: CRYPTO_PAY_API_TOKEN is not set"
+        );
+    }
+
+    #[test]
+    fn test_user_message_hides_internals() {
+        let api_error = |code, message: &str| CryptoBotError::ApiError {
+            code,
+            message: message.to_string(),
+            details: None,
+        };
+
+        assert_eq!(
+            api_error(400, "NOT_ENOUGH_COINS").user_message(),
+            "There are not enough funds to complete the payment."
+        );
+        assert_eq!(
+            api_error(401, "UNAUTHORIZED token=1234:abcd").user_message(),
+            "Payments are temporarily unavailable. Please try again later."
+        );
+        assert_eq!(
+            CryptoBotError::ValidationError {
+                kind: ValidationErrorKind::Range,
+                code: ValidationCode::AmountRange,
+                message: "Amount must be between 1 and 25000 USD".to_string(),
+                field: Some("amount".to_string()),
+            }
+            .user_message(),
+            "This amount is too small or too large."
+        );
+        assert_eq!(
+            CryptoBotError::ConfigError {
+                message: "CRYPTO_PAY_API_TOKEN is not set".to_string(),
+            }
+            .user_message(),
+            "Something went wrong with the payment. Please try again later."
+        );
+    }
 }
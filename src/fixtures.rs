@@ -0,0 +1,225 @@
+//! Canonical sample payloads for tests
+//!
+//! Available with the `fixtures` feature. Every payload is a JSON object as returned by the
+//! Crypto Pay API, exposed both as a `&str` constant and as a helper returning the parsed model.
+//!
+//! # Example
+//! ```
+//! use crypto_pay_api::fixtures;
+//! use crypto_pay_api::prelude::*;
+//!
+//! let invoice = fixtures::paid_invoice();
+//! assert_eq!(invoice.status, InvoiceStatus::Paid);
+//!
+//! // Wrap a payload in an API response envelope for mock servers
+//! let body = fixtures::api_response(fixtures::TRANSFER);
+//! assert!(body.starts_with(r#"{"ok":true"#));
+//! ```
+
+use chrono::Utc;
+
+use crate::models::{Check, Invoice, Transfer, WebhookUpdate};
+
+/// A crypto invoice paid in TON.
+pub const PAID_INVOICE: &str = r#"{
+    "invoice_id": 528890,
+    "hash": "IVDoTcNBYEfk",
+    "currency_type": "crypto",
+    "asset": "TON",
+    "amount": "10.5",
+    "paid_asset": "TON",
+    "paid_amount": "10.5",
+    "fee_asset": "TON",
+    "fee_amount": "0.315",
+    "paid_usd_rate": "3.25",
+    "bot_invoice_url": "https://t.me/CryptoTestnetBot?start=IVDoTcNBYEfk",
+    "mini_app_invoice_url": "https://t.me/CryptoTestnetBot/app?startapp=invoice-IVDoTcNBYEfk",
+    "web_app_invoice_url": "https://testnet-app.send.tg/invoices/IVDoTcNBYEfk",
+    "description": "Test invoice",
+    "status": "paid",
+    "created_at": "2025-02-08T12:11:01.341Z",
+    "paid_at": "2025-02-08T12:13:45.120Z",
+    "paid_anonymously": false,
+    "allow_comments": true,
+    "allow_anonymous": true,
+    "payload": "order_42"
+}"#;
+
+/// A fiat invoice in USD, paid in USDT and swapped into TON.
+pub const FIAT_INVOICE_WITH_SWAP: &str = r#"{
+    "invoice_id": 528891,
+    "hash": "IVsWaPfIaT01",
+    "currency_type": "fiat",
+    "fiat": "USD",
+    "amount": "25",
+    "accept_asset": ["USDT", "TON"],
+    "paid_asset": "USDT",
+    "paid_amount": "25",
+    "paid_fiat_rate": "1",
+    "paid_usd_rate": "1",
+    "fee_asset": "USDT",
+    "fee_amount": "0.75",
+    "bot_invoice_url": "https://t.me/CryptoTestnetBot?start=IVsWaPfIaT01",
+    "mini_app_invoice_url": "https://t.me/CryptoTestnetBot/app?startapp=invoice-IVsWaPfIaT01",
+    "web_app_invoice_url": "https://testnet-app.send.tg/invoices/IVsWaPfIaT01",
+    "status": "paid",
+    "swap_to": "TON",
+    "is_swapped": "true",
+    "swapped_uid": "swap-0001",
+    "swapped_to": "TON",
+    "swapped_rate": "0.3076923",
+    "swapped_output": "7.4615384",
+    "swapped_usd_amount": "24.25",
+    "swapped_usd_rate": "3.25",
+    "created_at": "2025-02-08T12:11:01.341Z",
+    "paid_at": "2025-02-08T12:13:45.120Z",
+    "paid_anonymously": true,
+    "allow_comments": false,
+    "allow_anonymous": true
+}"#;
+
+/// An active check of 5 USDT.
+pub const CHECK: &str = r#"{
+    "check_id": 123,
+    "hash": "CHkHaSh00001",
+    "asset": "USDT",
+    "amount": "5",
+    "bot_check_url": "https://t.me/CryptoTestnetBot?start=CHkHaSh00001",
+    "status": "active",
+    "created_at": "2025-02-08T12:11:01.341Z",
+    "activated_at": "2025-02-08T12:20:00.000Z"
+}"#;
+
+/// A completed transfer of 50 USDT.
+pub const TRANSFER: &str = r#"{
+    "transfer_id": 1,
+    "spend_id": "unique_id_123",
+    "user_id": 123456789,
+    "asset": "USDT",
+    "amount": "50",
+    "status": "completed",
+    "completed_at": "2025-02-08T12:11:01.341Z",
+    "comment": "Payment for services"
+}"#;
+
+/// A webhook update for [`PAID_INVOICE`].
+///
+/// The `request_date` is fixed in the past, so a webhook handler with an expiration time
+/// rejects it. Use [`webhook_update_body`] to get a body dated now.
+pub const WEBHOOK_UPDATE: &str = r#"{
+    "update_id": 1,
+    "update_type": "invoice_paid",
+    "request_date": "2025-02-08T12:13:45.341Z",
+    "payload": {
+        "invoice_id": 528890,
+        "hash": "IVDoTcNBYEfk",
+        "currency_type": "crypto",
+        "asset": "TON",
+        "amount": "10.5",
+        "paid_asset": "TON",
+        "paid_amount": "10.5",
+        "fee_asset": "TON",
+        "fee_amount": "0.315",
+        "paid_usd_rate": "3.25",
+        "bot_invoice_url": "https://t.me/CryptoTestnetBot?start=IVDoTcNBYEfk",
+        "mini_app_invoice_url": "https://t.me/CryptoTestnetBot/app?startapp=invoice-IVDoTcNBYEfk",
+        "web_app_invoice_url": "https://testnet-app.send.tg/invoices/IVDoTcNBYEfk",
+        "description": "Test invoice",
+        "status": "paid",
+        "created_at": "2025-02-08T12:11:01.341Z",
+        "paid_at": "2025-02-08T12:13:45.120Z",
+        "paid_anonymously": false,
+        "allow_comments": true,
+        "allow_anonymous": true,
+        "payload": "order_42"
+    }
+}"#;
+
+fn parse<T: serde::de::DeserializeOwned>(json: &str) -> T {
+    serde_json::from_str(json).expect("fixture payloads are valid")
+}
+
+/// Returns [`PAID_INVOICE`] as an [`Invoice`]
+pub fn paid_invoice() -> Invoice {
+    parse(PAID_INVOICE)
+}
+
+/// Returns [`FIAT_INVOICE_WITH_SWAP`] as an [`Invoice`]
+pub fn fiat_invoice_with_swap() -> Invoice {
+    parse(FIAT_INVOICE_WITH_SWAP)
+}
+
+/// Returns [`CHECK`] as a [`Check`]
+pub fn check() -> Check {
+    parse(CHECK)
+}
+
+/// Returns [`TRANSFER`] as a [`Transfer`]
+pub fn transfer() -> Transfer {
+    parse(TRANSFER)
+}
+
+/// Returns [`WEBHOOK_UPDATE`] as a [`WebhookUpdate`]
+pub fn webhook_update() -> WebhookUpdate {
+    parse(WEBHOOK_UPDATE)
+}
+
+/// Returns [`WEBHOOK_UPDATE`] with the `request_date` set to now, ready for `WebhookHandler::handle_update`
+pub fn webhook_update_body() -> String {
+    let mut update: serde_json::Value = parse(WEBHOOK_UPDATE);
+    update["request_date"] = serde_json::Value::String(Utc::now().to_rfc3339());
+    update.to_string()
+}
+
+/// Wraps a payload in a successful API response envelope, e.g. `{"ok":true,"result":...}`
+pub fn api_response(result: &str) -> String {
+    let result: serde_json::Value = parse(result);
+    serde_json::json!({ "ok": true, "result": result }).to_string()
+}
+
+#[cfg(test)]
+mod tests {
+    use rust_decimal_macros::dec;
+
+    use super::*;
+    use crate::models::{CheckStatus, CurrencyType, InvoiceStatus, SwapToAssets, TransferStatus};
+
+    #[test]
+    fn test_invoice_fixtures() {
+        let invoice = paid_invoice();
+        assert_eq!(invoice.invoice_id, 528890);
+        assert_eq!(invoice.status, InvoiceStatus::Paid);
+        assert_eq!(invoice.paid_amount, Some(dec!(10.5)));
+
+        let invoice = fiat_invoice_with_swap();
+        assert_eq!(invoice.currency_type, CurrencyType::Fiat);
+        assert_eq!(invoice.swapped_to, Some(SwapToAssets::Ton));
+        assert_eq!(invoice.swapped_usd_amount, Some(dec!(24.25)));
+    }
+
+    #[test]
+    fn test_check_and_transfer_fixtures() {
+        assert_eq!(check().status, CheckStatus::Active);
+        assert_eq!(transfer().status, TransferStatus::Completed);
+        assert_eq!(transfer().amount, dec!(50));
+    }
+
+    #[test]
+    fn test_webhook_update_fixtures() {
+        let update = webhook_update();
+        assert_eq!(
+            update.invoice().map(|invoice| invoice.invoice_id),
+            Some(paid_invoice().invoice_id)
+        );
+
+        let body: serde_json::Value = serde_json::from_str(&webhook_update_body()).unwrap();
+        assert_ne!(body["request_date"], "2025-02-08T12:13:45.341Z");
+    }
+
+    #[test]
+    fn test_api_response() {
+        let body: serde_json::Value = serde_json::from_str(&api_response(CHECK)).unwrap();
+        assert_eq!(body["ok"], true);
+        assert_eq!(body["result"]["check_id"], 123);
+    }
+}
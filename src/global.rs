@@ -0,0 +1,92 @@
+//! Process-wide default client
+//!
+//! Available with the `global` feature, for small bots that would rather not pass the client
+//! through every function. Initialize it once at startup, then use it anywhere.
+//!
+//! # Example
+//! ```no_run
+//! use crypto_pay_api::prelude::*;
+//!
+//! # async fn example() -> Result<(), CryptoBotError> {
+//! crypto_pay_api::init_global_from_env()?;
+//!
+//! let balances = crypto_pay_api::global().get_balance().execute().await?;
+//! # Ok(())
+//! # }
+//! ```
+
+use std::sync::OnceLock;
+
+use crate::{
+    client::CryptoBot,
+    error::{CryptoBotError, CryptoBotResult},
+};
+
+static GLOBAL: OnceLock<CryptoBot> = OnceLock::new();
+
+/// Sets the global client
+///
+/// # Errors
+/// * `CryptoBotError::ConfigError` - If the global client is already initialized
+pub fn init_global(client: CryptoBot) -> CryptoBotResult<&'static CryptoBot> {
+    let mut client = Some(client);
+    let global = GLOBAL.get_or_init(|| client.take().expect("initializer runs at most once"));
+
+    match client {
+        None => Ok(global),
+        Some(_) => Err(CryptoBotError::ConfigError {
+            message: "the global client is already initialized".to_string(),
+        }),
+    }
+}
+
+/// Sets the global client from the environment variables read by `CryptoBot::from_env`
+///
+/// # Errors
+/// * `CryptoBotError::ConfigError` - If the environment is invalid or the global client is already initialized
+pub fn init_global_from_env() -> CryptoBotResult<&'static CryptoBot> {
+    init_global(CryptoBot::from_env()?)
+}
+
+/// Returns the global client
+///
+/// # Errors
+/// * `CryptoBotError::ConfigError` - If `init_global` has not been called yet
+pub fn try_global() -> CryptoBotResult<&'static CryptoBot> {
+    GLOBAL.get().ok_or_else(|| CryptoBotError::ConfigError {
+        message: "the global client is not initialized, call crypto_pay_api::init_global() first".to_string(),
+    })
+}
+
+/// Returns the global client
+///
+/// # Panics
+/// If `init_global` has not been called yet. Use `try_global` to handle that case.
+pub fn global() -> &'static CryptoBot {
+    match try_global() {
+        Ok(client) => client,
+        Err(error) => panic!("{error}"),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_global_client_lifecycle() {
+        assert!(
+            matches!(try_global(), Err(CryptoBotError::ConfigError { message }) if message.contains("init_global"))
+        );
+
+        let client = init_global(CryptoBot::test_client()).unwrap();
+        assert_eq!(client.api_token, "test_token");
+        assert!(std::ptr::eq(global(), client));
+
+        assert!(matches!(
+            init_global(CryptoBot::test_client()),
+            Err(CryptoBotError::ConfigError { .. })
+        ));
+        assert!(std::ptr::eq(try_global().unwrap(), client));
+    }
+}
@@ -24,7 +24,7 @@
 //! #[tokio::main]
 //! async fn main() -> Result<(), CryptoBotError> {
 //!     let client = CryptoBot::builder().api_token("YOUR_API_TOKEN").build()?;
-//!     let mut handler = client.webhook_handler().build();
+//!     let mut handler = client.webhook_handler().build()?;
 //!     handler.on_update(|update| async move {
 //!         println!("Invoice paid: {:?}", update.payload);
 //!         Ok(())
@@ -35,21 +35,118 @@
 //!
 //! For issues and contributions, please refer to the [GitHub repository](https://github.com/escwxyz/crypto-pay-api).
 
+#[cfg(feature = "client")]
 mod api;
+#[cfg(feature = "client")]
 mod client;
+pub mod config;
 mod error;
+#[cfg(feature = "global")]
+mod global;
 mod models;
+#[cfg(feature = "sqlx")]
+pub mod projection;
+#[cfg(feature = "teloxide")]
+pub mod teloxide;
 mod utils;
+// The request builders are the main users of the validation rules
+#[cfg_attr(not(feature = "client"), allow(dead_code))]
 mod validation;
+pub mod validators;
 mod webhook;
 
+#[cfg(any(test, feature = "fixtures"))]
+pub mod fixtures;
+
+#[cfg(feature = "global")]
+pub use global::{global, init_global, init_global_from_env, try_global};
+
+pub use utils::redact;
+
+/// actix-web integration of [`WebhookHandler`](crate::prelude::WebhookHandler)
+///
+/// Available with the `actix` feature. [`resource`](crate::actix::resource) mounts a handler in an actix-web
+/// `App`, reading the body and the `crypto-pay-api-signature` header of each POST request and passing them to
+/// `WebhookHandler::handle_request`, so the expiration and deduplication settings of the handler apply.
+/// Requests without a signature are always rejected, even if the handler was built without
+/// `require_signature`. Responses follow the same statuses as the axum integration: 200 with the JSON
+/// `WebhookResponse` once handled, 401 for a missing or invalid signature, 400 for a rejected
+/// update and 500 when the update handler failed, so Crypto Bot delivers the update again. A batch
+/// with failed updates is answered with 500 only when deduplication is enabled, otherwise with 200
+/// and the per-update `results`.
+///
+/// # Example
+/// ```no_run
+/// use actix_web::{web, App, HttpServer};
+/// use crypto_pay_api::{actix, prelude::*};
+///
+/// # async fn example(client: CryptoBot) -> Result<(), Box<dyn std::error::Error>> {
+/// let mut handler = client.webhook_handler().require_signature(true).build()?;
+/// handler.on_update(|update| async move {
+///     println!("Invoice paid: {:?}", update.payload);
+///     Ok(())
+/// });
+///
+/// // Shared by the workers of the server
+/// let handler = web::Data::new(handler);
+/// HttpServer::new(move || App::new().service(actix::resource("/webhook", handler.clone())))
+///     .bind(("127.0.0.1", 3000))?
+///     .run()
+///     .await?;
+/// # Ok(())
+/// # }
+/// ```
+#[cfg(feature = "actix")]
+pub mod actix {
+    pub use crate::webhook::actix::{handle_webhook, resource};
+}
+
+/// Local development helpers for webhooks
+///
+/// Available with the `dev` feature. [`LocalSender`](crate::dev::LocalSender) posts signed synthetic updates
+/// to a locally running webhook endpoint, so the full stack can be exercised without real payments.
+///
+/// # Example
+/// ```no_run
+/// use crypto_pay_api::dev::{LocalSender, Scenario};
+/// use std::time::Duration;
+///
+/// #[tokio::main]
+/// async fn main() -> Result<(), crypto_pay_api::prelude::CryptoBotError> {
+///     let outcomes = LocalSender::new("http://127.0.0.1:3000/webhook", "YOUR_API_TOKEN")
+///         .scenario(Scenario::Paid)
+///         .scenario(Scenario::Expired)
+///         .interval(Duration::from_secs(2))
+///         .run()
+///         .await?;
+///
+///     for outcome in outcomes {
+///         println!("{:?} -> {}", outcome.scenario, outcome.status);
+///     }
+///     Ok(())
+/// }
+/// ```
+#[cfg(feature = "dev")]
+pub mod dev {
+    pub use crate::webhook::dev::{sign_body, LocalSender, Scenario, SendOutcome};
+}
+
 pub mod prelude {
     // Third-party crates re-exports
     pub use crate::utils::types::*;
 
     // Local crates re-exports
+    #[cfg(feature = "client")]
     pub use crate::api::*;
-    pub use crate::client::CryptoBot;
+    #[cfg(feature = "client")]
+    pub use crate::client::{
+        AlertState, AppMetadata, BalanceAlert, BalanceMonitor, CircuitBreakerConfig, CircuitState, ClientConfig,
+        CryptoBot, EndpointStats, Environment, ErrorCounts, IdGenerator, IdKind, JournalEntry, LinkDecorator, Metadata,
+        RandomIdGenerator, RecordedError, ReplayTransport, ResponseSizeStats, SeededIdGenerator, StatsSnapshot,
+        ThresholdDirection, Transport, TransportRequest,
+    };
+    #[cfg(all(feature = "client", feature = "tokio"))]
+    pub use crate::client::{RateLimiter, RetryBudget, RetryConfig};
     pub use crate::error::*;
     pub use crate::models::*;
     pub use crate::webhook::*;
@@ -0,0 +1,406 @@
+use rust_decimal::Decimal;
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    error::{CryptoBotError, CryptoBotResult, ValidationCode, ValidationErrorKind},
+    utils::{serialize_decimal_to_string, types::IntoDecimal},
+    validation::validate_positive_amount,
+};
+
+use super::{CryptoCurrencyCode, FiatCurrencyCode};
+
+/// Maximum number of significant digits accepted for an amount.
+pub const MAX_AMOUNT_SIGNIFICANT_DIGITS: u32 = 19;
+
+/// Number of decimal places fiat amounts are rounded to by the conversion helpers.
+pub const FIAT_DECIMAL_PLACES: u32 = 2;
+
+/// How converted amounts are rounded to the precision of their currency
+///
+/// Used by the conversion helpers, e.g. `ExchangeRate::convert_rounded` and `Invoice::usd_value_rounded`,
+/// and set for `amount_fiat` with `ClientBuilder::rounding`. Defaults to `Ceiling`, so a price converted to
+/// crypto is never charged short.
+///
+/// # Example
+/// ```
+/// use crypto_pay_api::prelude::*;
+///
+/// assert_eq!(Rounding::Ceiling.round(dec!(1.231), 2), dec!(1.24));
+/// assert_eq!(Rounding::Bankers.round(dec!(1.225), 2), dec!(1.22));
+/// ```
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+#[non_exhaustive]
+pub enum Rounding {
+    /// Rounds midpoints to the nearest even digit, e.g. 1.225 to 1.22 and 1.235 to 1.24.
+    Bankers,
+    /// Rounds towards negative infinity.
+    Floor,
+    /// Rounds towards positive infinity.
+    #[default]
+    Ceiling,
+    /// Rounds midpoints away from zero, e.g. 1.225 to 1.23.
+    HalfUp,
+}
+
+impl Rounding {
+    /// Rounds `value` to `decimal_places`. Values with fewer decimal places are returned unchanged.
+    pub fn round(self, value: Decimal, decimal_places: u32) -> Decimal {
+        let strategy = match self {
+            Rounding::Bankers => rust_decimal::RoundingStrategy::MidpointNearestEven,
+            Rounding::Floor => rust_decimal::RoundingStrategy::ToNegativeInfinity,
+            Rounding::Ceiling => rust_decimal::RoundingStrategy::ToPositiveInfinity,
+            Rounding::HalfUp => rust_decimal::RoundingStrategy::MidpointAwayFromZero,
+        };
+        value.round_dp_with_strategy(decimal_places, strategy)
+    }
+}
+
+/// Total amount in one asset, e.g. of transfers, received payments or fees.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct AssetTotal {
+    /// Asset of the amounts.
+    pub asset: CryptoCurrencyCode,
+
+    /// Exact sum of the amounts.
+    pub total: Decimal,
+
+    /// Number of amounts summed.
+    pub count: usize,
+}
+
+impl AssetTotal {
+    /// Adds `amount` to the total of `asset` in `totals`, appending a total for a new asset
+    pub(crate) fn add_to(totals: &mut Vec<AssetTotal>, asset: &CryptoCurrencyCode, amount: Decimal) {
+        match totals.iter_mut().find(|total| total.asset == *asset) {
+            Some(total) => {
+                total.total += amount;
+                total.count += 1;
+            }
+            None => totals.push(AssetTotal {
+                asset: asset.clone(),
+                total: amount,
+                count: 1,
+            }),
+        }
+    }
+}
+
+/// Sums amounts per asset, in order of first appearance
+pub(crate) fn asset_totals<'a>(
+    amounts: impl IntoIterator<Item = (&'a CryptoCurrencyCode, Decimal)>,
+) -> Vec<AssetTotal> {
+    let mut totals = Vec::new();
+    for (asset, amount) in amounts {
+        AssetTotal::add_to(&mut totals, asset, amount);
+    }
+    totals
+}
+
+/// A strictly positive amount that is safe to send to the API.
+///
+/// The value is checked once at construction, so any `PositiveAmount` is guaranteed to be
+/// greater than zero and to have at most 19 significant digits. Use [`PositiveAmount::for_asset`]
+/// to additionally enforce the number of decimal places supported by an asset.
+///
+/// # Example
+/// ```
+/// use crypto_pay_api::prelude::*;
+///
+/// let amount = PositiveAmount::for_asset(dec!(10.5), &CryptoCurrencyCode::Ton).unwrap();
+/// assert_eq!(amount.value(), dec!(10.5));
+///
+/// assert!(PositiveAmount::new(-1).is_err());
+/// assert!(PositiveAmount::for_asset(dec!(0.0000001), &CryptoCurrencyCode::Usdt).is_err());
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize)]
+#[serde(transparent)]
+pub struct PositiveAmount(#[serde(serialize_with = "serialize_decimal_to_string")] Decimal);
+
+impl PositiveAmount {
+    /// Creates a new positive amount
+    ///
+    /// # Errors
+    /// * `ValidationErrorKind::Format` - If the value cannot be converted into a decimal
+    /// * `ValidationErrorKind::Range` - If the value is not greater than 0 or has too many significant digits
+    pub fn new(value: impl IntoDecimal) -> CryptoBotResult<Self> {
+        let value = value.try_into_decimal().map_err(|e| CryptoBotError::ValidationError {
+            kind: ValidationErrorKind::Format,
+            code: ValidationCode::AmountFormat,
+            message: e,
+            field: Some("amount".to_string()),
+        })?;
+
+        validate_positive_amount(value)?;
+
+        let normalized = value.normalize();
+
+        if significant_digits(&normalized) > MAX_AMOUNT_SIGNIFICANT_DIGITS {
+            return Err(CryptoBotError::ValidationError {
+                kind: ValidationErrorKind::Range,
+                code: ValidationCode::AmountPrecision,
+                message: format!("Amount must have at most {MAX_AMOUNT_SIGNIFICANT_DIGITS} significant digits"),
+                field: Some("amount".to_string()),
+            });
+        }
+
+        Ok(Self(normalized))
+    }
+
+    /// Creates a new positive amount and checks the scale supported by the asset
+    ///
+    /// # Errors
+    /// Same as [`PositiveAmount::new`], plus `ValidationErrorKind::Range` if the amount has more
+    /// decimal places than the asset supports.
+    pub fn for_asset(value: impl IntoDecimal, asset: &CryptoCurrencyCode) -> CryptoBotResult<Self> {
+        Self::with_max_scale(value, asset.max_scale(), &asset.to_string())
+    }
+
+    /// Creates a new positive amount of a fiat price, with at most `FIAT_DECIMAL_PLACES` decimal places
+    ///
+    /// # Errors
+    /// Same as [`PositiveAmount::new`], plus `ValidationErrorKind::Range` if the amount has more
+    /// than `FIAT_DECIMAL_PLACES` decimal places.
+    pub fn for_fiat(value: impl IntoDecimal, fiat: &FiatCurrencyCode) -> CryptoBotResult<Self> {
+        Self::with_max_scale(value, FIAT_DECIMAL_PLACES, &fiat.to_string())
+    }
+
+    fn with_max_scale(value: impl IntoDecimal, max_scale: u32, currency: &str) -> CryptoBotResult<Self> {
+        let amount = Self::new(value)?;
+
+        if amount.0.scale() > max_scale {
+            return Err(CryptoBotError::ValidationError {
+                kind: ValidationErrorKind::Range,
+                code: ValidationCode::AmountPrecision,
+                message: format!("Amount for {currency} must have at most {max_scale} decimal places"),
+                field: Some("amount".to_string()),
+            });
+        }
+
+        Ok(amount)
+    }
+
+    /// Returns the underlying decimal value
+    pub fn value(&self) -> Decimal {
+        self.0
+    }
+}
+
+/// Formats an amount the way the API expects it
+///
+/// The result uses plain notation without exponent and without trailing zeros, e.g. `10.5` for
+/// `10.500`, and `0` for negative zero.
+///
+/// # Example
+/// ```
+/// use crypto_pay_api::prelude::*;
+///
+/// assert_eq!(format_amount(&dec!(10.500)), "10.5");
+/// assert_eq!(format_amount(&dec!(1e-7)), "0.0000001");
+/// ```
+pub fn format_amount(value: &Decimal) -> String {
+    value.normalize().to_string()
+}
+
+/// Formats an amount like [`format_amount`], truncated to the decimal places supported by the asset
+///
+/// # Example
+/// ```
+/// use crypto_pay_api::prelude::*;
+///
+/// assert_eq!(format_amount_for_asset(&dec!(1.23456789), &CryptoCurrencyCode::Usdt), "1.234567");
+/// ```
+pub fn format_amount_for_asset(value: &Decimal, asset: &CryptoCurrencyCode) -> String {
+    format_amount(&value.round_dp_with_strategy(asset.max_scale(), rust_decimal::RoundingStrategy::ToZero))
+}
+
+fn significant_digits(value: &Decimal) -> u32 {
+    let mantissa = value.mantissa().unsigned_abs();
+    if mantissa == 0 {
+        0
+    } else {
+        mantissa.ilog10() + 1
+    }
+}
+
+impl TryFrom<Decimal> for PositiveAmount {
+    type Error = CryptoBotError;
+
+    fn try_from(value: Decimal) -> Result<Self, Self::Error> {
+        Self::new(value)
+    }
+}
+
+impl From<PositiveAmount> for Decimal {
+    fn from(amount: PositiveAmount) -> Self {
+        amount.0
+    }
+}
+
+impl IntoDecimal for PositiveAmount {
+    fn into_decimal(self) -> Decimal {
+        self.0
+    }
+
+    fn try_into_decimal(self) -> Result<Decimal, String> {
+        Ok(self.0)
+    }
+}
+
+impl std::fmt::Display for PositiveAmount {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.0)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rust_decimal_macros::dec;
+
+    #[test]
+    fn test_rounding_strategies_at_boundaries() {
+        let round = |strategy: Rounding, value| strategy.round(value, 2);
+
+        for (value, bankers, floor, ceiling, half_up) in [
+            (dec!(1.225), dec!(1.22), dec!(1.22), dec!(1.23), dec!(1.23)),
+            (dec!(1.235), dec!(1.24), dec!(1.23), dec!(1.24), dec!(1.24)),
+            (dec!(-1.225), dec!(-1.22), dec!(-1.23), dec!(-1.22), dec!(-1.23)),
+            (dec!(1.2250001), dec!(1.23), dec!(1.22), dec!(1.23), dec!(1.23)),
+            (dec!(1.23), dec!(1.23), dec!(1.23), dec!(1.23), dec!(1.23)),
+            (dec!(1.2), dec!(1.2), dec!(1.2), dec!(1.2), dec!(1.2)),
+        ] {
+            assert_eq!(round(Rounding::Bankers, value), bankers, "bankers {value}");
+            assert_eq!(round(Rounding::Floor, value), floor, "floor {value}");
+            assert_eq!(round(Rounding::Ceiling, value), ceiling, "ceiling {value}");
+            assert_eq!(round(Rounding::HalfUp, value), half_up, "half_up {value}");
+        }
+        assert_eq!(Rounding::default(), Rounding::Ceiling);
+        assert_eq!(Rounding::Ceiling.round(dec!(0.0000000001), 9), dec!(0.000000001));
+    }
+
+    #[test]
+    fn test_format_amount_is_canonical() {
+        assert_eq!(format_amount(&dec!(10.500)), "10.5");
+        assert_eq!(format_amount(&dec!(100)), "100");
+        assert_eq!(format_amount(&dec!(100.00)), "100");
+        assert_eq!(format_amount(&dec!(0.000)), "0");
+        assert_eq!(format_amount(&-dec!(0.0)), "0");
+        assert_eq!(
+            format_amount(&Decimal::from_scientific("1.5e-7").unwrap()),
+            "0.00000015"
+        );
+        assert_eq!(
+            format_amount(&Decimal::from_scientific("2.5e10").unwrap()),
+            "25000000000"
+        );
+    }
+
+    #[test]
+    fn test_format_amount_for_asset_truncates_to_scale() {
+        assert_eq!(
+            format_amount_for_asset(&dec!(1.2345678), &CryptoCurrencyCode::Usdt),
+            "1.234567"
+        );
+        assert_eq!(
+            format_amount_for_asset(&dec!(1.999999999), &CryptoCurrencyCode::Btc),
+            "1.99999999"
+        );
+        assert_eq!(format_amount_for_asset(&dec!(10.50), &CryptoCurrencyCode::Ton), "10.5");
+    }
+
+    #[test]
+    fn test_amount_serialization_round_trip() {
+        #[derive(serde::Deserialize)]
+        struct Parsed(#[serde(deserialize_with = "crate::utils::deserialize_decimal")] Decimal);
+
+        for value in [dec!(10.500), dec!(0.00000001), dec!(123456789.123456789), dec!(1)] {
+            let json = serde_json::to_string(&PositiveAmount::new(value).unwrap()).unwrap();
+            assert!(!json.contains('e') && !json.contains('E'));
+
+            let Parsed(parsed) = serde_json::from_str(&json).unwrap();
+            assert_eq!(parsed, value);
+            assert_eq!(
+                serde_json::to_string(&PositiveAmount::new(parsed).unwrap()).unwrap(),
+                json
+            );
+        }
+    }
+
+    #[test]
+    fn test_positive_amount_accepts_valid_values() {
+        assert_eq!(PositiveAmount::new(dec!(10.50)).unwrap().value(), dec!(10.5));
+        assert_eq!(PositiveAmount::new(5).unwrap().value(), dec!(5));
+        assert_eq!(PositiveAmount::new("0.001").unwrap().value(), dec!(0.001));
+        assert!(PositiveAmount::new(dec!(1234567890.123456789)).is_ok());
+    }
+
+    #[test]
+    fn test_positive_amount_rejects_non_positive() {
+        for value in [dec!(0), dec!(-0.5), dec!(-100)] {
+            assert!(matches!(
+                PositiveAmount::new(value),
+                Err(CryptoBotError::ValidationError {
+                    kind: ValidationErrorKind::Range,
+                    field: Some(field),
+                    ..
+                }) if field == "amount"
+            ));
+        }
+    }
+
+    #[test]
+    fn test_positive_amount_rejects_invalid_input() {
+        assert!(matches!(
+            PositiveAmount::new("abc"),
+            Err(CryptoBotError::ValidationError {
+                kind: ValidationErrorKind::Format,
+                ..
+            })
+        ));
+        assert!(PositiveAmount::new(f64::NAN).is_err());
+    }
+
+    #[test]
+    fn test_positive_amount_rejects_too_many_digits() {
+        assert!(PositiveAmount::new(dec!(12345678901.234567891)).is_err());
+        assert!(PositiveAmount::new(dec!(1234567890.123456789)).is_ok());
+    }
+
+    #[test]
+    fn test_positive_amount_for_asset_scale() {
+        assert!(PositiveAmount::for_asset(dec!(1.123456), &CryptoCurrencyCode::Usdt).is_ok());
+        assert!(PositiveAmount::for_asset(dec!(1.1234567), &CryptoCurrencyCode::Usdt).is_err());
+        assert!(PositiveAmount::for_asset(dec!(1.123456789), &CryptoCurrencyCode::Ton).is_ok());
+        // Trailing zeros do not count towards the scale
+        assert!(PositiveAmount::for_asset(dec!(1.1000000000), &CryptoCurrencyCode::Usdt).is_ok());
+    }
+
+    #[test]
+    fn test_positive_amount_for_fiat_scale() {
+        assert!(PositiveAmount::for_fiat(dec!(10.55), &FiatCurrencyCode::Usd).is_ok());
+        assert!(PositiveAmount::for_fiat(dec!(10.550), &FiatCurrencyCode::Usd).is_ok());
+        assert!(matches!(
+            PositiveAmount::for_fiat(dec!(10.555), &FiatCurrencyCode::Usd),
+            Err(CryptoBotError::ValidationError {
+                code: ValidationCode::AmountPrecision,
+                ..
+            })
+        ));
+    }
+
+    #[test]
+    fn test_positive_amount_serialization() {
+        let amount = PositiveAmount::new(dec!(125.50)).unwrap();
+        assert_eq!(serde_json::to_string(&amount).unwrap(), "\"125.5\"");
+    }
+
+    #[test]
+    fn test_positive_amount_conversions() {
+        let amount = PositiveAmount::try_from(dec!(3)).unwrap();
+        let value: Decimal = amount.into();
+        assert_eq!(value, dec!(3));
+        assert_eq!(amount.into_decimal(), dec!(3));
+        assert_eq!(amount.to_string(), "3");
+    }
+}
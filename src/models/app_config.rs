@@ -0,0 +1,196 @@
+use std::fmt::Display;
+
+use super::GetMeResponse;
+
+/// Expected configuration of the application, compared with the deployed one by `GetMeResponse::diff_config`
+///
+/// Only the fields that were set are compared.
+///
+/// # Example
+/// ```no_run
+/// use crypto_pay_api::prelude::*;
+///
+/// # async fn example(client: CryptoBot) -> Result<(), CryptoBotError> {
+/// let expected = ExpectedAppConfig::new()
+///     .name("My Shop")
+///     .payment_bot("CryptoBot")
+///     .webhook_url("https://example.com/webhook");
+///
+/// let mismatches = client.get_me().execute().await?.diff_config(&expected);
+/// for mismatch in &mismatches {
+///     eprintln!("{mismatch}");
+/// }
+/// assert!(mismatches.is_empty(), "the deployed app is misconfigured");
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct ExpectedAppConfig {
+    app_id: Option<i64>,
+    name: Option<String>,
+    payment_bot: Option<String>,
+    /// `Some(None)` expects webhooks to be disabled
+    webhook_url: Option<Option<String>>,
+}
+
+impl ExpectedAppConfig {
+    /// Creates an expectation without fields, matched by any configuration
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Expects the application ID
+    pub fn app_id(mut self, app_id: i64) -> Self {
+        self.app_id = Some(app_id);
+        self
+    }
+
+    /// Expects the application name
+    pub fn name(mut self, name: impl Into<String>) -> Self {
+        self.name = Some(name.into());
+        self
+    }
+
+    /// Expects the username of the payment processing bot, e.g. `CryptoTestnetBot` for a testnet app
+    pub fn payment_bot(mut self, username: impl Into<String>) -> Self {
+        self.payment_bot = Some(username.into());
+        self
+    }
+
+    /// Expects webhooks to be enabled with the endpoint `url`. URLs are compared after normalization.
+    pub fn webhook_url(mut self, url: impl Into<String>) -> Self {
+        self.webhook_url = Some(Some(url.into()));
+        self
+    }
+
+    /// Expects webhooks to be disabled
+    pub fn no_webhook(mut self) -> Self {
+        self.webhook_url = Some(None);
+        self
+    }
+}
+
+/// A field of the deployed application that differs from its [`ExpectedAppConfig`]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ConfigMismatch {
+    /// Name of the `getMe` field, e.g. `webhook_endpoint`.
+    pub field: &'static str,
+    /// Expected value, `None` if the field was expected to be unset.
+    pub expected: Option<String>,
+    /// Deployed value, `None` if the field is unset.
+    pub actual: Option<String>,
+}
+
+impl Display for ConfigMismatch {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let value = |value: &Option<String>| value.clone().unwrap_or_else(|| "unset".to_string());
+        write!(
+            f,
+            "{}: expected {}, found {}",
+            self.field,
+            value(&self.expected),
+            value(&self.actual)
+        )
+    }
+}
+
+impl GetMeResponse {
+    /// Compares the deployed application with `expected`, returning the fields that differ
+    ///
+    /// An empty list means the configuration matches, e.g. in a smoke check after a deployment.
+    /// Webhook endpoints are compared as parsed URLs, so case in the host and default ports do not count.
+    pub fn diff_config(&self, expected: &ExpectedAppConfig) -> Vec<ConfigMismatch> {
+        let mut mismatches = Vec::new();
+        let mut compare = |field, expected: Option<String>, actual: Option<String>, equal: bool| {
+            if !equal {
+                mismatches.push(ConfigMismatch {
+                    field,
+                    expected,
+                    actual,
+                });
+            }
+        };
+
+        if let Some(app_id) = expected.app_id {
+            compare(
+                "app_id",
+                Some(app_id.to_string()),
+                Some(self.app_id.to_string()),
+                app_id == self.app_id,
+            );
+        }
+        if let Some(name) = &expected.name {
+            compare("name", Some(name.clone()), Some(self.name.clone()), *name == self.name);
+        }
+        if let Some(username) = &expected.payment_bot {
+            compare(
+                "payment_processing_bot_username",
+                Some(username.clone()),
+                Some(self.payment_processing_bot_username.clone()),
+                username.eq_ignore_ascii_case(&self.payment_processing_bot_username),
+            );
+        }
+        if let Some(url) = &expected.webhook_url {
+            let equal = match (url, &self.webhook_endpoint) {
+                (Some(expected), Some(actual)) => match (url::Url::parse(expected), self.webhook_url()) {
+                    (Ok(expected), Some(actual)) => expected == actual,
+                    _ => expected == actual,
+                },
+                (expected, actual) => expected == actual,
+            };
+            compare("webhook_endpoint", url.clone(), self.webhook_endpoint.clone(), equal);
+        }
+
+        mismatches
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn me(webhook_endpoint: &str) -> GetMeResponse {
+        serde_json::from_value(serde_json::json!({
+            "app_id": 28692,
+            "name": "Stated Seaslug App",
+            "payment_processing_bot_username": "CryptoTestnetBot",
+            "webhook_endpoint": webhook_endpoint,
+            "permissions": ["transfers"]
+        }))
+        .unwrap()
+    }
+
+    #[test]
+    fn test_diff_config() {
+        let deployed = me("https://Example.com:443/webhook");
+        assert_eq!(deployed.extra["permissions"], serde_json::json!(["transfers"]));
+
+        let matching = ExpectedAppConfig::new()
+            .app_id(28692)
+            .name("Stated Seaslug App")
+            .payment_bot("cryptotestnetbot")
+            .webhook_url("https://example.com/webhook");
+        assert!(deployed.diff_config(&matching).is_empty());
+        assert!(deployed.diff_config(&ExpectedAppConfig::new()).is_empty());
+
+        let mismatches = deployed.diff_config(&matching.clone().name("Shop").no_webhook());
+        assert_eq!(
+            mismatches.iter().map(ToString::to_string).collect::<Vec<_>>(),
+            [
+                "name: expected Shop, found Stated Seaslug App",
+                "webhook_endpoint: expected unset, found https://Example.com:443/webhook",
+            ]
+        );
+
+        let disabled = me("");
+        assert_eq!(disabled.webhook_endpoint, None);
+        assert_eq!(
+            disabled.diff_config(&matching),
+            [ConfigMismatch {
+                field: "webhook_endpoint",
+                expected: Some("https://example.com/webhook".to_string()),
+                actual: None,
+            }]
+        );
+    }
+}
@@ -1,10 +1,11 @@
 use crate::utils::deserialize_decimal;
 
-use super::CryptoCurrencyCode;
+use super::{format_amount, CryptoCurrencyCode};
 use rust_decimal::Decimal;
 use serde::Deserialize;
 
 #[derive(Debug, Deserialize)]
+#[non_exhaustive]
 pub struct Balance {
     /// Cryptocurrency alphabetic code.
     /// Currently, can be “USDT”, “TON”, “BTC”, “ETH”, “LTC”, “BNB”, “TRX”, "SEND" and “USDC” (and “JET” for testnet).
@@ -18,3 +19,93 @@ pub struct Balance {
     #[serde(deserialize_with = "deserialize_decimal")]
     pub onhold: Decimal,
 }
+
+impl Balance {
+    /// Creates a balance of `currency_code`, e.g. to test code working on `get_balance` results.
+    pub fn new(currency_code: CryptoCurrencyCode, available: Decimal, onhold: Decimal) -> Self {
+        Self {
+            currency_code,
+            available,
+            onhold,
+        }
+    }
+
+    /// Returns the available and on hold amounts combined
+    pub fn total(&self) -> Decimal {
+        self.available + self.onhold
+    }
+}
+
+/// Lookup helpers for a list of balances, as returned by `get_balance`.
+///
+/// # Example
+/// ```no_run
+/// # use crypto_pay_api::prelude::*;
+/// # async fn example(client: CryptoBot) -> Result<(), CryptoBotError> {
+/// let balances = client.get_balance().execute().await?;
+/// let ton = balances.find(&CryptoCurrencyCode::Ton).map_or(Decimal::ZERO, |balance| balance.available);
+/// # Ok(())
+/// # }
+/// ```
+pub trait Balances {
+    /// Returns the balance of the given asset, if the app holds it.
+    fn find(&self, asset: &CryptoCurrencyCode) -> Option<&Balance>;
+}
+
+impl Balances for [Balance] {
+    fn find(&self, asset: &CryptoCurrencyCode) -> Option<&Balance> {
+        self.iter().find(|balance| balance.currency_code == *asset)
+    }
+}
+
+/// Single-line summary, e.g. `TON 100.5 available, 0 on hold`.
+impl std::fmt::Display for Balance {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "{} {} available, {} on hold",
+            self.currency_code.to_string().to_uppercase(),
+            format_amount(&self.available),
+            format_amount(&self.onhold)
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use rust_decimal_macros::dec;
+
+    use super::*;
+
+    #[test]
+    fn test_balance_new() {
+        let balance = Balance::new(CryptoCurrencyCode::Ton, dec!(10), dec!(2.5));
+        assert_eq!(balance.total(), dec!(12.5));
+    }
+
+    #[test]
+    fn test_balance_display() {
+        let balance = Balance {
+            currency_code: CryptoCurrencyCode::Ton,
+            available: dec!(100.50),
+            onhold: dec!(0.0),
+        };
+
+        assert_eq!(balance.to_string(), "TON 100.5 available, 0 on hold");
+    }
+
+    #[test]
+    fn test_balances_find_and_total() {
+        let balances: Vec<Balance> = serde_json::from_str(
+            r#"[
+                {"currency_code": "TON", "available": "100.5", "onhold": "0.25"},
+                {"currency_code": "USDT", "available": 3, "onhold": 0.5}
+            ]"#,
+        )
+        .unwrap();
+
+        assert_eq!(balances.find(&CryptoCurrencyCode::Ton).unwrap().total(), dec!(100.75));
+        assert_eq!(balances.find(&CryptoCurrencyCode::Usdt).unwrap().onhold, dec!(0.5));
+        assert!(balances.find(&CryptoCurrencyCode::Btc).is_none());
+    }
+}
@@ -0,0 +1,21 @@
+use std::fmt::Display;
+
+/// A feature of the Crypto Pay API that has to be enabled for the application.
+///
+/// Transfers and checks must be enabled in the app settings of [@CryptoBot](https://t.me/CryptoBot)
+/// before they can be used.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Capability {
+    /// Creating and listing invoices.
+    Invoices,
+    /// Sending coins to users with `transfer`.
+    Transfers,
+    /// Creating and listing checks.
+    Checks,
+}
+
+impl Display for Capability {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{self:?}")
+    }
+}
@@ -1,5 +1,7 @@
 mod params;
 
+use std::collections::BTreeMap;
+
 use chrono::{DateTime, Utc};
 pub use params::*;
 use rust_decimal::Decimal;
@@ -7,9 +9,11 @@ use serde::{Deserialize, Serialize};
 
 use crate::utils::deserialize_decimal;
 
-use super::CryptoCurrencyCode;
+use super::{format_amount, CryptoCurrencyCode};
 
-#[derive(Debug, Deserialize)]
+#[derive(Debug, Clone, Deserialize)]
+#[cfg_attr(feature = "raw-json", serde(remote = "Self"))]
+#[non_exhaustive]
 pub struct Check {
     /// Unique ID for this check.
     pub check_id: u64,
@@ -35,11 +39,63 @@ pub struct Check {
 
     /// Date the check was activated in ISO 8601 format.
     pub activated_at: DateTime<Utc>,
+
+    /// Local description set with `CreateCheckBuilder::description`.
+    /// Not part of the API, so only filled in on the check returned by `create_check`.
+    #[serde(skip)]
+    pub description: Option<String>,
+
+    /// Local metadata set with `CreateCheckBuilder::metadata`.
+    /// Not part of the API, so only filled in on the check returned by `create_check`.
+    #[serde(skip)]
+    pub metadata: BTreeMap<String, String>,
+
+    /// JSON the check was deserialized from, including fields unknown to this version of the library.
+    /// Available with the `raw-json` feature.
+    #[cfg(feature = "raw-json")]
+    #[serde(skip)]
+    pub raw: Option<std::sync::Arc<serde_json::Value>>,
 }
 
-#[derive(Debug, Deserialize, Serialize, PartialEq)]
+deserialize_with_raw!(Check);
+
+#[derive(Debug, Clone, Deserialize, Serialize, PartialEq)]
 #[serde(rename_all = "lowercase")]
+#[non_exhaustive]
 pub enum CheckStatus {
     Active,
     Activated,
 }
+
+impl std::fmt::Display for CheckStatus {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(match self {
+            CheckStatus::Active => "active",
+            CheckStatus::Activated => "activated",
+        })
+    }
+}
+
+/// Single-line summary, e.g. `Check #1 10.5 TON active`.
+impl std::fmt::Display for Check {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "Check #{} {} {} {}",
+            self.check_id,
+            format_amount(&self.amount),
+            self.asset.to_string().to_uppercase(),
+            self.status
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::fixtures;
+
+    #[test]
+    fn test_check_display() {
+        assert_eq!(fixtures::check().to_string(), "Check #123 5 USDT active");
+    }
+}
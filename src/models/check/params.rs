@@ -1,21 +1,20 @@
-use rust_decimal::Decimal;
 use serde::Serialize;
 
 use crate::{
-    models::CryptoCurrencyCode,
-    utils::{serialize_comma_separated_list, serialize_decimal_to_string},
+    models::{CryptoCurrencyCode, PositiveAmount},
+    utils::{is_none_or_empty, redact_option, serialize_comma_separated_list},
 };
 
 use super::CheckStatus;
 
-#[derive(Debug, Serialize)]
+/// Parameters of `createCheck`. The `Debug` output redacts the username the check is pinned to.
+#[derive(Serialize)]
 pub struct CreateCheckParams {
     /// Cryptocurrency alphabetic code. Supported assets: “USDT”, “TON”, “BTC”, “ETH”, “LTC”, “BNB”, “TRX” and “USDC” (and “JET” for testnet).
     pub(crate) asset: CryptoCurrencyCode,
 
     /// Amount of the check in float. For example: 125.50
-    #[serde(serialize_with = "serialize_decimal_to_string")]
-    pub(crate) amount: Decimal,
+    pub(crate) amount: PositiveAmount,
 
     /// Optional. ID of the user who will be able to activate the check.
     #[serde(skip_serializing_if = "Option::is_none")]
@@ -26,7 +25,18 @@ pub struct CreateCheckParams {
     pub(crate) pin_to_username: Option<String>,
 }
 
-#[derive(Debug, Default, Serialize)]
+impl std::fmt::Debug for CreateCheckParams {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("CreateCheckParams")
+            .field("asset", &self.asset)
+            .field("amount", &self.amount)
+            .field("pin_to_user_id", &self.pin_to_user_id)
+            .field("pin_to_username", &redact_option(&self.pin_to_username))
+            .finish()
+    }
+}
+
+#[derive(Debug, Clone, Default, Serialize)]
 pub struct GetChecksParams {
     /// Optional. Cryptocurrency alphabetic code. Supported assets: “USDT”, “TON”, “BTC”, “ETH”, “LTC”, “BNB”, “TRX” and “USDC” (and “JET” for testnet).
     /// Defaults to all currencies.
@@ -36,7 +46,7 @@ pub struct GetChecksParams {
     /// Optional. List of check IDs separated by comma.
     #[serde(
         serialize_with = "serialize_comma_separated_list",
-        skip_serializing_if = "GetChecksParams::should_skip_check_ids"
+        skip_serializing_if = "is_none_or_empty"
     )]
     pub(crate) check_ids: Option<Vec<u64>>,
 
@@ -56,12 +66,6 @@ pub struct GetChecksParams {
     pub(crate) count: Option<u16>,
 }
 
-impl GetChecksParams {
-    fn should_skip_check_ids(check_ids: &Option<Vec<u64>>) -> bool {
-        !matches!(check_ids, Some(check_ids) if !check_ids.is_empty())
-    }
-}
-
 #[derive(Debug, Serialize)]
 pub struct DeleteCheckParams {
     pub check_id: u64,
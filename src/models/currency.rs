@@ -1,9 +1,11 @@
 use std::fmt::Display;
 
-use crate::utils::deserialize_currency_code;
+use crate::utils::{deserialize_currency_code, deserialize_optional_string};
+use rust_decimal::Decimal;
 use serde::{Deserialize, Serialize};
 
-#[derive(Debug, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[non_exhaustive]
 pub struct Currency {
     pub is_blockchain: bool,
     pub is_stablecoin: bool,
@@ -12,10 +14,21 @@ pub struct Currency {
     #[serde(deserialize_with = "deserialize_currency_code")]
     pub code: CurrencyCode,
     #[serde(skip_serializing_if = "Option::is_none")]
+    #[serde(default, deserialize_with = "deserialize_optional_string")]
     pub url: Option<String>,
     pub decimals: u8,
 }
 
+impl Currency {
+    /// Returns the blockchain of a crypto currency, see `CryptoCurrencyCode::native_chain`
+    pub fn native_chain(&self) -> Option<Chain> {
+        match &self.code {
+            CurrencyCode::Crypto(code) => code.native_chain(),
+            CurrencyCode::Fiat(_) => None,
+        }
+    }
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
 #[serde(untagged)]
 pub enum CurrencyCode {
@@ -33,7 +46,7 @@ impl Display for CurrencyCode {
     }
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq, Hash)]
 #[serde(rename_all = "UPPERCASE")]
 pub enum CryptoCurrencyCode {
     Usdt,
@@ -51,6 +64,70 @@ pub enum CryptoCurrencyCode {
     Unknown,
 }
 
+/// Blockchain an asset is native to
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[non_exhaustive]
+pub enum Chain {
+    Ton,
+    Bitcoin,
+    Ethereum,
+    Litecoin,
+    BnbSmartChain,
+    Tron,
+    Dogecoin,
+}
+
+impl CryptoCurrencyCode {
+    /// Returns true for assets that only exist on testnet, currently JET
+    pub fn is_testnet_only(&self) -> bool {
+        matches!(self, CryptoCurrencyCode::Jet)
+    }
+
+    /// Returns true for assets pegged to the US dollar, currently USDT and USDC
+    pub fn is_stablecoin(&self) -> bool {
+        matches!(self, CryptoCurrencyCode::Usdt | CryptoCurrencyCode::Usdc)
+    }
+
+    /// Returns the blockchain the asset is native to
+    ///
+    /// Returns `None` for stablecoins, which are issued on several chains, and for unknown assets.
+    /// SEND and JET are jettons on TON.
+    pub fn native_chain(&self) -> Option<Chain> {
+        match self {
+            CryptoCurrencyCode::Ton | CryptoCurrencyCode::Send | CryptoCurrencyCode::Jet => Some(Chain::Ton),
+            CryptoCurrencyCode::Btc => Some(Chain::Bitcoin),
+            CryptoCurrencyCode::Eth => Some(Chain::Ethereum),
+            CryptoCurrencyCode::Ltc => Some(Chain::Litecoin),
+            CryptoCurrencyCode::Bnb => Some(Chain::BnbSmartChain),
+            CryptoCurrencyCode::Trx => Some(Chain::Tron),
+            CryptoCurrencyCode::Doge => Some(Chain::Dogecoin),
+            CryptoCurrencyCode::Usdt | CryptoCurrencyCode::Usdc | CryptoCurrencyCode::Unknown => None,
+        }
+    }
+
+    /// Returns the number of decimal places amounts in this asset are expressed with, or `None` for unknown assets
+    ///
+    /// A hint for formatting amounts without a request. `Currency::decimals` from `getCurrencies`
+    /// is authoritative when available.
+    pub fn decimals_hint(&self) -> Option<u32> {
+        match self {
+            CryptoCurrencyCode::Unknown => None,
+            asset => Some(asset.max_scale()),
+        }
+    }
+
+    /// Maximum number of decimal places accepted for amounts in this asset.
+    pub(crate) fn max_scale(&self) -> u32 {
+        match self {
+            CryptoCurrencyCode::Usdt | CryptoCurrencyCode::Usdc | CryptoCurrencyCode::Trx => 6,
+            CryptoCurrencyCode::Btc | CryptoCurrencyCode::Ltc | CryptoCurrencyCode::Doge => 8,
+            CryptoCurrencyCode::Ton | CryptoCurrencyCode::Send | CryptoCurrencyCode::Jet => 9,
+            CryptoCurrencyCode::Eth | CryptoCurrencyCode::Bnb => 18,
+            CryptoCurrencyCode::Unknown => Decimal::MAX_SCALE,
+        }
+    }
+}
+
 #[cfg(not(tarpaulin))]
 impl Display for CryptoCurrencyCode {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
@@ -95,7 +172,7 @@ impl Display for FiatCurrencyCode {
     }
 }
 
-#[derive(Debug, Deserialize, Serialize, PartialEq, Eq, Clone)]
+#[derive(Debug, Deserialize, Serialize, PartialEq, Eq, Clone, Copy)]
 #[serde(rename_all = "lowercase")]
 pub enum CurrencyType {
     Crypto,
@@ -125,6 +202,28 @@ impl From<FiatCurrencyCode> for CurrencyCode {
 mod tests {
     use super::*;
 
+    #[test]
+    fn test_crypto_currency_code_predicates() {
+        let stablecoins: Vec<_> = [
+            CryptoCurrencyCode::Usdt,
+            CryptoCurrencyCode::Ton,
+            CryptoCurrencyCode::Usdc,
+            CryptoCurrencyCode::Unknown,
+        ]
+        .into_iter()
+        .filter(CryptoCurrencyCode::is_stablecoin)
+        .collect();
+        assert_eq!(stablecoins, vec![CryptoCurrencyCode::Usdt, CryptoCurrencyCode::Usdc]);
+
+        assert_eq!(CryptoCurrencyCode::Send.native_chain(), Some(Chain::Ton));
+        assert_eq!(CryptoCurrencyCode::Trx.native_chain(), Some(Chain::Tron));
+        assert_eq!(CryptoCurrencyCode::Usdt.native_chain(), None);
+
+        assert_eq!(CryptoCurrencyCode::Btc.decimals_hint(), Some(8));
+        assert_eq!(CryptoCurrencyCode::Usdt.decimals_hint(), Some(6));
+        assert_eq!(CryptoCurrencyCode::Unknown.decimals_hint(), None);
+    }
+
     #[test]
     fn test_currency_type_serialization() {
         let crypto = CurrencyType::Crypto;
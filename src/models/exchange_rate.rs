@@ -1,10 +1,13 @@
 use crate::utils::{deserialize_decimal, serialize_decimal_to_string};
+use chrono::{DateTime, Utc};
 use rust_decimal::Decimal;
 use serde::{Deserialize, Serialize};
+use std::time::Duration;
 
-use super::{CryptoCurrencyCode, FiatCurrencyCode};
+use super::{CryptoCurrencyCode, FiatCurrencyCode, Rounding, FIAT_DECIMAL_PLACES};
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
+#[non_exhaustive]
 pub struct ExchangeRate {
     /// True, if the received rate is up-to-date.
     pub is_valid: bool,
@@ -20,4 +23,171 @@ pub struct ExchangeRate {
     #[serde(deserialize_with = "deserialize_decimal")]
     #[serde(serialize_with = "serialize_decimal_to_string")]
     pub rate: Decimal, // 1 source = rate target
+    #[serde(skip)]
+    pub(crate) fetched_at: Option<DateTime<Utc>>,
+}
+
+impl ExchangeRate {
+    /// Creates a valid rate of one `source` unit in `target`, e.g. for `WebhookHandler::set_exchange_rates` in tests.
+    pub fn new(source: CryptoCurrencyCode, target: FiatCurrencyCode, rate: Decimal) -> Self {
+        Self {
+            is_valid: true,
+            is_crypto: true,
+            is_fiat: false,
+            source,
+            target,
+            rate,
+            fetched_at: None,
+        }
+    }
+
+    /// Returns the time the rate was fetched by this client, if known. Not part of the API response.
+    pub fn fetched_at(&self) -> Option<DateTime<Utc>> {
+        self.fetched_at
+    }
+
+    /// Returns the `(source, target)` currency pair of the rate.
+    pub fn pair(&self) -> (CryptoCurrencyCode, FiatCurrencyCode) {
+        (self.source.clone(), self.target.clone())
+    }
+
+    /// Returns the value of one unit of the target currency in the source asset, or `None` if the rate is zero.
+    pub fn inverted(&self) -> Option<Decimal> {
+        Decimal::ONE.checked_div(self.rate)
+    }
+
+    /// Converts an amount of the source asset into the target currency.
+    pub fn convert(&self, amount: Decimal) -> Decimal {
+        amount * self.rate
+    }
+
+    /// Converts an amount of the source asset into the target currency, rounded to whole cents with `rounding`.
+    pub fn convert_rounded(&self, amount: Decimal, rounding: Rounding) -> Decimal {
+        rounding.round(self.convert(amount), FIAT_DECIMAL_PLACES)
+    }
+
+    /// Converts an amount of the target currency into the source asset, rounded to the precision of the asset
+    /// with `rounding`. Returns `None` if the rate is not positive.
+    pub fn convert_to_source(&self, amount: Decimal, rounding: Rounding) -> Option<Decimal> {
+        if self.rate <= Decimal::ZERO {
+            return None;
+        }
+        Some(rounding.round(amount / self.rate, self.source.max_scale()).normalize())
+    }
+
+    /// Returns true if the rate is flagged as outdated, or was fetched more than `ttl` before `now`.
+    ///
+    /// Rates without a fetch time, e.g. constructed by hand, are judged by `is_valid` only.
+    pub fn is_stale(&self, now: DateTime<Utc>, ttl: Duration) -> bool {
+        if !self.is_valid {
+            return true;
+        }
+
+        self.fetched_at
+            .is_some_and(|fetched_at| (now - fetched_at).to_std().is_ok_and(|age| age > ttl))
+    }
+}
+
+/// Filtering helpers for a list of exchange rates, as returned by `get_exchange_rates`.
+pub trait ExchangeRates {
+    /// Returns the rates from a cryptocurrency to a fiat currency.
+    fn crypto_to_fiat(&self) -> Vec<&ExchangeRate>;
+
+    /// Returns the rates of the given source asset.
+    fn for_source(&self, asset: &CryptoCurrencyCode) -> Vec<&ExchangeRate>;
+
+    /// Returns the rate between the given asset and fiat currency, if available.
+    fn find_rate(&self, source: &CryptoCurrencyCode, target: &FiatCurrencyCode) -> Option<&ExchangeRate>;
+}
+
+impl ExchangeRates for [ExchangeRate] {
+    fn crypto_to_fiat(&self) -> Vec<&ExchangeRate> {
+        self.iter().filter(|rate| rate.is_crypto && !rate.is_fiat).collect()
+    }
+
+    fn for_source(&self, asset: &CryptoCurrencyCode) -> Vec<&ExchangeRate> {
+        self.iter().filter(|rate| rate.source == *asset).collect()
+    }
+
+    fn find_rate(&self, source: &CryptoCurrencyCode, target: &FiatCurrencyCode) -> Option<&ExchangeRate> {
+        self.iter()
+            .find(|rate| rate.source == *source && rate.target == *target)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::TimeDelta;
+    use rust_decimal_macros::dec;
+
+    use super::*;
+    use crate::utils::test_utils::TestContext;
+
+    #[test]
+    fn test_exchange_rate_new() {
+        let rate = ExchangeRate::new(CryptoCurrencyCode::Ton, FiatCurrencyCode::Usd, dec!(3.5));
+        assert_eq!(rate.convert(dec!(12.5)), dec!(43.75));
+        assert_eq!(rate.convert_rounded(dec!(0.001), Rounding::HalfUp), dec!(0.00));
+        assert_eq!(rate.convert_rounded(dec!(0.001), Rounding::Ceiling), dec!(0.01));
+        assert_eq!(
+            rate.convert_to_source(dec!(1), Rounding::Floor),
+            Some(dec!(0.285714285))
+        );
+        assert_eq!(
+            rate.convert_to_source(dec!(1), Rounding::Ceiling),
+            Some(dec!(0.285714286))
+        );
+        assert!(!rate.is_stale(Utc::now(), Duration::from_secs(60)));
+    }
+
+    #[test]
+    fn test_exchange_rate_pair_and_inverted() {
+        let ton_usd = ExchangeRate::new(CryptoCurrencyCode::Ton, FiatCurrencyCode::Usd, dec!(4));
+
+        assert_eq!(ton_usd.pair(), (CryptoCurrencyCode::Ton, FiatCurrencyCode::Usd));
+        assert_eq!(ton_usd.inverted(), Some(dec!(0.25)));
+        assert_eq!(ton_usd.convert(dec!(2.5)), dec!(10));
+        assert_eq!(
+            ExchangeRate::new(CryptoCurrencyCode::Ton, FiatCurrencyCode::Usd, dec!(0)).inverted(),
+            None
+        );
+    }
+
+    #[test]
+    fn test_exchange_rate_is_stale() {
+        let now = Utc::now();
+        let ttl = Duration::from_secs(60);
+        let mut ton_usd = ExchangeRate::new(CryptoCurrencyCode::Ton, FiatCurrencyCode::Usd, dec!(4));
+
+        assert!(!ton_usd.is_stale(now, ttl));
+
+        ton_usd.fetched_at = Some(now - TimeDelta::seconds(30));
+        assert!(!ton_usd.is_stale(now, ttl));
+
+        ton_usd.fetched_at = Some(now - TimeDelta::seconds(90));
+        assert!(ton_usd.is_stale(now, ttl));
+
+        ton_usd.fetched_at = Some(now);
+        ton_usd.is_valid = false;
+        assert!(ton_usd.is_stale(now, ttl));
+    }
+
+    #[test]
+    fn test_exchange_rates_filters() {
+        let mut rates = TestContext::mock_exchange_rates();
+        let mut fiat_source = ExchangeRate::new(CryptoCurrencyCode::Usdt, FiatCurrencyCode::Usd, dec!(1));
+        fiat_source.is_crypto = false;
+        fiat_source.is_fiat = true;
+        rates.push(fiat_source);
+
+        assert_eq!(rates.crypto_to_fiat().len(), 2);
+        assert_eq!(rates.for_source(&CryptoCurrencyCode::Ton).len(), 2);
+        assert!(rates.for_source(&CryptoCurrencyCode::Btc).is_empty());
+        assert_eq!(
+            rates
+                .find_rate(&CryptoCurrencyCode::Ton, &FiatCurrencyCode::Eur)
+                .map(|rate| rate.rate),
+            Some(dec!(3.59048268))
+        );
+    }
 }
@@ -0,0 +1,138 @@
+use std::collections::HashMap;
+
+use super::{Chain, CryptoCurrencyCode};
+
+/// Placeholder replaced with the transaction hash or address in explorer templates
+const PLACEHOLDER: &str = "{id}";
+
+/// Block explorer links for transactions and addresses found in payment records
+///
+/// Each chain has a URL template where `{id}` is replaced with the percent-encoded transaction
+/// hash or address. Assets are mapped to chains with [`CryptoCurrencyCode::native_chain`], so
+/// stablecoins have no link until they are mapped to the chain the app uses them on. Both the
+/// templates and the asset mapping can be overridden.
+///
+/// # Example
+/// ```
+/// use crypto_pay_api::prelude::*;
+///
+/// let links = ExplorerLinks::default()
+///     .template(Chain::Ton, "https://tonscan.org/tx/{id}")
+///     .asset_chain(CryptoCurrencyCode::Usdt, Chain::Tron);
+///
+/// assert_eq!(
+///     links.explorer_url(&CryptoCurrencyCode::Ton, "abc123").as_deref(),
+///     Some("https://tonscan.org/tx/abc123")
+/// );
+/// assert!(links.explorer_url(&CryptoCurrencyCode::Usdt, "abc123").is_some());
+/// assert!(links.explorer_url(&CryptoCurrencyCode::Usdc, "abc123").is_none());
+/// ```
+#[derive(Debug, Clone, PartialEq)]
+pub struct ExplorerLinks {
+    templates: HashMap<Chain, String>,
+    asset_chains: HashMap<CryptoCurrencyCode, Chain>,
+}
+
+impl Default for ExplorerLinks {
+    /// Links to a public explorer of each supported chain, searching for the transaction or address
+    fn default() -> Self {
+        let templates = [
+            Chain::Ton,
+            Chain::Bitcoin,
+            Chain::Ethereum,
+            Chain::Litecoin,
+            Chain::BnbSmartChain,
+            Chain::Tron,
+            Chain::Dogecoin,
+        ]
+        .into_iter()
+        .filter_map(|chain| Some((chain, chain.default_explorer_template()?.to_string())))
+        .collect();
+
+        Self {
+            templates,
+            asset_chains: HashMap::new(),
+        }
+    }
+}
+
+impl ExplorerLinks {
+    /// Creates links without any template, for apps supplying their own table
+    pub fn empty() -> Self {
+        Self {
+            templates: HashMap::new(),
+            asset_chains: HashMap::new(),
+        }
+    }
+
+    /// Sets the URL template of `chain`, where `{id}` stands for the transaction hash or address
+    pub fn template(mut self, chain: Chain, template: impl Into<String>) -> Self {
+        self.templates.insert(chain, template.into());
+        self
+    }
+
+    /// Links `asset` to the explorer of `chain` instead of its native chain
+    pub fn asset_chain(mut self, asset: CryptoCurrencyCode, chain: Chain) -> Self {
+        self.asset_chains.insert(asset, chain);
+        self
+    }
+
+    /// Returns the chain payments in `asset` are looked up on
+    pub fn chain(&self, asset: &CryptoCurrencyCode) -> Option<Chain> {
+        self.asset_chains.get(asset).copied().or_else(|| asset.native_chain())
+    }
+
+    /// Returns the explorer URL of a transaction hash or address in `asset`
+    ///
+    /// Returns `None` when the asset has no chain or the chain has no template.
+    pub fn explorer_url(&self, asset: &CryptoCurrencyCode, tx_or_address: &str) -> Option<String> {
+        let template = self.templates.get(&self.chain(asset)?)?;
+        let id: String = url::form_urlencoded::byte_serialize(tx_or_address.trim().as_bytes()).collect();
+        Some(template.replace(PLACEHOLDER, &id))
+    }
+}
+
+impl Chain {
+    /// Returns the explorer template used by `ExplorerLinks::default` for this chain
+    pub fn default_explorer_template(&self) -> Option<&'static str> {
+        match self {
+            Chain::Ton => Some("https://tonviewer.com/{id}"),
+            Chain::Bitcoin => Some("https://blockchair.com/search?q={id}"),
+            Chain::Ethereum => Some("https://etherscan.io/search?q={id}"),
+            Chain::Litecoin => Some("https://blockchair.com/search?q={id}"),
+            Chain::BnbSmartChain => Some("https://bscscan.com/search?q={id}"),
+            Chain::Tron => Some("https://blockchair.com/search?q={id}"),
+            Chain::Dogecoin => Some("https://blockchair.com/search?q={id}"),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_explorer_url_uses_overrides_and_native_chains() {
+        let links = ExplorerLinks::default();
+        assert_eq!(
+            links.explorer_url(&CryptoCurrencyCode::Btc, " 4a5e1e ").as_deref(),
+            Some("https://blockchair.com/search?q=4a5e1e")
+        );
+        assert_eq!(
+            links.explorer_url(&CryptoCurrencyCode::Send, "EQ+a/b").as_deref(),
+            Some("https://tonviewer.com/EQ%2Ba%2Fb")
+        );
+        assert_eq!(links.explorer_url(&CryptoCurrencyCode::Usdt, "abc"), None);
+        assert_eq!(links.explorer_url(&CryptoCurrencyCode::Unknown, "abc"), None);
+
+        let links = ExplorerLinks::empty()
+            .template(Chain::Tron, "https://example.com/tron/{id}")
+            .asset_chain(CryptoCurrencyCode::Usdt, Chain::Tron);
+        assert_eq!(links.chain(&CryptoCurrencyCode::Usdt), Some(Chain::Tron));
+        assert_eq!(
+            links.explorer_url(&CryptoCurrencyCode::Usdt, "abc").as_deref(),
+            Some("https://example.com/tron/abc")
+        );
+        assert_eq!(links.explorer_url(&CryptoCurrencyCode::Btc, "abc"), None);
+    }
+}
@@ -0,0 +1,259 @@
+use std::time::Duration;
+
+use chrono::{Datelike, NaiveDate, TimeDelta};
+use rust_decimal::Decimal;
+use serde::Serialize;
+
+use crate::models::{asset_totals, AssetTotal, CryptoCurrencyCode, CurrencyType};
+
+use super::{Invoice, InvoiceStatus};
+
+/// Length of the periods revenue is grouped by
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "lowercase")]
+#[non_exhaustive]
+pub enum TimeBucket {
+    /// Calendar days in UTC.
+    Day,
+    /// ISO weeks in UTC, starting on Monday.
+    Week,
+}
+
+impl TimeBucket {
+    /// Returns the first day of the period containing `date`
+    pub fn start_of(&self, date: NaiveDate) -> NaiveDate {
+        match self {
+            TimeBucket::Day => date,
+            TimeBucket::Week => date - TimeDelta::days(date.weekday().num_days_from_monday() as i64),
+        }
+    }
+}
+
+/// Revenue received in one period.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct PeriodRevenue {
+    /// First day of the period, in UTC.
+    pub start: NaiveDate,
+
+    /// Amounts received per asset, in order of first appearance.
+    pub totals: Vec<AssetTotal>,
+
+    /// Number of invoices paid in the period.
+    pub count: usize,
+}
+
+/// Share of the paid invoices settled in one asset.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct AssetShare {
+    /// Asset the invoices were paid in.
+    pub asset: CryptoCurrencyCode,
+
+    /// Exact sum of the amounts received.
+    pub total: Decimal,
+
+    /// Number of invoices paid in the asset.
+    pub count: usize,
+
+    /// Fraction of the paid invoices, between 0 and 1.
+    pub share: Decimal,
+}
+
+/// How many of the created invoices were paid.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize)]
+pub struct Conversion {
+    /// Number of invoices.
+    pub created: usize,
+
+    /// Number of paid invoices.
+    pub paid: usize,
+
+    /// Number of expired invoices.
+    pub expired: usize,
+
+    /// Fraction of the invoices that were paid, `None` without invoices.
+    pub rate: Option<Decimal>,
+}
+
+/// Groups the revenue of the paid invoices by period, for `Invoices::revenue_by`
+pub(super) fn revenue_by(invoices: &[Invoice], bucket: TimeBucket) -> Vec<PeriodRevenue> {
+    let mut payments: Vec<(NaiveDate, &CryptoCurrencyCode, Decimal)> = invoices
+        .iter()
+        .filter_map(|invoice| {
+            let (asset, amount) = received(invoice)?;
+            Some((bucket.start_of(invoice.paid_at?.date_naive()), asset, amount))
+        })
+        .collect();
+    payments.sort_by_key(|(start, _, _)| *start);
+
+    let mut periods: Vec<PeriodRevenue> = Vec::new();
+    for (start, asset, amount) in payments {
+        let period = match periods.last_mut() {
+            Some(last) if last.start == start => last,
+            _ => {
+                periods.push(PeriodRevenue {
+                    start,
+                    totals: Vec::new(),
+                    count: 0,
+                });
+                periods.last_mut().expect("just pushed")
+            }
+        };
+        period.count += 1;
+        AssetTotal::add_to(&mut period.totals, asset, amount);
+    }
+    periods
+}
+
+/// Counts the paid and expired invoices, for `Invoices::conversion`
+pub(super) fn conversion(invoices: &[Invoice]) -> Conversion {
+    let created = invoices.len();
+    let paid = invoices
+        .iter()
+        .filter(|invoice| invoice.status == InvoiceStatus::Paid)
+        .count();
+    let expired = invoices
+        .iter()
+        .filter(|invoice| invoice.status == InvoiceStatus::Expired)
+        .count();
+
+    Conversion {
+        created,
+        paid,
+        expired,
+        rate: (created > 0).then(|| Decimal::from(paid) / Decimal::from(created)),
+    }
+}
+
+/// Returns the median time to pay, for `Invoices::median_time_to_pay`
+pub(super) fn median_time_to_pay(invoices: &[Invoice]) -> Option<Duration> {
+    let mut durations: Vec<Duration> = invoices
+        .iter()
+        .filter_map(|invoice| (invoice.paid_at? - invoice.created_at).to_std().ok())
+        .collect();
+    durations.sort_unstable();
+
+    let middle = durations.len() / 2;
+    match durations.len() {
+        0 => None,
+        len if len % 2 == 1 => Some(durations[middle]),
+        _ => Some((durations[middle - 1] + durations[middle]) / 2),
+    }
+}
+
+/// Returns the paid invoices per asset, for `Invoices::paid_by_asset`
+pub(super) fn paid_by_asset(invoices: &[Invoice]) -> Vec<AssetShare> {
+    let received: Vec<(&CryptoCurrencyCode, Decimal)> = invoices.iter().filter_map(received).collect();
+    let totals = asset_totals(received);
+
+    let paid: usize = totals.iter().map(|total| total.count).sum();
+    let mut shares: Vec<AssetShare> = totals
+        .into_iter()
+        .map(|total| AssetShare {
+            share: Decimal::from(total.count) / Decimal::from(paid),
+            asset: total.asset,
+            total: total.total,
+            count: total.count,
+        })
+        .collect();
+    shares.sort_by_key(|share| std::cmp::Reverse(share.count));
+    shares
+}
+
+/// Returns the asset and amount received for a paid invoice
+///
+/// Crypto invoices are paid in their `asset`, fiat invoices in `paid_asset`.
+fn received(invoice: &Invoice) -> Option<(&CryptoCurrencyCode, Decimal)> {
+    if invoice.status != InvoiceStatus::Paid {
+        return None;
+    }
+
+    match invoice.currency_type {
+        CurrencyType::Crypto => Some((invoice.asset.as_ref()?, invoice.amount)),
+        CurrencyType::Fiat => Some((invoice.paid_asset.as_ref()?, invoice.paid_amount?)),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::{TimeZone, Utc};
+    use rust_decimal_macros::dec;
+
+    use super::*;
+    use crate::{fixtures, models::Invoices};
+
+    fn paid_on(day: u32, minutes_to_pay: i64) -> Invoice {
+        let mut invoice = fixtures::paid_invoice();
+        invoice.created_at = Utc.with_ymd_and_hms(2025, 2, day, 12, 0, 0).unwrap();
+        invoice.paid_at = Some(invoice.created_at + TimeDelta::minutes(minutes_to_pay));
+        invoice
+    }
+
+    #[test]
+    fn test_revenue_by_day_and_week() {
+        // 2025-02-09 is a Sunday, 2025-02-10 the Monday after
+        let mut usdt = fixtures::fiat_invoice_with_swap();
+        usdt.paid_at = Some(Utc.with_ymd_and_hms(2025, 2, 10, 8, 0, 0).unwrap());
+        let invoices = [paid_on(10, 5), paid_on(9, 5), usdt, paid_on(9, 5)];
+
+        let days = invoices.revenue_by(TimeBucket::Day);
+        assert_eq!(days.len(), 2);
+        assert_eq!(
+            (days[0].start, days[0].count, days[0].totals[0].total),
+            (NaiveDate::from_ymd_opt(2025, 2, 9).unwrap(), 2, dec!(21))
+        );
+        assert_eq!(days[1].count, 2);
+        assert_eq!(days[1].totals.len(), 2);
+
+        let weeks = invoices.revenue_by(TimeBucket::Week);
+        assert_eq!(weeks.len(), 2);
+        assert_eq!(weeks[0].start, NaiveDate::from_ymd_opt(2025, 2, 3).unwrap());
+        assert_eq!(weeks[1].start, NaiveDate::from_ymd_opt(2025, 2, 10).unwrap());
+    }
+
+    #[test]
+    fn test_conversion_time_to_pay_and_asset_shares() {
+        let mut active = fixtures::paid_invoice();
+        active.status = InvoiceStatus::Active;
+        active.paid_at = None;
+        let invoices = [
+            paid_on(3, 2),
+            paid_on(4, 10),
+            paid_on(5, 4),
+            fixtures::fiat_invoice_with_swap(),
+            active,
+        ];
+
+        let conversion = invoices.conversion();
+        assert_eq!((conversion.created, conversion.paid, conversion.expired), (5, 4, 0));
+        assert_eq!(conversion.rate, Some(dec!(0.8)));
+        assert_eq!([].conversion().rate, None);
+
+        assert_eq!(invoices[..3].median_time_to_pay(), Some(Duration::from_secs(4 * 60)));
+        assert_eq!(invoices[..2].median_time_to_pay(), Some(Duration::from_secs(6 * 60)));
+        assert_eq!(invoices[4..].median_time_to_pay(), None);
+
+        let shares = invoices.paid_by_asset();
+        assert_eq!(
+            shares
+                .iter()
+                .map(|share| (&share.asset, share.total, share.count, share.share))
+                .collect::<Vec<_>>(),
+            [
+                (&CryptoCurrencyCode::Ton, dec!(31.5), 3, dec!(0.75)),
+                (&CryptoCurrencyCode::Usdt, dec!(25), 1, dec!(0.25))
+            ]
+        );
+    }
+
+    #[test]
+    fn test_crypto_invoice_revenue_counts_in_invoice_asset() {
+        let mut invoice = paid_on(3, 2);
+        invoice.paid_asset = Some(CryptoCurrencyCode::Usdt);
+
+        let shares = [invoice].paid_by_asset();
+        assert_eq!(
+            (&shares[0].asset, shares[0].total),
+            (&CryptoCurrencyCode::Ton, dec!(10.5))
+        );
+    }
+}
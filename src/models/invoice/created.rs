@@ -0,0 +1,164 @@
+use chrono::Utc;
+use std::ops::Deref;
+
+use super::Invoice;
+
+/// Which of the invoice payment URLs to present to the user.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum PayUrlPreference {
+    /// URL opening the invoice in the Crypto Bot chat.
+    #[default]
+    Bot,
+    /// URL opening the invoice in the Telegram Mini App.
+    MiniApp,
+    /// URL opening the invoice in the Web version of Crypto Bot.
+    WebApp,
+}
+
+/// An invoice returned by `createInvoice`, with convenience accessors.
+///
+/// `CreatedInvoice` dereferences to [`Invoice`], so all fields remain directly accessible.
+///
+/// # Example
+/// ```no_run
+/// use crypto_pay_api::prelude::*;
+///
+/// #[tokio::main]
+/// async fn main() -> Result<(), CryptoBotError> {
+///     let client = CryptoBot::builder().api_token("YOUR_API_TOKEN").build()?;
+///     let invoice = client.create_invoice().amount(dec!(10)).asset(CryptoCurrencyCode::Ton).execute().await?;
+///
+///     println!("Pay here: {}", invoice.pay_url(PayUrlPreference::MiniApp));
+///     println!("Expires in: {:?}s", invoice.expires_in_secs());
+///     Ok(())
+/// }
+/// ```
+#[derive(Debug, Clone)]
+#[non_exhaustive]
+pub struct CreatedInvoice {
+    invoice: Invoice,
+}
+
+impl CreatedInvoice {
+    pub fn new(invoice: Invoice) -> Self {
+        Self { invoice }
+    }
+
+    /// Returns the payment URL matching the given preference
+    pub fn pay_url(&self, preference: PayUrlPreference) -> &str {
+        match preference {
+            PayUrlPreference::Bot => &self.invoice.bot_invoice_url,
+            PayUrlPreference::MiniApp => &self.invoice.mini_app_invoice_url,
+            PayUrlPreference::WebApp => &self.invoice.web_app_invoice_url,
+        }
+    }
+
+    /// Returns the number of seconds until the invoice expires
+    ///
+    /// # Returns
+    /// * `Some(secs)` - Seconds left, `0` if the invoice has already expired
+    /// * `None` - If the invoice has no expiration date
+    pub fn expires_in_secs(&self) -> Option<u64> {
+        self.invoice
+            .expires_date
+            .map(|expires| expires.signed_duration_since(Utc::now()).num_seconds().max(0) as u64)
+    }
+
+    /// Returns the content to encode in a QR code so the invoice can be paid by scanning it
+    pub fn qr_payload(&self) -> &str {
+        self.pay_url(PayUrlPreference::Bot)
+    }
+
+    /// Returns a reference to the raw invoice
+    pub fn invoice(&self) -> &Invoice {
+        &self.invoice
+    }
+
+    /// Consumes the wrapper and returns the raw invoice
+    pub fn into_inner(self) -> Invoice {
+        self.invoice
+    }
+}
+
+impl std::fmt::Display for CreatedInvoice {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        self.invoice.fmt(f)
+    }
+}
+
+impl Deref for CreatedInvoice {
+    type Target = Invoice;
+
+    fn deref(&self) -> &Self::Target {
+        &self.invoice
+    }
+}
+
+impl From<Invoice> for CreatedInvoice {
+    fn from(invoice: Invoice) -> Self {
+        Self::new(invoice)
+    }
+}
+
+impl From<CreatedInvoice> for Invoice {
+    fn from(created: CreatedInvoice) -> Self {
+        created.invoice
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::{DateTime, Duration};
+
+    use super::*;
+    use crate::{fixtures, models::InvoiceStatus};
+
+    fn invoice(expires_date: Option<DateTime<Utc>>) -> Invoice {
+        Invoice {
+            status: InvoiceStatus::Active,
+            expires_date,
+            ..fixtures::paid_invoice()
+        }
+    }
+
+    #[test]
+    fn test_pay_url_preferences() {
+        let created = CreatedInvoice::new(invoice(None));
+
+        assert_eq!(
+            created.pay_url(PayUrlPreference::Bot),
+            "https://t.me/CryptoTestnetBot?start=IVDoTcNBYEfk"
+        );
+        assert_eq!(
+            created.pay_url(PayUrlPreference::MiniApp),
+            "https://t.me/CryptoTestnetBot/app?startapp=invoice-IVDoTcNBYEfk"
+        );
+        assert_eq!(
+            created.pay_url(PayUrlPreference::WebApp),
+            "https://testnet-app.send.tg/invoices/IVDoTcNBYEfk"
+        );
+        assert_eq!(created.qr_payload(), created.bot_invoice_url);
+    }
+
+    #[test]
+    fn test_expires_in_secs() {
+        assert_eq!(CreatedInvoice::new(invoice(None)).expires_in_secs(), None);
+
+        let future = Utc::now() + Duration::hours(1);
+        let secs = CreatedInvoice::new(invoice(Some(future))).expires_in_secs().unwrap();
+        assert!(secs > 3500 && secs <= 3600);
+
+        let past = Utc::now() - Duration::hours(1);
+        assert_eq!(CreatedInvoice::new(invoice(Some(past))).expires_in_secs(), Some(0));
+    }
+
+    #[test]
+    fn test_deref_and_into_inner() {
+        let created = CreatedInvoice::from(invoice(None));
+        assert_eq!(created.invoice_id, 528890);
+        assert_eq!(created.invoice().hash, "IVDoTcNBYEfk");
+
+        let raw: Invoice = created.into_inner();
+        assert_eq!(raw.invoice_id, 528890);
+    }
+}
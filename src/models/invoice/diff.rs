@@ -0,0 +1,162 @@
+use serde::Serialize;
+use serde_json::Value;
+
+use super::Invoice;
+
+/// A field that differs between two versions of the same invoice
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct FieldChange {
+    /// Name of the field, as in the API.
+    pub field: &'static str,
+    /// Value in the invoice `diff` was called on, `null` if absent.
+    pub old: Value,
+    /// Value in the other invoice, `null` if absent.
+    pub new: Value,
+}
+
+macro_rules! diff_fields {
+    ($old:expr, $new:expr, [$($field:ident),* $(,)?]) => {{
+        // Fails to compile when a field is added to `Invoice` without being listed
+        let Invoice {
+            $($field: _,)*
+            #[cfg(feature = "raw-json")]
+            raw: _,
+        } = $old;
+
+        let mut changes = Vec::new();
+        $(
+            if $old.$field != $new.$field {
+                changes.push(FieldChange {
+                    field: stringify!($field),
+                    old: serde_json::to_value(&$old.$field).unwrap_or(Value::Null),
+                    new: serde_json::to_value(&$new.$field).unwrap_or(Value::Null),
+                });
+            }
+        )*
+        changes
+    }};
+}
+
+impl Invoice {
+    /// Compares two versions of an invoice field by field
+    ///
+    /// Useful to audit differences between the payload received by a webhook and the invoice
+    /// fetched later with `get_invoices`, e.g. fees that only appear afterwards. Every field is
+    /// compared except `raw`.
+    ///
+    /// # Example
+    /// ```
+    /// # #[cfg(feature = "fixtures")] {
+    /// use crypto_pay_api::fixtures;
+    ///
+    /// let webhook = fixtures::paid_invoice();
+    /// let mut fetched = fixtures::paid_invoice();
+    /// fetched.comment = Some("Thanks!".to_string());
+    ///
+    /// let changes = webhook.diff(&fetched);
+    /// assert_eq!(changes.len(), 1);
+    /// assert_eq!(changes[0].field, "comment");
+    /// # }
+    /// ```
+    pub fn diff(&self, other: &Invoice) -> Vec<FieldChange> {
+        diff_fields!(
+            self,
+            other,
+            [
+                invoice_id,
+                hash,
+                currency_type,
+                asset,
+                fiat,
+                amount,
+                paid_asset,
+                paid_amount,
+                paid_fiat_rate,
+                accept_asset,
+                fee_asset,
+                fee_amount,
+                bot_invoice_url,
+                mini_app_invoice_url,
+                web_app_invoice_url,
+                description,
+                status,
+                swap_to,
+                is_swapped,
+                swapped_uid,
+                swapped_to,
+                swapped_rate,
+                swapped_output,
+                swapped_usd_amount,
+                swapped_usd_rate,
+                created_at,
+                paid_usd_rate,
+                allow_comments,
+                allow_anonymous,
+                expires_date,
+                paid_at,
+                paid_anonymously,
+                comment,
+                hidden_message,
+                payload,
+                paid_btn_name,
+                paid_btn_url,
+                pay_url,
+                fee,
+                usd_rate,
+                receipt,
+            ]
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use rust_decimal_macros::dec;
+    use serde_json::json;
+
+    use super::*;
+    use crate::{fixtures, models::InvoiceStatus};
+
+    #[test]
+    fn test_diff_identical_invoices() {
+        let invoice = fixtures::paid_invoice();
+        assert!(invoice.diff(&invoice.clone()).is_empty());
+    }
+
+    #[test]
+    fn test_diff_reports_changed_fields() {
+        let mut webhook = fixtures::paid_invoice();
+        webhook.fee_amount = None;
+        webhook.status = InvoiceStatus::Active;
+
+        let mut fetched = fixtures::paid_invoice();
+        fetched.fee_amount = Some(dec!(0.315));
+
+        assert_eq!(
+            webhook.diff(&fetched),
+            vec![
+                FieldChange {
+                    field: "fee_amount",
+                    old: Value::Null,
+                    new: json!("0.315"),
+                },
+                FieldChange {
+                    field: "status",
+                    old: json!("active"),
+                    new: json!("paid"),
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn test_diff_reports_deprecated_fields() {
+        let webhook = fixtures::paid_invoice();
+        let mut fetched = fixtures::paid_invoice();
+        fetched.pay_url = Some("https://t.me/CryptoBot?start=IVabc".to_string());
+
+        let changes = webhook.diff(&fetched);
+        assert_eq!(changes.len(), 1);
+        assert_eq!(changes[0].field, "pay_url");
+    }
+}
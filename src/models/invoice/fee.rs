@@ -0,0 +1,233 @@
+use std::time::Duration;
+
+use rust_decimal::Decimal;
+use serde::Serialize;
+
+use crate::models::{
+    asset_totals, AssetTotal, CryptoCurrencyCode, CurrencyType, ExchangeRate, ExchangeRates, FiatCurrencyCode,
+    Rounding, FIAT_DECIMAL_PLACES,
+};
+
+use super::{
+    analytics::{self, AssetShare, Conversion, PeriodRevenue, TimeBucket},
+    Invoice,
+};
+
+/// Service fee charged when an invoice was paid, returned by `Invoice::fee_breakdown`
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct FeeBreakdown {
+    /// Asset the fee was charged in.
+    pub asset: CryptoCurrencyCode,
+
+    /// Amount of the fee, in `asset`.
+    pub amount: Decimal,
+
+    /// Price of `asset` in USD when the invoice was paid, if the API provided it.
+    pub usd_rate: Option<Decimal>,
+}
+
+impl FeeBreakdown {
+    /// Returns the value of the fee in USD
+    ///
+    /// Uses `usd_rate` if known, falling back to the given exchange rates. Returns `None` if no
+    /// matching rate is available.
+    pub fn usd_value(&self, rates: &[ExchangeRate]) -> Option<Decimal> {
+        let usd_rate = self.usd_rate.or_else(|| {
+            rates
+                .find_rate(&self.asset, &FiatCurrencyCode::Usd)
+                .map(|rate| rate.rate)
+        })?;
+
+        Some(self.amount * usd_rate)
+    }
+
+    /// Returns the value of the fee in USD like `usd_value`, rounded to whole cents with `rounding`
+    pub fn usd_value_rounded(&self, rates: &[ExchangeRate], rounding: Rounding) -> Option<Decimal> {
+        Some(rounding.round(self.usd_value(rates)?, FIAT_DECIMAL_PLACES))
+    }
+}
+
+impl Invoice {
+    /// Returns the service fee charged for the invoice, or `None` if it is not paid
+    ///
+    /// `paid_usd_rate` is the price of the paid asset, so it only becomes the `usd_rate` of the
+    /// fee when the fee was charged in that asset.
+    ///
+    /// # Example
+    /// ```
+    /// # #[cfg(feature = "fixtures")] {
+    /// use crypto_pay_api::{fixtures, prelude::*};
+    ///
+    /// let fee = fixtures::paid_invoice().fee_breakdown().unwrap();
+    /// assert_eq!(fee.asset, CryptoCurrencyCode::Ton);
+    /// assert_eq!(fee.usd_value(&[]), Some(dec!(1.02375)));
+    /// # }
+    /// ```
+    pub fn fee_breakdown(&self) -> Option<FeeBreakdown> {
+        let amount = self.fee_amount.or(self.fee)?;
+        let asset: CryptoCurrencyCode =
+            serde_json::from_value(serde_json::Value::String(self.fee_asset.as_deref()?.to_uppercase())).ok()?;
+
+        let paid_asset = match self.currency_type {
+            CurrencyType::Fiat => self.paid_asset.as_ref(),
+            CurrencyType::Crypto => self.paid_asset.as_ref().or(self.asset.as_ref()),
+        };
+        let usd_rate = self
+            .paid_usd_rate
+            .or(self.usd_rate)
+            .filter(|_| paid_asset == Some(&asset));
+
+        Some(FeeBreakdown {
+            asset,
+            amount,
+            usd_rate,
+        })
+    }
+}
+
+/// Reporting helpers for a list of invoices, as returned by `get_invoices`
+///
+/// Fees are summed as `Decimal`, so totals are exact. Fees in different assets are never added up.
+///
+/// The payment analytics complement `get_stats` with invoice-level figures. Revenue is the amount
+/// received: the `amount` in `asset` for crypto invoices, and `paid_amount` in `paid_asset` for fiat
+/// invoices. Amounts in different assets are never added up; convert them with `Invoice::usd_value`
+/// for a single total.
+///
+/// # Example
+/// ```
+/// # #[cfg(feature = "fixtures")] {
+/// use crypto_pay_api::{fixtures, prelude::*};
+///
+/// let invoices = vec![fixtures::paid_invoice(), fixtures::paid_invoice()];
+///
+/// let fees = invoices.fees_by_asset();
+/// assert_eq!(fees[0].total, dec!(0.63));
+/// assert_eq!(invoices.fees_usd_value(&[]), Some(dec!(2.0475)));
+/// assert_eq!(invoices.fees_usd_value_rounded(&[], Rounding::HalfUp), Some(dec!(2.05)));
+///
+/// let invoices = vec![fixtures::paid_invoice(), fixtures::fiat_invoice_with_swap()];
+///
+/// let revenue = invoices.revenue_by(TimeBucket::Day);
+/// assert_eq!(revenue[0].totals[0].asset, CryptoCurrencyCode::Ton);
+/// assert_eq!(invoices.conversion().paid, 2);
+/// # }
+/// ```
+pub trait Invoices {
+    /// Returns the fees of the paid invoices per asset, in order of first appearance.
+    fn fees_by_asset(&self) -> Vec<AssetTotal>;
+
+    /// Returns the sum of the fees in USD, or `None` if a fee has no matching rate.
+    fn fees_usd_value(&self, rates: &[ExchangeRate]) -> Option<Decimal>;
+
+    /// Returns the sum of the fees in USD like `fees_usd_value`, rounded to whole cents with `rounding`.
+    fn fees_usd_value_rounded(&self, rates: &[ExchangeRate], rounding: Rounding) -> Option<Decimal> {
+        Some(rounding.round(self.fees_usd_value(rates)?, FIAT_DECIMAL_PLACES))
+    }
+
+    /// Returns the revenue of the paid invoices per period of payment, ordered by date. Periods without payments are omitted.
+    fn revenue_by(&self, bucket: TimeBucket) -> Vec<PeriodRevenue>;
+
+    /// Returns how many of the invoices were paid.
+    fn conversion(&self) -> Conversion;
+
+    /// Returns the median time between creation and payment of the paid invoices, `None` if none was paid.
+    fn median_time_to_pay(&self) -> Option<Duration>;
+
+    /// Returns the paid invoices per asset, most used asset first.
+    fn paid_by_asset(&self) -> Vec<AssetShare>;
+}
+
+impl Invoices for [Invoice] {
+    fn fees_by_asset(&self) -> Vec<AssetTotal> {
+        let fees: Vec<FeeBreakdown> = self.iter().filter_map(Invoice::fee_breakdown).collect();
+        asset_totals(fees.iter().map(|fee| (&fee.asset, fee.amount)))
+    }
+
+    fn fees_usd_value(&self, rates: &[ExchangeRate]) -> Option<Decimal> {
+        self.iter()
+            .filter_map(Invoice::fee_breakdown)
+            .map(|fee| fee.usd_value(rates))
+            .sum()
+    }
+
+    fn revenue_by(&self, bucket: TimeBucket) -> Vec<PeriodRevenue> {
+        analytics::revenue_by(self, bucket)
+    }
+
+    fn conversion(&self) -> Conversion {
+        analytics::conversion(self)
+    }
+
+    fn median_time_to_pay(&self) -> Option<Duration> {
+        analytics::median_time_to_pay(self)
+    }
+
+    fn paid_by_asset(&self) -> Vec<AssetShare> {
+        analytics::paid_by_asset(self)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use rust_decimal_macros::dec;
+
+    use super::*;
+    use crate::{fixtures, utils::test_utils::TestContext};
+
+    #[test]
+    fn test_fee_breakdown() {
+        let invoice = fixtures::paid_invoice();
+        let fee = invoice.fee_breakdown().unwrap();
+        assert_eq!(
+            fee,
+            FeeBreakdown {
+                asset: CryptoCurrencyCode::Ton,
+                amount: dec!(0.315),
+                usd_rate: Some(dec!(3.25)),
+            }
+        );
+
+        let mut unpaid = invoice.clone();
+        unpaid.fee_asset = None;
+        unpaid.fee_amount = None;
+        assert!(unpaid.fee_breakdown().is_none());
+
+        // The rate of the paid asset does not apply to a fee in another asset
+        let mut other_asset = invoice.clone();
+        other_asset.fee_asset = Some("USDT".to_string());
+        let fee = other_asset.fee_breakdown().unwrap();
+        assert_eq!(fee.usd_rate, None);
+        assert_eq!(fee.usd_value(&[]), None);
+
+        let mut without_rate = invoice;
+        without_rate.paid_usd_rate = None;
+        let fee = without_rate.fee_breakdown().unwrap();
+        assert_eq!(
+            fee.usd_value(&TestContext::mock_exchange_rates()),
+            Some(dec!(0.315) * dec!(3.70824926))
+        );
+    }
+
+    #[test]
+    fn test_invoices_fee_totals() {
+        let invoices = [
+            fixtures::paid_invoice(),
+            fixtures::fiat_invoice_with_swap(),
+            fixtures::paid_invoice(),
+        ];
+
+        let fees = invoices.fees_by_asset();
+        assert_eq!(fees.len(), 2);
+        assert_eq!(
+            (&fees[0].asset, fees[0].total, fees[0].count),
+            (&CryptoCurrencyCode::Ton, dec!(0.63), 2)
+        );
+        assert_eq!(
+            (&fees[1].asset, fees[1].total, fees[1].count),
+            (&CryptoCurrencyCode::Usdt, dec!(0.75), 1)
+        );
+
+        assert_eq!(invoices.fees_usd_value(&[]), Some(dec!(0.63) * dec!(3.25) + dec!(0.75)));
+    }
+}
@@ -1,14 +1,32 @@
+mod analytics;
+mod created;
+mod diff;
+mod fee;
+mod order;
 mod params;
 
+pub use analytics::*;
 use chrono::{DateTime, Utc};
+pub use created::*;
+pub use diff::*;
+pub use fee::*;
+pub use order::*;
 pub use params::*;
 use rust_decimal::Decimal;
 use serde::{Deserialize, Serialize};
 
-use super::{CryptoCurrencyCode, CurrencyType, FiatCurrencyCode, PayButtonName};
-use crate::utils::{deserialize_decimal, deserialize_optional_decimal_from_string};
+use super::format_amount;
+use super::{
+    CryptoCurrencyCode, CurrencyType, ExchangeRate, ExchangeRates, FiatCurrencyCode, PayButtonName, Receipt, Rounding,
+    FIAT_DECIMAL_PLACES,
+};
+use crate::utils::{
+    deserialize_decimal, deserialize_optional_decimal_from_string, deserialize_optional_string, format_short_duration,
+};
 
 #[derive(Debug, Deserialize, Clone)]
+#[cfg_attr(feature = "raw-json", serde(remote = "Self"))]
+#[non_exhaustive]
 pub struct Invoice {
     /// Unique ID for this invoice.
     pub invoice_id: u64,
@@ -46,6 +64,7 @@ pub struct Invoice {
     pub accept_asset: Option<Vec<CryptoCurrencyCode>>,
 
     /// Optional. Asset of service fees charged when the invoice was paid. Available only if status is "paid".
+    #[serde(default, deserialize_with = "deserialize_optional_string")]
     pub fee_asset: Option<String>,
 
     /// Optional. Amount of service fees charged when the invoice was paid. Available only if status is "paid".
@@ -63,6 +82,7 @@ pub struct Invoice {
     pub web_app_invoice_url: String,
 
     /// Optional. Description for this invoice.
+    #[serde(default, deserialize_with = "deserialize_optional_string")]
     pub description: Option<String>,
 
     /// Status of the transfer, can be "active", "paid" or "expired".
@@ -72,9 +92,11 @@ pub struct Invoice {
     pub swap_to: Option<SwapToAssets>,
 
     /// Optional. For invoices with the "paid" status, this flag indicates whether the swap was successful (only applicable if swap_to is set).
+    #[serde(default, deserialize_with = "deserialize_optional_string")]
     pub is_swapped: Option<String>,
 
     /// Optional. If is_swapped is true, stores the unique identifier of the swap.
+    #[serde(default, deserialize_with = "deserialize_optional_string")]
     pub swapped_uid: Option<String>,
 
     /// Optional. If is_swapped is true, stores the asset into which the swap was made.
@@ -116,29 +138,142 @@ pub struct Invoice {
     pub paid_anonymously: Option<bool>,
 
     /// Optional. Comment to the payment from the user.
+    #[serde(default, deserialize_with = "deserialize_optional_string")]
     pub comment: Option<String>,
 
     /// Optional. Text of the hidden message for this invoice.
+    #[serde(default, deserialize_with = "deserialize_optional_string")]
     pub hidden_message: Option<String>,
 
     /// Optional. Previously provided data for this invoice.
+    #[serde(default, deserialize_with = "deserialize_optional_string")]
     pub payload: Option<String>,
 
     /// Optional. Label of the button, can be "viewItem", "openChannel", "openBot" or "callback".
     pub paid_btn_name: Option<PayButtonName>,
 
     /// Optional. URL opened using the button.
+    #[serde(default, deserialize_with = "deserialize_optional_string")]
     pub paid_btn_url: Option<String>,
+
+    /// Deprecated. Optional. URL to pay the invoice, superseded by `bot_invoice_url`.
+    /// Only sent by older versions of the API.
+    #[serde(default, deserialize_with = "deserialize_optional_string")]
+    pub pay_url: Option<String>,
+
+    /// Deprecated. Optional. Amount of service fees charged when the invoice was paid, superseded by `fee_amount`.
+    /// Only sent by older versions of the API.
+    #[serde(default)]
+    #[serde(deserialize_with = "deserialize_optional_decimal_from_string")]
+    pub fee: Option<Decimal>,
+
+    /// Deprecated. Optional. Price of the asset in USD, superseded by `paid_usd_rate`.
+    /// Only sent by older versions of the API.
+    #[serde(default)]
+    #[serde(deserialize_with = "deserialize_optional_decimal_from_string")]
+    pub usd_rate: Option<Decimal>,
+
+    /// Signed receipt of the invoice, set on invoices returned by `create_invoice` when
+    /// `ClientBuilder::receipt_key` is set.
+    #[serde(skip)]
+    pub receipt: Option<Receipt>,
+
+    /// JSON the invoice was deserialized from, including fields unknown to this version of the library.
+    /// Available with the `raw-json` feature.
+    #[cfg(feature = "raw-json")]
+    #[serde(skip)]
+    pub raw: Option<std::sync::Arc<serde_json::Value>>,
+}
+
+deserialize_with_raw!(Invoice);
+
+/// Separates the application payload from the marker appended by `CreateInvoiceBuilder::recovery_key`
+const RECOVERY_MARKER_SEPARATOR: &str = "\u{1e}recovery:";
+
+/// Returns the payload suffix marking an invoice created with `recovery_key`
+#[cfg_attr(not(feature = "client"), allow(dead_code))]
+pub(crate) fn recovery_marker(key: &str) -> String {
+    format!("{RECOVERY_MARKER_SEPARATOR}{key}")
+}
+
+impl Invoice {
+    /// Returns the payload without the marker appended by `CreateInvoiceBuilder::recovery_key`
+    ///
+    /// Returns `None` if the payload is empty apart from the marker.
+    pub fn app_payload(&self) -> Option<&str> {
+        let payload = self.payload.as_deref()?;
+        let payload = payload
+            .split_once(RECOVERY_MARKER_SEPARATOR)
+            .map_or(payload, |(payload, _)| payload);
+        Some(payload).filter(|payload| !payload.is_empty())
+    }
+
+    /// Returns the value of the invoice in USD
+    ///
+    /// Fiat invoices in USD are returned as is. Otherwise the paid crypto amount is converted
+    /// with `paid_usd_rate` if the API provided it, falling back to the given exchange rates.
+    /// Returns `None` if no matching rate is available.
+    pub fn usd_value(&self, rates: &[ExchangeRate]) -> Option<Decimal> {
+        let (amount, asset) = match self.currency_type {
+            CurrencyType::Fiat if self.fiat == Some(FiatCurrencyCode::Usd) => return Some(self.amount),
+            CurrencyType::Fiat => (self.paid_amount?, self.paid_asset.as_ref()?),
+            CurrencyType::Crypto => (self.amount, self.paid_asset.as_ref().or(self.asset.as_ref())?),
+        };
+
+        let usd_rate = self
+            .paid_usd_rate
+            .or_else(|| rates.find_rate(asset, &FiatCurrencyCode::Usd).map(|rate| rate.rate))?;
+
+        Some(amount * usd_rate)
+    }
+
+    /// Returns the value of the invoice in USD like `usd_value`, rounded to whole cents with `rounding`
+    pub fn usd_value_rounded(&self, rates: &[ExchangeRate], rounding: Rounding) -> Option<Decimal> {
+        Some(rounding.round(self.usd_value(rates)?, FIAT_DECIMAL_PLACES))
+    }
 }
 
 #[derive(Debug, Deserialize, Serialize, PartialEq, Eq, Clone)]
 #[serde(rename_all = "lowercase")]
+#[non_exhaustive]
 pub enum InvoiceStatus {
     Active,
     Paid,
     Expired,
 }
 
+impl std::fmt::Display for InvoiceStatus {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(match self {
+            InvoiceStatus::Active => "active",
+            InvoiceStatus::Paid => "paid",
+            InvoiceStatus::Expired => "expired",
+        })
+    }
+}
+
+/// Single-line summary, e.g. `Invoice #528890 10.5 TON active, expires in 1h`.
+impl std::fmt::Display for Invoice {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "Invoice #{} {}", self.invoice_id, format_amount(&self.amount))?;
+
+        match (&self.asset, &self.fiat) {
+            (Some(asset), _) => write!(f, " {}", asset.to_string().to_uppercase())?,
+            (None, Some(fiat)) => write!(f, " {}", fiat.to_string().to_uppercase())?,
+            (None, None) => {}
+        }
+
+        write!(f, " {}", self.status)?;
+
+        if let (InvoiceStatus::Active, Some(expires_date)) = (&self.status, self.expires_date) {
+            let secs = expires_date.signed_duration_since(Utc::now()).num_seconds().max(0) as u64;
+            write!(f, ", expires in {}", format_short_duration(secs))?;
+        }
+
+        Ok(())
+    }
+}
+
 #[derive(Debug, Deserialize, Serialize, PartialEq, Eq, Clone)]
 #[serde(rename_all = "UPPERCASE")]
 pub enum SwapToAssets {
@@ -150,3 +285,96 @@ pub enum SwapToAssets {
     Btc,
     Ltc,
 }
+
+#[cfg(test)]
+mod tests {
+    use rust_decimal_macros::dec;
+
+    use super::*;
+    use crate::{fixtures, utils::test_utils::TestContext};
+
+    #[cfg(feature = "raw-json")]
+    #[test]
+    fn test_invoice_keeps_raw_json() {
+        let mut json: serde_json::Value = serde_json::from_str(fixtures::PAID_INVOICE).unwrap();
+        json["new_api_field"] = "value".into();
+
+        let response: crate::models::GetInvoicesResponse =
+            serde_json::from_value(serde_json::json!({ "items": [json] })).unwrap();
+        let invoice = &response.items[0];
+
+        assert_eq!(invoice.invoice_id, 528890);
+        assert_eq!(invoice.raw.as_deref(), Some(&json));
+        assert!(fixtures::transfer().raw.is_some());
+        assert!(fixtures::check().raw.is_some());
+    }
+
+    #[test]
+    fn test_invoice_display() {
+        let mut invoice = fixtures::paid_invoice();
+        assert_eq!(
+            invoice.to_string(),
+            format!("Invoice #{} 10.5 TON paid", invoice.invoice_id)
+        );
+
+        invoice.status = InvoiceStatus::Active;
+        invoice.expires_date = Some(Utc::now() + chrono::Duration::seconds(3_600 + 30));
+        assert_eq!(
+            invoice.to_string(),
+            format!("Invoice #{} 10.5 TON active, expires in 1h", invoice.invoice_id)
+        );
+
+        let fiat = fixtures::fiat_invoice_with_swap();
+        assert!(fiat.to_string().starts_with(&format!("Invoice #{} ", fiat.invoice_id)));
+        assert!(fiat
+            .to_string()
+            .contains(&fiat.fiat.as_ref().unwrap().to_string().to_uppercase()));
+    }
+
+    #[test]
+    fn test_deprecated_fields() {
+        let mut value: serde_json::Value = serde_json::from_str(fixtures::PAID_INVOICE).unwrap();
+        value["pay_url"] = "https://t.me/CryptoTestnetBot?start=IVDoTcNBYEfk".into();
+        value["fee"] = "0.315".into();
+        value["usd_rate"] = "3.25".into();
+
+        let invoice: Invoice = serde_json::from_value(value).unwrap();
+        assert_eq!(invoice.pay_url.as_deref(), Some(invoice.bot_invoice_url.as_str()));
+        assert_eq!(invoice.fee, invoice.fee_amount);
+        assert_eq!(invoice.usd_rate, invoice.paid_usd_rate);
+
+        let invoice = fixtures::paid_invoice();
+        assert!(invoice.pay_url.is_none() && invoice.fee.is_none() && invoice.usd_rate.is_none());
+    }
+
+    #[test]
+    fn test_usd_value_uses_paid_usd_rate() {
+        let invoice = fixtures::paid_invoice();
+        assert_eq!(invoice.usd_value(&[]), Some(dec!(34.125)));
+    }
+
+    #[test]
+    fn test_usd_value_falls_back_to_exchange_rates() {
+        let mut invoice = fixtures::paid_invoice();
+        invoice.paid_usd_rate = None;
+
+        assert_eq!(invoice.usd_value(&[]), None);
+        assert_eq!(
+            invoice.usd_value(&TestContext::mock_exchange_rates()),
+            Some(dec!(10.5) * dec!(3.70824926))
+        );
+    }
+
+    #[test]
+    fn test_usd_value_fiat_invoice() {
+        let invoice = fixtures::fiat_invoice_with_swap();
+        assert_eq!(invoice.usd_value(&[]), Some(dec!(25)));
+
+        let mut invoice = fixtures::fiat_invoice_with_swap();
+        invoice.fiat = Some(FiatCurrencyCode::Eur);
+        assert_eq!(invoice.usd_value(&[]), Some(dec!(25)));
+
+        invoice.paid_amount = None;
+        assert_eq!(invoice.usd_value(&[]), None);
+    }
+}
@@ -0,0 +1,82 @@
+use std::cmp::Ordering;
+
+use serde::Serialize;
+
+use super::Invoice;
+
+/// Field invoices are ordered by with `GetInvoicesBuilder::order_by`
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+#[non_exhaustive]
+pub enum InvoiceOrderBy {
+    CreatedAt,
+    /// The `amount` of the invoice, compared as a number whatever its asset or fiat currency
+    Amount,
+    /// Unpaid invoices come last in both directions
+    PaidAt,
+}
+
+/// Direction of an ordering
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum SortDirection {
+    #[default]
+    Asc,
+    Desc,
+}
+
+impl InvoiceOrderBy {
+    /// Sorts `invoices` in place, keeping the API order of invoices that compare equal
+    #[cfg_attr(not(feature = "client"), allow(dead_code))]
+    pub(crate) fn sort(self, invoices: &mut [Invoice], direction: SortDirection) {
+        let directed = |ordering: Ordering| match direction {
+            SortDirection::Asc => ordering,
+            SortDirection::Desc => ordering.reverse(),
+        };
+
+        invoices.sort_by(|a, b| match self {
+            InvoiceOrderBy::CreatedAt => directed(a.created_at.cmp(&b.created_at)),
+            InvoiceOrderBy::Amount => directed(a.amount.cmp(&b.amount)),
+            InvoiceOrderBy::PaidAt => match (a.paid_at, b.paid_at) {
+                (Some(a), Some(b)) => directed(a.cmp(&b)),
+                (a, b) => a.is_none().cmp(&b.is_none()),
+            },
+        });
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::{TimeDelta, Utc};
+    use rust_decimal_macros::dec;
+
+    use super::*;
+    use crate::fixtures;
+
+    #[test]
+    fn test_sort_invoices() {
+        let now = Utc::now();
+        let invoices: Vec<Invoice> = [(1, dec!(5), Some(2)), (2, dec!(20), None), (3, dec!(1), Some(1))]
+            .into_iter()
+            .map(|(id, amount, paid_mins_ago)| {
+                let mut invoice = fixtures::paid_invoice();
+                invoice.invoice_id = id;
+                invoice.amount = amount;
+                invoice.created_at = now - TimeDelta::minutes(10 - id as i64);
+                invoice.paid_at = paid_mins_ago.map(|mins| now - TimeDelta::minutes(mins));
+                invoice
+            })
+            .collect();
+        let ids = |by: InvoiceOrderBy, direction| {
+            let mut invoices = invoices.clone();
+            by.sort(&mut invoices, direction);
+            invoices.iter().map(|invoice| invoice.invoice_id).collect::<Vec<_>>()
+        };
+
+        assert_eq!(ids(InvoiceOrderBy::CreatedAt, SortDirection::Desc), [3, 2, 1]);
+        assert_eq!(ids(InvoiceOrderBy::Amount, SortDirection::Asc), [3, 1, 2]);
+        assert_eq!(ids(InvoiceOrderBy::Amount, SortDirection::Desc), [2, 1, 3]);
+        assert_eq!(ids(InvoiceOrderBy::PaidAt, SortDirection::Asc), [1, 3, 2]);
+        assert_eq!(ids(InvoiceOrderBy::PaidAt, SortDirection::Desc), [3, 1, 2]);
+    }
+}
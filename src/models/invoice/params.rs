@@ -1,9 +1,10 @@
-use rust_decimal::Decimal;
 use serde::Serialize;
 
 use crate::{
-    models::{CryptoCurrencyCode, CurrencyType, FiatCurrencyCode, PayButtonName, SwapToAssets},
-    utils::{serialize_comma_separated_list, serialize_decimal_to_string},
+    error::CryptoBotResult,
+    models::{CryptoCurrencyCode, CurrencyType, FiatCurrencyCode, PayButtonName, PositiveAmount, SwapToAssets},
+    utils::{is_none_or_empty, redact_option, serialize_comma_separated_list},
+    validation::{FieldValidate, InvoiceFieldRules},
 };
 
 use super::InvoiceStatus;
@@ -24,7 +25,7 @@ pub struct GetInvoicesParams {
     /// Optional. List of invoice IDs separated by comma.
     #[serde(
         serialize_with = "serialize_comma_separated_list",
-        skip_serializing_if = "GetInvoicesParams::should_skip_invoice_ids"
+        skip_serializing_if = "is_none_or_empty"
     )]
     pub(crate) invoice_ids: Option<Vec<u64>>,
 
@@ -44,17 +45,12 @@ pub struct GetInvoicesParams {
     pub(crate) count: Option<u16>,
 }
 
-impl GetInvoicesParams {
-    fn should_skip_invoice_ids(ids: &Option<Vec<u64>>) -> bool {
-        !matches!(ids, Some(ids) if !ids.is_empty())
-    }
-}
-
 /* #endregion */
 
 /* #region CreateInvoiceParams */
 
-#[derive(Debug, Serialize)]
+/// Parameters of `createInvoice`. The `Debug` output redacts texts shown to or provided by customers.
+#[derive(Serialize)]
 pub struct CreateInvoiceParams {
     /// Optional. Type of the price, can be "crypto" or "fiat". Defaults to crypto.
     #[serde(skip_serializing_if = "Option::is_none")]
@@ -68,13 +64,12 @@ pub struct CreateInvoiceParams {
     #[serde(skip_serializing_if = "Option::is_none")]
     pub(crate) fiat: Option<FiatCurrencyCode>,
 
-    /// Optional. List of cryptocurrency alphabetic codes separated comma. Assets which can be used to pay the invoice. Available only if currency_type is "crypto". Supported assets: "USDT", "TON", "BTC", "ETH", "LTC", "BNB", "TRX" and "USDC" ("JET" for testnet). Defaults to all currencies.
-    #[serde(skip_serializing_if = "Option::is_none")]
+    /// Optional. List of cryptocurrency alphabetic codes separated comma. Assets which can be used to pay the invoice. Available only if currency_type is "fiat". Supported assets: "USDT", "TON", "BTC", "ETH", "LTC", "BNB", "TRX" and "USDC" ("JET" for testnet). Defaults to all currencies.
+    #[serde(skip_serializing_if = "is_none_or_empty")]
     pub(crate) accept_asset: Option<Vec<CryptoCurrencyCode>>,
 
     /// Amount of the invoice in float. For example: 125.50
-    #[serde(serialize_with = "serialize_decimal_to_string")]
-    pub(crate) amount: Decimal,
+    pub(crate) amount: PositiveAmount,
 
     /// Optional. Description for the invoice. User will see this description when they pay the invoice. Up to 1024 characters.
     #[serde(skip_serializing_if = "Option::is_none")]
@@ -125,6 +120,43 @@ pub struct CreateInvoiceParams {
     pub(crate) expires_in: Option<u32>,
 }
 
+impl FieldValidate for CreateInvoiceParams {
+    fn validate(&self) -> CryptoBotResult<()> {
+        InvoiceFieldRules {
+            amount: self.amount.value(),
+            currency_type: self.currency_type,
+            accept_asset: self.accept_asset.as_deref(),
+            description: self.description.as_deref(),
+            hidden_message: self.hidden_message.as_deref(),
+            paid_btn_url: self.paid_btn_url.as_deref(),
+            payload: self.payload.as_deref(),
+            expires_in: self.expires_in,
+        }
+        .validate()
+    }
+}
+
+impl std::fmt::Debug for CreateInvoiceParams {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("CreateInvoiceParams")
+            .field("currency_type", &self.currency_type)
+            .field("asset", &self.asset)
+            .field("fiat", &self.fiat)
+            .field("accept_asset", &self.accept_asset)
+            .field("amount", &self.amount)
+            .field("description", &redact_option(&self.description))
+            .field("hidden_message", &redact_option(&self.hidden_message))
+            .field("paid_btn_name", &self.paid_btn_name)
+            .field("paid_btn_url", &redact_option(&self.paid_btn_url))
+            .field("swap_to", &self.swap_to)
+            .field("payload", &redact_option(&self.payload))
+            .field("allow_comments", &self.allow_comments)
+            .field("allow_anonymous", &self.allow_anonymous)
+            .field("expires_in", &self.expires_in)
+            .finish()
+    }
+}
+
 /* #endregion */
 
 /* #region DeleteInvoiceParams */
@@ -1,25 +1,40 @@
+#[macro_use]
+mod raw;
+
+mod amount;
+mod app_config;
 mod balance;
+mod capability;
 mod check;
 mod currency;
 mod exchange_rate;
+mod explorer;
 mod invoice;
+mod receipt;
 mod response;
 mod stats;
 mod transfer;
 mod webhook;
 
+pub use amount::*;
+pub use app_config::{ConfigMismatch, ExpectedAppConfig};
 pub use balance::*;
+pub use capability::*;
 pub use check::*;
 pub use currency::*;
 pub use exchange_rate::*;
+pub use explorer::ExplorerLinks;
 pub use invoice::*;
+pub use receipt::Receipt;
+#[cfg(feature = "client")]
+pub(crate) use receipt::ReceiptKey;
 pub use response::*;
 use serde::{Deserialize, Serialize};
 pub use stats::*;
 pub use transfer::*;
 pub use webhook::*;
 
-#[derive(Debug)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
 pub enum APIEndpoint {
     GetMe,
     CreateInvoice,
@@ -54,14 +69,33 @@ impl APIEndpoint {
             APIEndpoint::GetStats => "getStats",
         }
     }
+
+    /// Returns whether sending the request twice has the same effect as sending it once
+    ///
+    /// Reads and deletions are idempotent, and so are transfers since the API accepts each `spend_id`
+    /// only once. Creating an invoice or a check is not: a repeated request creates a second one.
+    pub fn is_idempotent(&self) -> bool {
+        !matches!(self, APIEndpoint::CreateInvoice | APIEndpoint::CreateCheck)
+    }
 }
 
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum Method {
     POST,
     GET,
     DELETE,
 }
 
+impl Method {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Method::POST => "POST",
+            Method::GET => "GET",
+            Method::DELETE => "DELETE",
+        }
+    }
+}
+
 pub struct APIMethod {
     pub endpoint: APIEndpoint,
     pub method: Method,
@@ -81,3 +115,16 @@ pub enum PayButtonName {
 
 pub struct Missing;
 pub struct Set;
+
+/// Tells whether a required builder field has been set, used by the builder getters.
+pub trait FieldState {
+    const IS_SET: bool;
+}
+
+impl FieldState for Missing {
+    const IS_SET: bool = false;
+}
+
+impl FieldState for Set {
+    const IS_SET: bool = true;
+}
@@ -0,0 +1,21 @@
+/// Implements `Deserialize` for a model that keeps the JSON it was deserialized from in its `raw` field
+///
+/// The model derives `Deserialize` with `#[serde(remote = "Self")]` when the `raw-json` feature is
+/// enabled, which turns the derived implementation into an inherent function this one delegates to.
+/// The input is read into a `serde_json::Value` once and the typed view is built from that value.
+macro_rules! deserialize_with_raw {
+    ($model:ty) => {
+        #[cfg(feature = "raw-json")]
+        impl<'de> serde::Deserialize<'de> for $model {
+            fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+            where
+                D: serde::Deserializer<'de>,
+            {
+                let raw = serde_json::Value::deserialize(deserializer)?;
+                let mut model = <$model>::deserialize(&raw).map_err(serde::de::Error::custom)?;
+                model.raw = Some(std::sync::Arc::new(raw));
+                Ok(model)
+            }
+        }
+    };
+}
@@ -0,0 +1,158 @@
+use hmac::{Hmac, Mac};
+use serde::{Deserialize, Serialize};
+#[cfg(feature = "client")]
+use serde_json::Value;
+use sha2::Sha256;
+
+#[cfg(feature = "client")]
+use super::APIEndpoint;
+
+/// Signed record of a created invoice or transfer, for internal audit logs
+///
+/// Attached to the result of `create_invoice` and `transfer` when `ClientBuilder::receipt_key` is set.
+/// `body` is the canonical JSON of the result returned by the API: object keys sorted and no
+/// whitespace, so the same result always produces the same bytes. Store the receipt as is and
+/// call [`Receipt::verify`] with the same key to detect records changed after the fact.
+///
+/// # Example
+/// ```no_run
+/// use crypto_pay_api::prelude::*;
+///
+/// # async fn example() -> Result<(), CryptoBotError> {
+/// let client = CryptoBot::builder()
+///     .api_token("YOUR_API_TOKEN")
+///     .receipt_key("audit-secret")
+///     .build()?;
+///
+/// let invoice = client.create_invoice().asset(CryptoCurrencyCode::Ton).amount(dec!(10)).execute().await?;
+/// let receipt = invoice.receipt.clone().unwrap();
+/// assert!(receipt.verify(b"audit-secret"));
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Receipt {
+    /// Method that returned the result, e.g. `createInvoice`.
+    pub method: String,
+
+    /// Canonical JSON of the result returned by the API.
+    pub body: String,
+
+    /// Hex encoded HMAC-SHA-256 of `method`, a newline and `body`, keyed with the receipt key.
+    pub signature: String,
+}
+
+impl Receipt {
+    #[cfg(feature = "client")]
+    pub(crate) fn sign(key: &[u8], endpoint: APIEndpoint, result: &Value) -> Self {
+        let method = endpoint.as_str().to_string();
+        let mut body = String::new();
+        write_canonical(result, &mut body);
+        let signature = hex::encode(receipt_mac(key, &method, &body).finalize().into_bytes());
+
+        Self {
+            method,
+            body,
+            signature,
+        }
+    }
+
+    /// Returns true if the receipt was signed with `key` and has not been changed since
+    pub fn verify(&self, key: &[u8]) -> bool {
+        hex::decode(&self.signature).is_ok_and(|signature| {
+            receipt_mac(key, &self.method, &self.body)
+                .verify_slice(&signature)
+                .is_ok()
+        })
+    }
+}
+
+fn receipt_mac(key: &[u8], method: &str, body: &str) -> Hmac<Sha256> {
+    let mut mac = Hmac::<Sha256>::new_from_slice(key).expect("HMAC can take key of any size");
+    mac.update(method.as_bytes());
+    mac.update(b"\n");
+    mac.update(body.as_bytes());
+    mac
+}
+
+/// Writes `value` without whitespace and with the keys of every object sorted
+#[cfg(feature = "client")]
+fn write_canonical(value: &Value, out: &mut String) {
+    match value {
+        Value::Array(items) => {
+            out.push('[');
+            for (i, item) in items.iter().enumerate() {
+                if i > 0 {
+                    out.push(',');
+                }
+                write_canonical(item, out);
+            }
+            out.push(']');
+        }
+        Value::Object(map) => {
+            let mut entries: Vec<(&String, &Value)> = map.iter().collect();
+            entries.sort_by_key(|(key, _)| *key);
+
+            out.push('{');
+            for (i, (key, value)) in entries.into_iter().enumerate() {
+                if i > 0 {
+                    out.push(',');
+                }
+                out.push_str(&Value::String(key.clone()).to_string());
+                out.push(':');
+                write_canonical(value, out);
+            }
+            out.push('}');
+        }
+        scalar => out.push_str(&scalar.to_string()),
+    }
+}
+
+/// Key signing receipts, set with `ClientBuilder::receipt_key`
+#[cfg(feature = "client")]
+#[derive(Clone)]
+pub(crate) struct ReceiptKey(pub(crate) Vec<u8>);
+
+#[cfg(feature = "client")]
+impl std::fmt::Debug for ReceiptKey {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str("ReceiptKey(***)")
+    }
+}
+
+#[cfg(all(test, feature = "client"))]
+mod tests {
+    use serde_json::json;
+
+    use super::*;
+
+    #[test]
+    fn test_receipt_body_is_canonical() {
+        let first = json!({ "b": 1, "a": { "d": [true, null], "c": "x y" } });
+        let second: Value = serde_json::from_str(r#"{"a":{"c":"x y","d":[true,null]},"b":1}"#).unwrap();
+
+        let receipt = Receipt::sign(b"key", APIEndpoint::CreateInvoice, &first);
+        assert_eq!(receipt.body, r#"{"a":{"c":"x y","d":[true,null]},"b":1}"#);
+        assert_eq!(receipt, Receipt::sign(b"key", APIEndpoint::CreateInvoice, &second));
+        assert_eq!(receipt.method, "createInvoice");
+    }
+
+    #[test]
+    fn test_receipt_verify_detects_tampering() {
+        let receipt = Receipt::sign(b"key", APIEndpoint::Transfer, &json!({ "amount": "10" }));
+        assert!(receipt.verify(b"key"));
+        assert!(!receipt.verify(b"other key"));
+
+        let tampered = Receipt {
+            body: r#"{"amount":"100"}"#.to_string(),
+            ..receipt.clone()
+        };
+        assert!(!tampered.verify(b"key"));
+
+        let other_method = Receipt {
+            method: "createInvoice".to_string(),
+            ..receipt
+        };
+        assert!(!other_method.verify(b"key"));
+    }
+}
@@ -1,6 +1,8 @@
 use serde::Deserialize;
 
-use super::{Check, Invoice, Transfer};
+use crate::utils::deserialize_optional_string;
+
+use super::{APIEndpoint, Check, Invoice, Transfer};
 
 #[derive(Debug, Deserialize)]
 pub struct ApiResponse<T> {
@@ -10,7 +12,34 @@ pub struct ApiResponse<T> {
     pub error_code: Option<i32>,
 }
 
-#[derive(Debug, Deserialize)]
+/// The response envelope of an API call, as passed to a hook registered with
+/// `ClientBuilder::on_response`.
+///
+/// It is available for every response that could be parsed as an envelope, whether the
+/// call succeeded or not, before the `result` is extracted.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ResponseEnvelope {
+    /// The endpoint that was called.
+    pub endpoint: APIEndpoint,
+    /// Whether the API reported success.
+    pub ok: bool,
+    /// Error code reported by the API, if any.
+    pub error_code: Option<i32>,
+    /// Error name reported by the API, if any.
+    pub error: Option<String>,
+    /// The raw JSON body of the response.
+    pub raw: String,
+}
+
+impl ResponseEnvelope {
+    /// Parses the raw body into a JSON value
+    pub fn json(&self) -> serde_json::Result<serde_json::Value> {
+        serde_json::from_str(&self.raw)
+    }
+}
+
+#[derive(Debug, Clone, Deserialize)]
+#[non_exhaustive]
 pub struct GetMeResponse {
     /// Unique ID of the application.
     pub app_id: i64,
@@ -20,20 +49,34 @@ pub struct GetMeResponse {
     pub payment_processing_bot_username: String,
     /// Optional. Webhook endpoint for the application.
     #[serde(skip_serializing_if = "Option::is_none")]
+    #[serde(default, deserialize_with = "deserialize_optional_string")]
     pub webhook_endpoint: Option<String>,
+    /// Fields the API returned beyond the documented ones, e.g. permissions added in a later API version.
+    #[serde(flatten)]
+    pub extra: serde_json::Map<String, serde_json::Value>,
+}
+
+impl GetMeResponse {
+    /// Returns the webhook endpoint set in @CryptoBot, or `None` if webhooks are disabled or the endpoint is not a URL
+    pub fn webhook_url(&self) -> Option<url::Url> {
+        url::Url::parse(self.webhook_endpoint.as_deref()?).ok()
+    }
 }
 
 #[derive(Debug, Deserialize)]
+#[non_exhaustive]
 pub struct GetInvoicesResponse {
     pub items: Vec<Invoice>,
 }
 
 #[derive(Debug, Deserialize)]
+#[non_exhaustive]
 pub struct GetTransfersResponse {
     pub items: Vec<Transfer>,
 }
 
 #[derive(Debug, Deserialize)]
+#[non_exhaustive]
 pub struct GetChecksResponse {
     pub items: Vec<Check>,
 }
@@ -8,6 +8,7 @@ use rust_decimal::Decimal;
 use serde::{Deserialize, Serialize};
 
 #[derive(Debug, Deserialize, Serialize)]
+#[non_exhaustive]
 pub struct AppStats {
     /// Total volume of paid invoices in USD.
     #[serde(deserialize_with = "deserialize_decimal")]
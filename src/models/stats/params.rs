@@ -1,4 +1,4 @@
-use chrono::{DateTime, Utc};
+use chrono::{DateTime, FixedOffset, NaiveDate, Utc};
 use serde::Serialize;
 
 #[derive(Debug, Default, Serialize)]
@@ -13,3 +13,61 @@ pub struct GetStatsParams {
     #[serde(skip_serializing_if = "Option::is_none")]
     pub(crate) end_at: Option<DateTime<Utc>>,
 }
+
+/// Point in time accepted by `GetStatsBuilder::start_at` and `end_at`
+///
+/// Converts from `DateTime<Utc>`, `DateTime<FixedOffset>`, `NaiveDate` (midnight UTC) and unix
+/// timestamps in seconds (`i64`).
+///
+/// # Example
+/// ```
+/// use chrono::{NaiveDate, TimeZone, Utc};
+/// use crypto_pay_api::prelude::*;
+///
+/// let from_unix = StatsTime::from(1_700_000_000);
+/// assert_eq!(from_unix.to_datetime(), Utc.timestamp_opt(1_700_000_000, 0).single());
+///
+/// let from_date = StatsTime::from(NaiveDate::from_ymd_opt(2025, 1, 31).unwrap());
+/// assert_eq!(from_date.to_datetime(), Utc.with_ymd_and_hms(2025, 1, 31, 0, 0, 0).single());
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum StatsTime {
+    /// An instant in UTC.
+    At(DateTime<Utc>),
+    /// Seconds since the unix epoch. Checked when the request is executed.
+    UnixSeconds(i64),
+}
+
+impl StatsTime {
+    /// Returns the instant in UTC, or `None` if the unix timestamp is out of range
+    pub fn to_datetime(self) -> Option<DateTime<Utc>> {
+        match self {
+            StatsTime::At(at) => Some(at),
+            StatsTime::UnixSeconds(secs) => DateTime::from_timestamp(secs, 0),
+        }
+    }
+}
+
+impl From<DateTime<Utc>> for StatsTime {
+    fn from(at: DateTime<Utc>) -> Self {
+        StatsTime::At(at)
+    }
+}
+
+impl From<DateTime<FixedOffset>> for StatsTime {
+    fn from(at: DateTime<FixedOffset>) -> Self {
+        StatsTime::At(at.to_utc())
+    }
+}
+
+impl From<NaiveDate> for StatsTime {
+    fn from(date: NaiveDate) -> Self {
+        StatsTime::At(date.and_time(chrono::NaiveTime::MIN).and_utc())
+    }
+}
+
+impl From<i64> for StatsTime {
+    fn from(secs: i64) -> Self {
+        StatsTime::UnixSeconds(secs)
+    }
+}
@@ -0,0 +1,183 @@
+use rust_decimal::Decimal;
+use serde::Serialize;
+
+use crate::{
+    error::{CryptoBotError, CryptoBotResult},
+    models::{
+        Balance, Balances, CryptoCurrencyCode, ExchangeRate, ExchangeRates, FiatCurrencyCode, Rounding,
+        FIAT_DECIMAL_PLACES,
+    },
+};
+
+/// Affordability report for a transfer, returned by `TransferBuilder::check_funds`.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct FundsCheck {
+    /// Asset of the transfer.
+    pub asset: CryptoCurrencyCode,
+
+    /// Amount of the transfer.
+    pub amount: Decimal,
+
+    /// Available balance of the asset. Zero if the app holds no balance in it.
+    pub available: Decimal,
+
+    /// True, if the available balance covers the amount, the fee and the reserve.
+    pub sufficient: bool,
+
+    /// Missing amount in the asset, zero if the balance is sufficient.
+    pub shortfall: Decimal,
+
+    /// Fee charged on top of the amount, if known. Transfers from the app balance are currently free.
+    pub fee: Option<Decimal>,
+
+    /// Balance that must stay available after the payment, e.g. set with `CreateCheckBuilder::balance_reserve`.
+    pub reserve: Decimal,
+
+    /// Amount of the transfer valued in USD, if an exchange rate is available.
+    /// Rounded to whole cents with the rounding of the client.
+    pub amount_usd: Option<Decimal>,
+
+    /// Shortfall valued in USD, if an exchange rate is available.
+    /// Rounded to whole cents with the rounding of the client.
+    pub shortfall_usd: Option<Decimal>,
+}
+
+impl FundsCheck {
+    #[cfg_attr(not(feature = "client"), allow(dead_code))]
+    pub(crate) fn new(
+        asset: CryptoCurrencyCode,
+        amount: Decimal,
+        fee: Option<Decimal>,
+        reserve: Decimal,
+        balances: &[Balance],
+        rates: &[ExchangeRate],
+        rounding: Rounding,
+    ) -> Self {
+        let available = balances.find(&asset).map_or(Decimal::ZERO, |balance| balance.available);
+
+        let required = amount + fee.unwrap_or(Decimal::ZERO) + reserve;
+        let shortfall = (required - available).max(Decimal::ZERO);
+
+        let usd_rate = rates.find_rate(&asset, &FiatCurrencyCode::Usd).map(|rate| rate.rate);
+        let to_usd = |amount: Decimal| usd_rate.map(|rate| rounding.round(amount * rate, FIAT_DECIMAL_PLACES));
+
+        Self {
+            asset,
+            amount,
+            available,
+            sufficient: shortfall.is_zero(),
+            shortfall,
+            fee,
+            reserve,
+            amount_usd: to_usd(amount),
+            shortfall_usd: to_usd(shortfall),
+        }
+    }
+
+    /// Fails with `CryptoBotError::InsufficientBalance` if the balance is not sufficient
+    #[cfg_attr(not(feature = "client"), allow(dead_code))]
+    pub(crate) fn ensure_sufficient(self) -> CryptoBotResult<()> {
+        if self.sufficient {
+            return Ok(());
+        }
+        Err(CryptoBotError::InsufficientBalance {
+            asset: self.asset,
+            required: self.available + self.shortfall,
+            available: self.available,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use rust_decimal_macros::dec;
+
+    use super::*;
+    use crate::utils::test_utils::TestContext;
+
+    fn balance(currency_code: CryptoCurrencyCode, available: Decimal) -> Balance {
+        Balance {
+            currency_code,
+            available,
+            onhold: Decimal::ZERO,
+        }
+    }
+
+    #[test]
+    fn test_funds_check_sufficient() {
+        let balances = [balance(CryptoCurrencyCode::Ton, dec!(100))];
+        let check = FundsCheck::new(
+            CryptoCurrencyCode::Ton,
+            dec!(10),
+            Some(Decimal::ZERO),
+            Decimal::ZERO,
+            &balances,
+            &TestContext::mock_exchange_rates(),
+            Rounding::Ceiling,
+        );
+
+        assert!(check.sufficient);
+        assert_eq!(check.available, dec!(100));
+        assert_eq!(check.shortfall, Decimal::ZERO);
+        // 37.0824926 USD rounded up
+        assert_eq!(check.amount_usd, Some(dec!(37.09)));
+        assert_eq!(check.shortfall_usd, Some(Decimal::ZERO));
+    }
+
+    #[test]
+    fn test_funds_check_shortfall_includes_fee() {
+        let balances = [balance(CryptoCurrencyCode::Ton, dec!(10))];
+        let check = FundsCheck::new(
+            CryptoCurrencyCode::Ton,
+            dec!(10),
+            Some(dec!(0.5)),
+            Decimal::ZERO,
+            &balances,
+            &[],
+            Rounding::default(),
+        );
+
+        assert!(!check.sufficient);
+        assert_eq!(check.shortfall, dec!(0.5));
+        assert_eq!(check.amount_usd, None);
+    }
+
+    #[test]
+    fn test_funds_check_missing_balance() {
+        let check = FundsCheck::new(
+            CryptoCurrencyCode::Usdt,
+            dec!(5),
+            None,
+            Decimal::ZERO,
+            &[],
+            &[],
+            Rounding::default(),
+        );
+
+        assert!(!check.sufficient);
+        assert_eq!(check.available, Decimal::ZERO);
+        assert_eq!(check.shortfall, dec!(5));
+    }
+
+    #[test]
+    fn test_funds_check_reserve() {
+        let balances = [balance(CryptoCurrencyCode::Ton, dec!(100))];
+        let check = FundsCheck::new(
+            CryptoCurrencyCode::Ton,
+            dec!(10),
+            None,
+            dec!(95),
+            &balances,
+            &[],
+            Rounding::default(),
+        );
+
+        assert!(!check.sufficient);
+        assert_eq!(check.shortfall, dec!(5));
+        assert!(matches!(
+            check.ensure_sufficient(),
+            Err(CryptoBotError::InsufficientBalance { required, available, .. })
+                if required == dec!(105) && available == dec!(100)
+        ));
+    }
+}
@@ -1,15 +1,23 @@
+mod funds;
 mod params;
+mod report;
+mod sweep;
 
+pub use funds::*;
 pub use params::*;
+pub use report::*;
+pub use sweep::*;
 
-use super::CryptoCurrencyCode;
+use super::{format_amount, CryptoCurrencyCode, Receipt};
 use chrono::{DateTime, Utc};
 use rust_decimal::Decimal;
 use serde::{Deserialize, Serialize};
 
-use crate::utils::deserialize_decimal;
+use crate::utils::{deserialize_decimal, deserialize_optional_string};
 
 #[derive(Debug, Deserialize)]
+#[cfg_attr(feature = "raw-json", serde(remote = "Self"))]
+#[non_exhaustive]
 pub struct Transfer {
     /// Unique ID for this transfer.
     pub transfer_id: u64,
@@ -34,11 +42,81 @@ pub struct Transfer {
     pub completed_at: DateTime<Utc>,
 
     /// Optional. Comment for this transfer.
+    #[serde(default, deserialize_with = "deserialize_optional_string")]
     pub comment: Option<String>,
+
+    /// Signed receipt of the transfer, set on transfers returned by `transfer` when
+    /// `ClientBuilder::receipt_key` is set.
+    #[serde(skip)]
+    pub receipt: Option<Receipt>,
+
+    /// JSON the transfer was deserialized from, including fields unknown to this version of the library.
+    /// Available with the `raw-json` feature.
+    #[cfg(feature = "raw-json")]
+    #[serde(skip)]
+    pub raw: Option<std::sync::Arc<serde_json::Value>>,
 }
 
+deserialize_with_raw!(Transfer);
+
 #[derive(Debug, Deserialize, Serialize, PartialEq)]
 #[serde(rename_all = "lowercase")]
+#[non_exhaustive]
 pub enum TransferStatus {
     Completed,
 }
+
+/// Single-line summary, e.g. `Transfer #1 10.5 TON to user 123456789 completed`.
+impl std::fmt::Display for Transfer {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "Transfer #{} {} {} to user {} completed",
+            self.transfer_id,
+            format_amount(&self.amount),
+            self.asset.to_string().to_uppercase(),
+            self.user_id
+        )
+    }
+}
+
+/// Default options applied to every transfer made with a client.
+///
+/// Set them once with `ClientBuilder::transfer_defaults`, e.g. to never notify users about
+/// internal settlements. Values set on an individual `TransferBuilder` take precedence.
+///
+/// # Example
+/// ```
+/// use crypto_pay_api::prelude::*;
+///
+/// let client = CryptoBot::builder()
+///     .api_token("YOUR_API_TOKEN")
+///     .transfer_defaults(TransferDefaults {
+///         disable_send_notification: Some(true),
+///         ..Default::default()
+///     })
+///     .build()
+///     .unwrap();
+/// ```
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct TransferDefaults {
+    /// Optional. Default comment for transfers.
+    pub comment: Option<String>,
+
+    /// Optional. Default for not sending the user a notification about the transfer.
+    pub disable_send_notification: Option<bool>,
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::fixtures;
+
+    #[test]
+    fn test_transfer_display() {
+        assert_eq!(
+            fixtures::transfer().to_string(),
+            "Transfer #1 50 USDT to user 123456789 completed"
+        );
+    }
+}
@@ -1,12 +1,11 @@
-use rust_decimal::Decimal;
 use serde::Serialize;
 
 use crate::{
-    models::CryptoCurrencyCode,
-    utils::{serialize_comma_separated_list, serialize_decimal_to_string},
+    models::{CryptoCurrencyCode, PositiveAmount},
+    utils::{is_none_or_empty, redact_option, serialize_comma_separated_list},
 };
 
-#[derive(Debug, Serialize, Default)]
+#[derive(Debug, Clone, Serialize, Default)]
 pub struct GetTransfersParams {
     /// Optional. Cryptocurrency alphabetic code. Supported assets: “USDT”, “TON”, “BTC”, “ETH”, “LTC”, “BNB”, “TRX” and “USDC” (and “JET” for testnet).
     /// Defaults to all currencies.
@@ -16,7 +15,7 @@ pub struct GetTransfersParams {
     /// Optional. List of transfer IDs separated by comma.
     #[serde(
         serialize_with = "serialize_comma_separated_list",
-        skip_serializing_if = "GetTransfersParams::should_skip_transfer_ids"
+        skip_serializing_if = "is_none_or_empty"
     )]
     pub(crate) transfer_ids: Option<Vec<u64>>,
 
@@ -36,13 +35,8 @@ pub struct GetTransfersParams {
     pub(crate) count: Option<u16>,
 }
 
-impl GetTransfersParams {
-    fn should_skip_transfer_ids(ids: &Option<Vec<u64>>) -> bool {
-        !matches!(ids, Some(ids) if !ids.is_empty())
-    }
-}
-
-#[derive(Debug, Serialize)]
+/// Parameters of `transfer`. The `Debug` output redacts the comment shown to the user.
+#[derive(Serialize)]
 pub struct TransferParams {
     /// User ID in Telegram. User must have previously used @CryptoBot (@CryptoTestnetBot for testnet).
     pub(crate) user_id: u64,
@@ -53,8 +47,7 @@ pub struct TransferParams {
     /// Amount of the transfer in float.
     /// The minimum and maximum amount limits for each of the supported assets roughly correspond to 1-25000 USD.
     /// Use getExchangeRates to convert amounts. For example: 125.50
-    #[serde(serialize_with = "serialize_decimal_to_string")]
-    pub(crate) amount: Decimal,
+    pub(crate) amount: PositiveAmount,
 
     /// Random UTF-8 string unique per transfer for idempotent requests.
     /// The same spend_id can be accepted only once from your app.
@@ -72,3 +65,16 @@ pub struct TransferParams {
     #[serde(skip_serializing_if = "Option::is_none")]
     pub(crate) disable_send_notification: Option<bool>,
 }
+
+impl std::fmt::Debug for TransferParams {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("TransferParams")
+            .field("user_id", &self.user_id)
+            .field("asset", &self.asset)
+            .field("amount", &self.amount)
+            .field("spend_id", &self.spend_id)
+            .field("comment", &redact_option(&self.comment))
+            .field("disable_send_notification", &self.disable_send_notification)
+            .finish()
+    }
+}
@@ -0,0 +1,209 @@
+use std::collections::BTreeMap;
+
+use chrono::NaiveDate;
+use serde::Serialize;
+
+use crate::models::{asset_totals, AssetTotal, CryptoCurrencyCode};
+
+use super::Transfer;
+
+/// Transfers sent to one user.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct UserTotal {
+    /// Telegram user ID the transfers were sent to.
+    pub user_id: u64,
+
+    /// Totals per asset, in order of first appearance.
+    pub totals: Vec<AssetTotal>,
+
+    /// Number of transfers across all assets.
+    pub count: usize,
+}
+
+/// Number of transfers completed on one day.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct DailyCount {
+    /// Day the transfers were completed, in UTC.
+    pub date: NaiveDate,
+
+    /// Number of transfers.
+    pub count: usize,
+}
+
+/// Reporting helpers for a list of transfers, as returned by `get_transfers`.
+///
+/// Amounts are summed as `Decimal`, so totals are exact. Amounts in different assets are never added up.
+///
+/// # Example
+/// ```
+/// # #[cfg(feature = "fixtures")] {
+/// use crypto_pay_api::{fixtures, prelude::*};
+///
+/// let transfers = vec![fixtures::transfer()];
+///
+/// let totals = transfers.totals_by_asset();
+/// assert_eq!(totals[0].asset, CryptoCurrencyCode::Usdt);
+/// assert_eq!(totals[0].count, 1);
+/// # }
+/// ```
+pub trait Transfers {
+    /// Returns the totals per asset, in order of first appearance.
+    fn totals_by_asset(&self) -> Vec<AssetTotal>;
+
+    /// Returns the totals per user, ordered by user ID.
+    fn totals_by_user(&self) -> Vec<UserTotal>;
+
+    /// Returns the number of transfers per day of completion, ordered by date. Days without transfers are omitted.
+    fn count_by_day(&self) -> Vec<DailyCount>;
+
+    /// Returns up to `limit` transfers in the given asset, largest amount first.
+    fn largest(&self, asset: &CryptoCurrencyCode, limit: usize) -> Vec<&Transfer>;
+}
+
+impl Transfers for [Transfer] {
+    fn totals_by_asset(&self) -> Vec<AssetTotal> {
+        asset_totals(self.iter().map(|transfer| (&transfer.asset, transfer.amount)))
+    }
+
+    fn totals_by_user(&self) -> Vec<UserTotal> {
+        let mut by_user: BTreeMap<u64, Vec<AssetTotal>> = BTreeMap::new();
+        for transfer in self {
+            AssetTotal::add_to(
+                by_user.entry(transfer.user_id).or_default(),
+                &transfer.asset,
+                transfer.amount,
+            );
+        }
+
+        by_user
+            .into_iter()
+            .map(|(user_id, totals)| UserTotal {
+                user_id,
+                count: totals.iter().map(|total| total.count).sum(),
+                totals,
+            })
+            .collect()
+    }
+
+    fn count_by_day(&self) -> Vec<DailyCount> {
+        let mut dates: Vec<NaiveDate> = self.iter().map(|transfer| transfer.completed_at.date_naive()).collect();
+        dates.sort_unstable();
+
+        let mut counts: Vec<DailyCount> = Vec::new();
+        for date in dates {
+            match counts.last_mut() {
+                Some(last) if last.date == date => last.count += 1,
+                _ => counts.push(DailyCount { date, count: 1 }),
+            }
+        }
+        counts
+    }
+
+    fn largest(&self, asset: &CryptoCurrencyCode, limit: usize) -> Vec<&Transfer> {
+        let mut transfers: Vec<&Transfer> = self.iter().filter(|transfer| transfer.asset == *asset).collect();
+        transfers.sort_by_key(|transfer| std::cmp::Reverse(transfer.amount));
+        transfers.truncate(limit);
+        transfers
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::{TimeZone, Utc};
+    use rust_decimal_macros::dec;
+
+    use super::*;
+    use crate::fixtures;
+
+    fn transfers() -> Vec<Transfer> {
+        let day = |day| Utc.with_ymd_and_hms(2025, 2, day, 12, 0, 0).unwrap();
+        vec![
+            Transfer {
+                transfer_id: 1,
+                user_id: 2,
+                asset: CryptoCurrencyCode::Ton,
+                amount: dec!(0.1),
+                completed_at: day(1),
+                ..fixtures::transfer()
+            },
+            Transfer {
+                transfer_id: 2,
+                user_id: 1,
+                asset: CryptoCurrencyCode::Usdt,
+                amount: dec!(5),
+                completed_at: day(1),
+                ..fixtures::transfer()
+            },
+            Transfer {
+                transfer_id: 3,
+                user_id: 2,
+                asset: CryptoCurrencyCode::Ton,
+                amount: dec!(0.2),
+                completed_at: day(3),
+                ..fixtures::transfer()
+            },
+            Transfer {
+                transfer_id: 4,
+                user_id: 2,
+                asset: CryptoCurrencyCode::Usdt,
+                amount: dec!(2.5),
+                completed_at: day(3),
+                ..fixtures::transfer()
+            },
+        ]
+    }
+
+    #[test]
+    fn test_totals_by_asset() {
+        let totals = transfers().totals_by_asset();
+
+        assert_eq!(
+            totals,
+            vec![
+                AssetTotal {
+                    asset: CryptoCurrencyCode::Ton,
+                    total: dec!(0.3),
+                    count: 2,
+                },
+                AssetTotal {
+                    asset: CryptoCurrencyCode::Usdt,
+                    total: dec!(7.5),
+                    count: 2,
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn test_totals_by_user() {
+        let totals = transfers().totals_by_user();
+
+        assert_eq!(totals.len(), 2);
+        assert_eq!(totals[0].user_id, 1);
+        assert_eq!(totals[0].count, 1);
+        assert_eq!(totals[1].user_id, 2);
+        assert_eq!(totals[1].count, 3);
+        assert_eq!(totals[1].totals[0].total, dec!(0.3));
+        assert_eq!(totals[1].totals[1].total, dec!(2.5));
+    }
+
+    #[test]
+    fn test_count_by_day_and_largest() {
+        let transfers = transfers();
+
+        let days: Vec<(u32, usize)> = transfers
+            .count_by_day()
+            .iter()
+            .map(|day| (chrono::Datelike::day(&day.date), day.count))
+            .collect();
+        assert_eq!(days, vec![(1, 2), (3, 2)]);
+
+        let largest: Vec<u64> = transfers
+            .largest(&CryptoCurrencyCode::Usdt, 1)
+            .iter()
+            .map(|transfer| transfer.transfer_id)
+            .collect();
+        assert_eq!(largest, vec![2]);
+        assert!(transfers.largest(&CryptoCurrencyCode::Btc, 3).is_empty());
+    }
+}
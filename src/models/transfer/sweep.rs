@@ -0,0 +1,54 @@
+use rust_decimal::Decimal;
+
+use crate::{
+    error::CryptoBotError,
+    models::{format_amount, CryptoCurrencyCode},
+};
+
+use super::Transfer;
+
+/// A single transfer of a balance sweep, returned by `SweepBuilder::execute`.
+#[derive(Debug)]
+pub struct SweepItem {
+    /// Swept asset.
+    pub asset: CryptoCurrencyCode,
+
+    /// Amount transferred, i.e. the available balance rounded down to the precision of the asset.
+    pub amount: Decimal,
+
+    /// Spend ID of the transfer. It is the same for every run with the same sweep ID.
+    pub spend_id: String,
+
+    /// What happened to the transfer.
+    pub outcome: SweepOutcome,
+}
+
+/// Outcome of a single sweep transfer.
+#[derive(Debug)]
+pub enum SweepOutcome {
+    /// Dry run, the transfer was not sent.
+    Planned,
+    /// The transfer was made.
+    Transferred(Transfer),
+    /// The transfer failed, e.g. because this sweep ID was already used for the asset.
+    Failed(CryptoBotError),
+}
+
+/// Single-line summary, e.g. `TON 100.5 -> planned (sweep-20250208-123-TON)`.
+impl std::fmt::Display for SweepItem {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let outcome = match &self.outcome {
+            SweepOutcome::Planned => "planned".to_string(),
+            SweepOutcome::Transferred(transfer) => format!("transfer #{}", transfer.transfer_id),
+            SweepOutcome::Failed(error) => format!("failed: {error}"),
+        };
+        write!(
+            f,
+            "{} {} -> {} ({})",
+            self.asset.to_string().to_uppercase(),
+            format_amount(&self.amount),
+            outcome,
+            self.spend_id
+        )
+    }
+}
@@ -1,34 +1,254 @@
-use serde::{Deserialize, Serialize};
+use chrono::{SecondsFormat, Utc};
+use rust_decimal::Decimal;
+use serde::{de::Error as _, Deserialize, Deserializer, Serialize};
 
-use super::Invoice;
+use super::{Check, Invoice};
 
-#[derive(Debug, Deserialize, Clone, PartialEq, Eq)]
-#[serde(rename_all = "snake_case")]
+/// Type of a webhook update.
+///
+/// Types unknown to this version of the library are kept as `Unknown` instead of failing
+/// deserialization, so new server-side update types can be ignored gracefully.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+#[non_exhaustive]
 pub enum UpdateType {
-    #[serde(rename = "invoice_paid")]
+    /// An invoice was paid.
     InvoicePaid,
+    /// A check was activated.
+    CheckActivated,
+    /// An update type not known to this version of the library.
+    Unknown(String),
 }
-#[derive(Debug, Deserialize)]
+
+impl UpdateType {
+    /// Returns the update type as sent by the API, e.g. `invoice_paid`
+    pub fn as_str(&self) -> &str {
+        match self {
+            UpdateType::InvoicePaid => "invoice_paid",
+            UpdateType::CheckActivated => "check_activated",
+            UpdateType::Unknown(update_type) => update_type,
+        }
+    }
+}
+
+impl From<&str> for UpdateType {
+    fn from(value: &str) -> Self {
+        match value {
+            "invoice_paid" => UpdateType::InvoicePaid,
+            "check_activated" => UpdateType::CheckActivated,
+            other => UpdateType::Unknown(other.to_string()),
+        }
+    }
+}
+
+impl std::fmt::Display for UpdateType {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+impl<'de> Deserialize<'de> for UpdateType {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let value = String::deserialize(deserializer)?;
+        Ok(UpdateType::from(value.as_str()))
+    }
+}
+
+#[derive(Debug, Clone)]
+#[non_exhaustive]
 pub struct WebhookUpdate {
     pub update_id: i64,
     pub update_type: UpdateType,
     pub request_date: String,
     pub payload: WebhookPayload,
+    /// USD value of the paid invoice, attached by the webhook handler when USD enrichment is enabled.
+    pub paid_usd_amount: Option<Decimal>,
+}
+
+impl WebhookUpdate {
+    /// Creates an update carrying `payload`, dated now, e.g. to test an update handler
+    ///
+    /// The update type follows the payload. Set `request_date` to test expiration.
+    pub fn new(update_id: i64, payload: WebhookPayload) -> Self {
+        let update_type = match &payload {
+            WebhookPayload::InvoicePaid(_) => UpdateType::InvoicePaid,
+            WebhookPayload::CheckActivated(_) => UpdateType::CheckActivated,
+            WebhookPayload::Unknown(_) => UpdateType::Unknown("unknown".to_string()),
+        };
+
+        Self {
+            update_id,
+            update_type,
+            request_date: Utc::now().to_rfc3339_opts(SecondsFormat::Millis, true),
+            payload,
+            paid_usd_amount: None,
+        }
+    }
+
+    /// Returns the invoice carried by the update, if any
+    pub fn invoice(&self) -> Option<&Invoice> {
+        match &self.payload {
+            WebhookPayload::InvoicePaid(invoice) => Some(invoice),
+            _ => None,
+        }
+    }
+
+    /// Returns the check carried by the update, if any
+    pub fn check(&self) -> Option<&Check> {
+        match &self.payload {
+            WebhookPayload::CheckActivated(check) => Some(check),
+            _ => None,
+        }
+    }
+}
+
+impl<'de> Deserialize<'de> for WebhookUpdate {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        #[derive(Deserialize)]
+        struct RawUpdate {
+            update_id: i64,
+            update_type: UpdateType,
+            request_date: String,
+            payload: serde_json::Value,
+        }
+
+        let raw = RawUpdate::deserialize(deserializer)?;
+        let payload = match &raw.update_type {
+            UpdateType::InvoicePaid => {
+                WebhookPayload::InvoicePaid(serde_json::from_value(raw.payload).map_err(D::Error::custom)?)
+            }
+            UpdateType::CheckActivated => {
+                WebhookPayload::CheckActivated(serde_json::from_value(raw.payload).map_err(D::Error::custom)?)
+            }
+            UpdateType::Unknown(_) => WebhookPayload::Unknown(raw.payload),
+        };
+
+        Ok(WebhookUpdate {
+            update_id: raw.update_id,
+            update_type: raw.update_type,
+            request_date: raw.request_date,
+            payload,
+            paid_usd_amount: None,
+        })
+    }
 }
 
-#[derive(Debug, Deserialize, Clone)]
-#[serde(untagged)]
+/// Payload of a webhook update, matching its [`UpdateType`].
+#[derive(Debug, Clone)]
+#[non_exhaustive]
+#[allow(clippy::large_enum_variant)] // invoices are by far the most common payload
 pub enum WebhookPayload {
     InvoicePaid(Invoice),
+    CheckActivated(Check),
+    /// Raw payload of an update type not known to this version of the library.
+    Unknown(serde_json::Value),
 }
 
 #[derive(Debug, Serialize)]
 pub struct WebhookResponse {
+    /// True, if every update in the request was handled successfully.
     pub ok: bool,
+    /// True, if the update was already handled and was skipped. Only set for single updates.
+    #[serde(skip_serializing_if = "std::ops::Not::not")]
+    pub duplicate: bool,
+    /// Outcome of each update, only filled in for batched requests.
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub results: Vec<WebhookUpdateResult>,
 }
 
 impl WebhookResponse {
     pub fn ok() -> Self {
-        Self { ok: true }
+        Self {
+            ok: true,
+            duplicate: false,
+            results: Vec::new(),
+        }
+    }
+
+    /// Acknowledges an update that was already handled, so Crypto Bot stops redelivering it
+    ///
+    /// Reply with a 2xx status like for `ok`; `duplicate` tells the two apart for logging.
+    pub fn ok_duplicate() -> Self {
+        Self {
+            duplicate: true,
+            ..Self::ok()
+        }
+    }
+
+    /// Aggregates the outcomes of a batched request
+    pub fn batch(results: Vec<WebhookUpdateResult>) -> Self {
+        Self {
+            ok: results.iter().all(|result| result.ok),
+            duplicate: false,
+            results,
+        }
+    }
+}
+
+/// Outcome of a single update of a batched webhook request.
+#[derive(Debug, Serialize)]
+pub struct WebhookUpdateResult {
+    /// ID of the update, if it could be read.
+    pub update_id: Option<i64>,
+    /// True, if the update was handled successfully.
+    pub ok: bool,
+    /// True, if the update was already handled and was skipped.
+    #[serde(skip_serializing_if = "std::ops::Not::not")]
+    pub duplicate: bool,
+    /// Error message, if the update failed.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::fixtures;
+
+    #[test]
+    fn test_webhook_update_new() {
+        let update = WebhookUpdate::new(7, WebhookPayload::CheckActivated(fixtures::check()));
+        assert_eq!(update.update_type, UpdateType::CheckActivated);
+        assert!(update.check().is_some());
+        assert!(chrono::DateTime::parse_from_rfc3339(&update.request_date).is_ok());
+
+        let update = WebhookUpdate::new(8, WebhookPayload::Unknown(serde_json::json!({})));
+        assert!(matches!(update.update_type, UpdateType::Unknown(_)));
+    }
+
+    #[test]
+    fn test_update_type_round_trip() {
+        for update_type in ["invoice_paid", "check_activated", "invoice_refunded"] {
+            let parsed: UpdateType = serde_json::from_str(&format!("\"{update_type}\"")).unwrap();
+            assert_eq!(parsed.as_str(), update_type);
+        }
+
+        assert_eq!(UpdateType::from("invoice_paid"), UpdateType::InvoicePaid);
+        assert_eq!(
+            UpdateType::from("invoice_refunded"),
+            UpdateType::Unknown("invoice_refunded".to_string())
+        );
+    }
+
+    #[test]
+    fn test_check_activated_update() {
+        let body = format!(
+            r#"{{"update_id": 2, "update_type": "check_activated", "request_date": "2025-02-08T12:13:45.341Z", "payload": {}}}"#,
+            fixtures::CHECK
+        );
+        let update: WebhookUpdate = serde_json::from_str(&body).unwrap();
+
+        assert_eq!(update.update_type, UpdateType::CheckActivated);
+        assert_eq!(update.check().map(|check| check.check_id), Some(123));
+        assert!(update.invoice().is_none());
+    }
+
+    #[test]
+    fn test_unknown_update_keeps_raw_payload() {
+        let body = r#"{"update_id": 3, "update_type": "invoice_refunded", "request_date": "2025-02-08T12:13:45.341Z", "payload": {"invoice_id": 1}}"#;
+        let update: WebhookUpdate = serde_json::from_str(body).unwrap();
+
+        assert_eq!(update.update_type, UpdateType::Unknown("invoice_refunded".to_string()));
+        assert!(matches!(&update.payload, WebhookPayload::Unknown(payload) if payload["invoice_id"] == 1));
+        assert!(update.invoice().is_none());
     }
 }
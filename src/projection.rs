@@ -0,0 +1,266 @@
+//! Durable invoice state in Postgres or SQLite, projected from webhook updates
+//!
+//! Available with the `sqlx` feature. [`InvoiceProjection`] upserts invoices into the
+//! `crypto_pay_invoices` table through a [`sqlx::AnyPool`], from webhook updates as they arrive and
+//! from periodic reconciliation of invoices still recorded as active, in case an update was missed.
+//! It is meant as a reference implementation: copy [`INVOICE_PROJECTION_SCHEMA`] into your own
+//! migrations and adapt it as needed.
+//!
+//! # Example
+//! ```no_run
+//! use crypto_pay_api::prelude::*;
+//! use crypto_pay_api::projection::InvoiceProjection;
+//! use sqlx::any::{install_default_drivers, AnyPoolOptions};
+//!
+//! # async fn example(client: CryptoBot, update: WebhookUpdate) -> Result<(), CryptoBotError> {
+//! install_default_drivers();
+//! let pool = AnyPoolOptions::new().connect("postgres://localhost/shop").await?;
+//! let projection = InvoiceProjection::new(pool);
+//! projection.migrate().await?;
+//!
+//! // In the webhook handler
+//! projection.apply(&update).await?;
+//!
+//! // Periodically, to catch invoices whose update was lost
+//! projection.reconcile(&client).await?;
+//! # Ok(())
+//! # }
+//! ```
+
+use chrono::Utc;
+use sqlx::AnyPool;
+
+use crate::{
+    api::InvoiceAPI,
+    client::CryptoBot,
+    error::CryptoBotResult,
+    models::{Invoice, InvoiceStatus, WebhookUpdate},
+};
+
+/// Schema of the table written by [`InvoiceProjection`], valid for both Postgres and SQLite
+///
+/// Amounts are stored as text to keep their exact decimal value, and dates as RFC 3339 text.
+pub const INVOICE_PROJECTION_SCHEMA: &str = "CREATE TABLE IF NOT EXISTS crypto_pay_invoices (
+    invoice_id BIGINT PRIMARY KEY,
+    hash TEXT NOT NULL,
+    status TEXT NOT NULL,
+    currency_type TEXT NOT NULL,
+    asset TEXT,
+    fiat TEXT,
+    amount TEXT NOT NULL,
+    paid_asset TEXT,
+    paid_amount TEXT,
+    paid_usd_rate TEXT,
+    payload TEXT,
+    created_at TEXT NOT NULL,
+    paid_at TEXT,
+    updated_at TEXT NOT NULL
+)";
+
+// A stored paid or expired invoice is final, so a stale `active` state never overwrites it.
+const UPSERT_INVOICE: &str = "INSERT INTO crypto_pay_invoices (
+    invoice_id, hash, status, currency_type, asset, fiat, amount, paid_asset, paid_amount,
+    paid_usd_rate, payload, created_at, paid_at, updated_at
+) VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10, $11, $12, $13, $14)
+ON CONFLICT (invoice_id) DO UPDATE SET
+    status = excluded.status,
+    paid_asset = excluded.paid_asset,
+    paid_amount = excluded.paid_amount,
+    paid_usd_rate = excluded.paid_usd_rate,
+    paid_at = excluded.paid_at,
+    updated_at = excluded.updated_at
+WHERE crypto_pay_invoices.status = 'active'";
+
+/// Projects invoices into the `crypto_pay_invoices` table
+#[derive(Debug, Clone)]
+pub struct InvoiceProjection {
+    pool: AnyPool,
+}
+
+impl InvoiceProjection {
+    /// Creates a projection writing to `pool`
+    ///
+    /// Call `sqlx::any::install_default_drivers` before connecting the pool.
+    pub fn new(pool: AnyPool) -> Self {
+        Self { pool }
+    }
+
+    /// Creates the `crypto_pay_invoices` table if it does not exist
+    pub async fn migrate(&self) -> CryptoBotResult<()> {
+        sqlx::query(INVOICE_PROJECTION_SCHEMA).execute(&self.pool).await?;
+        Ok(())
+    }
+
+    /// Upserts the invoice carried by a webhook update
+    ///
+    /// Returns false if the update carries no invoice or did not change the stored state, e.g. when
+    /// the update is delivered again.
+    pub async fn apply(&self, update: &WebhookUpdate) -> CryptoBotResult<bool> {
+        match update.invoice() {
+            Some(invoice) => self.upsert(invoice).await,
+            None => Ok(false),
+        }
+    }
+
+    /// Upserts an invoice, e.g. right after creating it
+    ///
+    /// Returns false if the stored invoice is already paid or expired and was left unchanged.
+    pub async fn upsert(&self, invoice: &Invoice) -> CryptoBotResult<bool> {
+        let result = sqlx::query(UPSERT_INVOICE)
+            .bind(invoice.invoice_id as i64)
+            .bind(invoice.hash.clone())
+            .bind(invoice.status.to_string())
+            .bind(invoice.currency_type.to_string())
+            .bind(invoice.asset.as_ref().map(ToString::to_string))
+            .bind(invoice.fiat.as_ref().map(ToString::to_string))
+            .bind(invoice.amount.to_string())
+            .bind(invoice.paid_asset.as_ref().map(ToString::to_string))
+            .bind(invoice.paid_amount.map(|amount| amount.to_string()))
+            .bind(invoice.paid_usd_rate.map(|rate| rate.to_string()))
+            .bind(invoice.app_payload().map(str::to_string))
+            .bind(invoice.created_at.to_rfc3339())
+            .bind(invoice.paid_at.map(|paid_at| paid_at.to_rfc3339()))
+            .bind(Utc::now().to_rfc3339())
+            .execute(&self.pool)
+            .await?;
+
+        Ok(result.rows_affected() > 0)
+    }
+
+    /// Returns the stored status of an invoice, or `None` if it is not projected
+    pub async fn status(&self, invoice_id: u64) -> CryptoBotResult<Option<InvoiceStatus>> {
+        let status: Option<String> = sqlx::query_scalar("SELECT status FROM crypto_pay_invoices WHERE invoice_id = $1")
+            .bind(invoice_id as i64)
+            .fetch_optional(&self.pool)
+            .await?;
+
+        Ok(status.and_then(|status| parse_status(&status)))
+    }
+
+    /// Re-fetches the invoices stored as active and upserts them
+    ///
+    /// Returns the number of invoices whose stored state changed.
+    pub async fn reconcile(&self, client: &CryptoBot) -> CryptoBotResult<usize> {
+        let ids: Vec<i64> = sqlx::query_scalar("SELECT invoice_id FROM crypto_pay_invoices WHERE status = 'active'")
+            .fetch_all(&self.pool)
+            .await?;
+        if ids.is_empty() {
+            return Ok(0);
+        }
+
+        let invoices = client
+            .get_invoices_by_ids(ids.into_iter().map(|id| id as u64).collect())
+            .await?;
+
+        let mut changed = 0;
+        for invoice in invoices
+            .iter()
+            .filter(|invoice| invoice.status != InvoiceStatus::Active)
+        {
+            if self.upsert(invoice).await? {
+                changed += 1;
+            }
+        }
+        Ok(changed)
+    }
+}
+
+fn parse_status(status: &str) -> Option<InvoiceStatus> {
+    match status {
+        "active" => Some(InvoiceStatus::Active),
+        "paid" => Some(InvoiceStatus::Paid),
+        "expired" => Some(InvoiceStatus::Expired),
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use sqlx::any::{install_default_drivers, AnyPoolOptions};
+
+    use super::*;
+    use crate::{fixtures, utils::test_utils::TestContext};
+
+    fn projection(ctx: &TestContext) -> InvoiceProjection {
+        install_default_drivers();
+        let pool = ctx
+            .run(AnyPoolOptions::new().max_connections(1).connect("sqlite::memory:"))
+            .unwrap();
+        let projection = InvoiceProjection::new(pool);
+        ctx.run(projection.migrate()).unwrap();
+        projection
+    }
+
+    fn invoice(status: InvoiceStatus) -> Invoice {
+        Invoice {
+            status,
+            ..fixtures::paid_invoice()
+        }
+    }
+
+    #[test]
+    fn test_upsert_keeps_final_status() {
+        let ctx = TestContext::new();
+        let projection = projection(&ctx);
+        let invoice_id = fixtures::paid_invoice().invoice_id;
+
+        assert!(ctx.run(projection.upsert(&invoice(InvoiceStatus::Active))).unwrap());
+        assert!(ctx.run(projection.upsert(&invoice(InvoiceStatus::Paid))).unwrap());
+        assert_eq!(
+            ctx.run(projection.status(invoice_id)).unwrap(),
+            Some(InvoiceStatus::Paid)
+        );
+
+        // A stale state or a redelivered update leaves the paid invoice unchanged
+        assert!(!ctx.run(projection.upsert(&invoice(InvoiceStatus::Active))).unwrap());
+        assert!(!ctx.run(projection.upsert(&invoice(InvoiceStatus::Paid))).unwrap());
+        assert_eq!(
+            ctx.run(projection.status(invoice_id)).unwrap(),
+            Some(InvoiceStatus::Paid)
+        );
+        assert_eq!(ctx.run(projection.status(invoice_id + 1)).unwrap(), None);
+    }
+
+    #[test]
+    fn test_reconcile_updates_active_invoices() {
+        let mut ctx = TestContext::new();
+        let projection = projection(&ctx);
+        let invoice_id = fixtures::paid_invoice().invoice_id;
+        ctx.run(projection.upsert(&invoice(InvoiceStatus::Active))).unwrap();
+
+        let _m = ctx
+            .server
+            .mock("GET", "/getInvoices")
+            .with_body(fixtures::api_response(&format!(
+                r#"{{"items": [{}]}}"#,
+                fixtures::PAID_INVOICE
+            )))
+            .create();
+        let client = CryptoBot::builder()
+            .api_token("test_token")
+            .base_url(ctx.server.url())
+            .build()
+            .unwrap();
+
+        assert_eq!(ctx.run(projection.reconcile(&client)).unwrap(), 1);
+        assert_eq!(
+            ctx.run(projection.status(invoice_id)).unwrap(),
+            Some(InvoiceStatus::Paid)
+        );
+        assert_eq!(ctx.run(projection.reconcile(&client)).unwrap(), 0);
+    }
+
+    #[test]
+    fn test_apply_webhook_update() {
+        let ctx = TestContext::new();
+        let projection = projection(&ctx);
+        let update = fixtures::webhook_update();
+        let invoice_id = update.invoice().unwrap().invoice_id;
+
+        assert!(ctx.run(projection.apply(&update)).unwrap());
+        assert_eq!(
+            ctx.run(projection.status(invoice_id)).unwrap(),
+            Some(InvoiceStatus::Paid)
+        );
+    }
+}
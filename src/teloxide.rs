@@ -0,0 +1,221 @@
+//! Glue for bots built with [teloxide](https://docs.rs/teloxide)
+//!
+//! Available with the `teloxide` feature. The helpers build on `teloxide-core` types, which
+//! teloxide re-exports as `teloxide::types` and `teloxide::payloads`.
+//!
+//! # Example
+//! ```no_run
+//! use crypto_pay_api::prelude::*;
+//! use crypto_pay_api::teloxide::{pay_keyboard, PaymentFlow};
+//!
+//! # async fn example(client: CryptoBot) -> Result<(), CryptoBotError> {
+//! let invoice = client.create_invoice().amount(dec!(10)).asset(CryptoCurrencyCode::Ton).execute().await?;
+//!
+//! // Attach to the message asking the user to pay
+//! let keyboard = pay_keyboard(&invoice, "Pay 10 TON", PayUrlPreference::MiniApp)?;
+//!
+//! // Keep in the dialogue state until the webhook update arrives
+//! let flow = PaymentFlow::start(&invoice, PayUrlPreference::MiniApp);
+//! # Ok(())
+//! # }
+//! ```
+
+use crate::{
+    error::CryptoBotResult,
+    models::{Invoice, InvoiceStatus, PayUrlPreference, WebhookPayload, WebhookUpdate},
+    validation::parse_url,
+};
+use serde::{Deserialize, Serialize};
+use teloxide_core::{
+    payloads::SendMessage,
+    types::{InlineKeyboardButton, InlineKeyboardMarkup, Recipient},
+};
+
+/// Returns the payment URL of an invoice matching the preference
+fn pay_url(invoice: &Invoice, preference: PayUrlPreference) -> &str {
+    match preference {
+        PayUrlPreference::Bot => &invoice.bot_invoice_url,
+        PayUrlPreference::MiniApp => &invoice.mini_app_invoice_url,
+        PayUrlPreference::WebApp => &invoice.web_app_invoice_url,
+    }
+}
+
+/// Builds an inline keyboard button opening the payment URL of an invoice
+///
+/// # Errors
+/// Returns a validation error if the payment URL is not a valid URL.
+pub fn pay_button(
+    invoice: &Invoice,
+    text: impl Into<String>,
+    preference: PayUrlPreference,
+) -> CryptoBotResult<InlineKeyboardButton> {
+    let url = parse_url(pay_url(invoice, preference), "pay_url")?;
+    Ok(InlineKeyboardButton::url(text.into(), url))
+}
+
+/// Builds an inline keyboard with a single payment button for an invoice
+pub fn pay_keyboard(
+    invoice: &Invoice,
+    text: impl Into<String>,
+    preference: PayUrlPreference,
+) -> CryptoBotResult<InlineKeyboardMarkup> {
+    Ok(InlineKeyboardMarkup::new([[pay_button(invoice, text, preference)?]]))
+}
+
+/// Returns the text of a notification about a webhook update, e.g. `Invoice #1 10.5 TON paid`
+///
+/// Returns `None` for update types that do not warrant a notification.
+pub fn notification_text(update: &WebhookUpdate) -> Option<String> {
+    match &update.payload {
+        WebhookPayload::InvoicePaid(invoice) => Some(invoice.to_string()),
+        WebhookPayload::CheckActivated(check) => Some(check.to_string()),
+        _ => None,
+    }
+}
+
+/// Converts a webhook update into a `sendMessage` request notifying `chat_id`
+///
+/// Returns `None` for update types that do not warrant a notification. Send it with
+/// `bot.send_message(payload.chat_id, payload.text)` or any teloxide requester.
+pub fn notification(update: &WebhookUpdate, chat_id: impl Into<Recipient>) -> Option<SendMessage> {
+    notification_text(update).map(|text| SendMessage::new(chat_id, text))
+}
+
+/// State of a payment within a bot dialogue.
+///
+/// The state is serializable, so it can be kept in any teloxide dialogue storage. Start it with
+/// [`PaymentFlow::start`] once the invoice is created, then advance it with webhook updates or
+/// polled invoices.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub enum PaymentFlow {
+    /// No payment in progress.
+    #[default]
+    Idle,
+    /// Waiting for the user to pay the invoice.
+    AwaitingPayment { invoice_id: u64, pay_url: String },
+    /// The invoice was paid.
+    Paid { invoice_id: u64 },
+    /// The invoice expired before it was paid.
+    Expired { invoice_id: u64 },
+}
+
+impl PaymentFlow {
+    /// Starts waiting for the payment of an invoice, keeping the payment URL matching `preference`
+    ///
+    /// Pass the preference used for [`pay_keyboard`], so the flow links to the same URL.
+    pub fn start(invoice: &Invoice, preference: PayUrlPreference) -> Self {
+        Self::AwaitingPayment {
+            invoice_id: invoice.invoice_id,
+            pay_url: pay_url(invoice, preference).to_string(),
+        }
+    }
+
+    /// Returns the ID of the invoice the flow is about, if any
+    pub fn invoice_id(&self) -> Option<u64> {
+        match self {
+            Self::Idle => None,
+            Self::AwaitingPayment { invoice_id, .. } | Self::Paid { invoice_id } | Self::Expired { invoice_id } => {
+                Some(*invoice_id)
+            }
+        }
+    }
+
+    /// Returns true if the flow reached a final state
+    pub fn is_finished(&self) -> bool {
+        matches!(self, Self::Paid { .. } | Self::Expired { .. })
+    }
+
+    /// Advances the flow with a webhook update
+    ///
+    /// Updates about other invoices leave the flow unchanged.
+    pub fn on_update(self, update: &WebhookUpdate) -> Self {
+        match update.invoice() {
+            Some(invoice) => self.on_invoice(invoice),
+            None => self,
+        }
+    }
+
+    /// Advances the flow with the current state of the invoice, e.g. fetched with `get_invoices`
+    ///
+    /// Invoices other than the awaited one leave the flow unchanged.
+    pub fn on_invoice(self, invoice: &Invoice) -> Self {
+        match self {
+            Self::AwaitingPayment { invoice_id, .. } if invoice_id == invoice.invoice_id => match invoice.status {
+                InvoiceStatus::Paid => Self::Paid { invoice_id },
+                InvoiceStatus::Expired => Self::Expired { invoice_id },
+                InvoiceStatus::Active => self,
+            },
+            _ => self,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use teloxide_core::types::{ChatId, InlineKeyboardButtonKind};
+
+    use super::*;
+    use crate::{error::ValidationCode, fixtures};
+
+    #[test]
+    fn test_pay_button_uses_preferred_url() {
+        let invoice = fixtures::paid_invoice();
+        let button = pay_button(&invoice, "Pay", PayUrlPreference::MiniApp).unwrap();
+
+        assert_eq!(button.text, "Pay");
+        assert!(matches!(
+            button.kind,
+            InlineKeyboardButtonKind::Url(url) if url.as_str() == invoice.mini_app_invoice_url
+        ));
+
+        let keyboard = pay_keyboard(&invoice, "Pay", PayUrlPreference::Bot).unwrap();
+        assert_eq!(keyboard.inline_keyboard.len(), 1);
+    }
+
+    #[test]
+    fn test_pay_button_rejects_invalid_url() {
+        let mut invoice = fixtures::paid_invoice();
+        invoice.web_app_invoice_url = "not a url".to_string();
+
+        let result = pay_button(&invoice, "Pay", PayUrlPreference::WebApp);
+        assert_eq!(result.unwrap_err().validation_code(), Some(ValidationCode::InvalidUrl));
+    }
+
+    #[test]
+    fn test_notification() {
+        let update = fixtures::webhook_update();
+        let message = notification(&update, ChatId(42)).unwrap();
+
+        assert_eq!(message.chat_id, Recipient::Id(ChatId(42)));
+        assert_eq!(message.text, "Invoice #528890 10.5 TON paid");
+    }
+
+    #[test]
+    fn test_payment_flow_transitions() {
+        let mut invoice = fixtures::paid_invoice();
+        invoice.status = InvoiceStatus::Active;
+
+        let flow = PaymentFlow::start(&invoice, PayUrlPreference::MiniApp);
+        assert_eq!(flow.invoice_id(), Some(528890));
+        assert!(matches!(
+            &flow,
+            PaymentFlow::AwaitingPayment { pay_url, .. } if *pay_url == invoice.mini_app_invoice_url
+        ));
+        assert!(!flow.is_finished());
+
+        let mut other = fixtures::paid_invoice();
+        other.invoice_id = 1;
+        let flow = flow.on_invoice(&other);
+        assert!(matches!(flow, PaymentFlow::AwaitingPayment { .. }));
+
+        let flow = flow.on_update(&fixtures::webhook_update());
+        assert_eq!(flow, PaymentFlow::Paid { invoice_id: 528890 });
+        assert!(flow.is_finished());
+
+        invoice.status = InvoiceStatus::Expired;
+        assert_eq!(
+            PaymentFlow::start(&invoice, PayUrlPreference::Bot).on_invoice(&invoice),
+            PaymentFlow::Expired { invoice_id: 528890 }
+        );
+    }
+}
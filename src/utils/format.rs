@@ -0,0 +1,38 @@
+/// Formats a duration with its two largest units, e.g. `1h`, `2h 5m` or `45s`.
+pub(crate) fn format_short_duration(secs: u64) -> String {
+    const UNITS: [(u64, &str); 4] = [(86_400, "d"), (3_600, "h"), (60, "m"), (1, "s")];
+
+    let parts: Vec<String> = UNITS
+        .iter()
+        .scan(secs, |rest, &(size, unit)| {
+            let value = *rest / size;
+            *rest %= size;
+            Some((value, unit))
+        })
+        .skip_while(|(value, _)| *value == 0)
+        .take(2)
+        .filter(|(value, _)| *value > 0)
+        .map(|(value, unit)| format!("{value}{unit}"))
+        .collect();
+
+    if parts.is_empty() {
+        "0s".to_string()
+    } else {
+        parts.join(" ")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_format_short_duration() {
+        assert_eq!(format_short_duration(0), "0s");
+        assert_eq!(format_short_duration(45), "45s");
+        assert_eq!(format_short_duration(3_600), "1h");
+        assert_eq!(format_short_duration(3_600 + 5 * 60 + 7), "1h 5m");
+        assert_eq!(format_short_duration(2 * 86_400 + 30), "2d");
+        assert_eq!(format_short_duration(86_400 + 3_600), "1d 1h");
+    }
+}
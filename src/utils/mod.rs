@@ -1,9 +1,15 @@
+mod format;
+mod redaction;
 mod serde_helpers;
 pub mod types;
 
+pub(crate) use format::*;
+pub use redaction::redact;
+pub(crate) use redaction::redact_option;
 pub use serde_helpers::*;
 
 #[cfg(test)]
+#[cfg_attr(not(feature = "client"), allow(dead_code))]
 pub mod test_utils {
     use mockito::ServerGuard;
     use rust_decimal_macros::dec;
@@ -35,22 +41,8 @@ pub mod test_utils {
 
         pub fn mock_exchange_rates() -> Vec<ExchangeRate> {
             vec![
-                ExchangeRate {
-                    is_valid: true,
-                    is_crypto: true,
-                    is_fiat: false,
-                    source: CryptoCurrencyCode::Ton,
-                    target: FiatCurrencyCode::Usd,
-                    rate: dec!(3.70824926),
-                },
-                ExchangeRate {
-                    is_valid: true,
-                    is_crypto: true,
-                    is_fiat: false,
-                    source: CryptoCurrencyCode::Ton,
-                    target: FiatCurrencyCode::Eur,
-                    rate: dec!(3.59048268),
-                },
+                ExchangeRate::new(CryptoCurrencyCode::Ton, FiatCurrencyCode::Usd, dec!(3.70824926)),
+                ExchangeRate::new(CryptoCurrencyCode::Ton, FiatCurrencyCode::Eur, dec!(3.59048268)),
             ]
         }
     }
@@ -0,0 +1,45 @@
+/// Placeholder shown instead of redacted values.
+pub(crate) const REDACTED: &str = "[redacted]";
+
+/// Number of leading characters of a secret kept by [`redact`], enough to tell tokens apart.
+const VISIBLE_PREFIX: usize = 4;
+
+/// Redacts a secret such as an API token for logs, keeping only a short prefix
+///
+/// Secrets shorter than 12 characters are redacted entirely.
+///
+/// # Example
+/// ```
+/// use crypto_pay_api::redact;
+///
+/// assert_eq!(redact("12345:AAzQcZWQqQAbsfgPnOLr4FHC8Doa4L7KryC"), "1234…[redacted]");
+/// assert_eq!(redact("short"), "[redacted]");
+/// ```
+pub fn redact(secret: &str) -> String {
+    if secret.chars().count() < 3 * VISIBLE_PREFIX {
+        return REDACTED.to_string();
+    }
+
+    let prefix: String = secret.chars().take(VISIBLE_PREFIX).collect();
+    format!("{prefix}…{REDACTED}")
+}
+
+/// Replaces an optional value with [`REDACTED`] for `Debug` output, keeping whether it is set
+pub(crate) fn redact_option<T>(value: &Option<T>) -> Option<&'static str> {
+    value.as_ref().map(|_| REDACTED)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_redact() {
+        assert_eq!(redact("12345:AAzQcZWQqQAbsfgPnOLr4FHC8Doa4L7KryC"), "1234…[redacted]");
+        assert_eq!(redact("ключ-ключ-ключ"), "ключ…[redacted]");
+        assert_eq!(redact("short"), REDACTED);
+        assert_eq!(redact(""), REDACTED);
+        assert_eq!(redact_option(&Some("payload")), Some(REDACTED));
+        assert_eq!(redact_option::<String>(&None), None);
+    }
+}
@@ -3,7 +3,15 @@ use serde::{Deserialize, Deserializer};
 use serde_json::Value;
 use std::str::FromStr;
 
-use crate::models::{CryptoCurrencyCode, CurrencyCode, FiatCurrencyCode};
+use crate::models::{format_amount, CryptoCurrencyCode, CurrencyCode, FiatCurrencyCode};
+
+/// Skips optional lists that are unset or empty, so an empty list is never sent to the API
+///
+/// Used by every optional list in the request params: an empty filter or `accept_asset` is
+/// treated as if it was not set.
+pub fn is_none_or_empty<T>(list: &Option<Vec<T>>) -> bool {
+    list.as_ref().is_none_or(Vec::is_empty)
+}
 
 /// Serialize a comma-separated list of u64 to a String
 pub fn serialize_comma_separated_list<S>(ids: &Option<Vec<u64>>, serializer: S) -> Result<S::Ok, S::Error>
@@ -40,7 +48,9 @@ where
                 Err(D::Error::custom("invalid numeric value for Decimal"))
             }
         }
-        Value::String(s) => Decimal::from_str(&s).map_err(D::Error::custom),
+        Value::String(s) => Decimal::from_str(&s)
+            .or_else(|_| Decimal::from_scientific(&s))
+            .map_err(D::Error::custom),
         other => Err(D::Error::custom(format!(
             "unexpected JSON value for Decimal: {:?}",
             other
@@ -48,12 +58,15 @@ where
     }
 }
 
-/// Serialize a Decimal to a String
+/// Serialize a Decimal to a String in the canonical format of `format_amount`
+///
+/// Does not round: the scale of request amounts is checked per currency by `PositiveAmount::for_asset`
+/// and `PositiveAmount::for_fiat` before sending.
 pub fn serialize_decimal_to_string<S>(value: &Decimal, serializer: S) -> Result<S::Ok, S::Error>
 where
     S: serde::Serializer,
 {
-    serializer.serialize_str(&value.to_string())
+    serializer.serialize_str(&format_amount(value))
 }
 
 /// Deserialize an optional String to a Decimal
@@ -68,6 +81,15 @@ where
     Ok(helper.map(|Helper(dec)| dec))
 }
 
+/// Deserialize an optional String, treating an empty string the same as a missing value
+pub fn deserialize_optional_string<'de, D>(deserializer: D) -> Result<Option<String>, D::Error>
+where
+    D: Deserializer<'de>,
+{
+    let value = Option::<String>::deserialize(deserializer)?;
+    Ok(value.filter(|s| !s.is_empty()))
+}
+
 /// Deserialize a String to a CurrencyCode
 pub fn deserialize_currency_code<'de, D>(deserializer: D) -> Result<CurrencyCode, D::Error>
 where
@@ -124,6 +146,41 @@ mod tests {
         value: Option<Decimal>,
     }
 
+    #[test]
+    fn test_empty_lists_are_skipped() {
+        #[derive(Serialize)]
+        struct Lists {
+            #[serde(skip_serializing_if = "is_none_or_empty")]
+            assets: Option<Vec<CryptoCurrencyCode>>,
+            #[serde(
+                skip_serializing_if = "is_none_or_empty",
+                serialize_with = "serialize_comma_separated_list"
+            )]
+            ids: Option<Vec<u64>>,
+        }
+
+        let empty = Lists {
+            assets: Some(vec![]),
+            ids: Some(vec![]),
+        };
+        assert_eq!(serde_json::to_value(&empty).unwrap(), json!({}));
+
+        let unset = Lists {
+            assets: None,
+            ids: None,
+        };
+        assert_eq!(serde_json::to_value(&unset).unwrap(), json!({}));
+
+        let set = Lists {
+            assets: Some(vec![CryptoCurrencyCode::Ton]),
+            ids: Some(vec![1, 2]),
+        };
+        assert_eq!(
+            serde_json::to_value(&set).unwrap(),
+            json!({ "assets": ["TON"], "ids": "1,2" })
+        );
+    }
+
     #[test]
     fn test_serialize_comma_separated_list() {
         // Test with Some values
@@ -184,6 +241,11 @@ mod tests {
         let result: TestDecimalDeserialize = serde_json::from_value(json).unwrap();
         assert_eq!(result.value, dec!(-42.5));
 
+        // Test exponent string
+        let json = json!({"value": "1.5e-7"});
+        let result: TestDecimalDeserialize = serde_json::from_value(json).unwrap();
+        assert_eq!(result.value, dec!(0.00000015));
+
         // Test invalid string
         let json = json!({"value": "invalid"});
         assert!(serde_json::from_value::<TestDecimalDeserialize>(json).is_err());
@@ -273,6 +335,31 @@ mod tests {
         assert!(serde_json::from_value::<TestOptionalDecimal>(json).is_err());
     }
 
+    #[derive(Debug, Deserialize)]
+    struct TestOptionalString {
+        #[serde(default, deserialize_with = "deserialize_optional_string")]
+        value: Option<String>,
+    }
+
+    #[test]
+    fn test_deserialize_optional_string() {
+        let result: TestOptionalString = serde_json::from_value(json!({"value": "text"})).unwrap();
+        assert_eq!(result.value, Some("text".to_string()));
+
+        let result: TestOptionalString = serde_json::from_value(json!({"value": ""})).unwrap();
+        assert_eq!(result.value, None);
+
+        let result: TestOptionalString = serde_json::from_value(json!({"value": null})).unwrap();
+        assert_eq!(result.value, None);
+
+        let result: TestOptionalString = serde_json::from_value(json!({})).unwrap();
+        assert_eq!(result.value, None);
+
+        // Whitespace is meaningful and kept as-is
+        let result: TestOptionalString = serde_json::from_value(json!({"value": " "})).unwrap();
+        assert_eq!(result.value, Some(" ".to_string()));
+    }
+
     #[test]
     fn test_deserialize_currency_code() {
         // Test valid crypto currency
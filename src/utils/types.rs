@@ -1,4 +1,6 @@
+#[cfg(feature = "client")]
 pub use reqwest::header::{HeaderName, HeaderValue};
+#[cfg(feature = "client")]
 pub use reqwest::Client;
 pub use rust_decimal::prelude::{FromPrimitive, ToPrimitive};
 pub use rust_decimal::Decimal;
@@ -130,11 +132,11 @@ mod tests {
 
     #[test]
     fn test_float_conversions() {
-        assert_eq!(3.14f32.into_decimal(), dec!(3.14));
-        assert!(3.14f32.try_into_decimal().is_ok());
+        assert_eq!(3.25f32.into_decimal(), dec!(3.25));
+        assert!(3.25f32.try_into_decimal().is_ok());
 
-        assert_eq!(2.718281828f64.into_decimal(), dec!(2.718281828));
-        assert!(2.718281828f64.try_into_decimal().is_ok());
+        assert_eq!(2.123456789f64.into_decimal(), dec!(2.123456789));
+        assert!(2.123456789f64.try_into_decimal().is_ok());
 
         assert!(f32::NAN.try_into_decimal().is_err());
         assert!(f32::INFINITY.try_into_decimal().is_err());
@@ -194,7 +196,7 @@ mod tests {
         assert_eq!("0".into_decimal(), Decimal::ZERO);
 
         assert_eq!((-42i32).into_decimal(), dec!(-42));
-        assert_eq!((-3.14f64).into_decimal(), dec!(-3.14));
+        assert_eq!((-3.25f64).into_decimal(), dec!(-3.25));
         assert_eq!("-123.45".into_decimal(), dec!(-123.45));
 
         assert_eq!(0.000001f32.into_decimal(), dec!(0.000001));
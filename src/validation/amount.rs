@@ -1,10 +1,24 @@
 use rust_decimal::Decimal;
 
-use crate::error::{CryptoBotError, CryptoBotResult, ValidationErrorKind};
-use crate::models::{CryptoCurrencyCode, FiatCurrencyCode};
+use crate::error::{CryptoBotError, CryptoBotResult, ValidationCode, ValidationErrorKind};
+use crate::models::{CryptoCurrencyCode, ExchangeRates, FiatCurrencyCode};
 
 use super::ValidationContext;
 
+/// Checks that an amount is greater than 0
+pub fn validate_positive_amount(amount: Decimal) -> CryptoBotResult<()> {
+    if amount <= Decimal::ZERO {
+        return Err(CryptoBotError::ValidationError {
+            kind: ValidationErrorKind::Range,
+            code: ValidationCode::AmountRange,
+            message: "Amount must be greater than 0".to_string(),
+            field: Some("amount".to_string()),
+        });
+    }
+
+    Ok(())
+}
+
 pub async fn validate_amount(
     amount: &Decimal,
     asset: &CryptoCurrencyCode,
@@ -16,15 +30,46 @@ pub async fn validate_amount(
         .find(|rate| rate.source == *asset && rate.target == FiatCurrencyCode::Usd)
         .ok_or_else(|| CryptoBotError::ValidationError {
             kind: ValidationErrorKind::Missing,
+            code: ValidationCode::ExchangeRateMissing,
             message: "exchange_rate_not_found".to_string(),
             field: Some("exchange_rate".to_string()),
         })?;
 
-    let usd_value = amount * usd_rate.rate;
+    check_usd_range(amount * usd_rate.rate)
+}
+
+/// Checks that a fiat amount is worth between 1 and 25000 USD
+///
+/// Amounts in other fiat currencies than USD are converted with a cross rate through a
+/// cryptocurrency quoted in both, preferring USDT. The check is skipped if no cross rate is
+/// available, leaving the range check to the API.
+pub async fn validate_fiat_amount(
+    amount: &Decimal,
+    fiat: &FiatCurrencyCode,
+    ctx: &ValidationContext,
+) -> CryptoBotResult<()> {
+    if *fiat == FiatCurrencyCode::Usd {
+        return check_usd_range(*amount);
+    }
+
+    let rates = ctx.exchange_rates.as_slice();
+    let cross_rate = |asset: &CryptoCurrencyCode| {
+        let fiat_rate = rates.find_rate(asset, fiat).filter(|rate| !rate.rate.is_zero())?;
+        let usd_rate = rates.find_rate(asset, &FiatCurrencyCode::Usd)?;
+        Some(usd_rate.rate / fiat_rate.rate)
+    };
+
+    match cross_rate(&CryptoCurrencyCode::Usdt).or_else(|| rates.iter().find_map(|rate| cross_rate(&rate.source))) {
+        Some(fiat_to_usd) => check_usd_range(amount * fiat_to_usd),
+        None => Ok(()),
+    }
+}
 
+fn check_usd_range(usd_value: Decimal) -> CryptoBotResult<()> {
     if usd_value < Decimal::ONE || usd_value > Decimal::from(25000) {
         return Err(CryptoBotError::ValidationError {
             kind: ValidationErrorKind::Range,
+            code: ValidationCode::AmountRange,
             message: "Amount must be between 1 and 25000 USD".to_string(),
             field: Some("amount".to_string()),
         });
@@ -41,14 +86,7 @@ mod tests {
 
     fn create_test_context(rate: Decimal) -> ValidationContext {
         ValidationContext {
-            exchange_rates: vec![ExchangeRate {
-                source: CryptoCurrencyCode::Ton,
-                target: FiatCurrencyCode::Usd,
-                rate,
-                is_valid: true,
-                is_crypto: true,
-                is_fiat: false,
-            }],
+            exchange_rates: vec![ExchangeRate::new(CryptoCurrencyCode::Ton, FiatCurrencyCode::Usd, rate)],
         }
     }
 
@@ -81,6 +119,7 @@ mod tests {
             result,
             Err(CryptoBotError::ValidationError {
                 kind: ValidationErrorKind::Range,
+                code: ValidationCode::AmountRange,
                 message,
                 field: Some(field),
             }) if message == "Amount must be between 1 and 25000 USD" && field == "amount"
@@ -98,6 +137,7 @@ mod tests {
             result,
             Err(CryptoBotError::ValidationError {
                 kind: ValidationErrorKind::Range,
+                code: ValidationCode::AmountRange,
                 message,
                 field: Some(field),
             }) if message == "Amount must be between 1 and 25000 USD" && field == "amount"
@@ -115,12 +155,57 @@ mod tests {
             result,
             Err(CryptoBotError::ValidationError {
                 kind: ValidationErrorKind::Missing,
+                code: ValidationCode::ExchangeRateMissing,
                 message,
                 field: Some(field),
             }) if message == "exchange_rate_not_found" && field == "exchange_rate"
         ));
     }
 
+    #[tokio::test]
+    async fn test_validate_fiat_amount() {
+        let ctx = ValidationContext {
+            exchange_rates: vec![
+                ExchangeRate::new(CryptoCurrencyCode::Ton, FiatCurrencyCode::Usd, dec!(3)),
+                ExchangeRate::new(CryptoCurrencyCode::Ton, FiatCurrencyCode::Eur, dec!(2.5)),
+                ExchangeRate::new(CryptoCurrencyCode::Usdt, FiatCurrencyCode::Usd, dec!(1)),
+                ExchangeRate::new(CryptoCurrencyCode::Usdt, FiatCurrencyCode::Rub, dec!(100)),
+            ],
+        };
+
+        assert!(validate_fiat_amount(&dec!(1), &FiatCurrencyCode::Usd, &ctx)
+            .await
+            .is_ok());
+        assert!(validate_fiat_amount(&dec!(25001), &FiatCurrencyCode::Usd, &ctx)
+            .await
+            .is_err());
+
+        // 1 EUR = 3 / 2.5 = 1.2 USD through TON
+        assert!(validate_fiat_amount(&dec!(1), &FiatCurrencyCode::Eur, &ctx)
+            .await
+            .is_ok());
+        assert!(validate_fiat_amount(&dec!(0.8), &FiatCurrencyCode::Eur, &ctx)
+            .await
+            .is_err());
+
+        // 1 RUB = 0.01 USD through USDT
+        assert!(validate_fiat_amount(&dec!(100), &FiatCurrencyCode::Rub, &ctx)
+            .await
+            .is_ok());
+        assert_eq!(
+            validate_fiat_amount(&dec!(50), &FiatCurrencyCode::Rub, &ctx)
+                .await
+                .unwrap_err()
+                .validation_code(),
+            Some(ValidationCode::AmountRange)
+        );
+
+        // Without a GBP rate the range is left to the API
+        assert!(validate_fiat_amount(&dec!(0.01), &FiatCurrencyCode::Gbp, &ctx)
+            .await
+            .is_ok());
+    }
+
     #[tokio::test]
     async fn test_validate_amount_edge_cases() {
         let ctx = create_test_context(dec!(2.0)); // 1 TON = 2 USD
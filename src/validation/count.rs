@@ -1,9 +1,13 @@
-use crate::{error::CryptoBotError, error::CryptoBotResult, error::ValidationErrorKind};
+use crate::{error::CryptoBotError, error::CryptoBotResult, error::ValidationCode, error::ValidationErrorKind};
+
+pub const MIN_COUNT: u16 = 1;
+pub const MAX_COUNT: u16 = 1000;
 
 pub fn validate_count(count: u16) -> CryptoBotResult<()> {
-    if !(1..=1000).contains(&count) {
+    if !(MIN_COUNT..=MAX_COUNT).contains(&count) {
         return Err(CryptoBotError::ValidationError {
             kind: ValidationErrorKind::Range,
+            code: ValidationCode::CountRange,
             message: "Count must be between 1 and 1000".to_string(),
             field: Some("count".to_string()),
         });
@@ -12,6 +16,11 @@ pub fn validate_count(count: u16) -> CryptoBotResult<()> {
     Ok(())
 }
 
+/// Clamps the count into the range accepted by the API
+pub fn clamp_count(count: u16) -> u16 {
+    count.clamp(MIN_COUNT, MAX_COUNT)
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -24,13 +33,28 @@ mod tests {
         assert!(validate_count(1000).is_ok());
     }
 
+    #[test]
+    fn test_clamp_count() {
+        assert_eq!(clamp_count(0), 1);
+        assert_eq!(clamp_count(1), 1);
+        assert_eq!(clamp_count(500), 500);
+        assert_eq!(clamp_count(1000), 1000);
+        assert_eq!(clamp_count(u16::MAX), 1000);
+    }
+
     #[test]
     fn test_validate_count_too_small() {
         let result = validate_count(0);
         assert!(result.is_err());
         match result {
-            Err(CryptoBotError::ValidationError { kind, message, field }) => {
+            Err(CryptoBotError::ValidationError {
+                kind,
+                code,
+                message,
+                field,
+            }) => {
                 assert_eq!(kind, ValidationErrorKind::Range);
+                assert_eq!(code, ValidationCode::CountRange);
                 assert_eq!(message, "Count must be between 1 and 1000");
                 assert_eq!(field, Some("count".to_string()));
             }
@@ -43,8 +67,14 @@ mod tests {
         let result = validate_count(1001);
         assert!(result.is_err());
         match result {
-            Err(CryptoBotError::ValidationError { kind, message, field }) => {
+            Err(CryptoBotError::ValidationError {
+                kind,
+                code,
+                message,
+                field,
+            }) => {
                 assert_eq!(kind, ValidationErrorKind::Range);
+                assert_eq!(code, ValidationCode::CountRange);
                 assert_eq!(message, "Count must be between 1 and 1000");
                 assert_eq!(field, Some("count".to_string()));
             }
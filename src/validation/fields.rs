@@ -0,0 +1,197 @@
+use url::Url;
+
+use crate::error::{CryptoBotError, CryptoBotResult, ValidationCode, ValidationErrorKind};
+
+/// Maximum length of an invoice description, in characters.
+pub const MAX_DESCRIPTION_LENGTH: usize = 1024;
+/// Maximum length of an invoice hidden message, in characters.
+pub const MAX_HIDDEN_MESSAGE_LENGTH: usize = 2048;
+/// Maximum length of an invoice payload, in characters.
+pub const MAX_PAYLOAD_LENGTH: usize = 4096;
+/// Maximum length of a transfer comment, in characters.
+pub const MAX_COMMENT_LENGTH: usize = 1024;
+/// Maximum length of a transfer spend ID, in characters.
+pub const MAX_SPEND_ID_LENGTH: usize = 64;
+/// Minimum invoice expiration time, in seconds.
+pub const MIN_EXPIRES_IN: u32 = 1;
+/// Maximum invoice expiration time, in seconds (31 days).
+pub const MAX_EXPIRES_IN: u32 = 2_678_400;
+/// Maximum length of a URL, in characters.
+pub const MAX_URL_LENGTH: usize = 2048;
+
+/// Checks that a text field has at most `max` characters
+pub fn validate_max_length(value: &str, max: usize, field: &str) -> CryptoBotResult<()> {
+    check_length(value, max, field, ValidationCode::FieldLength)
+}
+
+fn check_length(value: &str, max: usize, field: &str, code: ValidationCode) -> CryptoBotResult<()> {
+    if value.chars().count() > max {
+        return Err(CryptoBotError::ValidationError {
+            kind: ValidationErrorKind::Range,
+            code,
+            message: format!("{field} must be at most {max} characters"),
+            field: Some(field.to_string()),
+        });
+    }
+
+    Ok(())
+}
+
+/// Checks the length of an invoice description
+pub fn validate_description(description: &str) -> CryptoBotResult<()> {
+    check_length(
+        description,
+        MAX_DESCRIPTION_LENGTH,
+        "description",
+        ValidationCode::DescriptionLength,
+    )
+}
+
+/// Checks the length of an invoice hidden message
+pub fn validate_hidden_message(hidden_message: &str) -> CryptoBotResult<()> {
+    check_length(
+        hidden_message,
+        MAX_HIDDEN_MESSAGE_LENGTH,
+        "hidden_message",
+        ValidationCode::HiddenMessageLength,
+    )
+}
+
+/// Checks the length of an invoice payload
+pub fn validate_payload(payload: &str) -> CryptoBotResult<()> {
+    check_length(payload, MAX_PAYLOAD_LENGTH, "payload", ValidationCode::PayloadLength)
+}
+
+/// Checks the length of a transfer comment
+pub fn validate_comment(comment: &str) -> CryptoBotResult<()> {
+    check_length(comment, MAX_COMMENT_LENGTH, "comment", ValidationCode::CommentLength)
+}
+
+/// Checks the length of a transfer spend ID
+pub fn validate_spend_id(spend_id: &str) -> CryptoBotResult<()> {
+    check_length(spend_id, MAX_SPEND_ID_LENGTH, "spend_id", ValidationCode::SpendIdLength)
+}
+
+/// Checks that an invoice expiration time is between 1 second and 31 days
+pub fn validate_expires_in(expires_in: u32) -> CryptoBotResult<()> {
+    if !(MIN_EXPIRES_IN..=MAX_EXPIRES_IN).contains(&expires_in) {
+        return Err(CryptoBotError::ValidationError {
+            kind: ValidationErrorKind::Range,
+            code: ValidationCode::ExpiresInRange,
+            message: format!("expires_in must be between {MIN_EXPIRES_IN} and {MAX_EXPIRES_IN} seconds"),
+            field: Some("expires_in".to_string()),
+        });
+    }
+
+    Ok(())
+}
+
+/// Checks that a URL parses, uses the http or https scheme, has a host and fits in [`MAX_URL_LENGTH`]
+pub fn validate_url(url: &str, field: &str) -> CryptoBotResult<()> {
+    parse_url(url, field).map(|_| ())
+}
+
+/// Parses an http or https URL, with the same checks as [`validate_url`]
+pub(crate) fn parse_url(url: &str, field: &str) -> CryptoBotResult<Url> {
+    let invalid = |reason: &str| CryptoBotError::ValidationError {
+        kind: ValidationErrorKind::Format,
+        code: ValidationCode::InvalidUrl,
+        message: format!("{field} is not a valid URL: {reason}"),
+        field: Some(field.to_string()),
+    };
+
+    if url.chars().count() > MAX_URL_LENGTH {
+        return Err(invalid(&format!("longer than {MAX_URL_LENGTH} characters")));
+    }
+
+    let parsed = Url::parse(url).map_err(|e| invalid(&e.to_string()))?;
+
+    if !matches!(parsed.scheme(), "http" | "https") {
+        return Err(invalid("scheme must be http or https"));
+    }
+
+    if parsed.host_str().is_none_or(str::is_empty) {
+        return Err(invalid("missing host"));
+    }
+
+    Ok(parsed)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_validate_max_length_counts_characters() {
+        assert!(validate_max_length("héllo", 5, "field").is_ok());
+        assert!(matches!(
+            validate_max_length("héllo!", 5, "field"),
+            Err(CryptoBotError::ValidationError {
+                kind: ValidationErrorKind::Range,
+                field: Some(field),
+                ..
+            }) if field == "field"
+        ));
+    }
+
+    #[test]
+    fn test_text_field_codes() {
+        let code = |result: CryptoBotResult<()>| result.unwrap_err().validation_code();
+
+        assert_eq!(
+            code(validate_max_length("ab", 1, "field")),
+            Some(ValidationCode::FieldLength)
+        );
+        assert_eq!(
+            code(validate_description(&"a".repeat(MAX_DESCRIPTION_LENGTH + 1))),
+            Some(ValidationCode::DescriptionLength)
+        );
+        assert_eq!(
+            code(validate_spend_id(&"a".repeat(MAX_SPEND_ID_LENGTH + 1))),
+            Some(ValidationCode::SpendIdLength)
+        );
+        assert_eq!(code(validate_expires_in(0)), Some(ValidationCode::ExpiresInRange));
+        assert_eq!(
+            code(validate_url("ftp://example.com", "url")),
+            Some(ValidationCode::InvalidUrl)
+        );
+    }
+
+    #[test]
+    fn test_text_field_limits() {
+        assert!(validate_description(&"a".repeat(MAX_DESCRIPTION_LENGTH)).is_ok());
+        assert!(validate_description(&"a".repeat(MAX_DESCRIPTION_LENGTH + 1)).is_err());
+        assert!(validate_hidden_message(&"a".repeat(MAX_HIDDEN_MESSAGE_LENGTH + 1)).is_err());
+        assert!(validate_payload(&"a".repeat(MAX_PAYLOAD_LENGTH + 1)).is_err());
+        assert!(validate_comment(&"a".repeat(MAX_COMMENT_LENGTH + 1)).is_err());
+        assert!(validate_spend_id(&"a".repeat(MAX_SPEND_ID_LENGTH)).is_ok());
+        assert!(validate_spend_id(&"a".repeat(MAX_SPEND_ID_LENGTH + 1)).is_err());
+    }
+
+    #[test]
+    fn test_validate_expires_in() {
+        assert!(validate_expires_in(MIN_EXPIRES_IN).is_ok());
+        assert!(validate_expires_in(MAX_EXPIRES_IN).is_ok());
+        assert!(validate_expires_in(0).is_err());
+        assert!(validate_expires_in(MAX_EXPIRES_IN + 1).is_err());
+    }
+
+    #[test]
+    fn test_validate_url() {
+        assert!(validate_url("https://example.com", "url").is_ok());
+        assert!(validate_url("http://example.com", "url").is_ok());
+        assert!(validate_url("http://127.0.0.1:8080/api", "url").is_ok());
+        assert!(validate_url("https://", "url").is_err());
+        assert!(validate_url("https://exa mple.com", "url").is_err());
+        assert!(validate_url("mailto:user@example.com", "url").is_err());
+        assert!(validate_url(&format!("https://example.com/{}", "a".repeat(MAX_URL_LENGTH)), "url").is_err());
+        assert!(matches!(
+            validate_url("ftp://example.com", "paid_btn_url"),
+            Err(CryptoBotError::ValidationError {
+                kind: ValidationErrorKind::Format,
+                field: Some(field),
+                ..
+            }) if field == "paid_btn_url"
+        ));
+    }
+}
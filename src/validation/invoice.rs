@@ -0,0 +1,215 @@
+use rust_decimal::Decimal;
+
+use crate::{
+    error::{CryptoBotError, CryptoBotResult, ValidationCode, ValidationErrorKind},
+    models::{CryptoCurrencyCode, CurrencyType},
+};
+
+use super::{
+    validate_description, validate_expires_in, validate_hidden_message, validate_payload, validate_positive_amount,
+    validate_url,
+};
+
+/// Rules for the fields of an invoice that do not depend on exchange rates
+///
+/// Shared by `CreateInvoiceBuilder` and `CreateInvoiceParams`, so both accept exactly the same
+/// invoices. Fields left as `None` are not checked.
+///
+/// # Example
+/// ```
+/// use crypto_pay_api::validators::InvoiceFieldRules;
+/// use rust_decimal_macros::dec;
+///
+/// let rules = InvoiceFieldRules {
+///     amount: dec!(10),
+///     description: Some("Premium subscription"),
+///     ..Default::default()
+/// };
+/// assert!(rules.validate().is_ok());
+/// assert!(InvoiceFieldRules { expires_in: Some(0), ..rules }.validate().is_err());
+/// ```
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct InvoiceFieldRules<'a> {
+    /// Amount of the invoice, in the asset or fiat currency it is priced in.
+    pub amount: Decimal,
+    /// Type of the price. `None` stands for the API default, crypto.
+    pub currency_type: Option<CurrencyType>,
+    /// Assets that can pay the invoice, only available for fiat invoices. An empty list counts as unset.
+    pub accept_asset: Option<&'a [CryptoCurrencyCode]>,
+    pub description: Option<&'a str>,
+    pub hidden_message: Option<&'a str>,
+    pub paid_btn_url: Option<&'a str>,
+    pub payload: Option<&'a str>,
+    pub expires_in: Option<u32>,
+}
+
+impl InvoiceFieldRules<'_> {
+    /// Checks the fields in a fixed order, returning the error of the first invalid one
+    pub fn validate(&self) -> CryptoBotResult<()> {
+        validate_positive_amount(self.amount)?;
+
+        if self.accept_asset.is_some_and(|assets| !assets.is_empty()) && self.currency_type != Some(CurrencyType::Fiat)
+        {
+            return Err(CryptoBotError::ValidationError {
+                kind: ValidationErrorKind::Invalid,
+                code: ValidationCode::FieldConflict,
+                message: "accept_asset is only available for invoices priced in fiat".to_string(),
+                field: Some("accept_asset".to_string()),
+            });
+        }
+
+        if let Some(description) = self.description {
+            validate_description(description)?;
+        }
+
+        if let Some(hidden_message) = self.hidden_message {
+            validate_hidden_message(hidden_message)?;
+        }
+
+        if let Some(payload) = self.payload {
+            validate_payload(payload)?;
+        }
+
+        if let Some(expires_in) = self.expires_in {
+            validate_expires_in(expires_in)?;
+        }
+
+        if let Some(url) = self.paid_btn_url {
+            validate_url(url, "paid_btn_url")?;
+        }
+
+        Ok(())
+    }
+}
+
+#[cfg(all(test, feature = "client"))]
+mod tests {
+    use std::sync::OnceLock;
+
+    use proptest::prelude::*;
+
+    use super::*;
+    use crate::{
+        api::InvoiceAPI,
+        client::CryptoBot,
+        models::{CreateInvoiceParams, CryptoCurrencyCode, PayButtonName, PositiveAmount},
+        validation::{FieldValidate, MAX_DESCRIPTION_LENGTH, MAX_EXPIRES_IN},
+    };
+
+    fn client() -> &'static CryptoBot {
+        static CLIENT: OnceLock<CryptoBot> = OnceLock::new();
+        CLIENT.get_or_init(CryptoBot::test_client)
+    }
+
+    fn text(max: usize) -> impl Strategy<Value = Option<String>> {
+        proptest::option::of((max - 2..=max + 2).prop_map(|len| "a".repeat(len)))
+    }
+
+    prop_compose! {
+        fn invoice_fields()(
+            mantissa in -1_000_000i64..1_000_000,
+            scale in 0u32..=6,
+            description in text(MAX_DESCRIPTION_LENGTH),
+            payload in text(4096),
+            paid_btn_url in proptest::option::of(prop_oneof![
+                Just("https://example.com".to_string()),
+                Just("ftp://example.com".to_string()),
+                Just("not a url".to_string()),
+            ]),
+            expires_in in proptest::option::of(prop_oneof![0u32..=2, MAX_EXPIRES_IN - 1..=MAX_EXPIRES_IN + 1]),
+        ) -> (Decimal, Option<String>, Option<String>, Option<String>, Option<u32>) {
+            (Decimal::new(mantissa, scale), description, payload, paid_btn_url, expires_in)
+        }
+    }
+
+    proptest! {
+        #[test]
+        fn prop_builder_and_params_agree(
+            (amount, description, payload, paid_btn_url, expires_in) in invoice_fields()
+        ) {
+            let mut builder = client().create_invoice().asset(CryptoCurrencyCode::Ton).amount(amount);
+            if let Some(description) = &description {
+                builder = builder.description(description);
+            }
+            if let Some(payload) = &payload {
+                builder = builder.payload(payload);
+            }
+            if let Some(expires_in) = expires_in {
+                builder = builder.expires_in(expires_in);
+            }
+            let from_builder = match &paid_btn_url {
+                Some(url) => builder.paid_btn_name(PayButtonName::ViewItem).paid_btn_url(url).validate(),
+                None => builder.validate(),
+            };
+
+            // Params can only hold a positive amount, so a rejected amount fails before the rules run
+            let from_params = PositiveAmount::new(amount).and_then(|amount| {
+                CreateInvoiceParams {
+                    currency_type: Some(CurrencyType::Crypto),
+                    asset: Some(CryptoCurrencyCode::Ton),
+                    fiat: None,
+                    accept_asset: None,
+                    amount,
+                    description,
+                    hidden_message: None,
+                    paid_btn_name: paid_btn_url.as_ref().map(|_| PayButtonName::ViewItem),
+                    paid_btn_url,
+                    swap_to: None,
+                    payload,
+                    allow_comments: None,
+                    allow_anonymous: None,
+                    expires_in,
+                }
+                .validate()
+            });
+
+            prop_assert_eq!(from_builder.map_err(|e| e.to_string()), from_params.map_err(|e| e.to_string()));
+        }
+    }
+
+    #[test]
+    fn test_invoice_field_rules_order() {
+        let rules = InvoiceFieldRules {
+            amount: Decimal::ZERO,
+            expires_in: Some(0),
+            ..Default::default()
+        };
+        let error = rules.validate().unwrap_err();
+        assert_eq!(error.validation_code(), Some(ValidationCode::AmountRange));
+
+        let error = InvoiceFieldRules {
+            amount: Decimal::ONE,
+            ..rules
+        }
+        .validate()
+        .unwrap_err();
+        assert_eq!(error.validation_code(), Some(ValidationCode::ExpiresInRange));
+    }
+
+    #[test]
+    fn test_accept_asset_requires_fiat() {
+        let accept_asset = [CryptoCurrencyCode::Ton];
+        let rules = InvoiceFieldRules {
+            amount: Decimal::ONE,
+            accept_asset: Some(&accept_asset),
+            ..Default::default()
+        };
+
+        for currency_type in [None, Some(CurrencyType::Crypto)] {
+            let error = InvoiceFieldRules { currency_type, ..rules }.validate().unwrap_err();
+            assert_eq!(error.validation_code(), Some(ValidationCode::FieldConflict));
+        }
+        assert!(InvoiceFieldRules {
+            currency_type: Some(CurrencyType::Fiat),
+            ..rules
+        }
+        .validate()
+        .is_ok());
+        assert!(InvoiceFieldRules {
+            accept_asset: Some(&[]),
+            ..rules
+        }
+        .validate()
+        .is_ok());
+    }
+}
@@ -1,16 +1,16 @@
+use std::future::Future;
+
 use crate::error::CryptoBotResult;
 use crate::models::ExchangeRate;
-use async_trait::async_trait;
 
 pub trait FieldValidate {
     /// Validate every field of the model without context
     fn validate(&self) -> CryptoBotResult<()>;
 }
 
-#[async_trait]
 pub trait ContextValidate {
     /// Validate field of the model with external context
-    async fn validate_with_context(&self, ctx: &ValidationContext) -> CryptoBotResult<()>;
+    fn validate_with_context(&self, ctx: &ValidationContext) -> impl Future<Output = CryptoBotResult<()>> + Send;
 }
 
 pub struct ValidationContext {
@@ -23,6 +23,7 @@ macro_rules! validate_dependency {
         if $condition {
             return Err(CryptoBotError::ValidationError {
                 kind: ValidationErrorKind::Missing,
+                code: ValidationCode::MissingField,
                 message: $message.to_string(),
                 field: Some($field.to_string()),
             });
@@ -32,6 +33,10 @@ macro_rules! validate_dependency {
 
 mod amount;
 mod count;
+mod fields;
+mod invoice;
 
 pub use amount::*;
 pub use count::*;
+pub use fields::*;
+pub use invoice::*;
@@ -0,0 +1,20 @@
+//! Validation rules applied by the request builders
+//!
+//! Use them to check user input with exactly the same rules before submitting a request,
+//! e.g. in the form of a Telegram bot wizard.
+//!
+//! # Example
+//! ```
+//! use crypto_pay_api::validators;
+//!
+//! assert!(validators::validate_count(50).is_ok());
+//! assert!(validators::validate_spend_id(&"x".repeat(65)).is_err());
+//! assert!(validators::validate_url("https://example.com", "paid_btn_url").is_ok());
+//! ```
+
+pub use crate::validation::{
+    clamp_count, validate_comment, validate_count, validate_description, validate_expires_in, validate_hidden_message,
+    validate_max_length, validate_payload, validate_positive_amount, validate_spend_id, validate_url,
+    InvoiceFieldRules, MAX_COMMENT_LENGTH, MAX_COUNT, MAX_DESCRIPTION_LENGTH, MAX_EXPIRES_IN,
+    MAX_HIDDEN_MESSAGE_LENGTH, MAX_PAYLOAD_LENGTH, MAX_SPEND_ID_LENGTH, MAX_URL_LENGTH, MIN_COUNT, MIN_EXPIRES_IN,
+};
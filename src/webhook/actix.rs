@@ -0,0 +1,158 @@
+use actix_web::{
+    http::{header, StatusCode},
+    web, HttpRequest, HttpResponse, Resource,
+};
+
+use super::{handler::WebhookReply, WebhookHandler, SIGNATURE_HEADER};
+
+/// Returns a resource serving `handler` on `path`, to register with `App::service`
+///
+/// Bodies larger than the handler's `max_body_size` are refused with 413 before they are read.
+/// Without a limit, actix-web's default payload limit of 256 KiB applies.
+pub fn resource(path: &str, handler: web::Data<WebhookHandler>) -> Resource {
+    let mut resource = web::resource(path);
+    if let Some(limit) = handler.config.max_body_size {
+        resource = resource.app_data(web::PayloadConfig::new(limit));
+    }
+    resource.app_data(handler).route(web::post().to(handle_webhook))
+}
+
+/// Answers a webhook request, for apps that register the route themselves
+///
+/// The handler is taken from the `web::Data<WebhookHandler>` app data of the app or resource.
+pub async fn handle_webhook(handler: web::Data<WebhookHandler>, request: HttpRequest, body: String) -> HttpResponse {
+    let signature = request
+        .headers()
+        .get(SIGNATURE_HEADER)
+        .and_then(|value| value.to_str().ok());
+
+    match handler.reply(&body, signature).await {
+        WebhookReply::Handled(json) => HttpResponse::Ok()
+            .insert_header((header::CONTENT_TYPE, "application/json"))
+            .body(json),
+        WebhookReply::Rejected(status, message) => {
+            HttpResponse::build(StatusCode::from_u16(status).unwrap_or(StatusCode::BAD_REQUEST)).body(message)
+        }
+        WebhookReply::Failed => HttpResponse::InternalServerError().finish(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use actix_web::{test, App};
+
+    use super::*;
+    use crate::{
+        config::MIN_WEBHOOK_BODY_SIZE,
+        error::CryptoBotError,
+        fixtures,
+        webhook::{handler::sign_body, WebhookHandlerConfig, WebhookHandlerConfigBuilder},
+    };
+
+    #[test]
+    fn test_resource_rejects_unsigned_updates_by_default() {
+        let config = WebhookHandlerConfigBuilder::new().build_config().unwrap();
+        assert!(!config.require_signature);
+        let handler = web::Data::new(WebhookHandler::new("test_token", config).unwrap());
+        let body = fixtures::webhook_update_body();
+
+        actix_web::rt::System::new().block_on(async {
+            let app = test::init_service(App::new().service(resource("/webhook", handler))).await;
+
+            let request = test::TestRequest::post().uri("/webhook").set_payload(body.clone());
+            let response = test::call_service(&app, request.to_request()).await;
+            assert_eq!(response.status(), StatusCode::UNAUTHORIZED);
+
+            let request = test::TestRequest::post()
+                .uri("/webhook")
+                .insert_header((SIGNATURE_HEADER, sign_body("test_token", &body)))
+                .set_payload(body);
+            let response = test::call_service(&app, request.to_request()).await;
+            assert_eq!(response.status(), StatusCode::OK);
+        });
+    }
+
+    #[test]
+    fn test_resource_verifies_and_dispatches_updates() {
+        let config = WebhookHandlerConfig {
+            max_body_size: Some(MIN_WEBHOOK_BODY_SIZE),
+            require_signature: true,
+            ..WebhookHandlerConfig::default()
+        };
+        let handler = web::Data::new(WebhookHandler::new("test_token", config).unwrap());
+        let body = fixtures::webhook_update_body();
+        let large = " ".repeat(MIN_WEBHOOK_BODY_SIZE + 1);
+
+        actix_web::rt::System::new().block_on(async {
+            let app = test::init_service(App::new().service(resource("/webhook", handler))).await;
+            let post = |body: &str, signature: Option<String>| {
+                let mut request = test::TestRequest::post().uri("/webhook").set_payload(body.to_string());
+                if let Some(signature) = signature {
+                    request = request.insert_header((SIGNATURE_HEADER, signature));
+                }
+                request.to_request()
+            };
+
+            let response = test::call_service(&app, post(&body, Some(sign_body("test_token", &body)))).await;
+            assert_eq!(response.status(), StatusCode::OK);
+            assert_eq!(test::read_body(response).await, r#"{"ok":true}"#);
+
+            let response = test::call_service(&app, post(&body, None)).await;
+            assert_eq!(response.status(), StatusCode::UNAUTHORIZED);
+
+            let response = test::call_service(&app, post("{}", Some(sign_body("test_token", "{}")))).await;
+            assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+
+            let response = test::call_service(&app, post(&large, Some(sign_body("test_token", &large)))).await;
+            assert_eq!(response.status(), StatusCode::PAYLOAD_TOO_LARGE);
+        });
+    }
+
+    #[test]
+    fn test_resource_answers_failed_batches_by_dedup_setting() {
+        let update: serde_json::Value = serde_json::from_str(&fixtures::webhook_update_body()).unwrap();
+        let batch: Vec<_> = (1..=2)
+            .map(|update_id| {
+                let mut update = update.clone();
+                update["update_id"] = serde_json::json!(update_id);
+                update
+            })
+            .collect();
+        let body = serde_json::to_string(&batch).unwrap();
+
+        for dedup_capacity in [None, Some(16)] {
+            let config = WebhookHandlerConfig {
+                dedup_capacity,
+                ..WebhookHandlerConfig::default()
+            };
+            let mut handler = WebhookHandler::new("test_token", config).unwrap();
+            handler.on_update(|update| async move {
+                match update.update_id {
+                    2 => Err(CryptoBotError::HandlerError("failed".into())),
+                    _ => Ok(()),
+                }
+            });
+            let handler = web::Data::new(handler);
+
+            actix_web::rt::System::new().block_on(async {
+                let app = test::init_service(App::new().service(resource("/webhook", handler))).await;
+                let request = test::TestRequest::post()
+                    .uri("/webhook")
+                    .insert_header((SIGNATURE_HEADER, sign_body("test_token", &body)))
+                    .set_payload(body.clone());
+                let response = test::call_service(&app, request.to_request()).await;
+
+                match dedup_capacity {
+                    None => {
+                        assert_eq!(response.status(), StatusCode::OK);
+                        let response: serde_json::Value = test::read_body_json(response).await;
+                        assert_eq!(response["ok"], false);
+                        assert_eq!(response["results"][0]["ok"], true);
+                        assert_eq!(response["results"][1]["ok"], false);
+                    }
+                    Some(_) => assert_eq!(response.status(), StatusCode::INTERNAL_SERVER_ERROR),
+                }
+            });
+        }
+    }
+}
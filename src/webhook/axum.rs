@@ -0,0 +1,209 @@
+use std::sync::Arc;
+
+use axum::{
+    extract::{DefaultBodyLimit, State},
+    http::{header, HeaderMap, StatusCode},
+    response::{IntoResponse, Response},
+    routing::post,
+    Router,
+};
+
+use super::{handler::WebhookReply, WebhookHandler, SIGNATURE_HEADER};
+
+impl WebhookHandler {
+    /// Serves the handler on `path` as an axum router. Requires the `axum` feature.
+    ///
+    /// Each POST request is passed to `handle_request` with its body and `crypto-pay-api-signature`
+    /// header, so the expiration and deduplication settings of the handler apply. Requests without
+    /// the header are always rejected, even if the handler was built without `require_signature`. The
+    /// response status follows `webhook_status`: 200 with the JSON `WebhookResponse` once handled,
+    /// 401 for a missing or invalid signature, 400 for a rejected update and 500 when the update
+    /// handler failed, so Crypto Bot delivers the update again. A batch with failed updates is
+    /// answered with 500 only when deduplication is enabled, otherwise with 200 and the per-update
+    /// `results`. With `max_body_size`, larger bodies are refused with 413 before they are read.
+    ///
+    /// # Example
+    /// ```no_run
+    /// use crypto_pay_api::prelude::*;
+    ///
+    /// # async fn example(client: CryptoBot) -> Result<(), Box<dyn std::error::Error>> {
+    /// let mut handler = client.webhook_handler().require_signature(true).build()?;
+    /// handler.on_update(|update| async move {
+    ///     println!("Invoice paid: {:?}", update.payload);
+    ///     Ok(())
+    /// });
+    ///
+    /// let app = axum::Router::new().merge(handler.into_router("/webhook"));
+    /// let listener = tokio::net::TcpListener::bind("0.0.0.0:3000").await?;
+    /// axum::serve(listener, app).await?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn into_router<S>(self, path: &str) -> Router<S>
+    where
+        S: Clone + Send + Sync + 'static,
+    {
+        let body_limit = self.config.max_body_size;
+        let router = Router::new()
+            .route(path, post(handle_webhook))
+            .with_state(Arc::new(self));
+
+        match body_limit {
+            Some(limit) => router.layer(DefaultBodyLimit::max(limit)),
+            None => router,
+        }
+    }
+}
+
+/// Answers a webhook request with `handler`, for routers that need their own state or middleware
+///
+/// Mount it as `post(handle_webhook)` on a router whose state is an `Arc<WebhookHandler>`. Requests
+/// without a signature are rejected with 401, like with `into_router`.
+pub async fn handle_webhook(State(handler): State<Arc<WebhookHandler>>, headers: HeaderMap, body: String) -> Response {
+    let signature = headers.get(SIGNATURE_HEADER).and_then(|value| value.to_str().ok());
+
+    match handler.reply(&body, signature).await {
+        WebhookReply::Handled(json) => ([(header::CONTENT_TYPE, "application/json")], json).into_response(),
+        WebhookReply::Rejected(status, message) => {
+            (StatusCode::from_u16(status).unwrap_or(StatusCode::BAD_REQUEST), message).into_response()
+        }
+        WebhookReply::Failed => StatusCode::INTERNAL_SERVER_ERROR.into_response(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use axum::body::{to_bytes, Body};
+    use axum::http::Request;
+    use tower::ServiceExt;
+
+    use super::*;
+    use crate::{
+        config::MIN_WEBHOOK_BODY_SIZE,
+        error::CryptoBotError,
+        fixtures,
+        webhook::{handler::sign_body, WebhookHandlerConfig, WebhookHandlerConfigBuilder},
+    };
+
+    async fn post_webhook(router: Router, body: &str, signature: Option<&str>) -> (StatusCode, String) {
+        let mut request = Request::post("/webhook");
+        if let Some(signature) = signature {
+            request = request.header(SIGNATURE_HEADER, signature);
+        }
+        let response = router
+            .oneshot(request.body(Body::from(body.to_string())).unwrap())
+            .await
+            .unwrap();
+
+        let status = response.status();
+        let body = to_bytes(response.into_body(), usize::MAX).await.unwrap();
+        (status, String::from_utf8(body.to_vec()).unwrap())
+    }
+
+    #[tokio::test]
+    async fn test_router_rejects_unsigned_updates_by_default() {
+        let handler = WebhookHandlerConfigBuilder::new()
+            .build_config()
+            .and_then(|config| WebhookHandler::new("test_token", config))
+            .unwrap();
+        assert!(!handler.config.require_signature);
+        let router: Router = handler.into_router("/webhook");
+        let body = fixtures::webhook_update_body();
+
+        assert_eq!(
+            post_webhook(router.clone(), &body, None).await.0,
+            StatusCode::UNAUTHORIZED
+        );
+        assert_eq!(
+            post_webhook(router, &body, Some(&sign_body("test_token", &body)))
+                .await
+                .0,
+            StatusCode::OK
+        );
+    }
+
+    #[tokio::test]
+    async fn test_router_verifies_and_dispatches_updates() {
+        let config = WebhookHandlerConfig {
+            max_body_size: Some(MIN_WEBHOOK_BODY_SIZE),
+            require_signature: true,
+            ..WebhookHandlerConfig::default()
+        };
+        let mut handler = WebhookHandler::new("test_token", config).unwrap();
+        handler.on_update(|update| async move {
+            match update.invoice() {
+                Some(_) => Ok(()),
+                None => Err(CryptoBotError::HandlerError("not an invoice".into())),
+            }
+        });
+        let router: Router = handler.into_router("/webhook");
+        let body = fixtures::webhook_update_body();
+
+        assert_eq!(
+            post_webhook(router.clone(), &body, Some(&sign_body("test_token", &body))).await,
+            (StatusCode::OK, r#"{"ok":true}"#.to_string())
+        );
+        assert_eq!(
+            post_webhook(router.clone(), &body, None).await.0,
+            StatusCode::UNAUTHORIZED
+        );
+        assert_eq!(
+            post_webhook(router.clone(), &body, Some("00ff")).await.0,
+            StatusCode::UNAUTHORIZED
+        );
+        assert_eq!(
+            post_webhook(router.clone(), "{}", Some(&sign_body("test_token", "{}")))
+                .await
+                .0,
+            StatusCode::BAD_REQUEST
+        );
+
+        let large = " ".repeat(MIN_WEBHOOK_BODY_SIZE + 1);
+        assert_eq!(
+            post_webhook(router, &large, Some(&sign_body("test_token", &large)))
+                .await
+                .0,
+            StatusCode::PAYLOAD_TOO_LARGE
+        );
+    }
+
+    #[tokio::test]
+    async fn test_router_answers_failed_batches_by_dedup_setting() {
+        let update: serde_json::Value = serde_json::from_str(&fixtures::webhook_update_body()).unwrap();
+        let batch: Vec<_> = (1..=2)
+            .map(|update_id| {
+                let mut update = update.clone();
+                update["update_id"] = serde_json::json!(update_id);
+                update
+            })
+            .collect();
+        let body = serde_json::to_string(&batch).unwrap();
+
+        for dedup_capacity in [None, Some(16)] {
+            let config = WebhookHandlerConfig {
+                dedup_capacity,
+                ..WebhookHandlerConfig::default()
+            };
+            let mut handler = WebhookHandler::new("test_token", config).unwrap();
+            handler.on_update(|update| async move {
+                match update.update_id {
+                    2 => Err(CryptoBotError::HandlerError("failed".into())),
+                    _ => Ok(()),
+                }
+            });
+            let router: Router = handler.into_router("/webhook");
+            let (status, response) = post_webhook(router, &body, Some(&sign_body("test_token", &body))).await;
+
+            match dedup_capacity {
+                None => {
+                    assert_eq!(status, StatusCode::OK);
+                    let response: serde_json::Value = serde_json::from_str(&response).unwrap();
+                    assert_eq!(response["ok"], false);
+                    assert_eq!(response["results"][0]["ok"], true);
+                    assert_eq!(response["results"][1]["ok"], false);
+                }
+                Some(_) => assert_eq!(status, StatusCode::INTERNAL_SERVER_ERROR),
+            }
+        }
+    }
+}
@@ -1,10 +1,98 @@
 use std::time::Duration;
 
-use crate::client::DEFAULT_WEBHOOK_EXPIRATION_TIME;
+use crate::{
+    config::{
+        DEFAULT_WEBHOOK_EXPIRATION_TIME, MAX_WEBHOOK_BODY_SIZE, MIN_WEBHOOK_BODY_SIZE, MIN_WEBHOOK_EXPIRATION_TIME,
+    },
+    error::{CryptoBotError, CryptoBotResult},
+    models::Rounding,
+    utils::redact_option,
+    webhook::handler::WebhookHandler,
+};
 
-#[derive(Debug, Default)]
+/// Settings of a [`WebhookHandler`]. The `Debug` output redacts the payload secret.
+#[derive(Default)]
 pub struct WebhookHandlerConfig {
     pub expiration_time: Option<Duration>,
+    /// Whether `WebhookUpdate::paid_usd_amount` is filled in before calling the update handler.
+    pub enrich_usd: bool,
+    /// Rounding of `WebhookUpdate::paid_usd_amount` to whole cents, if rounded.
+    pub usd_rounding: Option<Rounding>,
+    /// Secret used to verify invoice payloads signed with `sign_payload`, if enabled.
+    pub payload_hmac_secret: Option<String>,
+    /// Number of dispatch lanes updates are hashed onto by invoice ID, if keyed serialization is enabled.
+    pub dispatch_lanes: Option<usize>,
+    /// Maximum size of a request body in bytes, if limited.
+    pub max_body_size: Option<usize>,
+    /// Number of recently handled update IDs remembered to skip redeliveries, if deduplication is enabled.
+    pub dedup_capacity: Option<usize>,
+    /// How long handled update IDs are remembered for deduplication, if limited.
+    pub dedup_ttl: Option<Duration>,
+    /// Whether `WebhookHandler::handle_request` rejects requests without a signature.
+    pub require_signature: bool,
+}
+
+impl std::fmt::Debug for WebhookHandlerConfig {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("WebhookHandlerConfig")
+            .field("expiration_time", &self.expiration_time)
+            .field("enrich_usd", &self.enrich_usd)
+            .field("usd_rounding", &self.usd_rounding)
+            .field("payload_hmac_secret", &redact_option(&self.payload_hmac_secret))
+            .field("dispatch_lanes", &self.dispatch_lanes)
+            .field("max_body_size", &self.max_body_size)
+            .field("dedup_capacity", &self.dedup_capacity)
+            .field("dedup_ttl", &self.dedup_ttl)
+            .field("require_signature", &self.require_signature)
+            .finish()
+    }
+}
+
+impl WebhookHandlerConfig {
+    /// Recommended settings for production
+    ///
+    /// # Settings
+    /// * Expiration time: 5 minutes
+    /// * Maximum body size: 256 KiB
+    /// * Signature required
+    pub fn default_strict() -> Self {
+        Self {
+            expiration_time: Some(Duration::from_secs(300)),
+            max_body_size: Some(256 * 1024),
+            require_signature: true,
+            ..Self::default()
+        }
+    }
+
+    pub(super) fn validate(&self) -> CryptoBotResult<()> {
+        if let Some(expiration_time) = self.expiration_time {
+            if expiration_time < Duration::from_secs(MIN_WEBHOOK_EXPIRATION_TIME) {
+                return Err(CryptoBotError::ConfigError {
+                    message: format!(
+                        "webhook expiration time must be at least {MIN_WEBHOOK_EXPIRATION_TIME} seconds, got {expiration_time:?}"
+                    ),
+                });
+            }
+        }
+
+        if let Some(max_body_size) = self.max_body_size {
+            if !(MIN_WEBHOOK_BODY_SIZE..=MAX_WEBHOOK_BODY_SIZE).contains(&max_body_size) {
+                return Err(CryptoBotError::ConfigError {
+                    message: format!(
+                        "webhook max body size must be between {MIN_WEBHOOK_BODY_SIZE} and {MAX_WEBHOOK_BODY_SIZE} bytes, got {max_body_size}"
+                    ),
+                });
+            }
+        }
+
+        if self.dedup_ttl.is_some() && self.dedup_capacity.is_none() {
+            return Err(CryptoBotError::ConfigError {
+                message: "webhook dedup TTL requires deduplication, see deduplicate(capacity)".to_string(),
+            });
+        }
+
+        Ok(())
+    }
 }
 
 pub struct WebhookHandlerConfigBuilder<'a> {
@@ -22,16 +110,33 @@ impl<'a> WebhookHandlerConfigBuilder<'a> {
             api_token: None,
             config: WebhookHandlerConfig {
                 expiration_time: Some(Duration::from_secs(DEFAULT_WEBHOOK_EXPIRATION_TIME)),
+                enrich_usd: false,
+                usd_rounding: None,
+                payload_hmac_secret: None,
+                dispatch_lanes: None,
+                max_body_size: None,
+                dedup_capacity: None,
+                dedup_ttl: None,
+                require_signature: false,
             },
         }
     }
 
-    /// Creates a new webhook handler config builder with client reference
-    pub(crate) fn new_with_client(api_token: &'a str) -> Self {
+    /// Creates a new webhook handler config builder with client reference, its default expiration time and rounding
+    #[cfg(feature = "client")]
+    pub(crate) fn new_with_client(api_token: &'a str, expiration_time: Duration, rounding: Rounding) -> Self {
         Self {
             api_token: Some(api_token),
             config: WebhookHandlerConfig {
-                expiration_time: Some(Duration::from_secs(DEFAULT_WEBHOOK_EXPIRATION_TIME)),
+                expiration_time: Some(expiration_time),
+                enrich_usd: false,
+                usd_rounding: Some(rounding),
+                payload_hmac_secret: None,
+                dispatch_lanes: None,
+                max_body_size: None,
+                dedup_capacity: None,
+                dedup_ttl: None,
+                require_signature: false,
             },
         }
     }
@@ -48,17 +153,101 @@ impl<'a> WebhookHandlerConfigBuilder<'a> {
         self
     }
 
-    /// Builds the webhook handler config (for backward compatibility)
-    pub fn build_config(self) -> WebhookHandlerConfig {
-        self.config
+    /// Attaches the USD value of the paid invoice to every update before calling the update handler
+    ///
+    /// The value is computed from the rate reported with the invoice, or from the exchange rates
+    /// cached with `WebhookHandler::refresh_exchange_rates`. Handlers created with `client.webhook_handler()`
+    /// round it to whole cents with the rounding of the client, others keep it exact unless `usd_rounding` is set.
+    pub fn enrich_usd_amount(mut self) -> Self {
+        self.config.enrich_usd = true;
+        self
+    }
+
+    /// Rounds the USD value attached by `enrich_usd_amount` to whole cents with `rounding`
+    pub fn usd_rounding(mut self, rounding: Rounding) -> Self {
+        self.config.usd_rounding = Some(rounding);
+        self
+    }
+
+    /// Only accepts updates whose invoice payload was signed with `sign_payload` using this secret
+    ///
+    /// Updates with a missing or invalid payload HMAC are rejected with
+    /// `WebhookErrorKind::InvalidSignature`. The HMAC is stripped before the update handler is
+    /// called, so it receives the original payload.
+    pub fn with_payload_hmac(mut self, secret: impl Into<String>) -> Self {
+        self.config.payload_hmac_secret = Some(secret.into());
+        self
+    }
+
+    /// Serializes the update handler per invoice while processing different invoices in parallel
+    ///
+    /// Updates are hashed by invoice ID onto `lanes` lanes, and updates in the same lane are never
+    /// processed concurrently. Two updates for the same invoice are therefore handled one after the
+    /// other, and at most `lanes` handlers run at once. Unrelated invoices sharing a lane also wait
+    /// for each other, so pick more lanes than the expected number of concurrent updates.
+    /// A value of 0 is treated as 1.
+    pub fn dispatch_lanes(mut self, lanes: usize) -> Self {
+        self.config.dispatch_lanes = Some(lanes.max(1));
+        self
+    }
+
+    /// Skips updates that were already handled, remembering the IDs of the last `capacity` updates
+    ///
+    /// Crypto Bot redelivers an update until it receives a 2xx response, so the same update can
+    /// arrive again, e.g. after a timeout. A repeated update does not call the update handler and
+    /// `handle_update` returns `WebhookResponse::ok_duplicate`, which should be answered with a
+    /// 2xx status as well. Updates whose handling failed are forgotten, so a redelivery is
+    /// processed again. IDs are kept in memory, so each instance of a horizontally scaled service
+    /// deduplicates on its own. A value of 0 is treated as 1.
+    pub fn deduplicate(mut self, capacity: usize) -> Self {
+        self.config.dedup_capacity = Some(capacity.max(1));
+        self
+    }
+
+    /// Forgets handled update IDs after `ttl`, in addition to the capacity set with `deduplicate`
+    ///
+    /// Crypto Bot stops redelivering an update after a while, so IDs older than that only hold
+    /// memory in a long-running process. Requires `deduplicate`.
+    pub fn dedup_ttl(mut self, ttl: Duration) -> Self {
+        self.config.dedup_ttl = Some(ttl);
+        self
+    }
+
+    /// Makes `WebhookHandler::handle_request` refuse requests without a signature
+    ///
+    /// Requests without a signature are rejected with `WebhookErrorKind::MissingSignature`, so no
+    /// update is processed unless it was verified. A signature that is present is always verified.
+    pub fn require_signature(mut self, require: bool) -> Self {
+        self.config.require_signature = require;
+        self
     }
 
-    /// Builds the webhook handler (requires client reference)
-    pub fn build(self) -> crate::webhook::handler::WebhookHandler {
+    /// Rejects request bodies larger than `bytes` with `WebhookErrorKind::InvalidPayload`
+    pub fn max_body_size(mut self, bytes: usize) -> Self {
+        self.config.max_body_size = Some(bytes);
+        self
+    }
+
+    /// Validates and builds the webhook handler config
+    ///
+    /// # Errors
+    /// * `CryptoBotError::ConfigError` - If the expiration time is shorter than
+    ///   [`MIN_WEBHOOK_EXPIRATION_TIME`] or the body size limit is outside
+    ///   [`MIN_WEBHOOK_BODY_SIZE`]..=[`MAX_WEBHOOK_BODY_SIZE`]
+    pub fn build_config(self) -> CryptoBotResult<WebhookHandlerConfig> {
+        self.config.validate()?;
+        Ok(self.config)
+    }
+
+    /// Validates the config and builds the webhook handler (requires client reference)
+    ///
+    /// # Errors
+    /// * `CryptoBotError::ConfigError` - If the config is invalid, see `build_config`
+    pub fn build(self) -> CryptoBotResult<WebhookHandler> {
         let api_token = self
             .api_token
             .expect("WebhookHandlerConfigBuilder must be created via client.webhook_handler()");
-        crate::webhook::handler::WebhookHandler::with_config(api_token, self.config)
+        WebhookHandler::new(api_token, self.config)
     }
 }
 
@@ -92,5 +281,108 @@ mod tests {
         let builder = WebhookHandlerConfigBuilder::default();
 
         assert_eq!(builder.config.expiration_time, Some(Duration::from_secs(600)));
+        assert!(!builder.config.enrich_usd);
+        assert_eq!(builder.config.payload_hmac_secret, None);
+    }
+
+    #[test]
+    fn test_webhook_handler_config_builder_dispatch_lanes() {
+        assert_eq!(WebhookHandlerConfigBuilder::new().config.dispatch_lanes, None);
+        assert_eq!(
+            WebhookHandlerConfigBuilder::new()
+                .dispatch_lanes(8)
+                .config
+                .dispatch_lanes,
+            Some(8)
+        );
+        assert_eq!(
+            WebhookHandlerConfigBuilder::new()
+                .dispatch_lanes(0)
+                .config
+                .dispatch_lanes,
+            Some(1)
+        );
+    }
+
+    #[test]
+    fn test_webhook_handler_config_builder_deduplicate() {
+        assert_eq!(WebhookHandlerConfigBuilder::new().config.dedup_capacity, None);
+        assert_eq!(
+            WebhookHandlerConfigBuilder::new().deduplicate(0).config.dedup_capacity,
+            Some(1)
+        );
+        assert!(matches!(
+            WebhookHandlerConfigBuilder::new()
+                .dedup_ttl(Duration::from_secs(3600))
+                .build_config(),
+            Err(CryptoBotError::ConfigError { .. })
+        ));
+
+        // Configs built by hand are validated by the handler
+        let config = WebhookHandlerConfig {
+            dedup_ttl: Some(Duration::from_secs(3600)),
+            ..WebhookHandlerConfig::default()
+        };
+        assert!(matches!(
+            WebhookHandler::new("test_token", config),
+            Err(CryptoBotError::ConfigError { .. })
+        ));
+    }
+
+    #[test]
+    fn test_webhook_handler_config_builder_with_payload_hmac() {
+        let builder = WebhookHandlerConfigBuilder::new().with_payload_hmac("secret");
+
+        assert_eq!(builder.config.payload_hmac_secret.as_deref(), Some("secret"));
+    }
+
+    #[test]
+    fn test_build_config_validation() {
+        assert!(matches!(
+            WebhookHandlerConfigBuilder::new()
+                .expiration_time(Duration::ZERO)
+                .build_config(),
+            Err(CryptoBotError::ConfigError { .. })
+        ));
+        assert!(WebhookHandlerConfigBuilder::new()
+            .expiration_time(Duration::from_secs(MIN_WEBHOOK_EXPIRATION_TIME))
+            .build_config()
+            .is_ok());
+        assert!(WebhookHandlerConfigBuilder::new()
+            .disable_expiration()
+            .build_config()
+            .is_ok());
+
+        for size in [0, MIN_WEBHOOK_BODY_SIZE - 1, MAX_WEBHOOK_BODY_SIZE + 1] {
+            assert!(WebhookHandlerConfigBuilder::new()
+                .max_body_size(size)
+                .build_config()
+                .is_err());
+        }
+        assert_eq!(
+            WebhookHandlerConfigBuilder::new()
+                .max_body_size(MIN_WEBHOOK_BODY_SIZE)
+                .build_config()
+                .unwrap()
+                .max_body_size,
+            Some(MIN_WEBHOOK_BODY_SIZE)
+        );
+    }
+
+    #[test]
+    fn test_default_strict_is_valid() {
+        let config = WebhookHandlerConfig::default_strict();
+
+        assert_eq!(config.expiration_time, Some(Duration::from_secs(300)));
+        assert_eq!(config.max_body_size, Some(256 * 1024));
+        assert!(config.require_signature);
+        assert!(config.validate().is_ok());
+    }
+
+    #[test]
+    fn test_webhook_handler_config_builder_enrich_usd_amount() {
+        let builder = WebhookHandlerConfigBuilder::new().enrich_usd_amount();
+
+        assert!(builder.config.enrich_usd);
     }
 }
@@ -0,0 +1,93 @@
+use std::{
+    collections::{HashSet, VecDeque},
+    time::Duration,
+};
+
+use chrono::{DateTime, Utc};
+
+/// IDs of recently handled updates, bounded by count and optionally by age
+///
+/// IDs are evicted oldest first once `capacity` is reached, and dropped when older than `ttl`.
+#[derive(Debug, Default)]
+pub(crate) struct SeenUpdates {
+    ids: HashSet<i64>,
+    order: VecDeque<(i64, DateTime<Utc>)>,
+}
+
+impl SeenUpdates {
+    /// Records `update_id` as seen at `now`, returning false if it is still remembered
+    pub(crate) fn claim(&mut self, update_id: i64, now: DateTime<Utc>, capacity: usize, ttl: Option<Duration>) -> bool {
+        self.evict_expired(now, ttl);
+        if self.ids.contains(&update_id) {
+            return false;
+        }
+
+        while self.order.len() >= capacity {
+            let Some((oldest, _)) = self.order.pop_front() else {
+                break;
+            };
+            self.ids.remove(&oldest);
+        }
+        self.ids.insert(update_id);
+        self.order.push_back((update_id, now));
+        true
+    }
+
+    /// Forgets `update_id`, e.g. when its handling failed
+    pub(crate) fn release(&mut self, update_id: i64) {
+        if self.ids.remove(&update_id) {
+            self.order.retain(|(id, _)| *id != update_id);
+        }
+    }
+
+    pub(crate) fn len(&self) -> usize {
+        self.order.len()
+    }
+
+    fn evict_expired(&mut self, now: DateTime<Utc>, ttl: Option<Duration>) {
+        let Some(cutoff) = ttl
+            .and_then(|ttl| chrono::TimeDelta::from_std(ttl).ok())
+            .map(|ttl| now - ttl)
+        else {
+            return;
+        };
+
+        while let Some((id, _)) = self.order.front().filter(|(_, seen_at)| *seen_at <= cutoff).copied() {
+            self.order.pop_front();
+            self.ids.remove(&id);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::TimeZone;
+
+    use super::*;
+
+    #[test]
+    fn test_seen_updates_evicts_oldest_and_expired() {
+        let start = Utc.with_ymd_and_hms(2025, 2, 8, 12, 0, 0).unwrap();
+        let mut seen = SeenUpdates::default();
+
+        assert!(seen.claim(1, start, 2, None));
+        assert!(!seen.claim(1, start, 2, None));
+        assert!(seen.claim(2, start, 2, None));
+        assert!(seen.claim(3, start, 2, None));
+        assert_eq!(seen.len(), 2);
+        assert!(seen.claim(1, start, 2, None));
+
+        let ttl = Some(Duration::from_secs(60));
+        let later = start + chrono::TimeDelta::seconds(30);
+        assert!(seen.claim(4, later, 10, ttl));
+        assert!(!seen.claim(4, later, 10, ttl));
+        assert_eq!(seen.len(), 3);
+
+        // Everything claimed at `start` is older than the TTL a minute later
+        assert!(seen.claim(5, start + chrono::TimeDelta::seconds(60), 10, ttl));
+        assert_eq!(seen.len(), 2);
+
+        seen.release(4);
+        assert_eq!(seen.len(), 1);
+    }
+}
@@ -0,0 +1,200 @@
+use std::{sync::atomic::AtomicI64, sync::atomic::Ordering, time::Duration};
+
+use chrono::Utc;
+
+use crate::{error::CryptoBotResult, fixtures};
+
+pub use super::handler::sign_body;
+use super::SIGNATURE_HEADER;
+
+/// Kind of synthetic update sent by [`LocalSender`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Scenario {
+    /// A fresh `invoice_paid` update with a valid signature.
+    Paid,
+    /// An `invoice_paid` update dated one day ago, which handlers reject as expired.
+    Expired,
+    /// An `invoice_paid` update signed with the wrong token.
+    InvalidSignature,
+}
+
+/// Result of sending a single update.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SendOutcome {
+    pub scenario: Scenario,
+    pub update_id: i64,
+    /// HTTP status code returned by the endpoint.
+    pub status: u16,
+}
+
+/// Posts signed synthetic webhook updates to a local endpoint.
+#[derive(Debug)]
+pub struct LocalSender {
+    url: String,
+    api_token: String,
+    client: reqwest::Client,
+    scenarios: Vec<Scenario>,
+    interval: Duration,
+    payload: Option<String>,
+    next_update_id: AtomicI64,
+}
+
+impl LocalSender {
+    /// Creates a sender posting to `url`, signing updates with `api_token` like Crypto Bot does
+    pub fn new(url: impl Into<String>, api_token: impl Into<String>) -> Self {
+        Self {
+            url: url.into(),
+            api_token: api_token.into(),
+            client: reqwest::Client::new(),
+            scenarios: Vec::new(),
+            interval: Duration::ZERO,
+            payload: None,
+            next_update_id: AtomicI64::new(1),
+        }
+    }
+
+    /// Adds a scenario to send with `run()`
+    pub fn scenario(mut self, scenario: Scenario) -> Self {
+        self.scenarios.push(scenario);
+        self
+    }
+
+    /// Sets the delay between updates sent by `run()`. Defaults to no delay.
+    pub fn interval(mut self, interval: Duration) -> Self {
+        self.interval = interval;
+        self
+    }
+
+    /// Sets the invoice payload of the updates, e.g. an order ID known to the application
+    pub fn payload(mut self, payload: impl Into<String>) -> Self {
+        self.payload = Some(payload.into());
+        self
+    }
+
+    /// Sends every configured scenario in order, waiting `interval` between them
+    pub async fn run(&self) -> CryptoBotResult<Vec<SendOutcome>> {
+        let mut outcomes = Vec::with_capacity(self.scenarios.len());
+        for (index, scenario) in self.scenarios.iter().enumerate() {
+            if index > 0 && !self.interval.is_zero() {
+                tokio::time::sleep(self.interval).await;
+            }
+            outcomes.push(self.send(*scenario).await?);
+        }
+        Ok(outcomes)
+    }
+
+    /// Sends a single update for the scenario
+    pub async fn send(&self, scenario: Scenario) -> CryptoBotResult<SendOutcome> {
+        let update_id = self.next_update_id.fetch_add(1, Ordering::Relaxed);
+        let body = self.build_body(scenario, update_id);
+
+        let token = match scenario {
+            Scenario::InvalidSignature => "invalid-token",
+            _ => self.api_token.as_str(),
+        };
+
+        let response = self
+            .client
+            .post(&self.url)
+            .header(reqwest::header::CONTENT_TYPE, "application/json")
+            .header(SIGNATURE_HEADER, sign_body(token, &body))
+            .body(body)
+            .send()
+            .await?;
+
+        Ok(SendOutcome {
+            scenario,
+            update_id,
+            status: response.status().as_u16(),
+        })
+    }
+
+    /// Returns the JSON body sent for a scenario
+    pub fn build_body(&self, scenario: Scenario, update_id: i64) -> String {
+        let mut update: serde_json::Value =
+            serde_json::from_str(fixtures::WEBHOOK_UPDATE).expect("fixture is valid JSON");
+
+        let request_date = match scenario {
+            Scenario::Expired => Utc::now() - chrono::Duration::days(1),
+            _ => Utc::now(),
+        };
+
+        update["update_id"] = update_id.into();
+        update["request_date"] = request_date.to_rfc3339().into();
+        if let Some(payload) = &self.payload {
+            update["payload"]["payload"] = payload.as_str().into();
+        }
+
+        update.to_string()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use mockito::Matcher;
+
+    use super::*;
+    use crate::{
+        error::{CryptoBotError, WebhookErrorKind},
+        webhook::{WebhookHandler, WebhookHandlerConfig},
+    };
+
+    #[test]
+    fn test_sign_body_matches_handler_verification() {
+        let handler = WebhookHandler::new("token", WebhookHandlerConfig::default()).unwrap();
+        let body = fixtures::webhook_update_body();
+
+        assert!(handler.verify_signature(&body, &sign_body("token", &body)));
+        assert!(!handler.verify_signature(&body, &sign_body("other", &body)));
+    }
+
+    #[tokio::test]
+    async fn test_scenario_bodies() {
+        let sender = LocalSender::new("http://127.0.0.1:1", "token").payload("order_7");
+        let mut handler = WebhookHandler::new("token", WebhookHandlerConfig::default()).unwrap();
+        handler.config.expiration_time = Some(Duration::from_secs(600));
+
+        let paid = sender.build_body(Scenario::Paid, 5);
+        let update = WebhookHandler::parse_update(&paid).unwrap();
+        assert_eq!(update.update_id, 5);
+        assert_eq!(update.invoice().unwrap().payload.as_deref(), Some("order_7"));
+        assert!(handler.handle_update(&paid).await.is_ok());
+
+        let expired = sender.build_body(Scenario::Expired, 6);
+        assert!(matches!(
+            handler.handle_update(&expired).await,
+            Err(CryptoBotError::WebhookError {
+                kind: WebhookErrorKind::Expired,
+                ..
+            })
+        ));
+    }
+
+    #[tokio::test]
+    async fn test_run_posts_signed_updates() {
+        let mut server = mockito::Server::new_async().await;
+        let _m = server
+            .mock("POST", "/webhook")
+            .match_header(SIGNATURE_HEADER, Matcher::Regex("^[0-9a-f]{64}$".to_string()))
+            .with_status(200)
+            .expect(3)
+            .create_async()
+            .await;
+
+        let outcomes = LocalSender::new(format!("{}/webhook", server.url()), "token")
+            .scenario(Scenario::Paid)
+            .scenario(Scenario::Expired)
+            .scenario(Scenario::InvalidSignature)
+            .interval(Duration::from_millis(1))
+            .run()
+            .await
+            .unwrap();
+
+        let ids: Vec<_> = outcomes
+            .iter()
+            .map(|outcome| (outcome.update_id, outcome.status))
+            .collect();
+        assert_eq!(ids, vec![(1, 200), (2, 200), (3, 200)]);
+        _m.assert_async().await;
+    }
+}
@@ -1,31 +1,340 @@
-use chrono::{DateTime, Utc};
+use chrono::{DateTime, NaiveDateTime, Utc};
 use hmac::{Hmac, Mac};
 use sha2::{Digest, Sha256};
+use std::collections::HashMap;
 use std::future::Future;
 use std::pin::Pin;
+use std::sync::RwLock;
+use std::time::Duration;
 
+use futures_util::lock::Mutex;
+
+#[cfg(feature = "client")]
+use crate::{api::ExchangeRateAPI, client::CryptoBot};
 use crate::{
-    error::{CryptoBotError, WebhookErrorKind},
-    models::{WebhookResponse, WebhookUpdate},
+    error::{CryptoBotError, CryptoBotResult, WebhookErrorKind},
+    models::{ExchangeRate, UpdateType, WebhookPayload, WebhookResponse, WebhookUpdate, WebhookUpdateResult},
+    utils::redact,
 };
 
-use super::WebhookHandlerConfig;
+use super::{dedup::SeenUpdates, payload::verify_signed_payload, Notifier, WebhookHandlerConfig};
 
 pub type WebhookHandlerFn =
     Box<dyn Fn(WebhookUpdate) -> Pin<Box<dyn Future<Output = Result<(), CryptoBotError>> + Send>> + Send + Sync>;
 
+/// Header carrying the signature of a webhook request, see `WebhookHandler::handle_request`.
+pub const SIGNATURE_HEADER: &str = "crypto-pay-api-signature";
+
+/// Callback receiving the errors of notifiers, see `WebhookHandler::on_notify_error`
+pub type NotifyErrorFn = Box<dyn Fn(&CryptoBotError) + Send + Sync>;
+
+/// Source of the current time used to check webhook expiration, see `WebhookHandler::set_clock`
+pub type WebhookClockFn = Box<dyn Fn() -> DateTime<Utc> + Send + Sync>;
+
+/// Returns whether a webhook request sent at `request_date` is older than `tolerance` at `now`
+///
+/// This is the check `WebhookHandler::handle_update` applies with the configured expiration time,
+/// exposed so application tests can exercise their own policies without a clock. Requests dated in
+/// the future are never expired.
+///
+/// # Example
+/// ```
+/// use crypto_pay_api::prelude::*;
+/// use chrono::{TimeDelta, Utc};
+/// use std::time::Duration;
+///
+/// let now = Utc::now();
+/// let tolerance = Duration::from_secs(600);
+///
+/// assert!(!is_expired(now - TimeDelta::minutes(10), now, tolerance));
+/// assert!(is_expired(now - TimeDelta::minutes(11), now, tolerance));
+/// ```
+pub fn is_expired(request_date: DateTime<Utc>, now: DateTime<Utc>, tolerance: Duration) -> bool {
+    match (now - request_date).to_std() {
+        Ok(age) => age > tolerance,
+        Err(_) => false,
+    }
+}
+
+/// Parses the `request_date` of a webhook update
+///
+/// Accepts RFC 3339 as well as common ISO 8601 variants: a space instead of `T`, a missing
+/// timezone (interpreted as UTC) and fractional seconds with more than nanosecond precision
+/// (extra digits are truncated).
+pub(crate) fn parse_request_date(raw: &str) -> Option<DateTime<Utc>> {
+    let value = truncate_fractional_seconds(raw.trim());
+
+    if let Ok(date) = DateTime::parse_from_rfc3339(&value) {
+        return Some(date.with_timezone(&Utc));
+    }
+
+    for format in [
+        "%Y-%m-%dT%H:%M:%S%.f%:z",
+        "%Y-%m-%d %H:%M:%S%.f%:z",
+        "%Y-%m-%dT%H:%M:%S%.f%z",
+    ] {
+        if let Ok(date) = DateTime::parse_from_str(&value, format) {
+            return Some(date.with_timezone(&Utc));
+        }
+    }
+
+    for format in ["%Y-%m-%dT%H:%M:%S%.f", "%Y-%m-%d %H:%M:%S%.f"] {
+        if let Ok(date) = NaiveDateTime::parse_from_str(&value, format) {
+            return Some(date.and_utc());
+        }
+    }
+
+    None
+}
+
+/// Keeps at most 9 digits of fractional seconds, which is the precision supported by chrono.
+fn truncate_fractional_seconds(value: &str) -> String {
+    let Some(dot) = value.find('.') else {
+        return value.to_string();
+    };
+
+    let digits = value[dot + 1..].chars().take_while(|c| c.is_ascii_digit()).count();
+    if digits <= 9 {
+        return value.to_string();
+    }
+
+    format!("{}{}", &value[..dot + 10], &value[dot + 1 + digits..])
+}
+
+/// Returns the HTTP status code answering a webhook request that failed with `error`
+///
+/// Used by the axum and actix-web integrations, and for other frameworks. Failures of the update
+/// handler are server errors, so Crypto Bot retries them. Requests with a missing or invalid
+/// signature are unauthorized, and other rejected updates are bad requests.
+#[cfg(any(feature = "axum", feature = "actix"))]
+pub fn webhook_status(error: &CryptoBotError) -> u16 {
+    match error {
+        CryptoBotError::WebhookError {
+            kind: WebhookErrorKind::InvalidSignature | WebhookErrorKind::MissingSignature,
+            ..
+        } => 401,
+        CryptoBotError::WebhookError { .. } => 400,
+        _ => 500,
+    }
+}
+
+/// How the web framework integrations answer a webhook request
+#[cfg(any(feature = "axum", feature = "actix"))]
+#[derive(Debug, PartialEq)]
+pub(crate) enum WebhookReply {
+    /// 200 with the JSON `WebhookResponse`
+    Handled(String),
+    /// A client error status with the error message
+    Rejected(u16, String),
+    /// 500 without a body, so Crypto Bot delivers the request again
+    Failed,
+}
+
+/// Verifies and dispatches webhook updates.
+///
+/// `WebhookHandler` is `Send + Sync`, and `handle_update` can be called concurrently, e.g. from an
+/// `Arc` in axum state. Registered update handlers must be `Send + Sync` as well.
+///
+/// The `Debug` output redacts the API token.
 pub struct WebhookHandler {
     pub(crate) api_token: String,
     pub(crate) config: WebhookHandlerConfig,
     pub(crate) update_handler: Option<WebhookHandlerFn>,
+    pub(crate) type_handlers: HashMap<UpdateType, WebhookHandlerFn>,
+    pub(crate) exchange_rates: RwLock<Vec<ExchangeRate>>,
+    pub(crate) lanes: Vec<Mutex<()>>,
+    pub(crate) seen_updates: std::sync::Mutex<SeenUpdates>,
+    pub(crate) clock: Option<WebhookClockFn>,
+    pub(crate) notifiers: Vec<Box<dyn Notifier>>,
+    pub(crate) notify_error_hook: Option<NotifyErrorFn>,
+}
+
+/// Sizes of the in-memory state of a [`WebhookHandler`], returned by `WebhookHandler::memory_usage`
+///
+/// Every entry is bounded: remembered update IDs by the deduplication capacity and TTL, exchange
+/// rates by the last refresh, and lanes and notifiers by the configuration.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct WebhookMemoryUsage {
+    /// Number of update IDs remembered for deduplication.
+    pub seen_updates: usize,
+    /// Maximum number of remembered update IDs, `None` if deduplication is disabled.
+    pub seen_updates_capacity: Option<usize>,
+    /// Number of cached exchange rates used for USD enrichment.
+    pub exchange_rates: usize,
+    /// Number of dispatch lanes.
+    pub dispatch_lanes: usize,
+    /// Number of registered notifiers.
+    pub notifiers: usize,
+}
+
+/// Whether an update was passed to the update handler or skipped as a redelivery
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Processed {
+    Handled,
+    Duplicate,
+}
+
+/// Returns the HMAC of a webhook body, keyed with the SHA-256 hash of the API token
+fn body_mac(api_token: &str, body: &str) -> Hmac<Sha256> {
+    let secret = Sha256::digest(api_token.as_bytes());
+    let mut mac = Hmac::<Sha256>::new_from_slice(&secret).expect("HMAC can take key of any size");
+    mac.update(body.as_bytes());
+    mac
+}
+
+/// Computes the `crypto-pay-api-signature` header Crypto Bot sends with a webhook body
+#[cfg(any(test, feature = "dev"))]
+pub fn sign_body(api_token: &str, body: &str) -> String {
+    hex::encode(body_mac(api_token, body).finalize().into_bytes())
+}
+
+/// An update claimed by `claim_update`, released on drop unless committed
+struct UpdateClaim<'a> {
+    handler: &'a WebhookHandler,
+    update_id: i64,
+    committed: bool,
+}
+
+impl UpdateClaim<'_> {
+    /// Keeps the update as seen once it was handled
+    fn commit(mut self) {
+        self.committed = true;
+    }
+}
+
+impl Drop for UpdateClaim<'_> {
+    fn drop(&mut self) {
+        if !self.committed && self.handler.config.dedup_capacity.is_some() {
+            self.handler.release_update(self.update_id);
+        }
+    }
+}
+
+impl std::fmt::Debug for WebhookHandler {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("WebhookHandler")
+            .field("api_token", &redact(&self.api_token))
+            .field("config", &self.config)
+            .field("has_update_handler", &self.update_handler.is_some())
+            .field("update_types", &self.type_handlers.keys().collect::<Vec<_>>())
+            .field("has_clock", &self.clock.is_some())
+            .field("notifiers", &self.notifiers.len())
+            .finish_non_exhaustive()
+    }
 }
 
 impl WebhookHandler {
-    pub(crate) fn with_config(api_token: impl Into<String>, config: WebhookHandlerConfig) -> Self {
-        Self {
+    /// Creates a webhook handler without a client, e.g. for a service that only verifies webhooks
+    ///
+    /// `api_token` is the token of the app the webhooks are sent for, used to verify signatures.
+    ///
+    /// # Example
+    /// ```
+    /// use crypto_pay_api::prelude::*;
+    ///
+    /// let config = WebhookHandlerConfigBuilder::new().build_config().unwrap();
+    /// let handler = WebhookHandler::new("YOUR_API_TOKEN", config).unwrap();
+    /// ```
+    ///
+    /// # Errors
+    /// * `CryptoBotError::ConfigError` - If the config is invalid, see `WebhookHandlerConfigBuilder::build_config`
+    pub fn new(api_token: impl Into<String>, config: WebhookHandlerConfig) -> CryptoBotResult<Self> {
+        config.validate()?;
+        Ok(Self {
             api_token: api_token.into(),
-            config,
             update_handler: None,
+            type_handlers: HashMap::new(),
+            exchange_rates: RwLock::new(Vec::new()),
+            lanes: (0..config.dispatch_lanes.unwrap_or(0))
+                .map(|_| Mutex::new(()))
+                .collect(),
+            seen_updates: std::sync::Mutex::new(SeenUpdates::default()),
+            clock: None,
+            notifiers: Vec::new(),
+            notify_error_hook: None,
+            config,
+        })
+    }
+
+    /// Fetches the current exchange rates and caches them for USD enrichment
+    ///
+    /// Call it periodically, e.g. from a background task, when the handler was built with
+    /// `enrich_usd_amount()`.
+    #[cfg(feature = "client")]
+    pub async fn refresh_exchange_rates(&self, client: &CryptoBot) -> CryptoBotResult<()> {
+        let rates = client.get_exchange_rates().execute().await?;
+        self.set_exchange_rates(rates);
+        Ok(())
+    }
+
+    /// Replaces the cached exchange rates used for USD enrichment
+    pub fn set_exchange_rates(&self, rates: Vec<ExchangeRate>) {
+        *self.exchange_rates.write().unwrap_or_else(|e| e.into_inner()) = rates;
+    }
+
+    /// Returns the dispatch lane of an update, if keyed serialization is enabled
+    fn lane(&self, update: &WebhookUpdate) -> Option<&Mutex<()>> {
+        if self.lanes.is_empty() {
+            return None;
+        }
+
+        let key = match &update.payload {
+            WebhookPayload::InvoicePaid(invoice) => invoice.invoice_id,
+            WebhookPayload::CheckActivated(check) => check.check_id,
+            _ => return None,
+        };
+        self.lanes.get((key % self.lanes.len() as u64) as usize)
+    }
+
+    /// Records an update as seen, returning `None` if it was already seen or is being handled
+    ///
+    /// The claim is released when dropped without `commit`, e.g. when the handler fails, panics or
+    /// its request is cancelled, so the redelivery of the update is processed again.
+    fn claim_update(&self, update_id: i64) -> Option<UpdateClaim<'_>> {
+        if let Some(capacity) = self.config.dedup_capacity {
+            let now = self.now();
+            let mut seen = self.seen_updates.lock().unwrap_or_else(|e| e.into_inner());
+            if !seen.claim(update_id, now, capacity, self.config.dedup_ttl) {
+                return None;
+            }
+        }
+
+        Some(UpdateClaim {
+            handler: self,
+            update_id,
+            committed: false,
+        })
+    }
+
+    /// Forgets an update whose handling failed, so its redelivery is processed again
+    fn release_update(&self, update_id: i64) {
+        let mut seen = self.seen_updates.lock().unwrap_or_else(|e| e.into_inner());
+        seen.release(update_id);
+    }
+
+    /// Returns the current time of the handler's clock
+    fn now(&self) -> DateTime<Utc> {
+        self.clock.as_ref().map_or_else(Utc::now, |clock| clock())
+    }
+
+    /// Returns the number of entries held in memory by the handler, e.g. to monitor a long-running process
+    ///
+    /// # Example
+    /// ```
+    /// use crypto_pay_api::prelude::*;
+    ///
+    /// let config = WebhookHandlerConfigBuilder::new().deduplicate(10_000).build_config().unwrap();
+    /// let handler = WebhookHandler::new("YOUR_API_TOKEN", config).unwrap();
+    /// let usage = handler.memory_usage();
+    /// assert_eq!((usage.seen_updates, usage.seen_updates_capacity), (0, Some(10_000)));
+    /// ```
+    pub fn memory_usage(&self) -> WebhookMemoryUsage {
+        WebhookMemoryUsage {
+            seen_updates: self.seen_updates.lock().unwrap_or_else(|e| e.into_inner()).len(),
+            seen_updates_capacity: self.config.dedup_capacity,
+            exchange_rates: self.exchange_rates.read().unwrap_or_else(|e| e.into_inner()).len(),
+            dispatch_lanes: self.lanes.len(),
+            notifiers: self.notifiers.len(),
         }
     }
 
@@ -56,7 +365,7 @@ impl WebhookHandler {
     /// #[tokio::main]
     /// async fn main() -> Result<(), CryptoBotError> {
     ///     let client = CryptoBot::builder().api_token("your_api_token").build().unwrap();
-    ///     let handler = client.webhook_handler().build();
+    ///     let handler = client.webhook_handler().build().unwrap();
     ///     let body = r#"{"update_id": 1, "update_type": "invoice_paid"}"#;
     ///     let signature = "1234567890abcdef"; // The actual signature from the request header
     ///
@@ -70,15 +379,78 @@ impl WebhookHandler {
     /// }
     /// ```
     pub fn verify_signature(&self, body: &str, signature: &str) -> bool {
-        let secret = Sha256::digest(self.api_token.as_bytes());
-        let mut mac = Hmac::<Sha256>::new_from_slice(&secret).expect("HMAC can take key of any size");
+        match hex::decode(signature) {
+            Ok(signature) => body_mac(&self.api_token, body).verify_slice(&signature).is_ok(),
+            Err(_) => false,
+        }
+    }
 
-        mac.update(body.as_bytes());
+    /// Verifies the signature of a webhook request and handles its update
+    ///
+    /// Prefer it over calling `verify_signature` and `handle_update` separately. A signature that
+    /// is present is always verified; a missing one is only accepted if the handler was not built
+    /// with `require_signature(true)`.
+    ///
+    /// # Arguments
+    /// * `body` - The raw webhook request body as JSON string
+    /// * `signature` - The 'crypto-pay-api-signature' header, if the request has one
+    ///
+    /// # Errors
+    /// * `WebhookErrorKind::MissingSignature` - If the signature is missing and required
+    /// * `WebhookErrorKind::InvalidSignature` - If the signature does not match the body
+    /// * Any error of `handle_update`
+    pub async fn handle_request(&self, body: &str, signature: Option<&str>) -> Result<WebhookResponse, CryptoBotError> {
+        self.handle_verified(body, signature, self.config.require_signature)
+            .await
+    }
+
+    /// Handles a request like `handle_request`, but always rejects a missing signature
+    ///
+    /// Used by the server integrations, which are reachable by anyone and must never accept
+    /// unsigned updates, whatever `require_signature` is set to.
+    #[cfg(any(feature = "axum", feature = "actix"))]
+    pub(crate) async fn handle_signed_request(
+        &self,
+        body: &str,
+        signature: Option<&str>,
+    ) -> Result<WebhookResponse, CryptoBotError> {
+        self.handle_verified(body, signature, true).await
+    }
 
-        if let Ok(hex_signature) = hex::decode(signature) {
-            mac.verify_slice(&hex_signature).is_ok()
-        } else {
-            false
+    /// Handles a request like `handle_signed_request` and returns how to answer it
+    ///
+    /// A batch with failed updates is answered with 500 only when deduplication is enabled, so
+    /// the updates that succeeded are skipped when Crypto Bot delivers the batch again. Without
+    /// it, the batch is answered with 200 and the per-update `results`, so no update is handled
+    /// twice.
+    #[cfg(any(feature = "axum", feature = "actix"))]
+    pub(crate) async fn reply(&self, body: &str, signature: Option<&str>) -> WebhookReply {
+        match self.handle_signed_request(body, signature).await {
+            Ok(response) if !response.ok && self.config.dedup_capacity.is_some() => WebhookReply::Failed,
+            Ok(response) => WebhookReply::Handled(serde_json::to_string(&response).unwrap_or_default()),
+            Err(error) => match webhook_status(&error) {
+                500 => WebhookReply::Failed,
+                status => WebhookReply::Rejected(status, error.to_string()),
+            },
+        }
+    }
+
+    async fn handle_verified(
+        &self,
+        body: &str,
+        signature: Option<&str>,
+        require_signature: bool,
+    ) -> Result<WebhookResponse, CryptoBotError> {
+        match signature {
+            Some(signature) if !self.verify_signature(body, signature) => Err(CryptoBotError::WebhookError {
+                kind: WebhookErrorKind::InvalidSignature,
+                message: "Invalid webhook signature".to_string(),
+            }),
+            None if require_signature => Err(CryptoBotError::WebhookError {
+                kind: WebhookErrorKind::MissingSignature,
+                message: "Webhook request has no signature".to_string(),
+            }),
+            _ => self.handle_update(body).await,
         }
     }
 
@@ -88,35 +460,103 @@ impl WebhookHandler {
     /// 1. Parses the webhook update from JSON
     /// 2. Validates the request date
     /// 3. Checks if the request has expired
-    /// 4. Calls the registered update handler if one exists
+    /// 4. Verifies the payload HMAC if enabled
+    /// 5. Attaches the USD value of the invoice if enabled
+    /// 6. Calls the registered update handler if one exists
+    ///
+    /// With deduplication enabled, an update that was already handled skips these steps and
+    /// `WebhookResponse::ok_duplicate` is returned, so Crypto Bot stops redelivering it.
+    ///
+    /// The body may also hold a JSON array of updates, in case Crypto Bot starts batching them.
+    /// Every update of a batch is processed in order, even if an earlier one fails, and the
+    /// outcome of each is reported in `WebhookResponse::results`. `WebhookResponse::ok` is false
+    /// if any of them failed.
     ///
     /// # Arguments
     /// * `body` - The raw webhook request body as JSON string
     ///
     /// # Returns
-    /// * `Ok(WebhookResponse)` - If the update was handled successfully
+    /// * `Ok(WebhookResponse)` - If the update was handled successfully or is a duplicate, or the batch
+    ///   was processed
     /// * `Err(CryptoBotError)` - If any validation fails or the handler returns an error
     ///
     /// # Errors
-    /// * `WebhookErrorKind::InvalidPayload` - If the JSON is invalid or missing required fields
+    /// * `WebhookErrorKind::InvalidPayload` - If the JSON is invalid or missing required fields, or the
+    ///   body exceeds the configured maximum size
     /// * `WebhookErrorKind::Expired` - If the request is older than the expiration time
+    /// * `WebhookErrorKind::InvalidSignature` - If payload HMAC verification is enabled and fails
     pub async fn handle_update(&self, body: &str) -> Result<WebhookResponse, CryptoBotError> {
-        let update: WebhookUpdate = Self::parse_update(body)?;
+        if let Some(max_body_size) = self.config.max_body_size {
+            if body.len() > max_body_size {
+                return Err(CryptoBotError::WebhookError {
+                    kind: WebhookErrorKind::InvalidPayload,
+                    message: format!(
+                        "request body of {} bytes exceeds the limit of {max_body_size} bytes",
+                        body.len()
+                    ),
+                });
+            }
+        }
+
+        if body.trim_start().starts_with('[') {
+            return self.handle_batch(body).await;
+        }
 
+        match self.process_update(Self::parse_update(body)?).await? {
+            Processed::Handled => Ok(WebhookResponse::ok()),
+            Processed::Duplicate => Ok(WebhookResponse::ok_duplicate()),
+        }
+    }
+
+    async fn handle_batch(&self, body: &str) -> Result<WebhookResponse, CryptoBotError> {
+        let items: Vec<serde_json::Value> = serde_json::from_str(body).map_err(|e| CryptoBotError::WebhookError {
+            kind: WebhookErrorKind::InvalidPayload,
+            message: e.to_string(),
+        })?;
+
+        let mut results = Vec::with_capacity(items.len());
+        for item in items {
+            let update_id = item.get("update_id").and_then(serde_json::Value::as_i64);
+            let outcome = match serde_json::from_value::<WebhookUpdate>(item) {
+                Ok(update) => self.process_update(update).await,
+                Err(e) => Err(CryptoBotError::WebhookError {
+                    kind: WebhookErrorKind::InvalidPayload,
+                    message: e.to_string(),
+                }),
+            };
+
+            results.push(WebhookUpdateResult {
+                update_id,
+                ok: outcome.is_ok(),
+                duplicate: matches!(outcome, Ok(Processed::Duplicate)),
+                error: outcome.err().map(|e| e.to_string()),
+            });
+        }
+
+        Ok(WebhookResponse::batch(results))
+    }
+
+    async fn process_update(&self, update: WebhookUpdate) -> CryptoBotResult<Processed> {
+        let Some(claim) = self.claim_update(update.update_id) else {
+            return Ok(Processed::Duplicate);
+        };
+
+        self.dispatch_update(update).await?;
+        claim.commit();
+        Ok(Processed::Handled)
+    }
+
+    async fn dispatch_update(&self, mut update: WebhookUpdate) -> CryptoBotResult<()> {
         if let Some(expiration_time) = self.config.expiration_time {
             let request_date =
-                DateTime::parse_from_rfc3339(&update.request_date).map_err(|_| CryptoBotError::WebhookError {
-                    kind: WebhookErrorKind::InvalidPayload, // TODO: test this
-                    message: "Invalid request date".to_string(),
+                parse_request_date(&update.request_date).ok_or_else(|| CryptoBotError::WebhookError {
+                    kind: WebhookErrorKind::InvalidPayload,
+                    message: format!("Invalid request date: {}", update.request_date),
                 })?;
 
-            let age = Utc::now().signed_duration_since(request_date.with_timezone(&Utc));
-
-            let webhook_expiration_time = expiration_time.as_secs();
+            let now = self.now();
 
-            let webhook_expiration = chrono::Duration::seconds(webhook_expiration_time as i64);
-
-            if age > webhook_expiration {
+            if is_expired(request_date, now, expiration_time) {
                 return Err(CryptoBotError::WebhookError {
                     kind: WebhookErrorKind::Expired,
                     message: "Webhook request too old".to_string(),
@@ -124,18 +564,101 @@ impl WebhookHandler {
             }
         }
 
-        if let Some(handler) = &self.update_handler {
+        // Handlers only see the application payload, without the marker of `create_invoice_recoverable`
+        if let WebhookPayload::InvoicePaid(invoice) = &mut update.payload {
+            invoice.payload = invoice.app_payload().map(str::to_string);
+        }
+
+        if let (Some(secret), WebhookPayload::InvoicePaid(invoice)) =
+            (&self.config.payload_hmac_secret, &mut update.payload)
+        {
+            let payload = invoice
+                .payload
+                .as_deref()
+                .and_then(|signed| verify_signed_payload(secret, signed))
+                .ok_or_else(|| CryptoBotError::WebhookError {
+                    kind: WebhookErrorKind::InvalidSignature,
+                    message: format!("Invalid payload HMAC for invoice {}", invoice.invoice_id),
+                })?;
+            invoice.payload = Some(payload.to_string()).filter(|payload| !payload.is_empty());
+        }
+
+        if self.config.enrich_usd {
+            let rates = self.exchange_rates.read().unwrap_or_else(|e| e.into_inner());
+            update.paid_usd_amount = update.invoice().and_then(|invoice| match self.config.usd_rounding {
+                Some(rounding) => invoice.usd_value_rounded(&rates, rounding),
+                None => invoice.usd_value(&rates),
+            });
+        }
+
+        let notified = (!self.notifiers.is_empty()).then(|| update.clone());
+
+        let handler = self
+            .type_handlers
+            .get(&update.update_type)
+            .or(self.update_handler.as_ref());
+        if let Some(handler) = handler {
+            let _lane = match self.lane(&update) {
+                Some(lane) => Some(lane.lock().await),
+                None => None,
+            };
             handler(update).await?;
         }
 
-        Ok(WebhookResponse::ok())
+        if let Some(update) = notified {
+            self.notify(&update).await;
+        }
+
+        Ok(())
+    }
+
+    /// Notifier errors never fail the update, or Crypto Bot would redeliver it to the update handler
+    async fn notify(&self, update: &WebhookUpdate) {
+        for notifier in &self.notifiers {
+            if let (Err(error), Some(hook)) = (notifier.notify(update).await, &self.notify_error_hook) {
+                hook(&error);
+            }
+        }
+    }
+
+    /// Attaches a notifier called for every update after the update handler succeeded, e.g. a `TelegramNotifier`
+    ///
+    /// Notifiers run one after the other. Their errors do not fail the update; receive them with `on_notify_error`.
+    pub fn add_notifier(&mut self, notifier: impl Notifier + 'static) {
+        self.notifiers.push(Box::new(notifier));
+    }
+
+    /// Registers a callback receiving the errors of notifiers, e.g. for logging
+    pub fn on_notify_error<F>(&mut self, hook: F)
+    where
+        F: Fn(&CryptoBotError) + Send + Sync + 'static,
+    {
+        self.notify_error_hook = Some(Box::new(hook));
+    }
+
+    /// Replaces the clock used to check whether updates have expired, e.g. with a fixed time in tests
+    ///
+    /// # Example
+    /// ```
+    /// use crypto_pay_api::prelude::*;
+    /// use chrono::{TimeZone, Utc};
+    ///
+    /// let config = WebhookHandlerConfigBuilder::new().build_config().unwrap();
+    /// let mut handler = WebhookHandler::new("YOUR_API_TOKEN", config).unwrap();
+    /// handler.set_clock(|| Utc.with_ymd_and_hms(2025, 2, 8, 12, 0, 0).unwrap());
+    /// ```
+    pub fn set_clock<F>(&mut self, clock: F)
+    where
+        F: Fn() -> DateTime<Utc> + Send + Sync + 'static,
+    {
+        self.clock = Some(Box::new(clock));
     }
 
     /// Registers a handler function for webhook updates
     ///
     /// The handler function will be called for each webhook update received through
-    /// `handle_update`. The function should process the update and return a Result
-    /// indicating success or failure.
+    /// `handle_update` whose type has no handler registered with `on_update_type`. The function
+    /// should process the update and return a Result indicating success or failure.
     ///
     /// # Arguments
     /// * `handler` - An async function that takes a `WebhookUpdate` and returns a `Result<(), CryptoBotError>`
@@ -157,7 +680,7 @@ impl WebhookHandler {
     /// #[tokio::main]
     /// async fn main() {
     ///     let client = CryptoBot::builder().api_token("YOUR_API_TOKEN").build().unwrap();
-    ///     let mut handler = client.webhook_handler().build();
+    ///     let mut handler = client.webhook_handler().build().unwrap();
     ///
     ///     handler.on_update(|update| async move {
     ///         match (update.update_type, update.payload) {
@@ -167,6 +690,8 @@ impl WebhookHandler {
     ///                 
     ///                 // Process the payment...
     ///             }
+    ///             // Other and future update types can be ignored
+    ///             _ => {}
     ///         }
     ///         Ok(())
     ///     });
@@ -181,16 +706,110 @@ impl WebhookHandler {
     {
         self.update_handler = Some(Box::new(move |update| Box::pin(handler(update))));
     }
+
+    /// Registers a handler function for updates of `update_type`, called instead of the `on_update` handler
+    ///
+    /// Updates of other types fall through to the `on_update` handler, or are acknowledged without a
+    /// handler if none is registered. Registering a type again replaces its handler.
+    ///
+    /// # Example
+    /// ```
+    /// use crypto_pay_api::prelude::*;
+    ///
+    /// let config = WebhookHandlerConfigBuilder::new().build_config().unwrap();
+    /// let mut handler = WebhookHandler::new("YOUR_API_TOKEN", config).unwrap();
+    ///
+    /// handler.on_invoice_paid(|update| async move {
+    ///     let invoice = update.invoice().expect("invoice_paid updates carry an invoice");
+    ///     println!("Invoice {} paid", invoice.invoice_id);
+    ///     Ok(())
+    /// });
+    /// handler.on_update_type(UpdateType::Unknown("invoice_refunded".to_string()), |_| async { Ok(()) });
+    /// handler.on_update(|update| async move {
+    ///     println!("Unhandled {} update", update.update_type);
+    ///     Ok(())
+    /// });
+    /// ```
+    pub fn on_update_type<F, Fut>(&mut self, update_type: UpdateType, handler: F)
+    where
+        F: Fn(WebhookUpdate) -> Fut + Send + Sync + 'static,
+        Fut: Future<Output = Result<(), CryptoBotError>> + Send + 'static,
+    {
+        self.type_handlers
+            .insert(update_type, Box::new(move |update| Box::pin(handler(update))));
+    }
+
+    /// Registers a handler function for `invoice_paid` updates, see `on_update_type`
+    pub fn on_invoice_paid<F, Fut>(&mut self, handler: F)
+    where
+        F: Fn(WebhookUpdate) -> Fut + Send + Sync + 'static,
+        Fut: Future<Output = Result<(), CryptoBotError>> + Send + 'static,
+    {
+        self.on_update_type(UpdateType::InvoicePaid, handler);
+    }
+
+    /// Registers a handler function for `check_activated` updates, see `on_update_type`
+    pub fn on_check_activated<F, Fut>(&mut self, handler: F)
+    where
+        F: Fn(WebhookUpdate) -> Fut + Send + Sync + 'static,
+        Fut: Future<Output = Result<(), CryptoBotError>> + Send + 'static,
+    {
+        self.on_update_type(UpdateType::CheckActivated, handler);
+    }
+
+    /// Registers a handler function returning an application error type
+    ///
+    /// Errors returned by the handler are propagated from `handle_update` as
+    /// `CryptoBotError::HandlerError`, and can be recovered with `CryptoBotError::handler_error`.
+    /// Map them to a server error status, unlike the client errors caused by invalid updates.
+    ///
+    /// # Example
+    /// ```
+    /// use crypto_pay_api::prelude::*;
+    ///
+    /// #[derive(Debug)]
+    /// struct OrderNotFound(String);
+    ///
+    /// impl std::fmt::Display for OrderNotFound {
+    ///     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+    ///         write!(f, "order {} not found", self.0)
+    ///     }
+    /// }
+    ///
+    /// impl std::error::Error for OrderNotFound {}
+    ///
+    /// let client = CryptoBot::builder().api_token("YOUR_API_TOKEN").build().unwrap();
+    /// let mut handler = client.webhook_handler().build().unwrap();
+    ///
+    /// handler.on_update_with_err(|update| async move {
+    ///     let order_id = update.invoice().and_then(|invoice| invoice.payload.clone());
+    ///     Err(OrderNotFound(order_id.unwrap_or_default()))
+    /// });
+    /// ```
+    pub fn on_update_with_err<F, Fut, E>(&mut self, handler: F)
+    where
+        F: Fn(WebhookUpdate) -> Fut + Send + Sync + 'static,
+        Fut: Future<Output = Result<(), E>> + Send + 'static,
+        E: Into<Box<dyn std::error::Error + Send + Sync>>,
+    {
+        self.update_handler = Some(Box::new(move |update| {
+            let future = handler(update);
+            Box::pin(async move { future.await.map_err(|e| CryptoBotError::HandlerError(e.into())) })
+        }));
+    }
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
     use crate::{
-        models::{InvoiceStatus, UpdateType, WebhookPayload},
+        fixtures,
+        models::{InvoiceStatus, Rounding, UpdateType, WebhookPayload},
+        utils::test_utils::TestContext,
         webhook::WebhookHandlerConfigBuilder,
     };
     use chrono::Utc;
+    use rust_decimal_macros::dec;
     use serde_json::json;
 
     use std::{sync::Arc, time::Duration};
@@ -198,7 +817,8 @@ mod tests {
 
     #[tokio::test]
     async fn test_webhook_handler() {
-        let mut handler = WebhookHandler::with_config("test_token", WebhookHandlerConfigBuilder::new().build_config());
+        let mut handler =
+            WebhookHandler::new("test_token", WebhookHandlerConfigBuilder::new().build_config().unwrap()).unwrap();
 
         let received = Arc::new(Mutex::new(None));
         let received_clone = received.clone();
@@ -245,12 +865,14 @@ mod tests {
                 assert_eq!(invoice.invoice_id, 528890);
                 assert_eq!(invoice.status, InvoiceStatus::Paid);
             }
+            other => panic!("Expected invoice payload, got {other:?}"),
         }
     }
 
     #[tokio::test]
     async fn test_webhook_handler_propagates_handler_error() {
-        let mut handler = WebhookHandler::with_config("test_token", WebhookHandlerConfigBuilder::new().build_config());
+        let mut handler =
+            WebhookHandler::new("test_token", WebhookHandlerConfigBuilder::new().build_config().unwrap()).unwrap();
         handler.on_update(|_| async move {
             Err(CryptoBotError::WebhookError {
                 kind: WebhookErrorKind::InvalidPayload,
@@ -293,7 +915,8 @@ mod tests {
 
     #[tokio::test]
     async fn test_webhook_handler_invalid_request_date() {
-        let handler = WebhookHandler::with_config("test_token", WebhookHandlerConfigBuilder::new().build_config());
+        let handler =
+            WebhookHandler::new("test_token", WebhookHandlerConfigBuilder::new().build_config().unwrap()).unwrap();
 
         let json = json!({
             "update_id": 1,
@@ -324,16 +947,547 @@ mod tests {
             Err(CryptoBotError::WebhookError {
                 kind: WebhookErrorKind::InvalidPayload,
                 message,
-            }) if message == "Invalid request date"
+            }) if message == "Invalid request date: invalid_date"
         ));
     }
 
+    async fn handle_and_capture(handler: &mut WebhookHandler, body: &str) -> WebhookUpdate {
+        let received = Arc::new(Mutex::new(None));
+        let received_clone = received.clone();
+
+        handler.on_update(move |update| {
+            let received = received_clone.clone();
+            async move {
+                *received.lock().await = Some(update);
+                Ok(())
+            }
+        });
+
+        handler.handle_update(body).await.unwrap();
+        let update = received.lock().await.take();
+        update.expect("Should have received update")
+    }
+
+    #[tokio::test]
+    async fn test_webhook_handler_enriches_usd_amount() {
+        let config = WebhookHandlerConfigBuilder::new()
+            .enrich_usd_amount()
+            .build_config()
+            .unwrap();
+        let mut handler = WebhookHandler::new("test_token", config).unwrap();
+
+        let update = handle_and_capture(&mut handler, &fixtures::webhook_update_body()).await;
+
+        assert_eq!(update.invoice().map(|invoice| invoice.invoice_id), Some(528890));
+        assert_eq!(update.paid_usd_amount, Some(dec!(34.125)));
+    }
+
+    #[tokio::test]
+    async fn test_webhook_handler_rounds_usd_amount() {
+        let config = WebhookHandlerConfigBuilder::new()
+            .enrich_usd_amount()
+            .usd_rounding(Rounding::Bankers)
+            .build_config()
+            .unwrap();
+        let mut handler = WebhookHandler::new("test_token", config).unwrap();
+
+        let update = handle_and_capture(&mut handler, &fixtures::webhook_update_body()).await;
+        assert_eq!(update.paid_usd_amount, Some(dec!(34.12)));
+    }
+
+    #[tokio::test]
+    async fn test_webhook_handler_enriches_usd_amount_from_cached_rates() {
+        let config = WebhookHandlerConfigBuilder::new()
+            .enrich_usd_amount()
+            .build_config()
+            .unwrap();
+        let mut handler = WebhookHandler::new("test_token", config).unwrap();
+
+        let mut body: serde_json::Value = serde_json::from_str(&fixtures::webhook_update_body()).unwrap();
+        body["payload"].as_object_mut().unwrap().remove("paid_usd_rate");
+        let body = body.to_string();
+
+        let update = handle_and_capture(&mut handler, &body).await;
+        assert_eq!(update.paid_usd_amount, None);
+
+        handler.set_exchange_rates(TestContext::mock_exchange_rates());
+        let update = handle_and_capture(&mut handler, &body).await;
+        assert_eq!(update.paid_usd_amount, Some(dec!(10.5) * dec!(3.70824926)));
+    }
+
+    #[tokio::test]
+    async fn test_webhook_handler_without_enrichment() {
+        let mut handler =
+            WebhookHandler::new("test_token", WebhookHandlerConfigBuilder::new().build_config().unwrap()).unwrap();
+
+        let update = handle_and_capture(&mut handler, &fixtures::webhook_update_body()).await;
+        assert_eq!(update.paid_usd_amount, None);
+    }
+
+    fn body_with_payload(payload: &str) -> String {
+        let mut body: serde_json::Value = serde_json::from_str(&fixtures::webhook_update_body()).unwrap();
+        body["payload"]["payload"] = json!(payload);
+        body.to_string()
+    }
+
+    fn body_with_invoice_id(invoice_id: u64) -> String {
+        let mut body: serde_json::Value = serde_json::from_str(&fixtures::webhook_update_body()).unwrap();
+        body["payload"]["invoice_id"] = json!(invoice_id);
+        body.to_string()
+    }
+
+    /// Handles the two bodies concurrently and returns the highest number of handlers running at once
+    async fn max_concurrent_handlers(lanes: usize, first: &str, second: &str) -> usize {
+        use std::sync::atomic::{AtomicUsize, Ordering};
+
+        let config = WebhookHandlerConfigBuilder::new()
+            .dispatch_lanes(lanes)
+            .build_config()
+            .unwrap();
+        let mut handler = WebhookHandler::new("test_token", config).unwrap();
+        let active = Arc::new(AtomicUsize::new(0));
+        let max_active = Arc::new(AtomicUsize::new(0));
+
+        let (active_in_handler, max_in_handler) = (active.clone(), max_active.clone());
+        handler.on_update(move |_| {
+            let (active, max_active) = (active_in_handler.clone(), max_in_handler.clone());
+            async move {
+                let now = active.fetch_add(1, Ordering::SeqCst) + 1;
+                max_active.fetch_max(now, Ordering::SeqCst);
+                tokio::time::sleep(Duration::from_millis(20)).await;
+                active.fetch_sub(1, Ordering::SeqCst);
+                Ok(())
+            }
+        });
+
+        let (a, b) = tokio::join!(handler.handle_update(first), handler.handle_update(second));
+        a.unwrap();
+        b.unwrap();
+
+        max_active.load(Ordering::SeqCst)
+    }
+
+    #[tokio::test]
+    async fn test_dispatch_lanes_serialize_same_invoice() {
+        let body = body_with_invoice_id(7);
+        assert_eq!(max_concurrent_handlers(4, &body, &body).await, 1);
+    }
+
+    #[tokio::test]
+    async fn test_dispatch_lanes_run_different_invoices_in_parallel() {
+        let (first, second) = (body_with_invoice_id(1), body_with_invoice_id(2));
+        assert_eq!(max_concurrent_handlers(4, &first, &second).await, 2);
+        assert_eq!(max_concurrent_handlers(1, &first, &second).await, 1);
+    }
+
+    #[tokio::test]
+    async fn test_handle_update_processes_batches_in_order() {
+        let mut handler = WebhookHandler::new("test_token", WebhookHandlerConfig::default()).unwrap();
+        let received = Arc::new(Mutex::new(Vec::new()));
+        let received_in_handler = received.clone();
+        handler.on_update(move |update| {
+            let received = received_in_handler.clone();
+            async move {
+                received.lock().await.push(update.update_id);
+                if update.update_id == 2 {
+                    return Err(CryptoBotError::NoResult);
+                }
+                Ok(())
+            }
+        });
+
+        let update: serde_json::Value = serde_json::from_str(&fixtures::webhook_update_body()).unwrap();
+        let mut batch = Vec::new();
+        for update_id in 1..=3 {
+            let mut update = update.clone();
+            update["update_id"] = json!(update_id);
+            batch.push(update);
+        }
+        batch.push(json!({ "update_id": 4, "update_type": "unknown" }));
+
+        let response = handler.handle_update(&json!(batch).to_string()).await.unwrap();
+
+        assert_eq!(*received.lock().await, vec![1, 2, 3]);
+        assert!(!response.ok);
+        let outcomes: Vec<_> = response.results.iter().map(|r| (r.update_id, r.ok)).collect();
+        assert_eq!(
+            outcomes,
+            vec![(Some(1), true), (Some(2), false), (Some(3), true), (Some(4), false)]
+        );
+        assert!(response.results[3].error.as_deref().unwrap().contains("InvalidPayload"));
+    }
+
+    #[tokio::test]
+    async fn test_handle_update_single_update_has_no_results() {
+        let handler = WebhookHandler::new("test_token", WebhookHandlerConfig::default()).unwrap();
+        let response = handler.handle_update(&fixtures::webhook_update_body()).await.unwrap();
+
+        assert!(response.ok);
+        assert!(response.results.is_empty());
+        assert_eq!(serde_json::to_string(&response).unwrap(), r#"{"ok":true}"#);
+    }
+
+    #[tokio::test]
+    async fn test_handle_update_acknowledges_duplicates() {
+        let config = WebhookHandlerConfigBuilder::new()
+            .deduplicate(16)
+            .build_config()
+            .unwrap();
+        let mut handler = WebhookHandler::new("test_token", config).unwrap();
+        let calls = Arc::new(Mutex::new(0));
+        let calls_in_handler = calls.clone();
+        handler.on_update(move |_| {
+            let calls = calls_in_handler.clone();
+            async move {
+                let mut calls = calls.lock().await;
+                *calls += 1;
+                if *calls == 1 {
+                    return Err(CryptoBotError::NoResult);
+                }
+                Ok(())
+            }
+        });
+        let body = fixtures::webhook_update_body();
+
+        // A failed update is processed again when redelivered
+        assert!(handler.handle_update(&body).await.is_err());
+        let response = handler.handle_update(&body).await.unwrap();
+        assert!(response.ok && !response.duplicate);
+
+        let response = handler.handle_update(&body).await.unwrap();
+        assert!(response.ok && response.duplicate);
+        assert_eq!(
+            serde_json::to_string(&response).unwrap(),
+            r#"{"ok":true,"duplicate":true}"#
+        );
+        assert_eq!(*calls.lock().await, 2);
+
+        let batch = format!("[{body},{body}]");
+        let response = handler.handle_update(&batch).await.unwrap();
+        assert!(response.ok);
+        assert!(response.results.iter().all(|result| result.duplicate));
+        assert_eq!(*calls.lock().await, 2);
+    }
+
+    #[tokio::test]
+    async fn test_cancelled_update_is_processed_when_redelivered() {
+        let config = WebhookHandlerConfigBuilder::new()
+            .deduplicate(16)
+            .build_config()
+            .unwrap();
+        let mut handler = WebhookHandler::new("test_token", config).unwrap();
+        let calls = Arc::new(std::sync::atomic::AtomicUsize::new(0));
+        let calls_in_handler = calls.clone();
+        handler.on_update(move |_| {
+            let call = calls_in_handler.fetch_add(1, std::sync::atomic::Ordering::SeqCst);
+            async move {
+                // The first delivery hangs until its request is dropped, e.g. by a server timeout
+                if call == 0 {
+                    std::future::pending::<()>().await;
+                }
+                Ok(())
+            }
+        });
+        let body = fixtures::webhook_update_body();
+
+        let cancelled = tokio::time::timeout(Duration::from_millis(10), handler.handle_update(&body)).await;
+        assert!(cancelled.is_err());
+
+        let response = handler.handle_update(&body).await.unwrap();
+        assert!(response.ok && !response.duplicate);
+        assert!(handler.handle_update(&body).await.unwrap().duplicate);
+        assert_eq!(calls.load(std::sync::atomic::Ordering::SeqCst), 2);
+    }
+
+    #[tokio::test]
+    async fn test_dedup_ttl_forgets_old_updates() {
+        use chrono::TimeZone;
+
+        let config = WebhookHandlerConfigBuilder::new()
+            .disable_expiration()
+            .deduplicate(16)
+            .dedup_ttl(Duration::from_secs(3600))
+            .build_config()
+            .unwrap();
+        let mut handler = WebhookHandler::new("test_token", config).unwrap();
+        let body = fixtures::webhook_update_body();
+
+        handler.set_clock(|| Utc.with_ymd_and_hms(2025, 2, 8, 12, 0, 0).unwrap());
+        assert!(!handler.handle_update(&body).await.unwrap().duplicate);
+        assert!(handler.handle_update(&body).await.unwrap().duplicate);
+        assert_eq!(handler.memory_usage().seen_updates, 1);
+
+        handler.set_clock(|| Utc.with_ymd_and_hms(2025, 2, 8, 13, 0, 0).unwrap());
+        assert!(!handler.handle_update(&body).await.unwrap().duplicate);
+        assert_eq!(
+            handler.memory_usage(),
+            WebhookMemoryUsage {
+                seen_updates: 1,
+                seen_updates_capacity: Some(16),
+                ..WebhookMemoryUsage::default()
+            }
+        );
+    }
+
+    #[tokio::test]
+    async fn test_handle_request_verifies_signature() {
+        let body = fixtures::webhook_update_body();
+        let signature = sign_body("test_token", &body);
+        let handler = WebhookHandler::new("test_token", WebhookHandlerConfig::default_strict()).unwrap();
+
+        assert!(handler.handle_request(&body, Some(&signature)).await.unwrap().ok);
+        assert!(matches!(
+            handler.handle_request(&body, Some("00ff")).await,
+            Err(CryptoBotError::WebhookError {
+                kind: WebhookErrorKind::InvalidSignature,
+                ..
+            })
+        ));
+        assert!(matches!(
+            handler.handle_request(&body, None).await,
+            Err(CryptoBotError::WebhookError {
+                kind: WebhookErrorKind::MissingSignature,
+                ..
+            })
+        ));
+
+        let lenient = WebhookHandler::new("test_token", WebhookHandlerConfig::default()).unwrap();
+        assert!(lenient.handle_request(&body, None).await.is_ok());
+    }
+
+    #[tokio::test]
+    async fn test_handle_update_rejects_malformed_batch() {
+        let handler = WebhookHandler::new("test_token", WebhookHandlerConfig::default()).unwrap();
+        let result = handler.handle_update("[{").await;
+
+        assert!(matches!(
+            result,
+            Err(CryptoBotError::WebhookError {
+                kind: WebhookErrorKind::InvalidPayload,
+                ..
+            })
+        ));
+    }
+
+    #[tokio::test]
+    async fn test_unknown_update_type_is_dispatched() {
+        let mut handler = WebhookHandler::new("test_token", WebhookHandlerConfig::default()).unwrap();
+        let received = Arc::new(Mutex::new(None));
+        let received_clone = received.clone();
+        handler.on_update(move |update| {
+            let received = received_clone.clone();
+            async move {
+                *received.lock().await = Some(update.update_type);
+                Ok(())
+            }
+        });
+
+        let body = json!({
+            "update_id": 7,
+            "update_type": "invoice_refunded",
+            "request_date": Utc::now().to_rfc3339(),
+            "payload": {"invoice_id": 528890}
+        })
+        .to_string();
+
+        assert!(handler.handle_update(&body).await.is_ok());
+        assert_eq!(
+            received.lock().await.take(),
+            Some(UpdateType::Unknown("invoice_refunded".to_string()))
+        );
+    }
+
+    #[tokio::test]
+    async fn test_update_type_handlers_fall_through_to_on_update() {
+        let mut handler = WebhookHandler::new("test_token", WebhookHandlerConfig::default()).unwrap();
+        let received = Arc::new(Mutex::new(Vec::new()));
+        let record = |name: &'static str| {
+            let received = received.clone();
+            move |update: WebhookUpdate| {
+                let received = received.clone();
+                async move {
+                    received.lock().await.push((name, update.update_type));
+                    Ok(())
+                }
+            }
+        };
+        handler.on_invoice_paid(record("invoice_paid"));
+        handler.on_update(record("default"));
+
+        let unknown = json!({
+            "update_id": 8,
+            "update_type": "invoice_refunded",
+            "request_date": Utc::now().to_rfc3339(),
+            "payload": {"invoice_id": 528890}
+        })
+        .to_string();
+        handler.handle_update(&fixtures::webhook_update_body()).await.unwrap();
+        handler.handle_update(&unknown).await.unwrap();
+
+        assert_eq!(
+            *received.lock().await,
+            [
+                ("invoice_paid", UpdateType::InvoicePaid),
+                ("default", UpdateType::Unknown("invoice_refunded".to_string()))
+            ]
+        );
+    }
+
+    #[derive(Debug, PartialEq)]
+    struct AppError(String);
+
+    impl std::fmt::Display for AppError {
+        fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+            write!(f, "app error: {}", self.0)
+        }
+    }
+
+    impl std::error::Error for AppError {}
+
+    #[tokio::test]
+    async fn test_on_update_with_err_propagates_application_error() {
+        let mut handler = WebhookHandler::new("test_token", WebhookHandlerConfig::default()).unwrap();
+        handler.on_update_with_err(|update| async move {
+            let payload = update.invoice().and_then(|invoice| invoice.payload.clone());
+            Err(AppError(payload.unwrap_or_default()))
+        });
+
+        let error = handler
+            .handle_update(&fixtures::webhook_update_body())
+            .await
+            .unwrap_err();
+
+        assert!(matches!(error, CryptoBotError::HandlerError(_)));
+        assert_eq!(
+            error.handler_error::<AppError>(),
+            Some(&AppError("order_42".to_string()))
+        );
+        assert_eq!(error.to_string(), "Webhook handler error: app error: order_42");
+    }
+
+    #[tokio::test]
+    async fn test_on_update_with_err_accepts_string_errors() {
+        let mut handler = WebhookHandler::new("test_token", WebhookHandlerConfig::default()).unwrap();
+        handler.on_update_with_err(|_| async { Err::<(), _>("database unavailable") });
+
+        let error = handler
+            .handle_update(&fixtures::webhook_update_body())
+            .await
+            .unwrap_err();
+
+        assert_eq!(error.to_string(), "Webhook handler error: database unavailable");
+        assert!(error.handler_error::<AppError>().is_none());
+    }
+
+    #[tokio::test]
+    async fn test_webhook_handler_verifies_payload_hmac() {
+        let config = WebhookHandlerConfigBuilder::new()
+            .with_payload_hmac("secret")
+            .build_config()
+            .unwrap();
+        let mut handler = WebhookHandler::new("test_token", config).unwrap();
+
+        let body = body_with_payload(&crate::webhook::sign_payload("secret", "order_42"));
+        let update = handle_and_capture(&mut handler, &body).await;
+
+        assert_eq!(
+            update.invoice().and_then(|invoice| invoice.payload.as_deref()),
+            Some("order_42")
+        );
+    }
+
+    #[tokio::test]
+    async fn test_webhook_handler_strips_recovery_marker() {
+        let mut handler = WebhookHandler::new("test_token", WebhookHandlerConfig::default()).unwrap();
+
+        let body = body_with_payload("order_42\u{1e}recovery:key-1");
+        let update = handle_and_capture(&mut handler, &body).await;
+
+        assert_eq!(
+            update.invoice().and_then(|invoice| invoice.payload.as_deref()),
+            Some("order_42")
+        );
+    }
+
+    #[tokio::test]
+    async fn test_webhook_handler_rejects_invalid_payload_hmac() {
+        let config = WebhookHandlerConfigBuilder::new()
+            .with_payload_hmac("secret")
+            .build_config()
+            .unwrap();
+        let handler = WebhookHandler::new("test_token", config).unwrap();
+
+        for body in [
+            body_with_payload(&crate::webhook::sign_payload("other_secret", "order_42")),
+            body_with_payload("order_42"),
+            body_with_payload(""),
+        ] {
+            let result = handler.handle_update(&body).await;
+            assert!(matches!(
+                result,
+                Err(CryptoBotError::WebhookError {
+                    kind: WebhookErrorKind::InvalidSignature,
+                    ..
+                })
+            ));
+        }
+    }
+
+    #[cfg(feature = "client")]
+    #[test]
+    fn test_refresh_exchange_rates() {
+        let mut ctx = TestContext::new();
+        let _m = ctx.mock_exchange_rates_response();
+
+        let client = CryptoBot::builder()
+            .api_token("test_token")
+            .base_url(ctx.server.url())
+            .build()
+            .unwrap();
+        let handler = client.webhook_handler().build().unwrap();
+
+        ctx.run(async { handler.refresh_exchange_rates(&client).await })
+            .unwrap();
+
+        assert!(!handler.exchange_rates.read().unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_parse_request_date_variants() {
+        let expected = chrono::NaiveDate::from_ymd_opt(2025, 2, 8)
+            .unwrap()
+            .and_hms_milli_opt(12, 11, 1, 341)
+            .unwrap()
+            .and_utc();
+
+        for raw in [
+            "2025-02-08T12:11:01.341Z",
+            "2025-02-08T12:11:01.341+00:00",
+            "2025-02-08T14:11:01.341+02:00",
+            "2025-02-08T12:11:01.341",
+            "2025-02-08 12:11:01.341",
+            "2025-02-08 12:11:01.341+00:00",
+            "2025-02-08T12:11:01.341000000000Z",
+            " 2025-02-08T12:11:01.341Z ",
+        ] {
+            assert_eq!(parse_request_date(raw), Some(expected), "failed to parse {raw}");
+        }
+
+        assert!(parse_request_date("2025-02-08T12:11:01Z").is_some());
+        assert!(parse_request_date("2025-02-08").is_none());
+        assert!(parse_request_date("invalid_date").is_none());
+    }
+
     #[tokio::test]
     async fn test_webhook_handler_with_disabled_expiration() {
-        let handler = WebhookHandler::with_config(
+        let handler = WebhookHandler::new(
             "test_token",
-            WebhookHandlerConfigBuilder::new().disable_expiration().build_config(),
-        );
+            WebhookHandlerConfigBuilder::new()
+                .disable_expiration()
+                .build_config()
+                .unwrap(),
+        )
+        .unwrap();
 
         let json = json!({
             "update_id": 1,
@@ -363,7 +1517,8 @@ mod tests {
 
     #[tokio::test]
     async fn test_default_webhook_expiration() {
-        let handler = WebhookHandler::with_config("test_token", WebhookHandlerConfigBuilder::new().build_config());
+        let handler =
+            WebhookHandler::new("test_token", WebhookHandlerConfigBuilder::new().build_config().unwrap()).unwrap();
 
         let date = (Utc::now() - chrono::Duration::minutes(3)).to_rfc3339();
 
@@ -396,12 +1551,14 @@ mod tests {
 
     #[tokio::test]
     async fn test_custom_webhook_expiration() {
-        let handler = WebhookHandler::with_config(
+        let handler = WebhookHandler::new(
             "test_token",
             WebhookHandlerConfigBuilder::new()
                 .expiration_time(Duration::from_secs(60))
-                .build_config(),
-        );
+                .build_config()
+                .unwrap(),
+        )
+        .unwrap();
 
         let old_date = (Utc::now() - chrono::Duration::minutes(2)).to_rfc3339();
 
@@ -438,9 +1595,94 @@ mod tests {
         ));
     }
 
+    #[tokio::test]
+    async fn test_webhook_handler_uses_clock() {
+        use chrono::TimeZone;
+
+        let config = WebhookHandlerConfigBuilder::new()
+            .expiration_time(Duration::from_secs(60))
+            .build_config()
+            .unwrap();
+        let mut handler = WebhookHandler::new("test_token", config).unwrap();
+
+        // WEBHOOK_UPDATE was sent at 12:13:45
+        handler.set_clock(|| Utc.with_ymd_and_hms(2025, 2, 8, 12, 14, 30).unwrap());
+        assert!(handler.handle_update(fixtures::WEBHOOK_UPDATE).await.is_ok());
+
+        handler.set_clock(|| Utc.with_ymd_and_hms(2025, 2, 8, 12, 15, 0).unwrap());
+        assert!(matches!(
+            handler.handle_update(fixtures::WEBHOOK_UPDATE).await,
+            Err(CryptoBotError::WebhookError {
+                kind: WebhookErrorKind::Expired,
+                ..
+            })
+        ));
+    }
+
+    #[test]
+    fn test_is_expired() {
+        let now = Utc::now();
+        let tolerance = Duration::from_secs(60);
+
+        assert!(!is_expired(now, now, tolerance));
+        assert!(!is_expired(now - chrono::Duration::seconds(60), now, tolerance));
+        assert!(is_expired(now - chrono::Duration::seconds(61), now, tolerance));
+        assert!(!is_expired(now + chrono::Duration::hours(1), now, tolerance));
+    }
+
+    #[tokio::test]
+    async fn test_notifiers_run_after_handler_without_failing_update() {
+        struct RecordingNotifier(Arc<std::sync::Mutex<Vec<i64>>>);
+
+        #[async_trait::async_trait]
+        impl Notifier for RecordingNotifier {
+            async fn notify(&self, update: &WebhookUpdate) -> CryptoBotResult<()> {
+                self.0.lock().unwrap().push(update.update_id);
+                Err(CryptoBotError::HandlerError("smtp down".into()))
+            }
+        }
+
+        let notified = Arc::new(std::sync::Mutex::new(Vec::new()));
+        let errors = Arc::new(std::sync::Mutex::new(Vec::new()));
+        let config = WebhookHandlerConfigBuilder::new().build_config().unwrap();
+        let mut handler = WebhookHandler::new("test_token", config).unwrap();
+        handler.add_notifier(RecordingNotifier(notified.clone()));
+        let errors_clone = errors.clone();
+        handler.on_notify_error(move |error| errors_clone.lock().unwrap().push(error.to_string()));
+
+        let update = handle_and_capture(&mut handler, &fixtures::webhook_update_body()).await;
+
+        assert_eq!(*notified.lock().unwrap(), vec![update.update_id]);
+        assert_eq!(
+            *errors.lock().unwrap(),
+            vec!["Webhook handler error: smtp down".to_string()]
+        );
+
+        // A failing update handler skips the notifiers
+        handler.on_update(|_| async { Err(CryptoBotError::NoResult) });
+        assert!(handler.handle_update(&fixtures::webhook_update_body()).await.is_err());
+        assert_eq!(notified.lock().unwrap().len(), 1);
+    }
+
+    #[test]
+    fn test_debug_redacts_secrets() {
+        let token = "12345:AAzQcZWQqQAbsfgPnOLr4FHC8Doa4L7KryC";
+        let config = WebhookHandlerConfigBuilder::new()
+            .with_payload_hmac("payload-secret")
+            .build_config()
+            .unwrap();
+        let handler = WebhookHandler::new(token, config).unwrap();
+
+        let debug = format!("{handler:?}");
+        assert!(!debug.contains(token));
+        assert!(!debug.contains("payload-secret"));
+        assert!(debug.contains("payload_hmac_secret: Some(\"[redacted]\")"));
+    }
+
     #[test]
     fn test_webhook_signature_verification() {
-        let handler = WebhookHandler::with_config("test_token", WebhookHandlerConfigBuilder::new().build_config());
+        let handler =
+            WebhookHandler::new("test_token", WebhookHandlerConfigBuilder::new().build_config().unwrap()).unwrap();
         let body = json!({
             "update_id": 1,
             "update_type": "invoice_paid",
@@ -464,6 +1706,21 @@ mod tests {
         assert!(!handler.verify_signature(&body, "invalid_signature"));
     }
 
+    #[tokio::test]
+    async fn test_webhook_handler_rejects_oversized_body() {
+        let handler = WebhookHandler::new("test_token", WebhookHandlerConfig::default_strict()).unwrap();
+        let body = fixtures::webhook_update_body();
+        let oversized = format!("{body}{}", " ".repeat(256 * 1024));
+
+        assert!(matches!(
+            handler.handle_update(&oversized).await,
+            Err(CryptoBotError::WebhookError {
+                kind: WebhookErrorKind::InvalidPayload,
+                ..
+            })
+        ));
+    }
+
     #[test]
     fn test_parse_webhook_update() {
         let json = json!({
@@ -501,6 +1758,7 @@ mod tests {
                 assert_eq!(invoice.invoice_id, 528890);
                 assert_eq!(invoice.status, InvoiceStatus::Paid);
             }
+            other => panic!("Expected invoice payload, got {other:?}"),
         }
     }
 
@@ -520,7 +1778,7 @@ mod tests {
 
     #[tokio::test]
     async fn test_handle_update_with_missing_handler_ok() {
-        let handler = WebhookHandler::with_config("test_token", WebhookHandlerConfig::default());
+        let handler = WebhookHandler::new("test_token", WebhookHandlerConfig::default()).unwrap();
 
         let json = json!({
             "update_id": 1,
@@ -546,4 +1804,17 @@ mod tests {
         let result = handler.handle_update(&json).await;
         assert!(result.is_ok());
     }
+
+    #[cfg(any(feature = "axum", feature = "actix"))]
+    #[test]
+    fn test_webhook_status() {
+        let webhook_error = |kind| CryptoBotError::WebhookError {
+            kind,
+            message: String::new(),
+        };
+
+        assert_eq!(webhook_status(&webhook_error(WebhookErrorKind::MissingSignature)), 401);
+        assert_eq!(webhook_status(&webhook_error(WebhookErrorKind::Expired)), 400);
+        assert_eq!(webhook_status(&CryptoBotError::HandlerError("failed".into())), 500);
+    }
 }
@@ -1,11 +1,44 @@
+#[cfg(feature = "actix")]
+pub(crate) mod actix;
+#[cfg(feature = "axum")]
+mod axum;
 mod config;
+mod dedup;
+#[cfg(feature = "dev")]
+pub(crate) mod dev;
 mod handler;
+mod notify;
+mod payload;
+#[cfg(all(feature = "client", feature = "tokio"))]
+mod polling;
+#[cfg(feature = "tokio")]
+mod updates;
+#[cfg(all(feature = "client", feature = "tokio"))]
+mod watcher;
 
+#[cfg(feature = "axum")]
+pub use axum::handle_webhook;
 pub use config::{WebhookHandlerConfig, WebhookHandlerConfigBuilder};
-pub use handler::WebhookHandler;
+#[cfg(any(feature = "axum", feature = "actix"))]
+pub use handler::webhook_status;
+pub use handler::{is_expired, NotifyErrorFn, WebhookClockFn, WebhookHandler, WebhookMemoryUsage, SIGNATURE_HEADER};
+#[cfg(feature = "notify-smtp")]
+pub use notify::SmtpNotifier;
+#[cfg(feature = "notify-telegram")]
+pub use notify::TelegramNotifier;
+pub use notify::{invoice_paid_message, Notifier};
+pub use payload::sign_payload;
+#[cfg(all(feature = "client", feature = "tokio"))]
+pub use polling::PollingUpdates;
+#[cfg(feature = "tokio")]
+pub use updates::{UpdatesSource, WebhookUpdates};
+#[cfg(all(feature = "client", feature = "tokio"))]
+pub use watcher::{InvoiceEvent, InvoiceEventFn, InvoiceWatcher};
 
+#[cfg(feature = "client")]
 use crate::client::CryptoBot;
 
+#[cfg(feature = "client")]
 impl CryptoBot {
     /// Creates a new webhook handler builder
     ///
@@ -20,16 +53,16 @@ impl CryptoBot {
     ///
     ///     let webhook_handler = client.webhook_handler()
     ///         .expiration_time(Duration::from_secs(60 * 10))
-    ///         .build();
+    ///         .build()?;
     ///     Ok(())
     /// }
     /// ```
     pub fn webhook_handler(&self) -> WebhookHandlerConfigBuilder<'_> {
-        WebhookHandlerConfigBuilder::new_with_client(&self.api_token)
+        WebhookHandlerConfigBuilder::new_with_client(&self.api_token, self.webhook_expiration_time, self.rounding)
     }
 }
 
-#[cfg(test)]
+#[cfg(all(test, feature = "client"))]
 mod tests {
     use super::*;
     use std::time::Duration;
@@ -39,7 +72,7 @@ mod tests {
         let client = CryptoBot::test_client();
 
         // Test with default config
-        let handler = client.webhook_handler().build();
+        let handler = client.webhook_handler().build().unwrap();
 
         assert_eq!(handler.api_token, client.api_token);
         assert_eq!(handler.config.expiration_time, Some(Duration::from_secs(600)));
@@ -48,9 +81,25 @@ mod tests {
         let handler = client
             .webhook_handler()
             .expiration_time(Duration::from_secs(300))
-            .build();
+            .build()
+            .unwrap();
 
         assert_eq!(handler.api_token, client.api_token);
         assert_eq!(handler.config.expiration_time, Some(Duration::from_secs(300)));
     }
+
+    #[test]
+    fn test_webhook_handler_uses_client_settings() {
+        let client = CryptoBot::builder()
+            .api_token("test_token")
+            .webhook_expiration_time(Duration::from_secs(120))
+            .rounding(crate::models::Rounding::Floor)
+            .build()
+            .unwrap();
+
+        let handler = client.webhook_handler().build().unwrap();
+
+        assert_eq!(handler.config.expiration_time, Some(Duration::from_secs(120)));
+        assert_eq!(handler.config.usd_rounding, Some(crate::models::Rounding::Floor));
+    }
 }
@@ -0,0 +1,302 @@
+use async_trait::async_trait;
+
+use crate::{
+    error::CryptoBotResult,
+    models::{WebhookPayload, WebhookUpdate},
+};
+
+#[cfg(any(feature = "notify-telegram", feature = "notify-smtp"))]
+use crate::error::CryptoBotError;
+
+/// Sends an alert about handled webhook updates, attached with `WebhookHandler::add_notifier`
+///
+/// Notifiers are called after the update handler succeeded. The built-in adapters,
+/// `TelegramNotifier` (`notify-telegram` feature) and `SmtpNotifier` (`notify-smtp` feature),
+/// only notify about paid invoices.
+#[async_trait]
+pub trait Notifier: Send + Sync {
+    /// Sends the alert for `update`, or does nothing if the update is not of interest
+    async fn notify(&self, update: &WebhookUpdate) -> CryptoBotResult<()>;
+}
+
+/// Returns the message sent by the built-in notifiers for a paid invoice, `None` for other updates
+///
+/// The first line summarizes the invoice, e.g. `Invoice #528890 10.5 TON paid`, followed by the
+/// USD value, description and payload if known.
+pub fn invoice_paid_message(update: &WebhookUpdate) -> Option<String> {
+    let WebhookPayload::InvoicePaid(invoice) = &update.payload else {
+        return None;
+    };
+
+    let mut lines = vec![invoice.to_string()];
+    if let Some(usd) = update.paid_usd_amount {
+        lines.push(format!("USD value: {}", usd.round_dp(2)));
+    }
+    if let Some(description) = &invoice.description {
+        lines.push(format!("Description: {description}"));
+    }
+    if let Some(payload) = invoice.app_payload() {
+        lines.push(format!("Payload: {payload}"));
+    }
+    Some(lines.join("\n"))
+}
+
+/// Sends a message about every paid invoice to a Telegram chat through the Bot API
+///
+/// # Example
+/// ```no_run
+/// use crypto_pay_api::prelude::*;
+///
+/// # fn example(client: CryptoBot) -> Result<(), CryptoBotError> {
+/// let mut handler = client.webhook_handler().build()?;
+/// handler.add_notifier(TelegramNotifier::new("BOT_TOKEN", 123456789));
+/// # Ok(())
+/// # }
+/// ```
+#[cfg(feature = "notify-telegram")]
+pub struct TelegramNotifier {
+    http: reqwest::Client,
+    api_url: String,
+    bot_token: String,
+    chat_id: i64,
+    timeout: std::time::Duration,
+}
+
+#[cfg(feature = "notify-telegram")]
+impl TelegramNotifier {
+    /// Notifies `chat_id` with the bot of `bot_token`, which must be allowed to write to the chat.
+    pub fn new(bot_token: impl Into<String>, chat_id: i64) -> Self {
+        Self {
+            http: reqwest::Client::new(),
+            api_url: "https://api.telegram.org".to_string(),
+            bot_token: bot_token.into(),
+            chat_id,
+            timeout: std::time::Duration::from_secs(10),
+        }
+    }
+
+    /// Sends the messages to a self-hosted Bot API server instead of `https://api.telegram.org`.
+    pub fn api_url(mut self, api_url: impl Into<String>) -> Self {
+        self.api_url = api_url.into().trim_end_matches('/').to_string();
+        self
+    }
+
+    /// Sets the timeout of each `sendMessage` request. Optional. Defaults to 10 seconds.
+    ///
+    /// Notifiers run before the webhook is acknowledged, so a slow Bot API delays the response to Crypto Pay.
+    pub fn timeout(mut self, timeout: std::time::Duration) -> Self {
+        self.timeout = timeout;
+        self
+    }
+}
+
+#[cfg(feature = "notify-telegram")]
+impl std::fmt::Debug for TelegramNotifier {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("TelegramNotifier")
+            .field("api_url", &self.api_url)
+            .field("bot_token", &crate::utils::redact(&self.bot_token))
+            .field("chat_id", &self.chat_id)
+            .field("timeout", &self.timeout)
+            .finish()
+    }
+}
+
+#[cfg(feature = "notify-telegram")]
+#[async_trait]
+impl Notifier for TelegramNotifier {
+    async fn notify(&self, update: &WebhookUpdate) -> CryptoBotResult<()> {
+        let Some(text) = invoice_paid_message(update) else {
+            return Ok(());
+        };
+
+        // The request URL contains the bot token, keep it out of the errors
+        let response: serde_json::Value = self
+            .http
+            .post(format!("{}/bot{}/sendMessage", self.api_url, self.bot_token))
+            .timeout(self.timeout)
+            .json(&serde_json::json!({ "chat_id": self.chat_id, "text": text }))
+            .send()
+            .await
+            .map_err(|e| e.without_url())?
+            .json()
+            .await
+            .map_err(|e| e.without_url())?;
+
+        if response["ok"].as_bool() != Some(true) {
+            let description = response["description"].as_str().unwrap_or("unknown error");
+            return Err(CryptoBotError::HandlerError(
+                format!("Telegram sendMessage failed: {description}").into(),
+            ));
+        }
+        Ok(())
+    }
+}
+
+/// Sends an email about every paid invoice over SMTP
+///
+/// # Example
+/// ```no_run
+/// use crypto_pay_api::prelude::*;
+///
+/// # fn example(client: CryptoBot) -> Result<(), CryptoBotError> {
+/// let mut handler = client.webhook_handler().build()?;
+/// handler.add_notifier(SmtpNotifier::relay(
+///     "smtp.example.com",
+///     "user",
+///     "password",
+///     "Shop <shop@example.com>",
+///     "owner@example.com",
+/// )?);
+/// # Ok(())
+/// # }
+/// ```
+#[cfg(feature = "notify-smtp")]
+pub struct SmtpNotifier {
+    transport: lettre::AsyncSmtpTransport<lettre::Tokio1Executor>,
+    from: lettre::message::Mailbox,
+    to: lettre::message::Mailbox,
+}
+
+#[cfg(feature = "notify-smtp")]
+impl SmtpNotifier {
+    /// Sends the emails with a configured `lettre` transport, e.g. to use a custom port or STARTTLS.
+    pub fn new(
+        transport: lettre::AsyncSmtpTransport<lettre::Tokio1Executor>,
+        from: &str,
+        to: &str,
+    ) -> CryptoBotResult<Self> {
+        Ok(Self {
+            transport,
+            from: parse_mailbox(from, "from")?,
+            to: parse_mailbox(to, "to")?,
+        })
+    }
+
+    /// Sends the emails through `host` over TLS, authenticated with `username` and `password`.
+    pub fn relay(host: &str, username: &str, password: &str, from: &str, to: &str) -> CryptoBotResult<Self> {
+        let transport = lettre::AsyncSmtpTransport::<lettre::Tokio1Executor>::relay(host)
+            .map_err(|e| CryptoBotError::ConfigError {
+                message: format!("invalid SMTP relay {host}: {e}"),
+            })?
+            .credentials(lettre::transport::smtp::authentication::Credentials::new(
+                username.to_string(),
+                password.to_string(),
+            ))
+            .build();
+        Self::new(transport, from, to)
+    }
+}
+
+#[cfg(feature = "notify-smtp")]
+fn parse_mailbox(address: &str, field: &str) -> CryptoBotResult<lettre::message::Mailbox> {
+    address.parse().map_err(|e| CryptoBotError::ConfigError {
+        message: format!("invalid {field} address '{address}': {e}"),
+    })
+}
+
+#[cfg(feature = "notify-smtp")]
+impl std::fmt::Debug for SmtpNotifier {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("SmtpNotifier")
+            .field("from", &self.from.to_string())
+            .field("to", &self.to.to_string())
+            .finish_non_exhaustive()
+    }
+}
+
+#[cfg(feature = "notify-smtp")]
+#[async_trait]
+impl Notifier for SmtpNotifier {
+    async fn notify(&self, update: &WebhookUpdate) -> CryptoBotResult<()> {
+        use lettre::AsyncTransport;
+
+        let (Some(text), Some(invoice)) = (invoice_paid_message(update), update.invoice()) else {
+            return Ok(());
+        };
+
+        let email = lettre::Message::builder()
+            .from(self.from.clone())
+            .to(self.to.clone())
+            .subject(format!("Invoice #{} paid", invoice.invoice_id))
+            .body(text)
+            .map_err(|e| CryptoBotError::HandlerError(e.into()))?;
+
+        self.transport
+            .send(email)
+            .await
+            .map_err(|e| CryptoBotError::HandlerError(e.into()))?;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use rust_decimal_macros::dec;
+
+    use super::*;
+    use crate::fixtures;
+
+    #[test]
+    fn test_invoice_paid_message() {
+        let mut update = fixtures::webhook_update();
+        update.paid_usd_amount = Some(dec!(34.125));
+
+        let message = invoice_paid_message(&update).unwrap();
+        let lines: Vec<&str> = message.lines().collect();
+        assert!(lines[0].starts_with("Invoice #"));
+        assert!(lines[0].ends_with(" paid"));
+        assert_eq!(lines[1], "USD value: 34.12");
+
+        let check_update = WebhookUpdate {
+            payload: WebhookPayload::CheckActivated(fixtures::check()),
+            ..fixtures::webhook_update()
+        };
+        assert!(invoice_paid_message(&check_update).is_none());
+    }
+
+    #[test]
+    fn test_invoice_paid_message_omits_recovery_marker() {
+        let mut update = fixtures::webhook_update();
+        if let WebhookPayload::InvoicePaid(invoice) = &mut update.payload {
+            invoice.payload = Some("order_42\u{1e}recovery:key-1".to_string());
+        }
+
+        let message = invoice_paid_message(&update).unwrap();
+        assert!(message.ends_with("Payload: order_42"), "{message}");
+    }
+
+    #[cfg(feature = "notify-telegram")]
+    #[test]
+    fn test_telegram_notifier_sends_message() {
+        let mut ctx = crate::utils::test_utils::TestContext::new();
+        let send = ctx
+            .server
+            .mock("POST", "/botBOT_TOKEN/sendMessage")
+            .match_body(mockito::Matcher::PartialJson(serde_json::json!({ "chat_id": 42 })))
+            .with_body(r#"{"ok":true,"result":{}}"#)
+            .expect(1)
+            .create();
+
+        let notifier = TelegramNotifier::new("BOT_TOKEN", 42).api_url(ctx.server.url());
+        ctx.run(notifier.notify(&fixtures::webhook_update())).unwrap();
+        send.assert();
+    }
+
+    #[cfg(feature = "notify-telegram")]
+    #[test]
+    fn test_telegram_notifier_errors_omit_bot_token() {
+        let mut ctx = crate::utils::test_utils::TestContext::new();
+        let _send = ctx
+            .server
+            .mock("POST", "/botSECRET_BOT_TOKEN/sendMessage")
+            .with_body("not json")
+            .create();
+
+        let notifier = TelegramNotifier::new("SECRET_BOT_TOKEN", 42).api_url(ctx.server.url());
+        let error = ctx.run(notifier.notify(&fixtures::webhook_update())).unwrap_err();
+        assert!(matches!(error, CryptoBotError::HttpError(_)));
+        assert!(!error.to_string().contains("SECRET_BOT_TOKEN"));
+        assert!(!format!("{error:?}").contains("SECRET_BOT_TOKEN"));
+    }
+}
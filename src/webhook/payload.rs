@@ -0,0 +1,70 @@
+use hmac::{Hmac, Mac};
+use sha2::Sha256;
+
+/// Separates the original payload from its HMAC in a signed payload.
+const SIGNATURE_SEPARATOR: char = '.';
+
+fn payload_mac(secret: &str, payload: &str) -> Hmac<Sha256> {
+    let mut mac = Hmac::<Sha256>::new_from_slice(secret.as_bytes()).expect("HMAC can take key of any size");
+    mac.update(payload.as_bytes());
+    mac
+}
+
+/// Appends an HMAC-SHA-256 of the payload, keyed with your own secret, to an invoice payload
+///
+/// The result has the form `<payload>.<hex hmac>` and is what `CreateInvoiceBuilder::signed_payload`
+/// sends to the API. A webhook handler built with `with_payload_hmac(secret)` only accepts
+/// updates whose payload carries a valid HMAC, so paid updates for invoices your system did
+/// not create are rejected.
+///
+/// # Example
+/// ```
+/// use crypto_pay_api::prelude::*;
+///
+/// let signed = sign_payload("my_secret", "order_42");
+/// assert!(signed.starts_with("order_42."));
+/// ```
+pub fn sign_payload(secret: &str, payload: &str) -> String {
+    let signature = hex::encode(payload_mac(secret, payload).finalize().into_bytes());
+    format!("{payload}{SIGNATURE_SEPARATOR}{signature}")
+}
+
+/// Verifies a payload produced by [`sign_payload`] and returns the original payload
+pub(crate) fn verify_signed_payload<'a>(secret: &str, signed: &'a str) -> Option<&'a str> {
+    let (payload, signature) = signed.rsplit_once(SIGNATURE_SEPARATOR)?;
+    let signature = hex::decode(signature).ok()?;
+
+    payload_mac(secret, payload)
+        .verify_slice(&signature)
+        .ok()
+        .map(|_| payload)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_sign_and_verify_payload() {
+        let signed = sign_payload("secret", "order.42");
+
+        assert_eq!(verify_signed_payload("secret", &signed), Some("order.42"));
+        assert_eq!(verify_signed_payload("other_secret", &signed), None);
+    }
+
+    #[test]
+    fn test_verify_rejects_tampered_payload() {
+        let signed = sign_payload("secret", "order_42");
+        let tampered = signed.replacen("order_42", "order_43", 1);
+
+        assert_eq!(verify_signed_payload("secret", &tampered), None);
+        assert_eq!(verify_signed_payload("secret", "order_42"), None);
+        assert_eq!(verify_signed_payload("secret", "order_42.not_hex"), None);
+    }
+
+    #[test]
+    fn test_sign_empty_payload() {
+        let signed = sign_payload("secret", "");
+        assert_eq!(verify_signed_payload("secret", &signed), Some(""));
+    }
+}
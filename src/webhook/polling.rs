@@ -0,0 +1,175 @@
+use std::{
+    collections::{HashSet, VecDeque},
+    time::Duration,
+};
+
+use async_trait::async_trait;
+use chrono::{DateTime, Utc};
+
+use crate::{
+    api::InvoiceAPI,
+    client::CryptoBot,
+    config::DEFAULT_POLL_INTERVAL,
+    error::CryptoBotResult,
+    models::{Invoice, InvoiceStatus, WebhookPayload, WebhookUpdate},
+};
+
+use super::UpdatesSource;
+
+/// Updates synthesized by polling `getInvoices` for paid invoices, returned by `CryptoBot::poll_updates`
+///
+/// Each invoice paid since the source was created is emitted once as an `invoice_paid` update,
+/// with its invoice ID as `update_id` and the time it was seen as `request_date`. Only the newest
+/// `count` paid invoices are polled, so invoices created long before they were paid can be missed
+/// when many invoices are paid between polls. The source is never closed.
+pub struct PollingUpdates<'a> {
+    client: &'a CryptoBot,
+    interval: Duration,
+    count: u16,
+    since: DateTime<Utc>,
+    seen: HashSet<u64>,
+    pending: VecDeque<WebhookUpdate>,
+    polled: bool,
+}
+
+impl<'a> PollingUpdates<'a> {
+    pub fn new(client: &'a CryptoBot) -> Self {
+        Self {
+            client,
+            interval: Duration::from_secs(DEFAULT_POLL_INTERVAL),
+            count: 100,
+            since: Utc::now(),
+            seen: HashSet::new(),
+            pending: VecDeque::new(),
+            polled: false,
+        }
+    }
+
+    /// Sets the delay between polls. Optional. Defaults to 5 seconds.
+    pub fn interval(mut self, interval: Duration) -> Self {
+        self.interval = interval;
+        self
+    }
+
+    /// Sets how many of the newest paid invoices each poll fetches, between 1 and 1000.
+    /// Optional. Defaults to 100.
+    pub fn count(mut self, count: u16) -> Self {
+        self.count = count;
+        self
+    }
+
+    /// Emits invoices paid since `since` instead of since the source was created, e.g. to catch up after a restart.
+    pub fn since(mut self, since: DateTime<Utc>) -> Self {
+        self.since = since;
+        self
+    }
+
+    async fn poll(&mut self) -> CryptoBotResult<()> {
+        let invoices = self
+            .client
+            .get_invoices()
+            .status(InvoiceStatus::Paid)
+            .count(self.count)
+            .execute()
+            .await?;
+
+        // Paid invoices only ever leave the page, so IDs no longer listed can be forgotten
+        let listed: HashSet<u64> = invoices.iter().map(|invoice| invoice.invoice_id).collect();
+        self.seen.retain(|invoice_id| listed.contains(invoice_id));
+
+        let mut paid: Vec<Invoice> = invoices
+            .into_iter()
+            .filter(|invoice| invoice.paid_at.is_some_and(|paid_at| paid_at >= self.since))
+            .filter(|invoice| !self.seen.contains(&invoice.invoice_id))
+            .collect();
+        paid.sort_by_key(|invoice| invoice.paid_at);
+
+        for invoice in paid {
+            self.seen.insert(invoice.invoice_id);
+            self.pending.push_back(WebhookUpdate::new(
+                invoice.invoice_id as i64,
+                WebhookPayload::InvoicePaid(invoice),
+            ));
+        }
+        Ok(())
+    }
+}
+
+#[async_trait]
+impl UpdatesSource for PollingUpdates<'_> {
+    async fn next_update(&mut self) -> CryptoBotResult<Option<WebhookUpdate>> {
+        loop {
+            if let Some(update) = self.pending.pop_front() {
+                return Ok(Some(update));
+            }
+            if self.polled {
+                tokio::time::sleep(self.interval).await;
+            }
+            self.polled = true;
+            self.poll().await?;
+        }
+    }
+}
+
+impl CryptoBot {
+    /// Returns an [`UpdatesSource`] polling for paid invoices, e.g. for apps that cannot receive webhooks
+    ///
+    /// # Example
+    /// ```no_run
+    /// use crypto_pay_api::prelude::*;
+    /// use std::time::Duration;
+    ///
+    /// # async fn example(client: CryptoBot) -> Result<(), CryptoBotError> {
+    /// let mut updates = client.poll_updates().interval(Duration::from_secs(10));
+    /// while let Some(update) = updates.next_update().await? {
+    ///     println!("Invoice {} paid", update.update_id);
+    /// }
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn poll_updates(&self) -> PollingUpdates<'_> {
+        PollingUpdates::new(self)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{fixtures, utils::test_utils::TestContext};
+
+    #[test]
+    fn test_polling_updates_emit_each_paid_invoice_once() {
+        let mut ctx = TestContext::new();
+        let invoices = ctx
+            .server
+            .mock("GET", "/getInvoices")
+            .with_body(fixtures::api_response(&format!(
+                r#"{{"items":[{}]}}"#,
+                fixtures::PAID_INVOICE
+            )))
+            .expect(2)
+            .create();
+
+        let client = CryptoBot::builder()
+            .api_token("test_token")
+            .base_url(ctx.server.url())
+            .build()
+            .unwrap();
+        let mut updates = client
+            .poll_updates()
+            .interval(Duration::from_millis(10))
+            .since(DateTime::UNIX_EPOCH);
+
+        ctx.run(async {
+            let update = updates.next_update().await.unwrap().unwrap();
+            assert_eq!(update.update_id, 528890);
+            assert_eq!(update.invoice().unwrap().payload.as_deref(), Some("order_42"));
+
+            // The invoice is still listed on the next poll but not emitted again
+            updates.poll().await.unwrap();
+            assert!(updates.pending.is_empty());
+        });
+
+        invoices.assert();
+    }
+}
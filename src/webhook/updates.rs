@@ -0,0 +1,115 @@
+use async_trait::async_trait;
+use tokio::sync::mpsc;
+
+use crate::{
+    error::{CryptoBotError, CryptoBotResult},
+    models::WebhookUpdate,
+};
+
+use super::WebhookHandler;
+
+/// A stream of updates, independent of how they are delivered
+///
+/// Crypto Pay only delivers updates through webhooks today. Application code written against this
+/// trait works unchanged with [`WebhookUpdates`], [`PollingUpdates`] and any streaming transport
+/// added later. Requires the `tokio` feature.
+///
+/// # Example
+/// ```no_run
+/// use crypto_pay_api::prelude::*;
+///
+/// async fn fulfil_orders(mut updates: impl UpdatesSource) -> Result<(), CryptoBotError> {
+///     while let Some(update) = updates.next_update().await? {
+///         if let Some(invoice) = update.invoice() {
+///             println!("Invoice {} paid", invoice.invoice_id);
+///         }
+///     }
+///     Ok(())
+/// }
+/// ```
+#[async_trait]
+pub trait UpdatesSource: Send {
+    /// Waits for the next update. Returns `None` once the source is closed.
+    ///
+    /// An error does not close the source, the next call tries again.
+    async fn next_update(&mut self) -> CryptoBotResult<Option<WebhookUpdate>>;
+}
+
+/// Updates received by a [`WebhookHandler`], returned by `WebhookHandler::updates`
+///
+/// Updates are acknowledged to Crypto Pay as soon as they are buffered, not when they are consumed.
+#[derive(Debug)]
+pub struct WebhookUpdates {
+    receiver: mpsc::Receiver<WebhookUpdate>,
+}
+
+#[async_trait]
+impl UpdatesSource for WebhookUpdates {
+    async fn next_update(&mut self) -> CryptoBotResult<Option<WebhookUpdate>> {
+        Ok(self.receiver.recv().await)
+    }
+}
+
+impl WebhookHandler {
+    /// Forwards every handled update to the returned [`UpdatesSource`], replacing the `on_update` handler
+    ///
+    /// Up to `capacity` updates are buffered. When the buffer is full, `handle_update` waits for the
+    /// source to catch up. Once the source is dropped, updates fail with `CryptoBotError::HandlerError`,
+    /// so Crypto Pay delivers them again later. The source is closed when the handler is dropped.
+    ///
+    /// An update is acknowledged, i.e. `handle_update` succeeds and the webhook answers 200, once it
+    /// is buffered. Crypto Pay does not deliver it again, even if the consumer fails or the process
+    /// exits before `next_update` returns it. Use `on_update` instead when an update must only be
+    /// acknowledged after it was processed.
+    ///
+    /// # Example
+    /// ```no_run
+    /// use crypto_pay_api::prelude::*;
+    ///
+    /// # async fn example(client: CryptoBot) -> Result<(), CryptoBotError> {
+    /// let mut handler = client.webhook_handler().build()?;
+    /// let mut updates = handler.updates(64);
+    ///
+    /// // Serve `handler` from your web framework, and consume the updates elsewhere
+    /// while let Some(update) = updates.next_update().await? {
+    ///     println!("{}", update.update_type);
+    /// }
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn updates(&mut self, capacity: usize) -> WebhookUpdates {
+        let (sender, receiver) = mpsc::channel(capacity.max(1));
+        self.on_update(move |update| {
+            let sender = sender.clone();
+            async move {
+                sender
+                    .send(update)
+                    .await
+                    .map_err(|_| CryptoBotError::HandlerError("the updates source was dropped".into()))
+            }
+        });
+        WebhookUpdates { receiver }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{fixtures, models::UpdateType, utils::test_utils::TestContext, webhook::WebhookHandlerConfig};
+
+    #[test]
+    fn test_webhook_updates_source() {
+        let ctx = TestContext::new();
+        let mut handler = WebhookHandler::new("test_token", WebhookHandlerConfig::default()).unwrap();
+        let mut updates = handler.updates(1);
+
+        ctx.run(async {
+            handler.handle_update(&fixtures::webhook_update_body()).await.unwrap();
+            let update = updates.next_update().await.unwrap().unwrap();
+            assert_eq!(update.update_type, UpdateType::InvoicePaid);
+
+            drop(handler);
+            assert!(updates.next_update().await.unwrap().is_none());
+        });
+    }
+}
@@ -0,0 +1,319 @@
+use std::{
+    collections::HashMap,
+    pin::pin,
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        Arc, Mutex,
+    },
+    time::{Duration, Instant},
+};
+
+use futures_util::future::select;
+use tokio::sync::{mpsc, Notify};
+
+use crate::{
+    api::InvoiceAPI,
+    client::CryptoBot,
+    error::{CryptoBotError, CryptoBotResult},
+    models::{Invoice, InvoiceStatus},
+};
+
+/// Event emitted by an [`InvoiceWatcher`]
+#[derive(Debug)]
+#[non_exhaustive]
+pub enum InvoiceEvent {
+    /// The watched invoice was paid.
+    Paid(Invoice),
+    /// The watched invoice expired without being paid.
+    Expired(Invoice),
+    /// The watch timed out while the invoice was still active.
+    TimedOut { invoice_id: u64 },
+    /// A poll failed. The watches are kept and polled again after the interval.
+    PollFailed(CryptoBotError),
+}
+
+/// Callback receiving the events of an [`InvoiceWatcher`], see `InvoiceWatcher::with_callback`
+pub type InvoiceEventFn = Box<dyn Fn(InvoiceEvent) + Send + Sync>;
+
+enum EventSink {
+    Channel(mpsc::UnboundedSender<InvoiceEvent>),
+    Callback(InvoiceEventFn),
+}
+
+struct Inner {
+    interval: Duration,
+    /// Watched invoice IDs with the deadline of their watch, if any
+    watches: Mutex<HashMap<u64, Option<Instant>>>,
+    sink: EventSink,
+    closed: AtomicBool,
+    wake: Notify,
+}
+
+/// Watches invoices by polling `getInvoices`, for deployments that cannot receive webhooks
+///
+/// Each watched invoice is emitted once as `InvoiceEvent::Paid` or `InvoiceEvent::Expired` and then
+/// forgotten. Watches can be given a timeout, emitting `InvoiceEvent::TimedOut` if the invoice is
+/// still active by then, and cancelled with `cancel`. `run` polls all watched invoices every
+/// interval, in chunks of up to 100 IDs per request, until `close` is called. Clones share the same
+/// watches, so invoices can be watched from request handlers while a task runs the watcher.
+///
+/// # Example
+/// ```no_run
+/// use crypto_pay_api::prelude::*;
+/// use std::{sync::Arc, time::Duration};
+///
+/// # async fn example(client: Arc<CryptoBot>) -> Result<(), CryptoBotError> {
+/// let (watcher, mut events) = InvoiceWatcher::channel(Duration::from_secs(5));
+/// tokio::spawn({
+///     let watcher = watcher.clone();
+///     async move { watcher.run(&client).await }
+/// });
+///
+/// watcher.watch_with_timeout(528890, Duration::from_secs(15 * 60));
+/// while let Some(event) = events.recv().await {
+///     match event {
+///         InvoiceEvent::Paid(invoice) => println!("Invoice {} paid", invoice.invoice_id),
+///         InvoiceEvent::PollFailed(error) => eprintln!("Polling failed: {error}"),
+///         _ => {}
+///     }
+/// }
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Clone)]
+pub struct InvoiceWatcher {
+    inner: Arc<Inner>,
+}
+
+impl std::fmt::Debug for InvoiceWatcher {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("InvoiceWatcher")
+            .field("interval", &self.inner.interval)
+            .field("watched", &self.len())
+            .field("closed", &self.is_closed())
+            .finish_non_exhaustive()
+    }
+}
+
+impl InvoiceWatcher {
+    /// Creates a watcher polling every `interval` and the receiver of its events
+    ///
+    /// The watcher closes once the receiver is dropped.
+    pub fn channel(interval: Duration) -> (Self, mpsc::UnboundedReceiver<InvoiceEvent>) {
+        let (sender, receiver) = mpsc::unbounded_channel();
+        (Self::with_sink(interval, EventSink::Channel(sender)), receiver)
+    }
+
+    /// Creates a watcher polling every `interval` and passing its events to `callback`
+    pub fn with_callback<F>(interval: Duration, callback: F) -> Self
+    where
+        F: Fn(InvoiceEvent) + Send + Sync + 'static,
+    {
+        Self::with_sink(interval, EventSink::Callback(Box::new(callback)))
+    }
+
+    fn with_sink(interval: Duration, sink: EventSink) -> Self {
+        Self {
+            inner: Arc::new(Inner {
+                interval,
+                watches: Mutex::new(HashMap::new()),
+                sink,
+                closed: AtomicBool::new(false),
+                wake: Notify::new(),
+            }),
+        }
+    }
+
+    /// Watches `invoice_id` until it is paid or expires
+    pub fn watch(&self, invoice_id: u64) {
+        self.watches().insert(invoice_id, None);
+    }
+
+    /// Watches `invoice_id` for at most `timeout`, replacing any previous watch of the invoice
+    pub fn watch_with_timeout(&self, invoice_id: u64, timeout: Duration) {
+        self.watches().insert(invoice_id, Some(Instant::now() + timeout));
+    }
+
+    /// Stops watching `invoice_id` without an event, returning false if it was not watched
+    pub fn cancel(&self, invoice_id: u64) -> bool {
+        self.watches().remove(&invoice_id).is_some()
+    }
+
+    /// Returns true if `invoice_id` is watched
+    pub fn is_watching(&self, invoice_id: u64) -> bool {
+        self.watches().contains_key(&invoice_id)
+    }
+
+    /// Returns the number of watched invoices
+    pub fn len(&self) -> usize {
+        self.watches().len()
+    }
+
+    /// Returns true if no invoice is watched
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Stops `run` without waiting for the current interval to end. Watches are kept.
+    pub fn close(&self) {
+        self.inner.closed.store(true, Ordering::SeqCst);
+        self.inner.wake.notify_waiters();
+    }
+
+    /// Returns true once `close` was called or the event receiver was dropped
+    pub fn is_closed(&self) -> bool {
+        self.inner.closed.load(Ordering::SeqCst)
+    }
+
+    /// Polls the watched invoices every interval until the watcher is closed
+    ///
+    /// Failed polls are emitted as `InvoiceEvent::PollFailed` and do not stop the watcher.
+    pub async fn run(&self, client: &CryptoBot) {
+        loop {
+            if let Err(error) = self.poll(client).await {
+                self.emit(InvoiceEvent::PollFailed(error));
+            }
+
+            let mut closed = pin!(self.inner.wake.notified());
+            closed.as_mut().enable();
+            if self.is_closed() {
+                return;
+            }
+            select(pin!(tokio::time::sleep(self.inner.interval)), closed).await;
+            if self.is_closed() {
+                return;
+            }
+        }
+    }
+
+    /// Polls the watched invoices once and emits their events, e.g. from an existing scheduler
+    ///
+    /// Timed out watches are emitted before the request. No request is sent if nothing is watched.
+    pub async fn poll(&self, client: &CryptoBot) -> CryptoBotResult<()> {
+        let now = Instant::now();
+        let (invoice_ids, timed_out) = {
+            let mut watches = self.watches();
+            let timed_out: Vec<u64> = watches
+                .iter()
+                .filter(|(_, deadline)| deadline.is_some_and(|deadline| deadline <= now))
+                .map(|(invoice_id, _)| *invoice_id)
+                .collect();
+            for invoice_id in &timed_out {
+                watches.remove(invoice_id);
+            }
+            (watches.keys().copied().collect::<Vec<_>>(), timed_out)
+        };
+
+        for invoice_id in timed_out {
+            self.emit(InvoiceEvent::TimedOut { invoice_id });
+        }
+        if invoice_ids.is_empty() {
+            return Ok(());
+        }
+
+        for invoice in client.get_invoices_by_ids(invoice_ids).await? {
+            let invoice_id = invoice.invoice_id;
+            let event = match invoice.status {
+                InvoiceStatus::Paid => InvoiceEvent::Paid(invoice),
+                InvoiceStatus::Expired => InvoiceEvent::Expired(invoice),
+                _ => continue,
+            };
+
+            // The watch may have been cancelled while the request was running
+            if self.cancel(invoice_id) {
+                self.emit(event);
+            }
+        }
+        Ok(())
+    }
+
+    fn emit(&self, event: InvoiceEvent) {
+        match &self.inner.sink {
+            EventSink::Channel(sender) => {
+                if sender.send(event).is_err() {
+                    self.close();
+                }
+            }
+            EventSink::Callback(callback) => callback(event),
+        }
+    }
+
+    fn watches(&self) -> std::sync::MutexGuard<'_, HashMap<u64, Option<Instant>>> {
+        self.inner.watches.lock().unwrap_or_else(|e| e.into_inner())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{fixtures, utils::test_utils::TestContext};
+
+    fn client(ctx: &TestContext) -> CryptoBot {
+        CryptoBot::builder()
+            .api_token("test_token")
+            .base_url(ctx.server.url())
+            .build()
+            .unwrap()
+    }
+
+    #[test]
+    fn test_watcher_emits_paid_and_timed_out_invoices() {
+        let mut ctx = TestContext::new();
+        let invoices = ctx
+            .server
+            .mock("GET", "/getInvoices")
+            .with_body(fixtures::api_response(&format!(
+                r#"{{"items":[{}]}}"#,
+                fixtures::PAID_INVOICE
+            )))
+            .expect(1)
+            .create();
+        let client = client(&ctx);
+
+        let (watcher, mut events) = InvoiceWatcher::channel(Duration::from_millis(10));
+        watcher.watch(528890);
+        watcher.watch_with_timeout(1, Duration::ZERO);
+        watcher.watch(2);
+        assert!(watcher.cancel(2));
+        assert!(!watcher.cancel(2));
+
+        ctx.run(watcher.poll(&client)).unwrap();
+        assert!(matches!(
+            events.try_recv(),
+            Ok(InvoiceEvent::TimedOut { invoice_id: 1 })
+        ));
+        assert!(matches!(events.try_recv(), Ok(InvoiceEvent::Paid(invoice)) if invoice.invoice_id == 528890));
+        assert!(events.try_recv().is_err());
+        assert!(watcher.is_empty());
+
+        // Nothing is watched, so no request is sent
+        ctx.run(watcher.poll(&client)).unwrap();
+        invoices.assert();
+    }
+
+    #[test]
+    fn test_watcher_run_stops_when_closed() {
+        let mut ctx = TestContext::new();
+        let _m = ctx
+            .server
+            .mock("GET", "/getInvoices")
+            .with_body(fixtures::api_response(r#"{"items":[]}"#))
+            .create();
+        let client = client(&ctx);
+
+        let watcher = InvoiceWatcher::with_callback(Duration::from_secs(3600), |_| {});
+        watcher.watch(528890);
+
+        let closer = watcher.clone();
+        ctx.run(async {
+            let close = async {
+                tokio::time::sleep(Duration::from_millis(50)).await;
+                closer.close();
+            };
+            futures::join!(watcher.run(&client), close);
+        });
+
+        assert!(watcher.is_closed());
+        assert!(watcher.is_watching(528890));
+    }
+}
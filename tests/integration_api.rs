@@ -1,3 +1,5 @@
+#![cfg(feature = "client")]
+
 use crypto_pay_api::prelude::*;
 use rust_decimal_macros::dec;
 
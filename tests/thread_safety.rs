@@ -0,0 +1,75 @@
+//! Compile-time checks that the client, the webhook handler and the futures they return can be
+//! shared across threads, e.g. in axum state on a multi-threaded tokio runtime.
+
+#![cfg(feature = "client")]
+
+use std::future::Future;
+
+use crypto_pay_api::prelude::*;
+
+fn assert_send_sync<T: Send + Sync>() {}
+
+fn assert_send<F: Future + Send>(_: F) {}
+
+#[test]
+fn test_types_are_send_and_sync() {
+    assert_send_sync::<CryptoBot>();
+    assert_send_sync::<CryptoBotError>();
+    assert_send_sync::<WebhookHandler>();
+    assert_send_sync::<WebhookHandlerConfig>();
+    assert_send_sync::<WebhookUpdate>();
+    assert_send_sync::<BalanceMonitor>();
+    assert_send_sync::<Invoice>();
+    assert_send_sync::<CreatedInvoice>();
+}
+
+#[test]
+fn test_request_futures_are_send() {
+    let client = CryptoBot::builder().api_token("token").build().unwrap();
+
+    assert_send(client.get_me().execute());
+    assert_send(client.get_balance().execute());
+    assert_send(client.get_exchange_rates().execute());
+    assert_send(client.get_currencies().execute());
+    assert_send(client.get_stats().execute());
+    assert_send(client.metadata().get());
+    assert_send(client.get_invoices().execute());
+    assert_send(client.get_invoices_by_ids(vec![1]));
+    assert_send(client.delete_invoice(1).execute());
+    assert_send(
+        client
+            .create_invoice()
+            .asset(CryptoCurrencyCode::Ton)
+            .amount(dec!(1))
+            .execute(),
+    );
+    assert_send(client.get_checks().execute());
+    assert_send(
+        client
+            .create_check()
+            .asset(CryptoCurrencyCode::Ton)
+            .amount(dec!(1))
+            .execute(),
+    );
+    assert_send(client.get_transfers().execute());
+    assert_send(
+        client
+            .transfer()
+            .user_id(1)
+            .asset(CryptoCurrencyCode::Ton)
+            .amount(dec!(1))
+            .spend_id("spend")
+            .execute(),
+    );
+    assert_send(client.sweep_to(1).execute());
+    assert_send(BalanceMonitor::new().check(&client));
+}
+
+#[test]
+fn test_webhook_futures_are_send() {
+    let client = CryptoBot::builder().api_token("token").build().unwrap();
+    let handler = client.webhook_handler().build().unwrap();
+
+    assert_send(handler.handle_update("{}"));
+    assert_send(handler.refresh_exchange_rates(&client));
+}